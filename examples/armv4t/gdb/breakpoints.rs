@@ -41,27 +41,41 @@ impl target::ext::breakpoints::SwBreakpoint for Emu {
 }
 
 impl target::ext::breakpoints::HwWatchpoint for Emu {
-    fn add_hw_watchpoint(&mut self, addr: u32, kind: WatchKind) -> TargetResult<bool, Self> {
-        match kind {
-            WatchKind::Write => self.watchpoints.push(addr),
-            WatchKind::Read => self.watchpoints.push(addr),
-            WatchKind::ReadWrite => self.watchpoints.push(addr),
-        };
+    fn add_hw_watchpoint(
+        &mut self,
+        addr: u32,
+        len: u32,
+        kind: WatchKind,
+    ) -> TargetResult<bool, Self> {
+        for addr in addr..(addr + len) {
+            match kind {
+                WatchKind::Write => self.watchpoints.push(addr),
+                WatchKind::Read => self.watchpoints.push(addr),
+                WatchKind::ReadWrite => self.watchpoints.push(addr),
+            };
+        }
 
         Ok(true)
     }
 
-    fn remove_hw_watchpoint(&mut self, addr: u32, kind: WatchKind) -> TargetResult<bool, Self> {
-        let pos = match self.watchpoints.iter().position(|x| *x == addr) {
-            None => return Ok(false),
-            Some(pos) => pos,
-        };
+    fn remove_hw_watchpoint(
+        &mut self,
+        addr: u32,
+        len: u32,
+        kind: WatchKind,
+    ) -> TargetResult<bool, Self> {
+        for addr in addr..(addr + len) {
+            let pos = match self.watchpoints.iter().position(|x| *x == addr) {
+                None => return Ok(false),
+                Some(pos) => pos,
+            };
 
-        match kind {
-            WatchKind::Write => self.watchpoints.remove(pos),
-            WatchKind::Read => self.watchpoints.remove(pos),
-            WatchKind::ReadWrite => self.watchpoints.remove(pos),
-        };
+            match kind {
+                WatchKind::Write => self.watchpoints.remove(pos),
+                WatchKind::Read => self.watchpoints.remove(pos),
+                WatchKind::ReadWrite => self.watchpoints.remove(pos),
+            };
+        }
 
         Ok(true)
     }
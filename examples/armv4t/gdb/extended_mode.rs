@@ -66,7 +66,13 @@ impl target::ext::extended_mode::ExtendedMode for Emu {
             "GDB queried if it was attached to a process with PID {}",
             pid
         );
-        Ok(AttachKind::Attach)
+        // processes created via `run` were spawned, whereas the emulator's initial
+        // process is treated as having been attached to
+        if pid.get() == 1337 {
+            Ok(AttachKind::Run)
+        } else {
+            Ok(AttachKind::Attach)
+        }
     }
 
     #[inline(always)]
@@ -1,6 +1,7 @@
 use core::convert::TryInto;
 
 use armv4t_emu::{reg, Memory};
+use gdbstub::common::Signal;
 use gdbstub::target;
 use gdbstub::target::ext::base::singlethread::{
     GdbInterrupt, ResumeAction, SingleThreadOps, SingleThreadReverseContOps,
@@ -110,7 +111,7 @@ impl Emu {
         };
 
         Ok(match event {
-            Event::Halted => StopReason::Terminated(19), // SIGSTOP
+            Event::Halted => StopReason::Terminated(Signal::SIGSTOP),
             Event::Break => StopReason::SwBreak,
             Event::WatchWrite(addr) => StopReason::Watch {
                 kind: WatchKind::Write,
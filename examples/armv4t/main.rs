@@ -73,7 +73,7 @@ fn main() -> DynResult<()> {
         }
         DisconnectReason::TargetExited(code) => println!("Target exited with code {}!", code),
         DisconnectReason::TargetTerminated(sig) => {
-            println!("Target terminated with signal {}!", sig)
+            println!("Target terminated with signal {:?}!", sig)
         }
         DisconnectReason::Kill => {
             println!("GDB sent a kill command!");
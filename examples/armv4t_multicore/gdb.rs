@@ -1,6 +1,6 @@
 use armv4t_emu::{reg, Memory};
 
-use gdbstub::common::Tid;
+use gdbstub::common::{Signal, Tid};
 use gdbstub::target;
 use gdbstub::target::ext::base::multithread::{
     GdbInterrupt, MultiThreadOps, ResumeAction, ThreadStopReason,
@@ -13,7 +13,7 @@ use crate::emu::{CpuId, Emu, Event};
 fn event_to_stopreason(e: Event, id: CpuId) -> ThreadStopReason<u32> {
     let tid = cpuid_to_tid(id);
     match e {
-        Event::Halted => ThreadStopReason::Terminated(19), // SIGSTOP
+        Event::Halted => ThreadStopReason::Terminated(Signal::SIGSTOP),
         Event::Break => ThreadStopReason::SwBreak(tid),
         Event::WatchWrite(addr) => ThreadStopReason::Watch {
             tid,
@@ -195,6 +195,18 @@ impl MultiThreadOps for Emu {
         Ok(())
     }
 
+    fn support_thread_extra_info(
+        &mut self,
+    ) -> Option<target::ext::base::multithread::MultiThreadExtraInfoOps<Self>> {
+        Some(self)
+    }
+
+    fn support_thread_list(
+        &mut self,
+    ) -> Option<target::ext::base::multithread::ThreadListOps<Self>> {
+        Some(self)
+    }
+
     fn list_active_threads(
         &mut self,
         register_thread: &mut dyn FnMut(Tid),
@@ -205,6 +217,39 @@ impl MultiThreadOps for Emu {
     }
 }
 
+impl target::ext::base::multithread::MultiThreadExtraInfo for Emu {
+    fn thread_extra_info(&mut self, tid: Tid, buf: &mut [u8]) -> TargetResult<usize, Self> {
+        let name: &[u8] = match tid_to_cpuid(tid).map_err(TargetError::Fatal)? {
+            CpuId::Cpu => b"CPU",
+            CpuId::Cop => b"COP",
+        };
+
+        let len = name.len().min(buf.len());
+        buf[..len].copy_from_slice(&name[..len]);
+        Ok(len)
+    }
+}
+
+impl target::ext::base::multithread::ThreadList for Emu {
+    fn list_threads(
+        &mut self,
+        thread_info: &mut dyn FnMut(target::ext::base::multithread::ThreadInfo<'_>),
+    ) -> Result<(), Self::Error> {
+        for (core, (id, name)) in [(CpuId::Cpu, "CPU"), (CpuId::Cop, "COP")]
+            .iter()
+            .enumerate()
+        {
+            thread_info(target::ext::base::multithread::ThreadInfo {
+                tid: cpuid_to_tid(*id),
+                name: Some(name),
+                core: Some(core),
+                pid: None,
+            });
+        }
+        Ok(())
+    }
+}
+
 impl target::ext::breakpoints::Breakpoints for Emu {
     fn sw_breakpoint(&mut self) -> Option<target::ext::breakpoints::SwBreakpointOps<Self>> {
         Some(self)
@@ -240,33 +285,47 @@ impl target::ext::breakpoints::SwBreakpoint for Emu {
 }
 
 impl target::ext::breakpoints::HwWatchpoint for Emu {
-    fn add_hw_watchpoint(&mut self, addr: u32, kind: WatchKind) -> TargetResult<bool, Self> {
-        self.watchpoints.push(addr);
-
-        let entry = self.watchpoint_kind.entry(addr).or_insert((false, false));
-        match kind {
-            WatchKind::Write => entry.1 = true,
-            WatchKind::Read => entry.0 = true,
-            WatchKind::ReadWrite => entry.0 = true, // arbitrary
-        };
+    fn add_hw_watchpoint(
+        &mut self,
+        addr: u32,
+        len: u32,
+        kind: WatchKind,
+    ) -> TargetResult<bool, Self> {
+        for addr in addr..(addr + len) {
+            self.watchpoints.push(addr);
+
+            let entry = self.watchpoint_kind.entry(addr).or_insert((false, false));
+            match kind {
+                WatchKind::Write => entry.1 = true,
+                WatchKind::Read => entry.0 = true,
+                WatchKind::ReadWrite => entry.0 = true, // arbitrary
+            };
+        }
 
         Ok(true)
     }
 
-    fn remove_hw_watchpoint(&mut self, addr: u32, kind: WatchKind) -> TargetResult<bool, Self> {
-        let entry = self.watchpoint_kind.entry(addr).or_insert((false, false));
-        match kind {
-            WatchKind::Write => entry.1 = false,
-            WatchKind::Read => entry.0 = false,
-            WatchKind::ReadWrite => entry.0 = false, // arbitrary
-        };
-
-        if !self.watchpoint_kind.contains_key(&addr) {
-            let pos = match self.watchpoints.iter().position(|x| *x == addr) {
-                None => return Ok(false),
-                Some(pos) => pos,
+    fn remove_hw_watchpoint(
+        &mut self,
+        addr: u32,
+        len: u32,
+        kind: WatchKind,
+    ) -> TargetResult<bool, Self> {
+        for addr in addr..(addr + len) {
+            let entry = self.watchpoint_kind.entry(addr).or_insert((false, false));
+            match kind {
+                WatchKind::Write => entry.1 = false,
+                WatchKind::Read => entry.0 = false,
+                WatchKind::ReadWrite => entry.0 = false, // arbitrary
             };
-            self.watchpoints.remove(pos);
+
+            if !self.watchpoint_kind.contains_key(&addr) {
+                let pos = match self.watchpoints.iter().position(|x| *x == addr) {
+                    None => return Ok(false),
+                    Some(pos) => pos,
+                };
+                self.watchpoints.remove(pos);
+            }
         }
 
         Ok(true)
@@ -0,0 +1,86 @@
+use core::convert::TryInto;
+
+use gdbstub::arch::{DeserializeError, Registers};
+
+/// AArch64 core registers.
+///
+/// Source: https://github.com/bminor/binutils-gdb/blob/master/gdb/features/aarch64-core.xml
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Aarch64CoreRegs {
+    /// General purpose registers (X0-X30)
+    pub x: [u64; 31],
+    /// Stack Pointer (i.e: `SP_EL0` or `SP_ELx`, as selected by `PSTATE.SP`)
+    pub sp: u64,
+    /// Program Counter
+    pub pc: u64,
+    /// Process State (GDB's `cpsr`), including the NZCV condition flags
+    pub cpsr: u32,
+}
+
+impl Registers for Aarch64CoreRegs {
+    type ProgramCounter = u64;
+
+    fn pc(&self) -> Self::ProgramCounter {
+        self.pc
+    }
+
+    fn pc_mut(&mut self) -> Option<&mut Self::ProgramCounter> {
+        Some(&mut self.pc)
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        macro_rules! write_bytes {
+            ($bytes:expr) => {
+                for b in $bytes {
+                    write_byte(Some(*b))
+                }
+            };
+        }
+
+        for reg in self.x.iter() {
+            write_bytes!(&reg.to_le_bytes());
+        }
+        write_bytes!(&self.sp.to_le_bytes());
+        write_bytes!(&self.pc.to_le_bytes());
+        write_bytes!(&self.cpsr.to_le_bytes());
+    }
+
+    fn gdb_serialize_into(&self, buf: &mut [u8]) -> Option<usize> {
+        // 33 64-bit registers, followed by the 32-bit cpsr
+        let buf = buf.get_mut(..33 * 8 + 4)?;
+
+        let (regs, cpsr) = buf.split_at_mut(33 * 8);
+        let vals = self
+            .x
+            .iter()
+            .chain(core::iter::once(&self.sp))
+            .chain(core::iter::once(&self.pc));
+        for (chunk, val) in regs.chunks_exact_mut(8).zip(vals) {
+            chunk.copy_from_slice(&val.to_le_bytes());
+        }
+        cpsr.copy_from_slice(&self.cpsr.to_le_bytes());
+
+        Some(buf.len())
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), DeserializeError> {
+        // 33 64-bit registers, followed by the 32-bit cpsr
+        if bytes.len() != 33 * 8 + 4 {
+            return Err(DeserializeError::SizeMismatch);
+        }
+
+        let (regs, cpsr) = bytes.split_at(33 * 8);
+        let mut regs = regs
+            .chunks_exact(8)
+            .map(|c| u64::from_le_bytes(c.try_into().unwrap()));
+
+        for reg in self.x.iter_mut() {
+            *reg = regs.next().ok_or(DeserializeError::SizeMismatch)?
+        }
+        self.sp = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.pc = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.cpsr = u32::from_le_bytes(cpsr.try_into().unwrap());
+
+        Ok(())
+    }
+}
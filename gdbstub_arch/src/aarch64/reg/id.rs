@@ -0,0 +1,97 @@
+use gdbstub::arch::RegId;
+
+/// AArch64 core register identifier.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum Aarch64CoreRegId {
+    /// General purpose registers (X0-X30)
+    Gpr(u8),
+    /// Stack Pointer
+    Sp,
+    /// Program Counter
+    Pc,
+    /// Process State (GDB's `cpsr`)
+    Cpsr,
+}
+
+impl RegId for Aarch64CoreRegId {
+    fn from_raw_id(id: usize) -> Option<(Self, usize)> {
+        let reg = match id {
+            0..=30 => (Self::Gpr(id as u8), 8),
+            31 => (Self::Sp, 8),
+            32 => (Self::Pc, 8),
+            33 => (Self::Cpsr, 4),
+            _ => return None,
+        };
+        Some(reg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gdbstub::arch::{Arch, RegId, Registers};
+
+    use crate::aarch64::reg::Aarch64CoreRegs;
+    use crate::aarch64::AArch64;
+
+    /// Compare the following two values which are expected to be the same:
+    /// * length of data written by `Registers::gdb_serialize()` in byte
+    /// * sum of sizes of all registers obtained by `RegId::from_raw_id()`
+    #[test]
+    fn test_aarch64() {
+        let mut serialized_data_len = 0;
+        let counter = |_: Option<u8>| serialized_data_len += 1;
+        Aarch64CoreRegs::default().gdb_serialize(counter);
+
+        let mut i = 0;
+        let mut sum_reg_sizes = 0;
+        while let Some((_, size)) = <AArch64 as Arch>::RegId::from_raw_id(i) {
+            sum_reg_sizes += size;
+            i += 1;
+        }
+
+        assert_eq!(serialized_data_len, sum_reg_sizes);
+    }
+
+    #[test]
+    fn test_aarch64_round_trip() {
+        let mut regs = Aarch64CoreRegs::default();
+        regs.x[0] = 0x0123_4567_89ab_cdef;
+        regs.x[30] = 0xffff_0000_0000_1234;
+        regs.sp = 0x0000_ffff_ffff_f000;
+        regs.pc = 0xffff_8000_1000_0000;
+        regs.cpsr = 0x6000_03c5;
+
+        let mut data = Vec::new();
+        regs.gdb_serialize(|b| data.push(b.unwrap()));
+        assert_eq!(&data[33 * 8..], &[0xc5, 0x03, 0x00, 0x60]);
+
+        let mut deserialized = Aarch64CoreRegs::default();
+        deserialized.gdb_deserialize(&data).unwrap();
+        assert_eq!(deserialized, regs);
+
+        data.push(0);
+        assert!(deserialized.gdb_deserialize(&data).is_err());
+    }
+
+    #[test]
+    fn test_aarch64_serialize_into() {
+        let mut regs = Aarch64CoreRegs::default();
+        for (i, reg) in regs.x.iter_mut().enumerate() {
+            *reg = 0x0101_0101_0101_0101 * i as u64;
+        }
+        regs.sp = 0x0000_ffff_ffff_f000;
+        regs.pc = 0xffff_8000_1000_0000;
+        regs.cpsr = 0x6000_03c5;
+
+        let mut data = Vec::new();
+        regs.gdb_serialize(|b| data.push(b.unwrap()));
+
+        let mut buf = [0; 0x200];
+        let len = regs.gdb_serialize_into(&mut buf).unwrap();
+        assert_eq!(&buf[..len], &data[..]);
+
+        // too small a buffer falls back to `gdb_serialize`
+        assert_eq!(regs.gdb_serialize_into(&mut buf[..len - 1]), None);
+    }
+}
@@ -0,0 +1,8 @@
+//! `Register` structs for the AArch64 architecture.
+
+/// `RegId` definitions for the AArch64 architecture.
+pub mod id;
+
+mod aarch64_core;
+
+pub use aarch64_core::Aarch64CoreRegs;
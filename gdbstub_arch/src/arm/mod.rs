@@ -1,6 +1,6 @@
 //! Implementations for various ARM architectures.
 
-use gdbstub::arch::Arch;
+use gdbstub::arch::{Arch, TargetDescriptionFeature};
 
 pub mod reg;
 
@@ -8,7 +8,7 @@ pub mod reg;
 ///
 /// Extracted from the GDB documentation at
 /// [E.5.1.1 ARM Breakpoint Kinds](https://sourceware.org/gdb/current/onlinedocs/gdb/ARM-Breakpoint-Kinds.html#ARM-Breakpoint-Kinds)
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ArmBreakpointKind {
     /// 16-bit Thumb mode breakpoint.
     Thumb16,
@@ -42,4 +42,103 @@ impl Arch for Armv4t {
     fn target_description_xml() -> Option<&'static str> {
         Some(r#"<target version="1.0"><architecture>armv4t</architecture></target>"#)
     }
+
+    /// Defaults to an ARM mode breakpoint, as the current instruction set
+    /// cannot be determined from the address alone.
+    fn breakpoint_kind(_addr: u32) -> Option<ArmBreakpointKind> {
+        Some(ArmBreakpointKind::Arm32)
+    }
 }
+
+/// Implements `Arch` for ARM cores with a VFPv3 (or NEON) floating point unit,
+/// exposing the 32 double-precision registers (`d0`-`d31`) and `fpscr`.
+///
+/// The single-precision `s0`-`s31` (and NEON `q0`-`q15`) registers are derived
+/// from the `d` registers by the GDB client.
+pub enum ArmWithFpu {}
+
+impl Arch for ArmWithFpu {
+    type Usize = u32;
+    type Registers = reg::ArmCoreRegsWithFpu;
+    type RegId = reg::id::ArmCoreRegIdWithFpu;
+    type BreakpointKind = ArmBreakpointKind;
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(r#"<target version="1.0"><architecture>arm</architecture></target>"#)
+    }
+
+    fn target_description_xml_features() -> &'static [TargetDescriptionFeature<'static>] {
+        &[
+            TargetDescriptionFeature {
+                annex: "arm-core.xml",
+                xml: ARM_CORE_XML,
+            },
+            TargetDescriptionFeature {
+                annex: "arm-vfpv3.xml",
+                xml: ARM_VFPV3_XML,
+            },
+        ]
+    }
+
+    fn breakpoint_kind(_addr: u32) -> Option<ArmBreakpointKind> {
+        Some(ArmBreakpointKind::Arm32)
+    }
+}
+
+// Source: https://github.com/bminor/binutils-gdb/blob/master/gdb/features/arm/arm-core.xml
+const ARM_CORE_XML: &str = r#"<feature name="org.gnu.gdb.arm.core">
+  <reg name="r0" bitsize="32" type="uint32"/>
+  <reg name="r1" bitsize="32" type="uint32"/>
+  <reg name="r2" bitsize="32" type="uint32"/>
+  <reg name="r3" bitsize="32" type="uint32"/>
+  <reg name="r4" bitsize="32" type="uint32"/>
+  <reg name="r5" bitsize="32" type="uint32"/>
+  <reg name="r6" bitsize="32" type="uint32"/>
+  <reg name="r7" bitsize="32" type="uint32"/>
+  <reg name="r8" bitsize="32" type="uint32"/>
+  <reg name="r9" bitsize="32" type="uint32"/>
+  <reg name="r10" bitsize="32" type="uint32"/>
+  <reg name="r11" bitsize="32" type="uint32"/>
+  <reg name="r12" bitsize="32" type="uint32"/>
+  <reg name="sp" bitsize="32" type="data_ptr"/>
+  <reg name="lr" bitsize="32"/>
+  <reg name="pc" bitsize="32" type="code_ptr"/>
+  <reg name="cpsr" bitsize="32" regnum="25"/>
+</feature>"#;
+
+// Source: https://github.com/bminor/binutils-gdb/blob/master/gdb/features/arm/arm-vfpv3.xml
+const ARM_VFPV3_XML: &str = r#"<feature name="org.gnu.gdb.arm.vfp">
+  <reg name="d0" bitsize="64" type="ieee_double"/>
+  <reg name="d1" bitsize="64" type="ieee_double"/>
+  <reg name="d2" bitsize="64" type="ieee_double"/>
+  <reg name="d3" bitsize="64" type="ieee_double"/>
+  <reg name="d4" bitsize="64" type="ieee_double"/>
+  <reg name="d5" bitsize="64" type="ieee_double"/>
+  <reg name="d6" bitsize="64" type="ieee_double"/>
+  <reg name="d7" bitsize="64" type="ieee_double"/>
+  <reg name="d8" bitsize="64" type="ieee_double"/>
+  <reg name="d9" bitsize="64" type="ieee_double"/>
+  <reg name="d10" bitsize="64" type="ieee_double"/>
+  <reg name="d11" bitsize="64" type="ieee_double"/>
+  <reg name="d12" bitsize="64" type="ieee_double"/>
+  <reg name="d13" bitsize="64" type="ieee_double"/>
+  <reg name="d14" bitsize="64" type="ieee_double"/>
+  <reg name="d15" bitsize="64" type="ieee_double"/>
+  <reg name="d16" bitsize="64" type="ieee_double"/>
+  <reg name="d17" bitsize="64" type="ieee_double"/>
+  <reg name="d18" bitsize="64" type="ieee_double"/>
+  <reg name="d19" bitsize="64" type="ieee_double"/>
+  <reg name="d20" bitsize="64" type="ieee_double"/>
+  <reg name="d21" bitsize="64" type="ieee_double"/>
+  <reg name="d22" bitsize="64" type="ieee_double"/>
+  <reg name="d23" bitsize="64" type="ieee_double"/>
+  <reg name="d24" bitsize="64" type="ieee_double"/>
+  <reg name="d25" bitsize="64" type="ieee_double"/>
+  <reg name="d26" bitsize="64" type="ieee_double"/>
+  <reg name="d27" bitsize="64" type="ieee_double"/>
+  <reg name="d28" bitsize="64" type="ieee_double"/>
+  <reg name="d29" bitsize="64" type="ieee_double"/>
+  <reg name="d30" bitsize="64" type="ieee_double"/>
+  <reg name="d31" bitsize="64" type="ieee_double"/>
+  <reg name="fpscr" bitsize="32" type="int" group="float"/>
+</feature>"#;
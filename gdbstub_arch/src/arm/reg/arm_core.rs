@@ -1,4 +1,4 @@
-use gdbstub::arch::Registers;
+use gdbstub::arch::{DeserializeError, Registers};
 
 /// 32-bit ARM core registers.
 ///
@@ -24,6 +24,10 @@ impl Registers for ArmCoreRegs {
         self.pc
     }
 
+    fn pc_mut(&mut self) -> Option<&mut Self::ProgramCounter> {
+        Some(&mut self.pc)
+    }
+
     fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
         macro_rules! write_bytes {
             ($bytes:expr) => {
@@ -40,7 +44,7 @@ impl Registers for ArmCoreRegs {
         write_bytes!(&self.lr.to_le_bytes());
         write_bytes!(&self.pc.to_le_bytes());
 
-        // Floating point registers (unused)
+        // Floating point registers f0-f7 (12 bytes each) + fps (unused)
         for _ in 0..25 {
             (0..4).for_each(|_| write_byte(None))
         }
@@ -48,10 +52,10 @@ impl Registers for ArmCoreRegs {
         write_bytes!(&self.cpsr.to_le_bytes());
     }
 
-    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), DeserializeError> {
         // ensure bytes.chunks_exact(4) won't panic
         if bytes.len() % 4 != 0 {
-            return Err(());
+            return Err(DeserializeError::SizeMismatch);
         }
 
         use core::convert::TryInto;
@@ -60,23 +64,71 @@ impl Registers for ArmCoreRegs {
             .map(|c| u32::from_le_bytes(c.try_into().unwrap()));
 
         for reg in self.r.iter_mut() {
-            *reg = regs.next().ok_or(())?
+            *reg = regs.next().ok_or(DeserializeError::SizeMismatch)?
         }
-        self.sp = regs.next().ok_or(())?;
-        self.lr = regs.next().ok_or(())?;
-        self.pc = regs.next().ok_or(())?;
+        self.sp = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.lr = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.pc = regs.next().ok_or(DeserializeError::SizeMismatch)?;
 
         // Floating point registers (unused)
         for _ in 0..25 {
-            regs.next().ok_or(())?;
+            regs.next().ok_or(DeserializeError::SizeMismatch)?;
         }
 
-        self.cpsr = regs.next().ok_or(())?;
+        self.cpsr = regs.next().ok_or(DeserializeError::SizeMismatch)?;
 
         if regs.next().is_some() {
-            return Err(());
+            return Err(DeserializeError::SizeMismatch);
         }
 
         Ok(())
     }
+
+    const INDEXED_ACCESS: bool = true;
+
+    fn gdb_read_register(&self, id: usize, mut write_byte: impl FnMut(Option<u8>)) -> bool {
+        let val = match id {
+            0..=12 => self.r[id],
+            13 => self.sp,
+            14 => self.lr,
+            15 => self.pc,
+            // Floating point registers f0-f7 (12 bytes each) (unused)
+            16..=23 => {
+                (0..12).for_each(|_| write_byte(None));
+                return true;
+            }
+            // fps (unused)
+            24 => {
+                (0..4).for_each(|_| write_byte(None));
+                return true;
+            }
+            25 => self.cpsr,
+            _ => return false,
+        };
+
+        for b in val.to_le_bytes().iter() {
+            write_byte(Some(*b))
+        }
+        true
+    }
+
+    fn gdb_write_register(&mut self, id: usize, bytes: &[u8]) -> Result<(), DeserializeError> {
+        let reg = match id {
+            0..=12 => &mut self.r[id],
+            13 => &mut self.sp,
+            14 => &mut self.lr,
+            15 => &mut self.pc,
+            // Floating point registers (unused)
+            16..=24 => return Ok(()),
+            25 => &mut self.cpsr,
+            _ => return Err(DeserializeError::InvalidValue),
+        };
+
+        use core::convert::TryInto;
+        let bytes = bytes
+            .try_into()
+            .map_err(|_| DeserializeError::SizeMismatch)?;
+        *reg = u32::from_le_bytes(bytes);
+        Ok(())
+    }
 }
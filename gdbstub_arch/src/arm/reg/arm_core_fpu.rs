@@ -0,0 +1,89 @@
+use core::convert::TryInto;
+
+use gdbstub::arch::{DeserializeError, Registers};
+
+use super::ArmCoreRegs;
+
+/// 32-bit ARM core registers, alongside the VFPv3 floating point registers.
+///
+/// Unlike [`ArmCoreRegs`], the legacy FPA registers are not included.
+///
+/// Source: https://github.com/bminor/binutils-gdb/blob/master/gdb/features/arm/arm-core.xml
+/// Additionally: https://github.com/bminor/binutils-gdb/blob/master/gdb/features/arm/arm-vfpv3.xml
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct ArmCoreRegsWithFpu {
+    /// Core registers
+    pub core: ArmCoreRegs,
+    /// Double-precision floating point registers (D0-D31)
+    pub d: [u64; 32],
+    /// Floating Point Status and Control Register
+    pub fpscr: u32,
+}
+
+impl Registers for ArmCoreRegsWithFpu {
+    type ProgramCounter = u32;
+
+    fn pc(&self) -> Self::ProgramCounter {
+        self.core.pc
+    }
+
+    fn pc_mut(&mut self) -> Option<&mut Self::ProgramCounter> {
+        Some(&mut self.core.pc)
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        macro_rules! write_bytes {
+            ($bytes:expr) => {
+                for b in $bytes {
+                    write_byte(Some(*b))
+                }
+            };
+        }
+
+        for reg in self.core.r.iter() {
+            write_bytes!(&reg.to_le_bytes());
+        }
+        write_bytes!(&self.core.sp.to_le_bytes());
+        write_bytes!(&self.core.lr.to_le_bytes());
+        write_bytes!(&self.core.pc.to_le_bytes());
+        write_bytes!(&self.core.cpsr.to_le_bytes());
+
+        for reg in self.d.iter() {
+            write_bytes!(&reg.to_le_bytes());
+        }
+        write_bytes!(&self.fpscr.to_le_bytes());
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), DeserializeError> {
+        // 17 32-bit core registers, 32 64-bit `d` registers, and `fpscr`
+        if bytes.len() != 17 * 4 + 32 * 8 + 4 {
+            return Err(DeserializeError::SizeMismatch);
+        }
+
+        let (core, rest) = bytes.split_at(17 * 4);
+        let (d, fpscr) = rest.split_at(32 * 8);
+
+        let mut regs = core
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes(c.try_into().unwrap()));
+
+        for reg in self.core.r.iter_mut() {
+            *reg = regs.next().ok_or(DeserializeError::SizeMismatch)?
+        }
+        self.core.sp = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.core.lr = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.core.pc = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.core.cpsr = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+
+        let mut regs = d
+            .chunks_exact(8)
+            .map(|c| u64::from_le_bytes(c.try_into().unwrap()));
+
+        for reg in self.d.iter_mut() {
+            *reg = regs.next().ok_or(DeserializeError::SizeMismatch)?
+        }
+        self.fpscr = u32::from_le_bytes(fpscr.try_into().unwrap());
+
+        Ok(())
+    }
+}
@@ -23,14 +23,116 @@ pub enum ArmCoreRegId {
 impl RegId for ArmCoreRegId {
     fn from_raw_id(id: usize) -> Option<(Self, usize)> {
         let reg = match id {
-            0..=12 => Self::Gpr(id as u8),
-            13 => Self::Sp,
-            14 => Self::Lr,
-            15 => Self::Pc,
-            16..=23 => Self::Fpr((id as u8) - 16),
-            25 => Self::Cpsr,
+            0..=12 => (Self::Gpr(id as u8), 4),
+            13 => (Self::Sp, 4),
+            14 => (Self::Lr, 4),
+            15 => (Self::Pc, 4),
+            // FPA registers are 96-bit extended precision values
+            16..=23 => (Self::Fpr((id as u8) - 16), 12),
+            24 => (Self::Fps, 4),
+            25 => (Self::Cpsr, 4),
             _ => return None,
         };
-        Some((reg, 4))
+        Some(reg)
+    }
+}
+
+/// 32-bit ARM core + VFPv3 register identifier.
+///
+/// Matches the register numbering used by [`ArmWithFpu`](crate::arm::ArmWithFpu)'s
+/// target description, where `cpsr` is register 25, immediately followed by
+/// the VFP registers.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum ArmCoreRegIdWithFpu {
+    /// Core registers (excluding the FPA registers)
+    Core(ArmCoreRegId),
+    /// Double-precision floating point registers (D0-D31)
+    D(u8),
+    /// Floating Point Status and Control Register
+    Fpscr,
+}
+
+impl RegId for ArmCoreRegIdWithFpu {
+    fn from_raw_id(id: usize) -> Option<(Self, usize)> {
+        let reg = match id {
+            0..=15 | 25 => {
+                let (reg, size) = ArmCoreRegId::from_raw_id(id)?;
+                (Self::Core(reg), size)
+            }
+            26..=57 => (Self::D((id - 26) as u8), 8),
+            58 => (Self::Fpscr, 4),
+            _ => return None,
+        };
+        Some(reg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gdbstub::arch::{Arch, RegId, Registers};
+
+    /// Compare the following two values which are expected to be the same:
+    /// * length of data written by `Registers::gdb_serialize()` in byte
+    ///   (including any missing registers)
+    /// * sum of sizes of all registers obtained by `RegId::from_raw_id()`
+    fn test<A: Arch>() {
+        // Obtain the data length written by `gdb_serialize` by passing a custom
+        // closure.
+        let mut serialized_data_len = 0;
+        let counter = |_: Option<u8>| serialized_data_len += 1;
+        A::Registers::default().gdb_serialize(counter);
+
+        // Accumulate register sizes returned by `from_raw_id`.
+        let mut i = 0;
+        let mut sum_reg_sizes = 0;
+        while let Some((_, size)) = A::RegId::from_raw_id(i) {
+            sum_reg_sizes += size;
+            i += 1;
+        }
+
+        assert_eq!(serialized_data_len, sum_reg_sizes);
+    }
+
+    #[test]
+    fn test_armv4t() {
+        test::<crate::arm::Armv4t>()
+    }
+
+    #[test]
+    fn test_arm_with_fpu() {
+        use crate::arm::reg::id::{ArmCoreRegId, ArmCoreRegIdWithFpu};
+        use crate::arm::reg::ArmCoreRegsWithFpu;
+
+        let mut regs = ArmCoreRegsWithFpu::default();
+        regs.core.r[0] = 0x1234_5678;
+        regs.core.cpsr = 0x6000_001f;
+        regs.d[0] = 1.5f64.to_bits();
+        regs.d[31] = 0xdead_beef_cafe_f00d;
+        regs.fpscr = 0x0300_0000;
+
+        let mut data = Vec::new();
+        regs.gdb_serialize(|b| data.push(b.unwrap()));
+
+        // registers are serialized in raw id order, skipping the missing FPA regs
+        let sum_reg_sizes: usize = (0..=15)
+            .chain(25..=58)
+            .map(|i| ArmCoreRegIdWithFpu::from_raw_id(i).unwrap().1)
+            .sum();
+        assert_eq!(data.len(), sum_reg_sizes);
+        assert!(ArmCoreRegIdWithFpu::from_raw_id(16).is_none());
+        assert!(matches!(
+            ArmCoreRegIdWithFpu::from_raw_id(25),
+            Some((ArmCoreRegIdWithFpu::Core(ArmCoreRegId::Cpsr), 4))
+        ));
+        assert_eq!(&data[16 * 4..17 * 4], &0x6000_001fu32.to_le_bytes());
+        assert_eq!(&data[17 * 4..17 * 4 + 8], &1.5f64.to_le_bytes());
+
+        let mut deserialized = ArmCoreRegsWithFpu::default();
+        deserialized.gdb_deserialize(&data).unwrap();
+        assert_eq!(deserialized, regs);
+
+        data.pop();
+        assert!(deserialized.gdb_deserialize(&data).is_err());
     }
 }
@@ -4,5 +4,7 @@
 pub mod id;
 
 mod arm_core;
+mod arm_core_fpu;
 
 pub use arm_core::ArmCoreRegs;
+pub use arm_core_fpu::ArmCoreRegsWithFpu;
@@ -1,7 +1,7 @@
 //! Implementations for the MIPS architecture.
 
-use gdbstub::arch::Arch;
 use gdbstub::arch::RegId;
+use gdbstub::arch::{Arch, SingleStepGdbBehavior};
 
 pub mod reg;
 
@@ -9,7 +9,7 @@ pub mod reg;
 ///
 /// Extracted from the GDB documentation at
 /// [E.5.1.1 MIPS Breakpoint Kinds](https://sourceware.org/gdb/current/onlinedocs/gdb/MIPS-Breakpoint-Kinds.html#MIPS-Breakpoint-Kinds)
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MipsBreakpointKind {
     /// 16-bit MIPS16 mode breakpoint.
     Mips16,
@@ -70,6 +70,17 @@ impl<RegIdImpl: RegId> Arch for Mips<RegIdImpl> {
     fn target_description_xml() -> Option<&'static str> {
         Some(r#"<target version="1.0"><architecture>mips</architecture></target>"#)
     }
+
+    /// Defaults to a standard MIPS mode breakpoint, as the current ISA mode
+    /// cannot be determined from the address alone.
+    fn breakpoint_kind(_addr: u32) -> Option<MipsBreakpointKind> {
+        Some(MipsBreakpointKind::Mips32)
+    }
+
+    /// GDB always single-steps MIPS targets using temporary breakpoints.
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Ignored
+    }
 }
 
 impl<RegIdImpl: RegId> Arch for Mips64<RegIdImpl> {
@@ -81,6 +92,14 @@ impl<RegIdImpl: RegId> Arch for Mips64<RegIdImpl> {
     fn target_description_xml() -> Option<&'static str> {
         Some(r#"<target version="1.0"><architecture>mips64</architecture></target>"#)
     }
+
+    fn breakpoint_kind(_addr: u64) -> Option<MipsBreakpointKind> {
+        Some(MipsBreakpointKind::Mips32)
+    }
+
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Ignored
+    }
 }
 
 impl Arch for MipsWithDsp {
@@ -94,6 +113,14 @@ impl Arch for MipsWithDsp {
             r#"<target version="1.0"><architecture>mips</architecture><feature name="org.gnu.gdb.mips.dsp"></feature></target>"#,
         )
     }
+
+    fn breakpoint_kind(_addr: u32) -> Option<MipsBreakpointKind> {
+        Some(MipsBreakpointKind::Mips32)
+    }
+
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Ignored
+    }
 }
 
 impl Arch for Mips64WithDsp {
@@ -107,4 +134,12 @@ impl Arch for Mips64WithDsp {
             r#"<target version="1.0"><architecture>mips64</architecture><feature name="org.gnu.gdb.mips.dsp"></feature></target>"#,
         )
     }
+
+    fn breakpoint_kind(_addr: u64) -> Option<MipsBreakpointKind> {
+        Some(MipsBreakpointKind::Mips32)
+    }
+
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Ignored
+    }
 }
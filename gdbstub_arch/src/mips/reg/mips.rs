@@ -2,7 +2,7 @@ use core::convert::TryInto;
 
 use num_traits::PrimInt;
 
-use gdbstub::arch::Registers;
+use gdbstub::arch::{DeserializeError, Registers};
 use gdbstub::internal::LeBytes;
 
 /// MIPS registers.
@@ -98,6 +98,10 @@ where
         self.pc
     }
 
+    fn pc_mut(&mut self) -> Option<&mut Self::ProgramCounter> {
+        Some(&mut self.pc)
+    }
+
     fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
         macro_rules! write_le_bytes {
             ($value:expr) => {
@@ -140,12 +144,12 @@ where
         write_le_bytes!(&self.fpu.fir);
     }
 
-    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), DeserializeError> {
         let ptrsize = core::mem::size_of::<U>();
 
         // Ensure bytes contains enough data for all 72 registers
         if bytes.len() < ptrsize * 72 {
-            return Err(());
+            return Err(DeserializeError::SizeMismatch);
         }
 
         // All core registers are the same size
@@ -155,31 +159,31 @@ where
 
         // Read GPRs
         for reg in self.r.iter_mut() {
-            *reg = regs.next().ok_or(())?
+            *reg = regs.next().ok_or(DeserializeError::SizeMismatch)?
         }
 
         // Read Status register
-        self.cp0.status = regs.next().ok_or(())?;
+        self.cp0.status = regs.next().ok_or(DeserializeError::SizeMismatch)?;
 
         // Read Low and High registers
-        self.lo = regs.next().ok_or(())?;
-        self.hi = regs.next().ok_or(())?;
+        self.lo = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.hi = regs.next().ok_or(DeserializeError::SizeMismatch)?;
 
         // Read Badvaddr and Cause registers
-        self.cp0.badvaddr = regs.next().ok_or(())?;
-        self.cp0.cause = regs.next().ok_or(())?;
+        self.cp0.badvaddr = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.cp0.cause = regs.next().ok_or(DeserializeError::SizeMismatch)?;
 
         // Read the Program Counter
-        self.pc = regs.next().ok_or(())?;
+        self.pc = regs.next().ok_or(DeserializeError::SizeMismatch)?;
 
         // Read FPRs
         for reg in self.fpu.r.iter_mut() {
-            *reg = regs.next().ok_or(())?
+            *reg = regs.next().ok_or(DeserializeError::SizeMismatch)?
         }
 
         // Read FCSR and FIR registers
-        self.fpu.fcsr = regs.next().ok_or(())?;
-        self.fpu.fir = regs.next().ok_or(())?;
+        self.fpu.fcsr = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.fpu.fir = regs.next().ok_or(DeserializeError::SizeMismatch)?;
 
         Ok(())
     }
@@ -195,6 +199,10 @@ where
         self.core.pc
     }
 
+    fn pc_mut(&mut self) -> Option<&mut Self::ProgramCounter> {
+        Some(&mut self.core.pc)
+    }
+
     fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
         macro_rules! write_le_bytes {
             ($value:expr) => {
@@ -226,7 +234,7 @@ where
         write_le_bytes!(&self.dsp.restart);
     }
 
-    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), DeserializeError> {
         // Deserialize the core registers first
         self.core.gdb_deserialize(bytes)?;
 
@@ -234,7 +242,7 @@ where
         // and the dspctl register which is always 4 bytes
         let ptrsize = core::mem::size_of::<U>();
         if bytes.len() < (ptrsize * 79) + 4 {
-            return Err(());
+            return Err(DeserializeError::SizeMismatch);
         }
 
         // Calculate the offsets to the DSP registers based on the ptrsize
@@ -246,12 +254,12 @@ where
             .chunks_exact(ptrsize)
             .map(|c| U::from_le_bytes(c).unwrap());
 
-        self.dsp.hi1 = regs.next().ok_or(())?;
-        self.dsp.lo1 = regs.next().ok_or(())?;
-        self.dsp.hi2 = regs.next().ok_or(())?;
-        self.dsp.lo2 = regs.next().ok_or(())?;
-        self.dsp.hi3 = regs.next().ok_or(())?;
-        self.dsp.lo3 = regs.next().ok_or(())?;
+        self.dsp.hi1 = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.dsp.lo1 = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.dsp.hi2 = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.dsp.lo2 = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.dsp.hi3 = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.dsp.lo3 = regs.next().ok_or(DeserializeError::SizeMismatch)?;
 
         // Dspctl will always be a u32
         self.dsp.dspctl =
@@ -262,7 +270,7 @@ where
             bytes[dspctl_start + 4..]
                 .chunks_exact(ptrsize)
                 .next()
-                .ok_or(())?,
+                .ok_or(DeserializeError::SizeMismatch)?,
         )
         .unwrap();
 
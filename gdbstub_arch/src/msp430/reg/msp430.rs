@@ -1,4 +1,4 @@
-use gdbstub::arch::Registers;
+use gdbstub::arch::{DeserializeError, Registers};
 
 /// 16-bit TI-MSP430 registers.
 #[derive(Debug, Default, Clone, Eq, PartialEq)]
@@ -20,6 +20,10 @@ impl Registers for Msp430Regs {
         self.pc
     }
 
+    fn pc_mut(&mut self) -> Option<&mut Self::ProgramCounter> {
+        Some(&mut self.pc)
+    }
+
     fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
         macro_rules! write_bytes {
             ($bytes:expr) => {
@@ -38,10 +42,10 @@ impl Registers for Msp430Regs {
         }
     }
 
-    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), DeserializeError> {
         // ensure bytes.chunks_exact(2) won't panic
         if bytes.len() % 2 != 0 {
-            return Err(());
+            return Err(DeserializeError::SizeMismatch);
         }
 
         use core::convert::TryInto;
@@ -49,21 +53,21 @@ impl Registers for Msp430Regs {
             .chunks_exact(2)
             .map(|c| u16::from_le_bytes(c.try_into().unwrap()));
 
-        self.pc = regs.next().ok_or(())?;
-        self.sp = regs.next().ok_or(())?;
-        self.sr = regs.next().ok_or(())?;
+        self.pc = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.sp = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.sr = regs.next().ok_or(DeserializeError::SizeMismatch)?;
 
         // Constant Generator (CG/R3) should always be 0
-        if regs.next().ok_or(())? != 0 {
-            return Err(());
+        if regs.next().ok_or(DeserializeError::SizeMismatch)? != 0 {
+            return Err(DeserializeError::InvalidValue);
         }
 
         for reg in self.r.iter_mut() {
-            *reg = regs.next().ok_or(())?
+            *reg = regs.next().ok_or(DeserializeError::SizeMismatch)?
         }
 
         if regs.next().is_some() {
-            return Err(());
+            return Err(DeserializeError::SizeMismatch);
         }
 
         Ok(())
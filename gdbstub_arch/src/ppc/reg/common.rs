@@ -1,4 +1,4 @@
-use gdbstub::arch::Registers;
+use gdbstub::arch::{DeserializeError, Registers};
 
 use super::PpcVector;
 
@@ -46,6 +46,10 @@ impl Registers for PowerPcCommonRegs {
         self.pc
     }
 
+    fn pc_mut(&mut self) -> Option<&mut Self::ProgramCounter> {
+        Some(&mut self.pc)
+    }
+
     fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
         macro_rules! write_bytes {
             ($bytes:expr) => {
@@ -81,9 +85,9 @@ impl Registers for PowerPcCommonRegs {
         write_regs!(vscr, vrsave);
     }
 
-    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), DeserializeError> {
         if bytes.len() < 0x3a4 {
-            return Err(());
+            return Err(DeserializeError::SizeMismatch);
         }
 
         let mut regs = bytes[0..0x80]
@@ -91,7 +95,7 @@ impl Registers for PowerPcCommonRegs {
             .map(|x| u32::from_be_bytes(x.try_into().unwrap()));
 
         for reg in &mut self.r {
-            *reg = regs.next().ok_or(())?;
+            *reg = regs.next().ok_or(DeserializeError::SizeMismatch)?;
         }
 
         let mut regs = bytes[0x80..0x180]
@@ -99,7 +103,7 @@ impl Registers for PowerPcCommonRegs {
             .map(|x| f64::from_be_bytes(x.try_into().unwrap()));
 
         for reg in &mut self.f {
-            *reg = regs.next().ok_or(())?;
+            *reg = regs.next().ok_or(DeserializeError::SizeMismatch)?;
         }
 
         macro_rules! parse_regs {
@@ -108,7 +112,7 @@ impl Registers for PowerPcCommonRegs {
                     .chunks_exact(4)
                     .map(|x| u32::from_be_bytes(x.try_into().unwrap()));
                 $(
-                    self.$reg = regs.next().ok_or(())?;
+                    self.$reg = regs.next().ok_or(DeserializeError::SizeMismatch)?;
                 )*
             }
         }
@@ -120,7 +124,7 @@ impl Registers for PowerPcCommonRegs {
             .map(|x| u128::from_be_bytes(x.try_into().unwrap()));
 
         for reg in &mut self.vr {
-            *reg = regs.next().ok_or(())?;
+            *reg = regs.next().ok_or(DeserializeError::SizeMismatch)?;
         }
 
         parse_regs!(0x39c..0x3a4, vscr, vrsave);
@@ -41,3 +41,59 @@ macro_rules! impl_riscv_reg_id {
 
 impl_riscv_reg_id!(u32);
 impl_riscv_reg_id!(u64);
+
+#[cfg(test)]
+mod tests {
+    use gdbstub::arch::{Arch, RegId, Registers};
+
+    /// Compare the following two values which are expected to be the same:
+    /// * length of data written by `Registers::gdb_serialize()` in byte
+    /// * sum of sizes of the registers obtained by `RegId::from_raw_id()`, up
+    ///   to (and including) the PC
+    ///
+    /// The FPRs and CSRs aren't part of the core register file.
+    fn test<A: Arch>() {
+        let mut serialized_data_len = 0;
+        let counter = |_: Option<u8>| serialized_data_len += 1;
+        A::Registers::default().gdb_serialize(counter);
+
+        let sum_reg_sizes: usize = (0..=32).map(|i| A::RegId::from_raw_id(i).unwrap().1).sum();
+
+        assert_eq!(serialized_data_len, sum_reg_sizes);
+    }
+
+    /// Check that `gdb_deserialize` is the inverse of `gdb_serialize`.
+    fn test_round_trip<A: Arch>(regs: A::Registers) {
+        let mut data = Vec::new();
+        regs.gdb_serialize(|b| data.push(b.unwrap()));
+
+        let mut deserialized = A::Registers::default();
+        deserialized.gdb_deserialize(&data).unwrap();
+        assert_eq!(deserialized, regs);
+
+        data.pop();
+        assert!(A::Registers::default().gdb_deserialize(&data).is_err());
+    }
+
+    #[test]
+    fn test_riscv32() {
+        test::<crate::riscv::Riscv32>();
+
+        let mut regs = crate::riscv::reg::RiscvCoreRegs::<u32>::default();
+        regs.x[1] = 0x8000_0010;
+        regs.x[31] = 0xdead_beef;
+        regs.pc = 0x8000_0000;
+        test_round_trip::<crate::riscv::Riscv32>(regs);
+    }
+
+    #[test]
+    fn test_riscv64() {
+        test::<crate::riscv::Riscv64>();
+
+        let mut regs = crate::riscv::reg::RiscvCoreRegs::<u64>::default();
+        regs.x[1] = 0x8000_0000_0000_0010;
+        regs.x[31] = 0xdead_beef_cafe_f00d;
+        regs.pc = 0x8000_0000_0000_0000;
+        test_round_trip::<crate::riscv::Riscv64>(regs);
+    }
+}
@@ -1,6 +1,6 @@
 use num_traits::PrimInt;
 
-use gdbstub::arch::Registers;
+use gdbstub::arch::{DeserializeError, Registers};
 use gdbstub::internal::LeBytes;
 
 /// RISC-V Integer registers.
@@ -28,6 +28,10 @@ where
         self.pc
     }
 
+    fn pc_mut(&mut self) -> Option<&mut Self::ProgramCounter> {
+        Some(&mut self.pc)
+    }
+
     fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
         macro_rules! write_le_bytes {
             ($value:expr) => {
@@ -46,16 +50,16 @@ where
             write_le_bytes!(reg);
         }
 
-        // Program Counter is regnum 33
+        // Program Counter is regnum 32
         write_le_bytes!(&self.pc);
     }
 
-    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), DeserializeError> {
         let ptrsize = core::mem::size_of::<U>();
 
         // ensure bytes.chunks_exact(ptrsize) won't panic
         if bytes.len() % ptrsize != 0 {
-            return Err(());
+            return Err(DeserializeError::SizeMismatch);
         }
 
         let mut regs = bytes
@@ -64,12 +68,12 @@ where
 
         // Read GPRs
         for reg in self.x.iter_mut() {
-            *reg = regs.next().ok_or(())?
+            *reg = regs.next().ok_or(DeserializeError::SizeMismatch)?
         }
-        self.pc = regs.next().ok_or(())?;
+        self.pc = regs.next().ok_or(DeserializeError::SizeMismatch)?;
 
         if regs.next().is_some() {
-            return Err(());
+            return Err(DeserializeError::SizeMismatch);
         }
 
         Ok(())
@@ -1,7 +1,7 @@
 //! Implementations for various x86 architectures.
 
-use gdbstub::arch::Arch;
 use gdbstub::arch::RegId;
+use gdbstub::arch::{Arch, SingleStepGdbBehavior};
 
 pub mod reg;
 
@@ -26,6 +26,11 @@ impl<RegIdImpl: RegId> Arch for X86_64_SSE<RegIdImpl> {
             r#"<target version="1.0"><architecture>i386:x86-64</architecture><feature name="org.gnu.gdb.i386.sse"></feature></target>"#,
         )
     }
+
+    /// GDB can't emulate single-stepping on x86.
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Required
+    }
 }
 
 /// Implements `Arch` for 32-bit x86 + SSE Extensions.
@@ -49,4 +54,8 @@ impl<RegIdImpl: RegId> Arch for X86_SSE<RegIdImpl> {
             r#"<target version="1.0"><architecture>i386:intel</architecture><feature name="org.gnu.gdb.i386.sse"></feature></target>"#,
         )
     }
+
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Required
+    }
 }
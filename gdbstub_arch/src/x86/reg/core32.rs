@@ -1,6 +1,6 @@
 use core::convert::TryInto;
 
-use gdbstub::arch::Registers;
+use gdbstub::arch::{DeserializeError, Registers};
 
 use super::{X86SegmentRegs, X87FpuInternalRegs, F80};
 
@@ -49,6 +49,10 @@ impl Registers for X86CoreRegs {
         self.eip
     }
 
+    fn pc_mut(&mut self) -> Option<&mut Self::ProgramCounter> {
+        Some(&mut self.eip)
+    }
+
     fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
         macro_rules! write_bytes {
             ($bytes:expr) => {
@@ -89,9 +93,9 @@ impl Registers for X86CoreRegs {
         (0..4).for_each(|_| write_byte(None))
     }
 
-    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), DeserializeError> {
         if bytes.len() < 0x138 {
-            return Err(());
+            return Err(DeserializeError::SizeMismatch);
         }
 
         macro_rules! parse_regs {
@@ -100,7 +104,7 @@ impl Registers for X86CoreRegs {
                     .chunks_exact(4)
                     .map(|x| u32::from_le_bytes(x.try_into().unwrap()));
                 $(
-                    self.$reg = regs.next().ok_or(())?;
+                    self.$reg = regs.next().ok_or(DeserializeError::SizeMismatch)?;
                 )*
             }
         }
@@ -112,7 +116,10 @@ impl Registers for X86CoreRegs {
         let mut regs = bytes[0x40..0x90].chunks_exact(10).map(TryInto::try_into);
 
         for reg in self.st.iter_mut() {
-            *reg = regs.next().ok_or(())?.map_err(|_| ())?;
+            *reg = regs
+                .next()
+                .ok_or(DeserializeError::SizeMismatch)?
+                .map_err(|_| DeserializeError::SizeMismatch)?;
         }
 
         self.fpu.gdb_deserialize(&bytes[0x90..0xb0])?;
@@ -122,7 +129,7 @@ impl Registers for X86CoreRegs {
             .map(|x| u128::from_le_bytes(x.try_into().unwrap()));
 
         for reg in self.xmm.iter_mut() {
-            *reg = regs.next().ok_or(())?;
+            *reg = regs.next().ok_or(DeserializeError::SizeMismatch)?;
         }
 
         self.mxcsr = u32::from_le_bytes(bytes[0x130..0x134].try_into().unwrap());
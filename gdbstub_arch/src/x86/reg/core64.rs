@@ -1,6 +1,6 @@
 use core::convert::TryInto;
 
-use gdbstub::arch::Registers;
+use gdbstub::arch::{DeserializeError, Registers};
 
 use super::{X86SegmentRegs, X87FpuInternalRegs, F80};
 
@@ -35,6 +35,10 @@ impl Registers for X86_64CoreRegs {
         self.rip
     }
 
+    fn pc_mut(&mut self) -> Option<&mut Self::ProgramCounter> {
+        Some(&mut self.rip)
+    }
+
     fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
         macro_rules! write_bytes {
             ($bytes:expr) => {
@@ -78,9 +82,9 @@ impl Registers for X86_64CoreRegs {
         (0..0x18).for_each(|_| write_byte(None))
     }
 
-    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), DeserializeError> {
         if bytes.len() < 0x218 {
-            return Err(());
+            return Err(DeserializeError::SizeMismatch);
         }
 
         let mut regs = bytes[0..0x80]
@@ -88,7 +92,7 @@ impl Registers for X86_64CoreRegs {
             .map(|x| u64::from_le_bytes(x.try_into().unwrap()));
 
         for reg in self.regs.iter_mut() {
-            *reg = regs.next().ok_or(())?;
+            *reg = regs.next().ok_or(DeserializeError::SizeMismatch)?;
         }
 
         self.rip = u64::from_le_bytes(bytes[0x80..0x88].try_into().unwrap());
@@ -99,7 +103,10 @@ impl Registers for X86_64CoreRegs {
         let mut regs = bytes[0xA4..0xF4].chunks_exact(10).map(TryInto::try_into);
 
         for reg in self.st.iter_mut() {
-            *reg = regs.next().ok_or(())?.map_err(|_| ())?;
+            *reg = regs
+                .next()
+                .ok_or(DeserializeError::SizeMismatch)?
+                .map_err(|_| DeserializeError::SizeMismatch)?;
         }
 
         self.fpu.gdb_deserialize(&bytes[0xF4..0x114])?;
@@ -109,7 +116,7 @@ impl Registers for X86_64CoreRegs {
             .map(|x| u128::from_le_bytes(x.try_into().unwrap()));
 
         for reg in self.xmm.iter_mut() {
-            *reg = regs.next().ok_or(())?;
+            *reg = regs.next().ok_or(DeserializeError::SizeMismatch)?;
         }
 
         self.mxcsr = u32::from_le_bytes(bytes[0x214..0x218].try_into().unwrap());
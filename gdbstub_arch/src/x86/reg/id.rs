@@ -172,8 +172,8 @@ impl RegId for X86_64CoreRegId {
 
         let r = match id {
             0..=15 => (Gpr(id as u8), 8),
-            16 => (Rip, 4),
-            17 => (Eflags, 8),
+            16 => (Rip, 8),
+            17 => (Eflags, 4),
             18..=23 => (Segment(X86SegmentRegId::from_u8(id as u8 - 18)?), 4),
             24..=31 => (St(id as u8 - 24), 10),
             32..=39 => (Fpu(X87FpuInternalRegId::from_u8(id as u8 - 32)?), 4),
@@ -224,4 +224,18 @@ mod tests {
     fn test_x86_64() {
         test::<crate::x86::reg::X86_64CoreRegs, crate::x86::reg::id::X86_64CoreRegId>()
     }
+
+    #[test]
+    fn test_x86_64_rip_eflags() {
+        use crate::x86::reg::id::X86_64CoreRegId;
+
+        assert!(matches!(
+            X86_64CoreRegId::from_raw_id(16),
+            Some((X86_64CoreRegId::Rip, 8))
+        ));
+        assert!(matches!(
+            X86_64CoreRegId::from_raw_id(17),
+            Some((X86_64CoreRegId::Eflags, 4))
+        ));
+    }
 }
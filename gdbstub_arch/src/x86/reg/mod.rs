@@ -2,7 +2,7 @@
 
 use core::convert::TryInto;
 
-use gdbstub::arch::Registers;
+use gdbstub::arch::{DeserializeError, Registers};
 
 /// `RegId` definitions for x86 architectures.
 pub mod id;
@@ -67,23 +67,23 @@ impl Registers for X87FpuInternalRegs {
         write_bytes!(&self.fop.to_le_bytes());
     }
 
-    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), DeserializeError> {
         if bytes.len() != 0x20 {
-            return Err(());
+            return Err(DeserializeError::SizeMismatch);
         }
 
         let mut regs = bytes
             .chunks_exact(4)
             .map(|x| u32::from_le_bytes(x.try_into().unwrap()));
 
-        self.fctrl = regs.next().ok_or(())?;
-        self.fstat = regs.next().ok_or(())?;
-        self.ftag = regs.next().ok_or(())?;
-        self.fiseg = regs.next().ok_or(())?;
-        self.fioff = regs.next().ok_or(())?;
-        self.foseg = regs.next().ok_or(())?;
-        self.fooff = regs.next().ok_or(())?;
-        self.fop = regs.next().ok_or(())?;
+        self.fctrl = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.fstat = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.ftag = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.fiseg = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.fioff = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.foseg = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.fooff = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.fop = regs.next().ok_or(DeserializeError::SizeMismatch)?;
 
         Ok(())
     }
@@ -134,21 +134,21 @@ impl Registers for X86SegmentRegs {
         write_bytes!(&self.gs.to_le_bytes());
     }
 
-    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), DeserializeError> {
         if bytes.len() != core::mem::size_of::<u32>() * 6 {
-            return Err(());
+            return Err(DeserializeError::SizeMismatch);
         }
 
         let mut regs = bytes
             .chunks_exact(4)
             .map(|x| u32::from_le_bytes(x.try_into().unwrap()));
 
-        self.cs = regs.next().ok_or(())?;
-        self.ss = regs.next().ok_or(())?;
-        self.ds = regs.next().ok_or(())?;
-        self.es = regs.next().ok_or(())?;
-        self.fs = regs.next().ok_or(())?;
-        self.gs = regs.next().ok_or(())?;
+        self.cs = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.ss = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.ds = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.es = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.fs = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.gs = regs.next().ok_or(DeserializeError::SizeMismatch)?;
 
         Ok(())
     }
@@ -48,6 +48,25 @@ impl RegId for () {
 ///
 /// e.g: for ARM:
 /// github.com/bminor/binutils-gdb/blob/master/gdb/features/arm/arm-core.xml
+///
+/// #### Mode-dependent register files
+///
+/// On some architectures (e.g: MIPS, or x86 real / protected / long mode), the
+/// effective width of the register file varies with the target's current
+/// execution mode. Such architectures should track the current mode as part of
+/// their `Registers` type, with `gdb_serialize` / `gdb_deserialize` using the
+/// appropriate register widths for that mode.
+///
+/// `Target::read_registers` reports the target's current mode by filling in the
+/// register file, and `gdbstub` always deserializes `G` packets into the
+/// target's current register file (i.e: the mode is known by the time
+/// `gdb_deserialize` is called).
+///
+/// As the GDB client derives the expected register widths from the target
+/// description, such targets should also report a mode-appropriate target
+/// description via the
+/// [`TargetDescriptionXmlOverride`](crate::target::ext::target_description_xml_override)
+/// extension.
 // TODO: add way to de/serialize arbitrary "missing"/"uncollected" registers.
 pub trait Registers: Default + Debug + Clone + PartialEq {
     /// The type of the architecture's program counter / instruction pointer.
@@ -57,14 +76,120 @@ pub trait Registers: Default + Debug + Clone + PartialEq {
     /// Return the value of the program counter / instruction pointer.
     fn pc(&self) -> Self::ProgramCounter;
 
+    /// (optional) Return a mutable reference to the program counter /
+    /// instruction pointer.
+    ///
+    /// Used to resume the target at a specific address (i.e: when GDB sends a
+    /// `c <addr>` / `s <addr>` packet). `gdbstub` reads the register file,
+    /// updates the program counter, and writes the register file back before
+    /// resuming the target.
+    ///
+    /// If this method returns `None`, requests to resume at a specific
+    /// address are rejected with a non-fatal error.
+    fn pc_mut(&mut self) -> Option<&mut Self::ProgramCounter> {
+        None
+    }
+
     /// Serialize `self` into a GDB register bytestream.
     ///
     /// Missing registers are serialized by passing `None` to write_byte.
     fn gdb_serialize(&self, write_byte: impl FnMut(Option<u8>));
 
+    /// (optional) Serialize `self` into `buf` in a single pass, returning the
+    /// number of bytes written.
+    ///
+    /// This is a faster alternative to [`gdb_serialize`](Self::gdb_serialize)
+    /// for the common case where every register is available, as it avoids
+    /// invoking a closure for each byte of the register file.
+    ///
+    /// Return `None` if `buf` is too small, or if any registers are missing,
+    /// in which case `gdbstub` falls back to `gdb_serialize`.
+    fn gdb_serialize_into(&self, buf: &mut [u8]) -> Option<usize> {
+        let _ = buf;
+        None
+    }
+
     /// Deserialize a GDB register bytestream into `self`.
-    #[allow(clippy::result_unit_err)]
-    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()>;
+    ///
+    /// The returned [`DeserializeError`] is reported back to GDB, and should
+    /// be used to indicate whether the bytestream was the wrong size, or if it
+    /// contained an invalid register value.
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), DeserializeError>;
+
+    /// (optional) Include the registers reported by
+    /// [`gdb_expedite`](Self::gdb_expedite) in `T` stop reply packets.
+    ///
+    /// When set, `gdbstub` reads the stopped thread's registers each time it
+    /// reports a `T` stop reply.
+    const EXPEDITE: bool = false;
+
+    /// (optional) Report a small set of "expedited" registers (typically the
+    /// PC, SP, and FP), which are included in `T` stop reply packets.
+    ///
+    /// Each register is reported by calling `write_reg` with its GDB register
+    /// number and its serialized value (i.e: using the same encoding as
+    /// `gdb_serialize`).
+    ///
+    /// The GDB client typically fetches the entire register file via `g`
+    /// after each stop. Expediting the registers it needs right away can save
+    /// a round trip on slow transports.
+    ///
+    /// Only called if [`EXPEDITE`](Self::EXPEDITE) is set.
+    fn gdb_expedite(&self, write_reg: impl FnMut(usize, &[u8])) {
+        let _ = write_reg;
+    }
+
+    /// (optional) Service single register accesses (i.e: `p` / `P` packets)
+    /// using [`gdb_read_register`](Self::gdb_read_register) and
+    /// [`gdb_write_register`](Self::gdb_write_register).
+    ///
+    /// This provides a convenient alternative to implementing the
+    /// [`SingleRegisterAccess`](crate::target::ext::base::SingleRegisterAccess)
+    /// extension for targets whose registers are stored in a plain struct, as
+    /// `gdbstub` takes care of fetching the register file (via
+    /// `read_registers`) and writing it back (via `write_registers`).
+    ///
+    /// If the target _does_ implement `SingleRegisterAccess`, the extension
+    /// takes precedence, and these methods are never called.
+    const INDEXED_ACCESS: bool = false;
+
+    /// (optional) Serialize the register with the given GDB register number,
+    /// using the same encoding as `gdb_serialize`.
+    ///
+    /// Returns `false` if the register doesn't exist.
+    ///
+    /// Only called if [`INDEXED_ACCESS`](Self::INDEXED_ACCESS) is set.
+    fn gdb_read_register(&self, id: usize, write_byte: impl FnMut(Option<u8>)) -> bool {
+        let _ = (id, write_byte);
+        false
+    }
+
+    /// (optional) Deserialize the register with the given GDB register number
+    /// from `bytes`, using the same encoding as `gdb_deserialize`.
+    ///
+    /// Only called with registers for which `gdb_read_register` returns
+    /// `true`.
+    ///
+    /// Only called if [`INDEXED_ACCESS`](Self::INDEXED_ACCESS) is set.
+    fn gdb_write_register(&mut self, id: usize, bytes: &[u8]) -> Result<(), DeserializeError> {
+        let _ = (id, bytes);
+        Err(DeserializeError::SizeMismatch)
+    }
+}
+
+/// Error returned by [`Registers::gdb_deserialize`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeserializeError {
+    /// The register bytestream was either too short, or too long. Reported to
+    /// GDB as `EINVAL`.
+    ///
+    /// This typically indicates a mismatch between the target's register file
+    /// and the target description used by GDB.
+    SizeMismatch,
+    /// The register bytestream contained an invalid register value (e.g: a
+    /// non-zero value for a hardwired zero register). Reported to GDB as
+    /// `ERANGE`.
+    InvalidValue,
 }
 
 /// Breakpoint kind for specific architectures.
@@ -82,6 +207,10 @@ pub trait Registers: Default + Debug + Clone + PartialEq {
 ///
 /// If no architecture-specific value is being used, _kind_ should be set to
 /// '0', and the `BreakpointKind` associated type should be `()`.
+///
+/// Incoming breakpoint kinds are validated using
+/// [`from_usize`](Self::from_usize). A kind of `0` which fails validation is
+/// instead resolved using [`Arch::breakpoint_kind`].
 pub trait BreakpointKind: Sized + Debug {
     /// Parse `Self` from a raw usize.
     fn from_usize(kind: usize) -> Option<Self>;
@@ -104,6 +233,39 @@ impl BreakpointKind for usize {
     }
 }
 
+/// A named block of a target description (e.g: `arm-vfpv3.xml`), served to
+/// GDB as a separate XML file.
+///
+/// Each feature is referenced from the top-level `target.xml` via an
+/// `<xi:include>` element, which `gdbstub` inserts automatically. See
+/// [`Arch::target_description_xml_features`] for more details.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TargetDescriptionFeature<'a> {
+    /// The feature's filename (i.e: the `qXfer:features:read` annex).
+    pub annex: &'a str,
+    /// The feature's XML (i.e: a `<feature>` element).
+    pub xml: &'a str,
+}
+
+/// Describes how the GDB client uses single-stepping on a particular
+/// architecture. See [`Arch::single_step_gdb_behavior`] for more details.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SingleStepGdbBehavior {
+    /// GDB can emulate single-stepping on this architecture (by inserting
+    /// temporary breakpoints at the next instruction(s) and continuing), and
+    /// will do so whenever the target doesn't support single-stepping.
+    Optional,
+    /// GDB cannot emulate single-stepping on this architecture, and requires
+    /// the target to support single-stepping.
+    Required,
+    /// GDB always emulates single-stepping on this architecture, and will
+    /// never ask the target to single-step (even if it supports doing so).
+    Ignored,
+    /// It's not known how GDB behaves on this architecture.
+    Unknown,
+}
+
 /// Encodes architecture-specific information, such as pointer size, register
 /// layout, etc...
 ///
@@ -150,4 +312,75 @@ pub trait Arch {
     fn target_description_xml() -> Option<&'static str> {
         None
     }
+
+    /// (optional) Return any additional target description features, which
+    /// are served to GDB as separate XML files.
+    ///
+    /// Real-world target descriptions typically split registers across
+    /// several `<feature>` blocks (e.g: `org.gnu.gdb.arm.core` and
+    /// `org.gnu.gdb.arm.vfp`). Instead of inlining each block into
+    /// [`target_description_xml`](Self::target_description_xml), an `Arch` can
+    /// declare them here, and `gdbstub` will add an `<xi:include>` for each
+    /// feature to the end of the top-level `<target>` element.
+    ///
+    /// e.g: the following top-level description + features:
+    ///
+    /// ```text
+    /// <target version="1.0"><architecture>arm</architecture></target>
+    /// core.xml: <feature name="org.gnu.gdb.arm.core">...</feature>
+    /// vfp.xml:  <feature name="org.gnu.gdb.arm.vfp">...</feature>
+    /// ```
+    ///
+    /// are served to GDB as:
+    ///
+    /// ```text
+    /// <target version="1.0"><architecture>arm</architecture><xi:include href="core.xml"/><xi:include href="vfp.xml"/></target>
+    /// ```
+    ///
+    /// This method has no effect unless `target_description_xml` is also
+    /// implemented.
+    fn target_description_xml_features() -> &'static [TargetDescriptionFeature<'static>] {
+        &[]
+    }
+
+    /// (optional) Return the kind of software breakpoint to set at `addr`,
+    /// when the GDB client doesn't specify one (i.e: sends a breakpoint
+    /// `kind` of `0`, which the arch's [`BreakpointKind`] doesn't accept).
+    ///
+    /// On architectures with mixed-width instruction sets (e.g: ARM / Thumb,
+    /// or MIPS / MIPS16), the appropriate kind may depend on the address
+    /// being patched.
+    ///
+    /// By default, such breakpoints are rejected.
+    fn breakpoint_kind(_addr: Self::Usize) -> Option<Self::BreakpointKind> {
+        None
+    }
+
+    /// (optional) Describe how the GDB client uses single-stepping on this
+    /// architecture.
+    ///
+    /// Not all targets can single-step in hardware. On architectures where
+    /// GDB knows how to decode the instruction stream, GDB can emulate
+    /// single-stepping itself by placing temporary breakpoints at the next
+    /// instruction(s) and continuing. Targets opt into this behavior by
+    /// returning `false` from `supports_single_step` (see
+    /// [`SingleThreadOps`](crate::target::ext::base::singlethread::SingleThreadOps::supports_single_step)
+    /// / [`MultiThreadOps`](crate::target::ext::base::multithread::MultiThreadOps::supports_single_step)),
+    /// in which case `gdbstub` will not advertise the `s` / `S` `vCont`
+    /// actions to the GDB client.
+    ///
+    /// On architectures where GDB _requires_ single-stepping support, targets
+    /// without a native single-step mechanism must emulate one themselves
+    /// (e.g: by setting an internal breakpoint after the current
+    /// instruction), and report the result as `DoneStep`. As doing so
+    /// requires decoding the target's instruction set, `gdbstub` doesn't
+    /// offer a generic helper for computing the next instruction's address.
+    ///
+    /// `gdbstub` only uses this hint to warn about targets which don't
+    /// support single-stepping on architectures where GDB requires it.
+    ///
+    /// By default, this method returns `SingleStepGdbBehavior::Unknown`.
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Unknown
+    }
 }
@@ -1,7 +1,215 @@
 //! Common types and definitions.
 
 /// Thread ID
+///
+/// The GDB Remote Serial Protocol reserves thread-id `0` (meaning "any
+/// thread") and `-1` (meaning "all threads"), which is why `Tid` is a
+/// `NonZeroUsize`. Targets with 0-based thread ids (e.g: CPU core indices)
+/// can use [`tid_from_zero_based`] and [`tid_to_zero_based`] to map their
+/// native ids to/from `Tid`s.
 pub type Tid = core::num::NonZeroUsize;
 
 /// Process ID
 pub type Pid = core::num::NonZeroUsize;
+
+/// Map a 0-based thread id onto a [`Tid`] (i.e: `id + 1`).
+///
+/// Returns `None` if `id` is `usize::MAX`, as it cannot be represented.
+pub fn tid_from_zero_based(id: usize) -> Option<Tid> {
+    Tid::new(id.checked_add(1)?)
+}
+
+/// Map a [`Tid`] back onto a 0-based thread id (i.e: `tid - 1`).
+///
+/// This is the inverse of [`tid_from_zero_based`].
+pub fn tid_to_zero_based(tid: Tid) -> usize {
+    tid.get() - 1
+}
+
+macro_rules! signals {
+    ($($(#[doc = $doc:literal])* $name:ident = $num:literal,)*) => {
+        /// A signal number, as defined by GDB.
+        ///
+        /// These are GDB's canonical (i.e: host-independent) signal numbers,
+        /// which do _not_ necessarily match the host's signal numbers. e.g:
+        /// `SIGUSR1` is `30` in GDB's numbering, but `10` on Linux x86.
+        /// Targets reporting signals from a real OS should translate them
+        /// accordingly.
+        ///
+        /// Signals without a dedicated variant can be represented using
+        /// [`Signal::Other`], though [`Signal::from_u8`] should be preferred
+        /// when converting from a raw signal number, as it maps known signal
+        /// numbers to their named variants.
+        #[derive(Clone, Copy, Debug)]
+        #[allow(clippy::upper_case_acronyms)]
+        #[non_exhaustive]
+        pub enum Signal {
+            $($(#[doc = $doc])* $name,)*
+            /// A signal without a dedicated variant.
+            Other(u8),
+        }
+
+        impl Signal {
+            /// Convert a raw GDB signal number into a `Signal`.
+            pub fn from_u8(sig: u8) -> Signal {
+                match sig {
+                    $($num => Signal::$name,)*
+                    _ => Signal::Other(sig),
+                }
+            }
+
+            /// Return the signal's raw GDB signal number.
+            pub fn as_u8(self) -> u8 {
+                match self {
+                    $(Signal::$name => $num,)*
+                    Signal::Other(sig) => sig,
+                }
+            }
+        }
+    };
+}
+
+signals! {
+    /// Hangup.
+    SIGHUP = 1,
+    /// Interrupt.
+    SIGINT = 2,
+    /// Quit.
+    SIGQUIT = 3,
+    /// Illegal instruction.
+    SIGILL = 4,
+    /// Trace/breakpoint trap.
+    SIGTRAP = 5,
+    /// Aborted.
+    SIGABRT = 6,
+    /// Emulation trap.
+    SIGEMT = 7,
+    /// Arithmetic exception.
+    SIGFPE = 8,
+    /// Killed.
+    SIGKILL = 9,
+    /// Bus error.
+    SIGBUS = 10,
+    /// Segmentation fault.
+    SIGSEGV = 11,
+    /// Bad system call.
+    SIGSYS = 12,
+    /// Broken pipe.
+    SIGPIPE = 13,
+    /// Alarm clock.
+    SIGALRM = 14,
+    /// Terminated.
+    SIGTERM = 15,
+    /// Urgent I/O condition.
+    SIGURG = 16,
+    /// Stopped (signal).
+    SIGSTOP = 17,
+    /// Stopped (user).
+    SIGTSTP = 18,
+    /// Continued.
+    SIGCONT = 19,
+    /// Child status changed.
+    SIGCHLD = 20,
+    /// Stopped (tty input).
+    SIGTTIN = 21,
+    /// Stopped (tty output).
+    SIGTTOU = 22,
+    /// I/O possible.
+    SIGIO = 23,
+    /// CPU time limit exceeded.
+    SIGXCPU = 24,
+    /// File size limit exceeded.
+    SIGXFSZ = 25,
+    /// Virtual timer expired.
+    SIGVTALRM = 26,
+    /// Profiling timer expired.
+    SIGPROF = 27,
+    /// Window size changed.
+    SIGWINCH = 28,
+    /// Resource lost.
+    SIGLOST = 29,
+    /// User defined signal 1.
+    SIGUSR1 = 30,
+    /// User defined signal 2.
+    SIGUSR2 = 31,
+    /// Power fail/restart.
+    SIGPWR = 32,
+    /// Pollable event occurred.
+    SIGPOLL = 33,
+}
+
+// `Signal::Other` may alias a named variant, so signals are compared by number
+impl PartialEq for Signal {
+    fn eq(&self, other: &Signal) -> bool {
+        self.as_u8() == other.as_u8()
+    }
+}
+
+impl Eq for Signal {}
+
+/// Common error codes, for use with
+/// [`TargetError::Errno`](crate::target::TargetError::Errno).
+///
+/// Error codes are sent to the GDB client as part of an `E NN` reply. The GDB
+/// client doesn't assign any particular meaning to these codes, so (like
+/// `gdbserver`) `gdbstub` uses the standard Linux `errno` values.
+pub mod errno {
+    /// Operation not permitted.
+    pub const EPERM: u8 = 1;
+    /// No such file or directory.
+    pub const ENOENT: u8 = 2;
+    /// I/O error.
+    pub const EIO: u8 = 5;
+    /// Bad file number.
+    pub const EBADF: u8 = 9;
+    /// Out of memory.
+    pub const ENOMEM: u8 = 12;
+    /// Permission denied.
+    pub const EACCES: u8 = 13;
+    /// Bad address.
+    pub const EFAULT: u8 = 14;
+    /// Device or resource busy.
+    pub const EBUSY: u8 = 16;
+    /// Invalid argument.
+    pub const EINVAL: u8 = 22;
+    /// No space left on device.
+    pub const ENOSPC: u8 = 28;
+    /// Result out of range.
+    pub const ERANGE: u8 = 34;
+    /// Function not implemented.
+    pub const ENOSYS: u8 = 38;
+    /// Remote I/O error.
+    pub const EREMOTEIO: u8 = 121;
+
+    /// An unspecified error, used when no `errno` value is applicable.
+    pub(crate) const UNSPECIFIED: u8 = 0;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_based_tid_roundtrip() {
+        for id in [0, 1, 2, 0x1000, usize::MAX - 1].iter().copied() {
+            let tid = tid_from_zero_based(id).unwrap();
+            assert_eq!(tid.get(), id + 1);
+            assert_eq!(tid_to_zero_based(tid), id);
+        }
+    }
+
+    #[test]
+    fn zero_based_tid_overflow() {
+        assert_eq!(tid_from_zero_based(usize::MAX), None);
+    }
+
+    #[test]
+    fn signal_roundtrip() {
+        for sig in 0..=255 {
+            assert_eq!(Signal::from_u8(sig).as_u8(), sig);
+        }
+        assert!(matches!(Signal::from_u8(5), Signal::SIGTRAP));
+        assert!(matches!(Signal::from_u8(200), Signal::Other(200)));
+        assert_eq!(Signal::Other(30), Signal::SIGUSR1);
+    }
+}
@@ -25,6 +25,10 @@ impl<E> Connection for Box<dyn Connection<Error = E>> {
         (**self).peek()
     }
 
+    fn poll_readable(&mut self) -> Result<bool, Self::Error> {
+        (**self).poll_readable()
+    }
+
     fn flush(&mut self) -> Result<(), Self::Error> {
         (**self).flush()
     }
@@ -0,0 +1,171 @@
+use core::time::Duration;
+
+use crate::{Connection, ConnectionExt};
+
+/// Wraps a [`Connection`], holding on to incoming bytes once they've been
+/// peeked at.
+///
+/// Once [`peek`](Connection::peek) (or
+/// [`poll_readable`](Connection::poll_readable)) observes an incoming byte, it
+/// is read from the inner connection and stored in a lookahead buffer, from
+/// which it is served by subsequent calls to `peek` and `read`. Repeatedly
+/// polling for a pending GDB interrupt (e.g: while the target is running)
+/// therefore only hits the inner connection until data arrives.
+///
+/// Transports which can cheaply check whether data is available, but which
+/// can't peek at it (e.g: a UART with a "data ready" status bit), can
+/// implement `Connection::peek` on top of their `poll_readable` check, and
+/// rely on `BufferedConnection` to avoid losing the byte.
+#[derive(Debug)]
+pub struct BufferedConnection<C> {
+    inner: C,
+    lookahead: Option<u8>,
+}
+
+impl<C: Connection> BufferedConnection<C> {
+    /// Wrap a connection.
+    pub fn new(inner: C) -> BufferedConnection<C> {
+        BufferedConnection {
+            inner,
+            lookahead: None,
+        }
+    }
+
+    /// Return a reference to the inner connection.
+    pub fn get_ref(&self) -> &C {
+        &self.inner
+    }
+
+    /// Return a mutable reference to the inner connection.
+    ///
+    /// Reading directly from the inner connection may result in data being
+    /// received out-of-order, as `BufferedConnection` may be holding on to a
+    /// peeked byte.
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.inner
+    }
+
+    /// Unwrap the inner connection, discarding any peeked byte.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C: Connection> Connection for BufferedConnection<C> {
+    type Error = C::Error;
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        match self.lookahead.take() {
+            Some(byte) => Ok(byte),
+            None => self.inner.read(),
+        }
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        let buf = match (self.lookahead, buf) {
+            (_, []) => return Ok(()),
+            (Some(byte), [first, rest @ ..]) => {
+                *first = byte;
+                self.lookahead = None;
+                rest
+            }
+            (None, buf) => buf,
+        };
+        self.inner.read_exact(buf)
+    }
+
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+        self.inner.write(byte)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.inner.write_all(buf)
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+        if self.lookahead.is_none() && self.inner.poll_readable()? {
+            // data is available, so this won't block
+            self.lookahead = Some(self.inner.read()?);
+        }
+        Ok(self.lookahead)
+    }
+
+    fn poll_readable(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.peek()?.is_some())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+
+    fn on_session_start(&mut self) -> Result<(), Self::Error> {
+        self.inner.on_session_start()
+    }
+}
+
+impl<C: ConnectionExt> ConnectionExt for BufferedConnection<C> {
+    fn read_with_timeout(&mut self, timeout: Duration) -> Result<Option<u8>, Self::Error> {
+        match self.lookahead.take() {
+            Some(byte) => Ok(Some(byte)),
+            None => self.inner.read_with_timeout(timeout),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A connection which can check whether data is available, but can't peek
+    /// at it (i.e: `peek` simply reports readiness, using a placeholder value).
+    struct ReadinessOnlyConnection {
+        rx: &'static [u8],
+        polls: usize,
+    }
+
+    impl Connection for ReadinessOnlyConnection {
+        type Error = ();
+
+        fn read(&mut self) -> Result<u8, ()> {
+            let (byte, rest) = self.rx.split_first().ok_or(())?;
+            self.rx = rest;
+            Ok(*byte)
+        }
+
+        fn write(&mut self, _byte: u8) -> Result<(), ()> {
+            Ok(())
+        }
+
+        fn peek(&mut self) -> Result<Option<u8>, ()> {
+            self.polls += 1;
+            Ok(if self.rx.is_empty() { None } else { Some(0) })
+        }
+
+        fn flush(&mut self) -> Result<(), ()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn peeked_byte_is_buffered() {
+        let mut conn = BufferedConnection::new(ReadinessOnlyConnection {
+            rx: b"abc",
+            polls: 0,
+        });
+
+        assert_eq!(conn.peek(), Ok(Some(b'a')));
+        assert_eq!(conn.poll_readable(), Ok(true));
+        assert_eq!(conn.peek(), Ok(Some(b'a')));
+        assert_eq!(conn.get_ref().polls, 1);
+
+        assert_eq!(conn.read(), Ok(b'a'));
+        assert_eq!(conn.peek(), Ok(Some(b'b')));
+
+        let mut buf = [0; 2];
+        conn.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"bc");
+
+        assert_eq!(conn.peek(), Ok(None));
+        assert_eq!(conn.get_ref().polls, 3);
+    }
+}
@@ -0,0 +1,157 @@
+use std::io::{self, Read, Write};
+
+use crate::Connection;
+
+/// Implements [`Connection`] for any type which implements
+/// [`std::io::Read`] + [`std::io::Write`] (e.g: a serial port, a file, or a
+/// pipe).
+///
+/// As `std::io::Read` has no way to peek at incoming data, `IoConnection`
+/// implements [`Connection::peek`] by reading a single byte from the inner I/O
+/// object, and holding on to it until the next call to `read`. As such,
+/// `peek` is only non-blocking if the inner I/O object is non-blocking, or
+/// has a read timeout (in which case `WouldBlock` and `TimedOut` errors are
+/// reported as "no data available").
+#[derive(Debug)]
+pub struct IoConnection<T> {
+    inner: T,
+    peeked: Option<u8>,
+}
+
+impl<T: Read + Write> IoConnection<T> {
+    /// Wrap an I/O object.
+    pub fn new(inner: T) -> IoConnection<T> {
+        IoConnection {
+            inner,
+            peeked: None,
+        }
+    }
+
+    /// Return a reference to the inner I/O object.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Return a mutable reference to the inner I/O object.
+    ///
+    /// Reading directly from the inner I/O object may result in data being
+    /// received out-of-order, as `IoConnection` may be holding on to a peeked
+    /// byte.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Unwrap the inner I/O object, discarding any peeked byte.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: Read + Write> Connection for IoConnection<T> {
+    type Error = io::Error;
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        if let Some(byte) = self.peeked.take() {
+            return Ok(byte);
+        }
+
+        let mut buf = [0u8];
+        self.inner.read_exact(&mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
+        let buf = match (self.peeked, buf) {
+            (_, []) => return Ok(()),
+            (Some(byte), [first, rest @ ..]) => {
+                *first = byte;
+                self.peeked = None;
+                rest
+            }
+            (None, buf) => buf,
+        };
+        self.inner.read_exact(buf)
+    }
+
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+        self.inner.write_all(&[byte])
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.inner.write_all(buf)
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+        if self.peeked.is_none() {
+            let mut buf = [0u8];
+            loop {
+                match self.inner.read(&mut buf) {
+                    Ok(0) => return Ok(None),
+                    Ok(_) => {
+                        self.peeked = Some(buf[0]);
+                        break;
+                    }
+                    Err(e) => match e.kind() {
+                        io::ErrorKind::Interrupted => continue,
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => return Ok(None),
+                        _ => return Err(e),
+                    },
+                }
+            }
+        }
+
+        Ok(self.peeked)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    /// An in-memory pipe, which reports `WouldBlock` (instead of EOF) once all
+    /// incoming data has been consumed.
+    struct Pipe(Cursor<Vec<u8>>);
+
+    impl Read for Pipe {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.read(buf)? {
+                0 if !buf.is_empty() => Err(io::ErrorKind::WouldBlock.into()),
+                n => Ok(n),
+            }
+        }
+    }
+
+    impl Write for Pipe {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn peeked_bytes_are_read_in_order() {
+        let mut conn = IoConnection::new(Pipe(Cursor::new(b"abcd".to_vec())));
+
+        assert_eq!(conn.peek().unwrap(), Some(b'a'));
+        assert_eq!(conn.peek().unwrap(), Some(b'a'));
+        assert_eq!(conn.read().unwrap(), b'a');
+
+        assert_eq!(conn.peek().unwrap(), Some(b'b'));
+        let mut buf = [0; 2];
+        conn.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"bc");
+
+        assert_eq!(conn.read().unwrap(), b'd');
+        assert_eq!(conn.peek().unwrap(), None);
+        assert!(!conn.poll_readable().unwrap());
+    }
+}
@@ -1,17 +1,30 @@
 //! Implementations of the [`Connection`] trait for various built-in types
-// TODO: impl Connection for all `Read + Write` (blocked on specialization)
+// NOTE: a blanket `impl Connection for T: Read + Write` is blocked on
+// specialization, hence the `IoConnection` wrapper.
 
 #[cfg(feature = "alloc")]
 mod boxed;
+mod buffered;
 
+#[cfg(feature = "std")]
+mod io;
+#[cfg(all(feature = "std", unix))]
+mod sys;
 #[cfg(feature = "std")]
 mod tcpstream;
+#[cfg(feature = "std")]
+mod timeout;
 
 #[cfg(all(feature = "std", unix))]
 mod unixstream;
 
 use super::Connection;
 
+pub use buffered::BufferedConnection;
+
+#[cfg(feature = "std")]
+pub use io::IoConnection;
+
 impl<E> Connection for &mut dyn Connection<Error = E> {
     type Error = E;
 
@@ -35,6 +48,10 @@ impl<E> Connection for &mut dyn Connection<Error = E> {
         (**self).peek()
     }
 
+    fn poll_readable(&mut self) -> Result<bool, Self::Error> {
+        (**self).poll_readable()
+    }
+
     fn flush(&mut self) -> Result<(), Self::Error> {
         (**self).flush()
     }
@@ -43,3 +60,60 @@ impl<E> Connection for &mut dyn Connection<Error = E> {
         (**self).on_session_start()
     }
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    use std::io::Write;
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+    use std::time::Duration;
+
+    /// Check that peeking at an idle connection doesn't leave it in
+    /// non-blocking mode, by making sure that a subsequent `read` waits for
+    /// data to arrive.
+    fn check_peek_keeps_blocking_mode<C, W>(mut conn: C, mut peer: W)
+    where
+        C: Connection<Error = std::io::Error>,
+        W: Write + Send + 'static,
+    {
+        conn.on_session_start().unwrap();
+
+        assert_eq!(conn.peek().unwrap(), None);
+        assert!(!conn.poll_readable().unwrap());
+
+        let writer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            peer.write_all(b"$?").unwrap();
+            peer
+        });
+
+        assert_eq!(conn.read().unwrap(), b'$');
+        assert_eq!(conn.peek().unwrap(), Some(b'?'));
+        assert!(conn.poll_readable().unwrap());
+        assert_eq!(conn.read().unwrap(), b'?');
+        assert_eq!(conn.peek().unwrap(), None);
+
+        drop(writer.join().unwrap());
+        assert_eq!(conn.peek().unwrap(), None);
+        assert!(conn.read().is_err());
+    }
+
+    #[test]
+    fn tcpstream_peek_keeps_blocking_mode() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+
+        check_peek_keeps_blocking_mode(server, client);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unixstream_peek_keeps_blocking_mode() {
+        let (server, client) = std::os::unix::net::UnixStream::pair().unwrap();
+
+        check_peek_keeps_blocking_mode(server, client);
+    }
+}
@@ -0,0 +1,55 @@
+//! Platform-specific helpers for the `std` socket implementations.
+
+use core::ffi::c_void;
+use std::io;
+use std::os::unix::io::RawFd;
+
+/// Peek at the next incoming byte on the socket `fd` without blocking,
+/// regardless of whether the socket is in blocking mode.
+///
+/// Unlike toggling `set_nonblocking` around a regular peek, this only requires
+/// a single syscall, and never changes the socket's blocking mode (which may be
+/// observed by other threads sharing the socket).
+#[allow(non_camel_case_types)]
+pub fn peek_nonblocking(fd: RawFd) -> io::Result<Option<u8>> {
+    // Define some libc types inline (to avoid bringing in entire libc dep)
+
+    // every platform supported by the libc crate uses c_int = i32
+    type c_int = i32;
+    type size_t = usize;
+    type ssize_t = isize;
+    const MSG_PEEK: c_int = 0x2;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const MSG_DONTWAIT: c_int = 0x40;
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    const MSG_DONTWAIT: c_int = 0x80;
+    extern "C" {
+        fn recv(socket: c_int, buf: *mut c_void, len: size_t, flags: c_int) -> ssize_t;
+    }
+
+    let mut buf = [0u8];
+    loop {
+        let ret = unsafe {
+            recv(
+                fd,
+                buf.as_mut_ptr() as *mut c_void,
+                buf.len(),
+                MSG_PEEK | MSG_DONTWAIT,
+            )
+        };
+
+        return match ret {
+            -1 => {
+                let e = io::Error::last_os_error();
+                match e.kind() {
+                    io::ErrorKind::Interrupted => continue,
+                    io::ErrorKind::WouldBlock => Ok(None),
+                    _ => Err(e),
+                }
+            }
+            // the connection was closed, which will be reported by the next read
+            0 => Ok(None),
+            _ => Ok(Some(buf[0])),
+        };
+    }
+}
@@ -1,15 +1,24 @@
 use std::net::TcpStream;
-
-use crate::Connection;
-
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+use std::time::Duration;
+
+#[cfg(unix)]
+use super::sys::peek_nonblocking;
+use super::timeout;
+use crate::{Connection, ConnectionExt};
+
+/// `TcpStream` is switched into (and then kept in) blocking mode by
+/// `on_session_start`, so `read` never needs to touch the socket's blocking
+/// mode. On unix platforms, `peek` uses `MSG_DONTWAIT` to avoid blocking,
+/// leaving the socket's blocking mode as-is. Elsewhere, `peek` briefly
+/// switches the socket into non-blocking mode.
 impl Connection for TcpStream {
     type Error = std::io::Error;
 
     fn read(&mut self) -> Result<u8, Self::Error> {
         use std::io::Read;
 
-        self.set_nonblocking(false)?;
-
         let mut buf = [0u8];
         match Read::read_exact(self, &mut buf) {
             Ok(_) => Ok(buf[0]),
@@ -20,16 +29,27 @@ impl Connection for TcpStream {
     fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
         use std::io::Read;
 
-        self.set_nonblocking(false)?;
-
         Read::read_exact(self, buf)
     }
 
+    #[cfg(unix)]
     fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+        peek_nonblocking(self.as_raw_fd())
+    }
+
+    #[cfg(not(unix))]
+    fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+        // no per-call non-blocking flag is available, so briefly switch the
+        // socket into non-blocking mode, making sure to always switch it back
         self.set_nonblocking(true)?;
 
         let mut buf = [0u8];
-        match Self::peek(self, &mut buf) {
+        let res = Self::peek(self, &mut buf);
+
+        self.set_nonblocking(false)?;
+
+        match res {
+            Ok(0) => Ok(None),
             Ok(_) => Ok(Some(buf[0])),
             Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => Ok(None),
             Err(e) => Err(e),
@@ -55,7 +75,56 @@ impl Connection for TcpStream {
     }
 
     fn on_session_start(&mut self) -> Result<(), Self::Error> {
+        self.set_nonblocking(false)?;
+        // clear any timeout left over from `read_with_timeout`
+        self.set_read_timeout(None)?;
         // see issue #28
         self.set_nodelay(true)
     }
 }
+
+/// The timeout is applied via `set_read_timeout` before each read, and is left
+/// in place afterwards (until it's cleared by `on_session_start`).
+impl ConnectionExt for TcpStream {
+    fn read_with_timeout(&mut self, timeout: Duration) -> Result<Option<u8>, Self::Error> {
+        self.set_read_timeout(Some(timeout::socket_timeout(timeout)))?;
+        timeout::read_with_timeout(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Write;
+    use std::net::TcpListener;
+
+    #[test]
+    fn read_with_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (mut server, _) = listener.accept().unwrap();
+
+        // zero-length timeouts simply poll the socket
+        assert_eq!(
+            server.read_with_timeout(Duration::from_secs(0)).unwrap(),
+            None
+        );
+
+        let timeout = Duration::from_millis(10);
+        assert_eq!(server.read_with_timeout(timeout).unwrap(), None);
+
+        Write::write_all(&mut client, b"$?").unwrap();
+        assert_eq!(server.read_with_timeout(timeout).unwrap(), Some(b'$'));
+        assert_eq!(server.read_with_timeout(timeout).unwrap(), Some(b'?'));
+        assert_eq!(server.read_with_timeout(timeout).unwrap(), None);
+
+        // starting a new session makes reads block once again
+        assert!(server.read_timeout().unwrap().is_some());
+        server.on_session_start().unwrap();
+        assert_eq!(server.read_timeout().unwrap(), None);
+
+        drop(client);
+        assert!(server.read_with_timeout(timeout).is_err());
+    }
+}
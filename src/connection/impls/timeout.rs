@@ -0,0 +1,35 @@
+//! Read timeout support shared by the `std` socket implementations.
+
+use std::io::{self, ErrorKind, Read};
+use std::time::Duration;
+
+/// Convert a timeout into one accepted by `set_read_timeout`.
+///
+/// `set_read_timeout` rejects zero-length timeouts, so they are rounded up to
+/// the shortest timeout supported by the platform (which effectively polls the
+/// socket).
+pub fn socket_timeout(timeout: Duration) -> Duration {
+    timeout.max(Duration::from_nanos(1))
+}
+
+/// Read a single byte from a socket which has a read timeout set, returning
+/// `None` if the timeout elapsed first.
+pub fn read_with_timeout(sock: &mut impl Read) -> io::Result<Option<u8>> {
+    let mut buf = [0u8];
+    let res = loop {
+        match sock.read(&mut buf) {
+            Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
+            res => break res,
+        }
+    };
+
+    match res {
+        Ok(0) => Err(ErrorKind::UnexpectedEof.into()),
+        Ok(_) => Ok(Some(buf[0])),
+        // the error kind used to report timeouts varies by platform
+        Err(ref e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
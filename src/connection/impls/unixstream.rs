@@ -1,59 +1,21 @@
-use core::ffi::c_void;
-use std::io;
 use std::os::unix::io::AsRawFd;
 use std::os::unix::net::UnixStream;
+use std::time::Duration;
 
-use crate::Connection;
-
-// TODO: Remove PeekExt once `gdbstub`'s MSRV >1.48 (rust-lang/rust#73761)
-trait PeekExt {
-    fn peek(&self, buf: &mut [u8]) -> io::Result<usize>;
-}
-
-impl PeekExt for UnixStream {
-    #[allow(non_camel_case_types)]
-    fn peek(&self, buf: &mut [u8]) -> io::Result<usize> {
-        // Define some libc types inline (to avoid bringing in entire libc dep)
-
-        // every platform supported by the libc crate uses c_int = i32
-        type c_int = i32;
-        type size_t = usize;
-        type ssize_t = isize;
-        const MSG_PEEK: c_int = 2;
-        extern "C" {
-            fn recv(socket: c_int, buf: *mut c_void, len: size_t, flags: c_int) -> ssize_t;
-        }
-
-        // from std/sys/unix/mod.rs
-        pub fn cvt(t: isize) -> io::Result<isize> {
-            if t == -1 {
-                Err(io::Error::last_os_error())
-            } else {
-                Ok(t)
-            }
-        }
-
-        // from std/sys/unix/net.rs
-        let ret = cvt(unsafe {
-            recv(
-                self.as_raw_fd(),
-                buf.as_mut_ptr() as *mut c_void,
-                buf.len(),
-                MSG_PEEK,
-            )
-        })?;
-        Ok(ret as usize)
-    }
-}
+use super::sys::peek_nonblocking;
+use super::timeout;
+use crate::{Connection, ConnectionExt};
 
+/// `UnixStream` is switched into (and then kept in) blocking mode by
+/// `on_session_start`, so `read` never needs to touch the socket's blocking
+/// mode. `peek` uses `MSG_DONTWAIT` to avoid blocking, leaving the socket's
+/// blocking mode as-is.
 impl Connection for UnixStream {
     type Error = std::io::Error;
 
     fn read(&mut self) -> Result<u8, Self::Error> {
         use std::io::Read;
 
-        self.set_nonblocking(false)?;
-
         let mut buf = [0u8];
         match Read::read_exact(self, &mut buf) {
             Ok(_) => Ok(buf[0]),
@@ -64,20 +26,11 @@ impl Connection for UnixStream {
     fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), Self::Error> {
         use std::io::Read;
 
-        self.set_nonblocking(false)?;
-
         Read::read_exact(self, buf)
     }
 
     fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
-        self.set_nonblocking(true)?;
-
-        let mut buf = [0u8];
-        match PeekExt::peek(self, &mut buf) {
-            Ok(_) => Ok(Some(buf[0])),
-            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => Ok(None),
-            Err(e) => Err(e),
-        }
+        peek_nonblocking(self.as_raw_fd())
     }
 
     fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
@@ -97,4 +50,19 @@ impl Connection for UnixStream {
 
         Write::flush(self)
     }
+
+    fn on_session_start(&mut self) -> Result<(), Self::Error> {
+        self.set_nonblocking(false)?;
+        // clear any timeout left over from `read_with_timeout`
+        self.set_read_timeout(None)
+    }
+}
+
+/// The timeout is applied via `set_read_timeout` before each read, and is left
+/// in place afterwards (until it's cleared by `on_session_start`).
+impl ConnectionExt for UnixStream {
+    fn read_with_timeout(&mut self, timeout: Duration) -> Result<Option<u8>, Self::Error> {
+        self.set_read_timeout(Some(timeout::socket_timeout(timeout)))?;
+        timeout::read_with_timeout(self)
+    }
 }
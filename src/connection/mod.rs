@@ -1,10 +1,19 @@
+use core::time::Duration;
+
 mod impls;
 
+pub use impls::BufferedConnection;
+
+#[cfg(feature = "std")]
+pub use impls::IoConnection;
+
 /// A trait to perform in-order, serial, byte-wise I/O.
 ///
 /// When the `std` feature is enabled, this trait is automatically implemented
 /// for [`TcpStream`](std::net::TcpStream) and
-/// [`UnixStream`](std::os::unix::net::UnixStream) (on unix systems).
+/// [`UnixStream`](std::os::unix::net::UnixStream) (on unix systems). Any other
+/// `std::io::Read + std::io::Write` type can be used by wrapping it in an
+/// [`IoConnection`](crate::IoConnection).
 pub trait Connection {
     /// Transport-specific error type.
     type Error;
@@ -30,6 +39,11 @@ pub trait Connection {
 
     /// Write the entire buffer, blocking until complete.
     ///
+    /// `gdbstub` buffers outgoing packets, and writes them using this method.
+    /// When the `alloc` feature is enabled, each packet is written using a
+    /// single call. Otherwise, packets are written in chunks of up to 256
+    /// bytes (though most packets fit in a single chunk).
+    ///
     /// This method's default implementation calls `self.write()` on each byte
     /// in the buffer. This can be quite inefficient, so if a more efficient
     /// implementation exists (such as calling `write_all()` on an underlying
@@ -45,6 +59,20 @@ pub trait Connection {
     /// `None` if no byte is available.
     fn peek(&mut self) -> Result<Option<u8>, Self::Error>;
 
+    /// Check if there is incoming data which can be read without blocking.
+    ///
+    /// While the target is running, `gdbstub` uses this method to cheaply
+    /// check for a pending GDB interrupt (see
+    /// [`GdbInterrupt`](crate::target::ext::base::GdbInterrupt)), only calling
+    /// [`peek`](Self::peek) once data is known to be available. Transports
+    /// with a cheap readiness check (e.g: a status register, or a flag set by
+    /// an async reactor) should override this method.
+    ///
+    /// This method's default implementation calls `self.peek()`.
+    fn poll_readable(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.peek()?.is_some())
+    }
+
     /// Flush this Connection, ensuring that all intermediately buffered
     /// contents reach their destination.
     ///
@@ -69,3 +97,19 @@ pub trait Connection {
         Ok(())
     }
 }
+
+/// Extends [`Connection`] with operations which not all transports can
+/// support.
+///
+/// When the `std` feature is enabled, this trait is automatically implemented
+/// for [`TcpStream`](std::net::TcpStream) and
+/// [`UnixStream`](std::os::unix::net::UnixStream) (on unix systems).
+pub trait ConnectionExt: Connection {
+    /// Read a single byte, giving up once `timeout` has elapsed.
+    ///
+    /// Returns `Ok(None)` if no byte arrived before the timeout.
+    ///
+    /// [`GdbStub::run_with_read_timeout`](crate::GdbStub::run_with_read_timeout)
+    /// calls this once per incoming byte, always with the same `timeout`.
+    fn read_with_timeout(&mut self, timeout: Duration) -> Result<Option<u8>, Self::Error>;
+}
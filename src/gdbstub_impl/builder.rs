@@ -3,7 +3,8 @@ use core::marker::PhantomData;
 
 use managed::ManagedSlice;
 
-use super::{Connection, GdbStub, GdbStubImpl, Target};
+use super::{Connection, GdbStub, GdbStubImpl, PacketTracer, Target};
+use crate::arch::{Arch, Registers};
 
 /// An error which may occur when building a [`GdbStub`].
 #[derive(Debug)]
@@ -12,6 +13,9 @@ pub enum GdbStubBuilderError {
     MissingPacketBuffer,
     /// Custom packet buffer size is larger than the provided buffer's length.
     PacketBufSizeMismatch,
+    /// Packet buffer is too small to receive the target's entire register
+    /// file (i.e: a `G` packet). Contains the minimum required size.
+    PacketBufTooSmall(usize),
 }
 
 impl Display for GdbStubBuilderError {
@@ -26,6 +30,11 @@ impl Display for GdbStubBuilderError {
                 f,
                 "`packet_buffer_size` is larger than `with_packet_buffer`'s size."
             ),
+            PacketBufTooSmall(min_len) => write!(
+                f,
+                "Packet buffer is too small to fit the target's registers (must be at least {} bytes).",
+                min_len
+            ),
         }
     }
 }
@@ -38,6 +47,9 @@ pub struct GdbStubBuilder<'a, T: Target, C: Connection> {
     conn: C,
     packet_buffer: Option<&'a mut [u8]>,
     packet_buffer_size: Option<usize>,
+    rle_enabled: bool,
+    no_ack_mode: bool,
+    tracer: Option<PacketTracer>,
 
     _target: PhantomData<T>,
 }
@@ -49,6 +61,9 @@ impl<'a, T: Target, C: Connection> GdbStubBuilder<'a, T, C> {
             conn,
             packet_buffer: None,
             packet_buffer_size: None,
+            rle_enabled: false,
+            no_ack_mode: false,
+            tracer: None,
 
             _target: PhantomData,
         }
@@ -66,11 +81,53 @@ impl<'a, T: Target, C: Connection> GdbStubBuilder<'a, T, C> {
     ///
     /// When used alongside `with_packet_buffer`, the provided `size` must be
     /// less than or equal to the length of the packet buffer.
+    ///
+    /// This is also the `PacketSize` reported to the GDB client, and must be
+    /// large enough to fit a `G` packet containing the target's entire
+    /// register file.
     pub fn packet_buffer_size(mut self, size: usize) -> Self {
         self.packet_buffer_size = Some(size);
         self
     }
 
+    /// Enable/Disable run-length encoding of outgoing packets. Defaults to
+    /// `false`.
+    ///
+    /// Run-length encoding significantly reduces the amount of data sent when
+    /// reading large, repetitive register files / memory regions, and is
+    /// supported by all GDB clients. It's left disabled by default, as it
+    /// changes the bytes sent over the wire, which may trip up non-standard
+    /// clients (or tools which inspect the raw packet stream).
+    pub fn rle(mut self, enabled: bool) -> Self {
+        self.rle_enabled = enabled;
+        self
+    }
+
+    /// Start the session with packet acknowledgments disabled. Defaults to
+    /// `false`.
+    ///
+    /// Typically, `gdbstub` acknowledges each packet until the GDB client
+    /// negotiates "no ack mode" via `QStartNoAckMode`. Enabling this option is
+    /// only useful when the client is known to run without acknowledgments
+    /// from the very start (e.g: a custom client on a reliable transport), as
+    /// the standard GDB client will wait for an acknowledgment of its first
+    /// packet.
+    pub fn no_ack_mode(mut self, enabled: bool) -> Self {
+        self.no_ack_mode = enabled;
+        self
+    }
+
+    /// Invoke `tracer` with the raw bytes of every packet sent or received by
+    /// the stub. See [`PacketTracer`] for details.
+    ///
+    /// This is particularly useful when bringing up a new target, as it makes
+    /// it possible to inspect the raw packet stream without setting up a
+    /// `log` backend.
+    pub fn packet_tracer(mut self, tracer: PacketTracer) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
     /// Build the GdbStub, returning an error if something went wrong.
     pub fn build(self) -> Result<GdbStub<'a, T, C>, GdbStubBuilderError> {
         let packet_buffer = match self.packet_buffer {
@@ -102,10 +159,29 @@ impl<'a, T: Target, C: Connection> GdbStubBuilder<'a, T, C> {
             }
         };
 
+        let min_len = min_packet_buffer_len::<T::Arch>();
+        if packet_buffer.len() < min_len {
+            return Err(GdbStubBuilderError::PacketBufTooSmall(min_len));
+        }
+
+        let mut state = GdbStubImpl::new();
+        state.rle_enabled = self.rle_enabled;
+        state.no_ack_mode = self.no_ack_mode;
+        state.tracer = self.tracer;
+
         Ok(GdbStub {
             conn: self.conn,
             packet_buffer,
-            state: GdbStubImpl::new(),
+            state,
         })
     }
 }
+
+/// The size of the largest packet `gdbstub` must be able to receive: a `G`
+/// packet containing the target's entire register file (i.e:
+/// `$G<hex-encoded registers>#XX`).
+fn min_packet_buffer_len<A: Arch>() -> usize {
+    let mut num_bytes = 0;
+    A::Registers::default().gdb_serialize(|_| num_bytes += 1);
+    "$G#XX".len() + num_bytes * 2
+}
@@ -16,6 +16,22 @@ pub enum GdbStubError<T, C> {
     ClientSentNack,
     /// Packet cannot fit in the provided packet buffer.
     PacketBufferOverflow,
+    /// GDB client sent a packet larger than the packet buffer (i.e: larger
+    /// than the reported `PacketSize`).
+    ///
+    /// The oversized packet is discarded in its entirety, and rejected with an
+    /// `E0c` (`ENOMEM`) reply, so it is safe to continue pumping bytes into
+    /// a [`GdbStubStateMachine`](crate::GdbStubStateMachine) after this error
+    /// (which is returned by `next_action`, once the reply has been sent).
+    /// [`GdbStub::run`](crate::GdbStub::run) never returns this error, and
+    /// simply carries on with the session.
+    PacketTooLarge,
+    /// GDB client sent a packet with an incorrect checksum.
+    ///
+    /// Corrupted packets are nack'd, prompting GDB to re-send them, so this
+    /// error is only returned once no-ack mode is enabled (as GDB no longer
+    /// re-sends packets at that point).
+    ChecksumMismatch,
     /// Could not parse the packet into a valid command.
     PacketParse(PacketParseError),
     /// GDB client sent an unexpected packet. This should never happen!
@@ -44,6 +60,26 @@ pub enum GdbStubError<T, C> {
     NonFatalError(u8),
 }
 
+impl<T, C> GdbStubError<T, C> {
+    /// Check if the session ended due to an error in the underlying
+    /// [`Connection`](crate::Connection) (e.g: the GDB client going away
+    /// without detaching), as opposed to a protocol or target error.
+    ///
+    /// Transport-level failures like these are typically transient, making it
+    /// reasonable to wait for a new connection and start a fresh session.
+    pub fn is_connection_error(&self) -> bool {
+        matches!(
+            self,
+            GdbStubError::ConnectionRead(_) | GdbStubError::ConnectionWrite(_)
+        )
+    }
+
+    /// Check if the session ended due to a fatal error raised by the target.
+    pub fn is_target_error(&self) -> bool {
+        matches!(self, GdbStubError::TargetError(_))
+    }
+}
+
 impl<T, C> From<ResponseWriterError<C>> for GdbStubError<T, C> {
     fn from(e: ResponseWriterError<C>) -> Self {
         GdbStubError::ConnectionWrite(e.0)
@@ -68,6 +104,8 @@ where
             ConnectionWrite(e) => write!(f, "Connection Error while writing response: {:?}", e),
             ClientSentNack => write!(f, "Client nack'd the last packet, but `gdbstub` doesn't implement re-transmission."),
             PacketBufferOverflow => write!(f, "Packet too big for provided buffer!"),
+            PacketTooLarge => write!(f, "Client sent a packet larger than the reported PacketSize."),
+            ChecksumMismatch => write!(f, "Client sent a packet with an incorrect checksum."),
             PacketParse(e) => write!(f, "Could not parse the packet into a valid command: {:?}", e),
             PacketUnexpected => write!(f, "Client sent an unexpected packet. This should never happen! Please file an issue at https://github.com/daniel5151/gdbstub/issues"),
             TargetMismatch => write!(f, "GDB client sent a packet with too much data for the given target."),
@@ -0,0 +1,29 @@
+use super::prelude::*;
+use crate::protocol::commands::ext::Auxv;
+
+impl<T: Target, C: Connection> GdbStubImpl<T, C> {
+    pub(crate) fn handle_auxv(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+        command: Auxv,
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        let ops = match target.auxv() {
+            Some(ops) => ops,
+            None => return Ok(HandlerStatus::Handled),
+        };
+
+        crate::__dead_code_marker!("auxv", "impl");
+
+        let handler_status = match command {
+            Auxv::qXferAuxvRead(cmd) => {
+                let auxv = ops.get_auxv().handle_error()?;
+                res.write_qxfer_chunk(auxv, cmd.offset, cmd.len)?;
+
+                HandlerStatus::Handled
+            }
+        };
+
+        Ok(handler_status)
+    }
+}
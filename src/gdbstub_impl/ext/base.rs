@@ -1,11 +1,13 @@
 use super::prelude::*;
 use crate::protocol::commands::ext::Base;
 
-use crate::arch::{Arch, Registers};
+use crate::arch::{Arch, DeserializeError, Registers, SingleStepGdbBehavior};
+use crate::protocol::commands::_qSupported::FeatureSupported;
+use crate::protocol::commands::_vCont::ResumeActions;
 use crate::protocol::{IdKind, SpecificIdKind, SpecificThreadId};
 use crate::target::ext::base::multithread::ThreadStopReason;
 use crate::target::ext::base::{BaseOps, GdbInterrupt, ReplayLogPosition, ResumeAction};
-use crate::{FAKE_PID, SINGLE_THREAD_TID};
+use crate::SINGLE_THREAD_TID;
 
 impl<T: Target, C: Connection> GdbStubImpl<T, C> {
     #[inline(always)]
@@ -41,9 +43,24 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
         let handler_status = match command {
             // ------------------ Handshaking and Queries ------------------- //
             Base::qSupported(cmd) => {
-                // XXX: actually read what the client supports, and enable/disable features
-                // appropriately
-                let _features = cmd.features.into_iter();
+                self.client_features = ClientFeatures::default();
+                // malformed features are ignored, as they may simply be ones
+                // which gdbstub doesn't know about
+                for feature in cmd.features.into_iter().flatten() {
+                    let supported = feature.status == FeatureSupported::Yes;
+                    match feature.name.as_ref() {
+                        b"multiprocess" => self.client_features.multiprocess = supported,
+                        b"swbreak" => self.client_features.swbreak = supported,
+                        b"hwbreak" => self.client_features.hwbreak = supported,
+                        b"fork-events" => self.client_features.fork_events = supported,
+                        b"vfork-events" => self.client_features.vfork_events = supported,
+                        b"exec-events" => self.client_features.exec_events = supported,
+                        b"xmlRegisters" => {
+                            self.client_features.xml_registers = feature.val.is_some()
+                        }
+                        _ => {}
+                    }
+                }
 
                 res.write_str("PacketSize=")?;
                 res.write_num(cmd.packet_buffer_len)?;
@@ -52,6 +69,10 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                 res.write_str(";multiprocess+")?;
                 res.write_str(";QStartNoAckMode+")?;
 
+                if let BaseOps::MultiThread(_) = target.base_ops() {
+                    res.write_str(";QThreadEvents+")?;
+                }
+
                 let (reverse_cont, reverse_step) = match target.base_ops() {
                     BaseOps::MultiThread(ops) => (
                         ops.support_reverse_cont().is_some(),
@@ -89,6 +110,18 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                     if ops.configure_working_dir().is_some() {
                         res.write_str(";QSetWorkingDir+")?;
                     }
+
+                    if ops.catch_events().is_some() {
+                        res.write_str(";fork-events+;vfork-events+;exec-events+")?;
+                    }
+                }
+
+                if let Some(ops) = target.pass_signals() {
+                    res.write_str(";QPassSignals+")?;
+
+                    if ops.support_program_signals().is_some() {
+                        res.write_str(";QProgramSignals+")?;
+                    }
                 }
 
                 if let Some(ops) = target.breakpoints() {
@@ -99,6 +132,10 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                     if ops.hw_breakpoint().is_some() || ops.hw_watchpoint().is_some() {
                         res.write_str(";hwbreak+")?;
                     }
+
+                    if ops.breakpoint_conditions().is_some() {
+                        res.write_str(";ConditionalBreakpoints+")?;
+                    }
                 }
 
                 if T::Arch::target_description_xml().is_some()
@@ -111,39 +148,135 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                     res.write_str(";qXfer:memory-map:read+")?;
                 }
 
+                if target.auxv().is_some() {
+                    res.write_str(";qXfer:auxv:read+")?;
+                }
+
+                if target.exec_file().is_some() {
+                    res.write_str(";qXfer:exec-file:read+")?;
+                }
+
+                if let Some(ops) = target.siginfo() {
+                    res.write_str(";qXfer:siginfo:read+")?;
+                    if ops.support_siginfo_write().is_some() {
+                        res.write_str(";qXfer:siginfo:write+")?;
+                    }
+                }
+
+                if target.libraries_svr4().is_some() {
+                    res.write_str(";qXfer:libraries-svr4:read+")?;
+                }
+
+                if target.tracepoints().is_some() {
+                    res.write_str(";ConditionalTracepoints+")?;
+                }
+
+                if let BaseOps::MultiThread(ops) = target.base_ops() {
+                    if ops.support_thread_list().is_some() {
+                        res.write_str(";qXfer:threads:read+")?;
+                    }
+
+                    if ops.support_non_stop().is_some() {
+                        res.write_str(";QNonStop+")?;
+                    }
+                }
+
+                HandlerStatus::Handled
+            }
+            Base::qSymbol(cmd) => {
+                let ops = match target.symbol_lookup() {
+                    Some(ops) => ops,
+                    // the target doesn't need any symbols
+                    None => return Ok(HandlerStatus::NeedsOk),
+                };
+
+                match cmd.name {
+                    None => {
+                        self.symbol_lookup_active = true;
+                        ops.start_symbol_lookup().handle_error()?;
+                    }
+                    // ignore stray lookup results
+                    Some(_) if !self.symbol_lookup_active => return Ok(HandlerStatus::NeedsOk),
+                    Some(name) => {
+                        let addr = match cmd.value {
+                            Some(value) => Some(
+                                <T::Arch as Arch>::Usize::from_be_bytes(value)
+                                    .ok_or(Error::TargetMismatch)?,
+                            ),
+                            None => None,
+                        };
+                        ops.symbol_resolved(name, addr).handle_error()?;
+                    }
+                }
+
+                match ops.next_symbol().handle_error()? {
+                    Some(name) => {
+                        res.write_str("qSymbol:")?;
+                        res.write_hex_buf(name)?;
+                        HandlerStatus::Handled
+                    }
+                    None => {
+                        self.symbol_lookup_active = false;
+                        HandlerStatus::NeedsOk
+                    }
+                }
+            }
+            Base::qRcmd(cmd) => self.handle_monitor_cmd(res, target, cmd)?,
+            Base::qTStatus(_) => {
+                Self::write_trace_status(res, target)?;
                 HandlerStatus::Handled
             }
             Base::QStartNoAckMode(_) => {
                 self.no_ack_mode = true;
                 HandlerStatus::NeedsOk
             }
+            Base::QThreadEvents(cmd) => {
+                self.thread_events = cmd.value;
+                HandlerStatus::NeedsOk
+            }
             Base::qXferFeaturesRead(cmd) => {
-                #[allow(clippy::redundant_closure)]
-                let xml = target
-                    .target_description_xml_override()
-                    .map(|ops| ops.target_description_xml())
-                    .or_else(|| T::Arch::target_description_xml());
-
-                match xml {
-                    Some(xml) => {
-                        let xml = xml.trim();
-                        if cmd.offset >= xml.len() {
-                            // no more data
-                            res.write_str("l")?;
-                        } else if cmd.offset + cmd.len >= xml.len() {
-                            // last little bit of data
-                            res.write_str("l")?;
-                            res.write_binary(&xml.as_bytes()[cmd.offset..])?
-                        } else {
-                            // still more data
-                            res.write_str("m")?;
-                            res.write_binary(&xml.as_bytes()[cmd.offset..(cmd.offset + cmd.len)])?
+                let (xml, features) = match target.target_description_xml_override() {
+                    Some(ops) => (
+                        Some(ops.target_description_xml()),
+                        ops.target_description_xml_features(),
+                    ),
+                    None => (
+                        T::Arch::target_description_xml(),
+                        T::Arch::target_description_xml_features(),
+                    ),
+                };
+
+                // If the target hasn't provided their own XML, then the initial response to
+                // "qSupported" wouldn't have included  "qXfer:features:read", and gdb wouldn't
+                // send this packet unless it was explicitly marked as supported.
+                let xml = xml.ok_or(Error::PacketUnexpected)?.trim();
+
+                if cmd.annex == b"target.xml" {
+                    // splice an `<xi:include>` for each feature into the top-level description
+                    let split = match xml.rfind("</target>") {
+                        Some(idx) if !features.is_empty() => idx,
+                        _ => xml.len(),
+                    };
+                    let (head, tail) = xml.as_bytes().split_at(split);
+
+                    let includes = features.iter().flat_map(|f| {
+                        core::iter::once(b"<xi:include href=\"" as &[u8])
+                            .chain(core::iter::once(f.annex.as_bytes()))
+                            .chain(core::iter::once(b"\"/>" as &[u8]))
+                    });
+                    let parts = core::iter::once(head)
+                        .chain(includes)
+                        .chain(core::iter::once(tail));
+
+                    res.write_qxfer_chunk_parts(parts, cmd.offset, cmd.len)?;
+                } else {
+                    match features.iter().find(|f| f.annex.as_bytes() == cmd.annex) {
+                        Some(f) => {
+                            res.write_qxfer_chunk(f.xml.trim().as_bytes(), cmd.offset, cmd.len)?
                         }
+                        // unknown annex
+                        None => return Err(Error::NonFatalError(errno::UNSPECIFIED)),
                     }
-                    // If the target hasn't provided their own XML, then the initial response to
-                    // "qSupported" wouldn't have included  "qXfer:features:read", and gdb wouldn't
-                    // send this packet unless it was explicitly marked as supported.
-                    None => return Err(Error::PacketUnexpected),
                 }
                 HandlerStatus::Handled
             }
@@ -151,6 +284,12 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
             // -------------------- "Core" Functionality -------------------- //
             // TODO: Improve the '?' response based on last-sent stop reason.
             // this will be particularly relevant when working on non-stop mode.
+            Base::QuestionMark(_) if self.non_stop => self.question_mark_non_stop(res, target)?,
+            Base::QuestionMark(_) if self.killed.is_some() => {
+                let pid = self.killed.unwrap();
+                Self::write_killed(res, pid)?;
+                HandlerStatus::Handled
+            }
             Base::QuestionMark(_) => {
                 res.write_str("S05")?;
                 HandlerStatus::Handled
@@ -163,13 +302,33 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                     // When running in extended mode, we must defer to the target
                     Some(ops) => {
                         let pid: Pid = cmd.pid.ok_or(Error::PacketUnexpected)?;
-                        ops.query_if_attached(pid).handle_error()?.was_attached()
+
+                        // the target is the single source of truth, regardless of whether or not
+                        // `alloc` is available. Responses for processes spawned / attached to
+                        // during this session are simply cached.
+                        #[cfg(feature = "alloc")]
+                        let cached = self.attached_pids.get(&pid).copied().flatten();
+                        #[cfg(not(feature = "alloc"))]
+                        let cached = None;
+
+                        match cached {
+                            Some(was_attached) => was_attached,
+                            None => {
+                                let was_attached =
+                                    ops.query_if_attached(pid).handle_error()?.was_attached();
+                                #[cfg(feature = "alloc")]
+                                if let Some(entry) = self.attached_pids.get_mut(&pid) {
+                                    *entry = Some(was_attached);
+                                }
+                                was_attached
+                            }
+                        }
                     }
                 };
                 res.write_str(if is_attached { "1" } else { "0" })?;
                 HandlerStatus::Handled
             }
-            Base::g(_) => {
+            Base::g(cmd) => {
                 let mut regs: <T::Arch as Arch>::Registers = Default::default();
                 match target.base_ops() {
                     BaseOps::SingleThread(ops) => ops.read_registers(&mut regs),
@@ -179,6 +338,13 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                 }
                 .handle_error()?;
 
+                // fast path: serialize the entire register file in one go
+                if let Some(len) = regs.gdb_serialize_into(cmd.buf) {
+                    let buf = cmd.buf.get(..len).ok_or(Error::PacketBufferOverflow)?;
+                    res.write_hex_buf(buf)?;
+                    return Ok(HandlerStatus::Handled);
+                }
+
                 let mut err = Ok(());
                 regs.gdb_serialize(|val| {
                     let res = match val {
@@ -193,9 +359,21 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                 HandlerStatus::Handled
             }
             Base::G(cmd) => {
+                // deserialize into the target's current register file, as its
+                // layout may depend on the target's current execution mode
                 let mut regs: <T::Arch as Arch>::Registers = Default::default();
-                regs.gdb_deserialize(cmd.vals)
-                    .map_err(|_| Error::TargetMismatch)?;
+                match target.base_ops() {
+                    BaseOps::SingleThread(ops) => ops.read_registers(&mut regs),
+                    BaseOps::MultiThread(ops) => {
+                        ops.read_registers(&mut regs, self.current_mem_tid)
+                    }
+                }
+                .handle_error()?;
+
+                regs.gdb_deserialize(cmd.vals).map_err(|e| match e {
+                    DeserializeError::SizeMismatch => Error::NonFatalError(errno::EINVAL),
+                    DeserializeError::InvalidValue => Error::NonFatalError(errno::ERANGE),
+                })?;
 
                 match target.base_ops() {
                     BaseOps::SingleThread(ops) => ops.write_registers(&regs),
@@ -206,47 +384,158 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                 HandlerStatus::NeedsOk
             }
             Base::m(cmd) => {
-                let buf = cmd.buf;
+                self.read_memory(res, target, cmd.addr, cmd.len, cmd.buf)?;
+                HandlerStatus::Handled
+            }
+            Base::M(cmd) => {
+                let addr = <T::Arch as Arch>::Usize::from_be_bytes(cmd.addr)
+                    .ok_or(Error::TargetMismatch)?;
+
+                match target.base_ops() {
+                    BaseOps::SingleThread(ops) => ops.write_addrs(addr, cmd.val),
+                    BaseOps::MultiThread(ops) => {
+                        ops.write_addrs(addr, cmd.val, self.current_mem_tid)
+                    }
+                }
+                .handle_error()?;
+
+                HandlerStatus::NeedsOk
+            }
+            Base::qSearchMemory(cmd) => {
+                use num_traits::{Bounded, CheckedAdd, NumCast, One, ToPrimitive, Zero};
+
                 let addr = <T::Arch as Arch>::Usize::from_be_bytes(cmd.addr)
                     .ok_or(Error::TargetMismatch)?;
+                let mut remaining = <T::Arch as Arch>::Usize::from_be_bytes(cmd.len)
+                    .ok_or(Error::TargetMismatch)?;
+                let pattern = cmd.pattern;
+
+                // Stop the search at the end of the address space, instead of wrapping
+                // around to address 0.
+                let to_end = <T::Arch as Arch>::Usize::max_value() - addr;
+                if remaining > to_end {
+                    // `to_end` can only be the max value if `addr` is 0, in which case
+                    // `remaining` can't be any larger
+                    remaining = to_end + <T::Arch as Arch>::Usize::one();
+                }
+                let buf = cmd.buf;
 
-                let mut i = 0;
-                let mut n = cmd.len;
-                while n != 0 {
-                    let chunk_size = n.min(buf.len());
+                // A match can't be detected without being able to fit the entire pattern in
+                // the scratch buffer. Fall back to an empty response, which will have GDB
+                // search memory itself.
+                if pattern.is_empty() || buf.len() < pattern.len() {
+                    return Ok(HandlerStatus::Handled);
+                }
 
-                    use num_traits::NumCast;
+                // The last `pattern.len() - 1` bytes of each chunk are carried over to the
+                // start of the next chunk, in order to catch matches which straddle the
+                // boundary between two reads.
+                let overlap = pattern.len() - 1;
+                let mut carried = 0;
+                let mut next_addr = addr;
+                let mut found = None;
+                while !remaining.is_zero() {
+                    let free = buf.len() - carried;
+                    let chunk_size = match <<T::Arch as Arch>::Usize as NumCast>::from(free) {
+                        Some(free_usize) if free_usize < remaining => free,
+                        // remaining <= free, and is therefore representable as a usize
+                        _ => remaining.to_usize().ok_or(Error::TargetMismatch)?,
+                    };
 
-                    let addr = addr + NumCast::from(i).ok_or(Error::TargetMismatch)?;
-                    let data = &mut buf[..chunk_size];
+                    let data = &mut buf[carried..(carried + chunk_size)];
                     match target.base_ops() {
-                        BaseOps::SingleThread(ops) => ops.read_addrs(addr, data),
+                        BaseOps::SingleThread(ops) => ops.read_addrs(next_addr, data),
                         BaseOps::MultiThread(ops) => {
-                            ops.read_addrs(addr, data, self.current_mem_tid)
+                            ops.read_addrs(next_addr, data, self.current_mem_tid)
                         }
                     }
                     .handle_error()?;
 
-                    n -= chunk_size;
-                    i += chunk_size;
+                    let window = &buf[..(carried + chunk_size)];
+                    if let Some(pos) = window.windows(pattern.len()).position(|w| w == pattern) {
+                        // `window` starts `carried` bytes before `next_addr`
+                        let offset: <T::Arch as Arch>::Usize =
+                            NumCast::from(pos).ok_or(Error::TargetMismatch)?;
+                        let carried_usize: <T::Arch as Arch>::Usize =
+                            NumCast::from(carried).ok_or(Error::TargetMismatch)?;
+                        found = Some(next_addr - carried_usize + offset);
+                        break;
+                    }
 
-                    res.write_hex_buf(data)?;
+                    let chunk_size_usize: <T::Arch as Arch>::Usize =
+                        NumCast::from(chunk_size).ok_or(Error::TargetMismatch)?;
+                    remaining = remaining - chunk_size_usize;
+                    next_addr = match next_addr.checked_add(&chunk_size_usize) {
+                        Some(addr) => addr,
+                        // the search reached the very end of the address space
+                        None => break,
+                    };
+
+                    let window_len = window.len();
+                    carried = overlap.min(window_len);
+                    buf.copy_within((window_len - carried)..window_len, 0);
+                }
+
+                match found {
+                    Some(addr) => {
+                        res.write_str("1,")?;
+                        res.write_num(addr)?;
+                    }
+                    None => res.write_str("0")?,
                 }
                 HandlerStatus::Handled
             }
-            Base::M(cmd) => {
-                let addr = <T::Arch as Arch>::Usize::from_be_bytes(cmd.addr)
+            Base::qCRC(cmd) => {
+                use num_traits::{NumCast, Saturating, ToPrimitive, Zero};
+
+                let mut addr = <T::Arch as Arch>::Usize::from_be_bytes(cmd.addr)
+                    .ok_or(Error::TargetMismatch)?;
+                let mut remaining = <T::Arch as Arch>::Usize::from_be_bytes(cmd.len)
                     .ok_or(Error::TargetMismatch)?;
+                let buf = cmd.buf;
 
-                match target.base_ops() {
-                    BaseOps::SingleThread(ops) => ops.write_addrs(addr, cmd.val),
-                    BaseOps::MultiThread(ops) => {
-                        ops.write_addrs(addr, cmd.val, self.current_mem_tid)
+                if buf.is_empty() {
+                    return Err(Error::PacketBufferOverflow);
+                }
+
+                let mut crc = 0xffff_ffff;
+                while !remaining.is_zero() {
+                    let chunk_size = match <<T::Arch as Arch>::Usize as NumCast>::from(buf.len()) {
+                        Some(buf_len) if buf_len < remaining => buf.len(),
+                        // remaining <= buf.len(), and is therefore representable as a usize
+                        _ => remaining.to_usize().ok_or(Error::TargetMismatch)?,
+                    };
+
+                    let data = &mut buf[..chunk_size];
+                    let ok = match target.base_ops() {
+                        BaseOps::SingleThread(ops) => ops.read_addrs(addr, data),
+                        BaseOps::MultiThread(ops) => {
+                            ops.read_addrs(addr, data, self.current_mem_tid)
+                        }
                     }
+                    .handle_error();
+
+                    match ok {
+                        Ok(()) => {}
+                        // matches the behavior of gdbserver when memory can't be read
+                        Err(Error::NonFatalError(_)) => {
+                            return Err(Error::NonFatalError(errno::EPERM))
+                        }
+                        Err(e) => return Err(e),
+                    }
+
+                    crc = crate::util::crc32::crc32(crc, data);
+
+                    let chunk_size: <T::Arch as Arch>::Usize =
+                        NumCast::from(chunk_size).ok_or(Error::TargetMismatch)?;
+                    // saturate, as the region may extend right up to the end of the address space
+                    addr = addr.saturating_add(chunk_size);
+                    remaining = remaining - chunk_size;
                 }
-                .handle_error()?;
 
-                HandlerStatus::NeedsOk
+                res.write_str("C")?;
+                res.write_num(crc)?;
+                HandlerStatus::Handled
             }
             Base::k(_) | Base::vKill(_) => {
                 match target.extended_mode() {
@@ -257,66 +546,145 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                     // a disconnect...
                     Some(ops) => {
                         let pid = match command {
-                            Base::vKill(cmd) => Some(cmd.pid),
+                            Base::vKill(ref cmd) => Some(cmd.pid),
                             _ => None,
                         };
 
                         let should_terminate = ops.kill(pid).handle_error()?;
+
+                        #[cfg(feature = "alloc")]
+                        match pid {
+                            Some(pid) => drop(self.attached_pids.remove(&pid)),
+                            None => self.attached_pids.clear(),
+                        }
+
                         if should_terminate.into_bool() {
                             // manually write OK, since we need to return a DisconnectReason
                             res.write_str("OK")?;
-                            HandlerStatus::Disconnect(DisconnectReason::Kill)
-                        } else {
-                            HandlerStatus::NeedsOk
+                            return Ok(HandlerStatus::Disconnect(DisconnectReason::Kill));
+                        }
+
+                        self.killed = Some(pid);
+                        match command {
+                            // `vKill` must be acknowledged with an "OK"...
+                            Base::vKill(_) => HandlerStatus::NeedsOk,
+                            // ...whereas `k` is answered with a stop reply describing the
+                            // killed process.
+                            _ => {
+                                Self::write_killed(res, pid)?;
+                                HandlerStatus::Handled
+                            }
                         }
                     }
                 }
             }
-            Base::D(_) => {
-                // TODO: plumb-through Pid when exposing full multiprocess + extended mode
-                res.write_str("OK")?; // manually write OK, since we need to return a DisconnectReason
-                HandlerStatus::Disconnect(DisconnectReason::Disconnect)
+            Base::D(cmd) => {
+                let last_process = match target.extended_mode() {
+                    // when _not_ running in extended mode, there's only a single process
+                    None => true,
+                    Some(ops) => {
+                        ops.detach(cmd.pid).handle_error()?;
+
+                        #[cfg(feature = "alloc")]
+                        {
+                            match cmd.pid {
+                                Some(pid) => drop(self.attached_pids.remove(&pid)),
+                                None => self.attached_pids.clear(),
+                            }
+                            self.attached_pids.is_empty()
+                        }
+                        #[cfg(not(feature = "alloc"))]
+                        true
+                    }
+                };
+
+                if last_process {
+                    // manually write OK, since we need to return a DisconnectReason
+                    res.write_str("OK")?;
+                    HandlerStatus::Disconnect(DisconnectReason::Disconnect)
+                } else {
+                    HandlerStatus::NeedsOk
+                }
             }
             Base::vCont(cmd) => {
                 use crate::protocol::commands::_vCont::vCont;
                 match cmd {
                     vCont::Query => {
-                        res.write_str("vCont;c;C;s;S")?;
-                        if match target.base_ops() {
-                            BaseOps::SingleThread(ops) => ops.support_resume_range_step().is_some(),
-                            BaseOps::MultiThread(ops) => ops.support_range_step().is_some(),
-                        } {
+                        let (with_signal, step, range_step, stop) = match target.base_ops() {
+                            BaseOps::SingleThread(ops) => (
+                                ops.supports_resume_with_signal(),
+                                ops.supports_single_step(),
+                                ops.support_resume_range_step().is_some(),
+                                false,
+                            ),
+                            BaseOps::MultiThread(ops) => (
+                                ops.supports_resume_with_signal(),
+                                ops.supports_single_step(),
+                                ops.support_range_step().is_some(),
+                                ops.support_non_stop().is_some(),
+                            ),
+                        };
+
+                        if !step
+                            && T::Arch::single_step_gdb_behavior()
+                                == SingleStepGdbBehavior::Required
+                        {
+                            warn!("GDB requires single-stepping support on this architecture, but the target doesn't support it");
+                        }
+
+                        res.write_str("vCont;c")?;
+                        if with_signal {
+                            res.write_str(";C")?;
+                        }
+                        if step {
+                            res.write_str(";s")?;
+                        }
+                        if step && with_signal {
+                            res.write_str(";S")?;
+                        }
+                        if range_step {
                             res.write_str(";r")?;
                         }
+                        if stop {
+                            res.write_str(";t")?;
+                        }
                         HandlerStatus::Handled
                     }
-                    vCont::Actions(actions) => self.do_vcont(res, target, actions)?,
-                }
-            }
-            // TODO?: support custom resume addr in 'c' and 's'
-            //
-            // unfortunately, this wouldn't be a particularly easy thing to implement, since the
-            // vCont packet doesn't natively support custom resume addresses. This leaves a few
-            // options for the implementation:
-            //
-            // 1. Adding new ResumeActions (i.e: ContinueWithAddr(U) and StepWithAddr(U))
-            // 2. Automatically calling `read_registers`, updating the `pc`, and calling
-            //    `write_registers` prior to resuming.
-            //    - will require adding some sort of `get_pc_mut` method to the `Registers` trait.
-            //
-            // Option 1 is easier to implement, but puts more burden on the implementor. Option 2
-            // will require more effort to implement (and will be less performant), but it will hide
-            // this protocol wart from the end user.
-            //
-            // Oh, one more thought - there's a subtle pitfall to watch out for if implementing
-            // Option 1: if the target is using conditional breakpoints, `do_vcont` has to be
-            // modified to only pass the resume with address variants on the _first_ iteration
-            // through the loop.
-            Base::c(_) => {
+                    vCont::Actions(actions) => self.do_vcont(target, actions)?,
+                }
+            }
+            Base::vCtrlC(_) if self.non_stop => {
+                if self.threads_running {
+                    // the resulting stops are reported via the usual stop notifications
+                    match target.base_ops() {
+                        BaseOps::MultiThread(ops) => ops
+                            .support_non_stop()
+                            .ok_or(Error::PacketUnexpected)?
+                            .request_stop(None)
+                            .map_err(Error::TargetError)?,
+                        BaseOps::SingleThread(_) => return Err(Error::PacketUnexpected),
+                    }
+                }
+                HandlerStatus::NeedsOk
+            }
+            // in all-stop mode, the target is never running while the stub is servicing
+            // packets, so there's nothing to interrupt.
+            Base::vCtrlC(_) => HandlerStatus::NeedsOk,
+            // GDB uses this packet to check that unknown `v` packets are replied to with
+            // an empty response, so there's nothing more to do here.
+            Base::vMustReplyEmpty(_) => HandlerStatus::Handled,
+            // NOTE: `vCont` doesn't support custom resume addresses, so the PC is updated
+            // _before_ resuming the target. This also ensures the PC is only updated once,
+            // as the target may be resumed several times (e.g: when evaluating
+            // conditional breakpoints).
+            Base::c(cmd) => {
                 use crate::protocol::commands::_vCont::Actions;
 
+                if let Some(addr) = cmd.addr {
+                    self.set_resume_addr(target, addr)?;
+                }
+
                 self.do_vcont(
-                    res,
                     target,
                     Actions::new_continue(SpecificThreadId {
                         pid: None,
@@ -324,11 +692,14 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                     }),
                 )?
             }
-            Base::s(_) => {
+            Base::s(cmd) => {
                 use crate::protocol::commands::_vCont::Actions;
 
+                if let Some(addr) = cmd.addr {
+                    self.set_resume_addr(target, addr)?;
+                }
+
                 self.do_vcont(
-                    res,
                     target,
                     Actions::new_step(SpecificThreadId {
                         pid: None,
@@ -340,13 +711,18 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
             // ------------------- Multi-threading Support ------------------ //
             Base::H(cmd) => {
                 use crate::protocol::commands::_h_upcase::Op;
+                if let Some(IdKind::WithId(pid)) = cmd.thread.pid {
+                    self.select_process(target, pid)?;
+                }
                 match cmd.kind {
-                    Op::Other => match cmd.thread.tid {
-                        IdKind::Any => self.current_mem_tid = self.get_sane_any_tid(target)?,
-                        // "All" threads doesn't make sense for memory accesses
-                        IdKind::All => return Err(Error::PacketUnexpected),
-                        IdKind::WithId(tid) => self.current_mem_tid = tid,
-                    },
+                    Op::Other => {
+                        match cmd.thread.tid {
+                            IdKind::Any => self.current_mem_tid = self.get_sane_any_tid(target)?,
+                            // "All" threads doesn't make sense for memory accesses
+                            IdKind::All => return Err(Error::PacketUnexpected),
+                            IdKind::WithId(tid) => self.current_mem_tid = tid,
+                        }
+                    }
                     // technically, this variant is deprecated in favor of vCont...
                     Op::StepContinue => match cmd.thread.tid {
                         IdKind::Any => {
@@ -361,273 +737,626 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                 }
                 HandlerStatus::NeedsOk
             }
-            Base::qfThreadInfo(_) => {
-                res.write_str("m")?;
-
-                match target.base_ops() {
-                    BaseOps::SingleThread(_) => res.write_specific_thread_id(SpecificThreadId {
-                        pid: Some(SpecificIdKind::WithId(FAKE_PID)),
-                        tid: SpecificIdKind::WithId(SINGLE_THREAD_TID),
-                    })?,
-                    BaseOps::MultiThread(ops) => {
-                        let mut err: Result<_, Error<T::Error, C::Error>> = Ok(());
-                        let mut first = true;
-                        ops.list_active_threads(&mut |tid| {
-                            // TODO: replace this with a try block (once stabilized)
-                            let e = (|| {
-                                if !first {
-                                    res.write_str(",")?
-                                }
-                                first = false;
-                                res.write_specific_thread_id(SpecificThreadId {
-                                    pid: Some(SpecificIdKind::WithId(FAKE_PID)),
-                                    tid: SpecificIdKind::WithId(tid),
-                                })?;
-                                Ok(())
-                            })();
-
-                            if let Err(e) = e {
-                                err = Err(e)
-                            }
-                        })
-                        .map_err(Error::TargetError)?;
-                        err?;
-                    }
-                }
-
+            Base::qfThreadInfo(cmd) => {
+                self.thread_info_cursor = 0;
+                self.write_thread_info(res, target, cmd.packet_buffer_len)?;
                 HandlerStatus::Handled
             }
-            Base::qsThreadInfo(_) => {
-                res.write_str("l")?;
+            Base::qsThreadInfo(cmd) => {
+                self.write_thread_info(res, target, cmd.packet_buffer_len)?;
+                HandlerStatus::Handled
+            }
+            Base::qC(_) => {
+                res.write_str("QC")?;
+                let tid = match target.base_ops() {
+                    BaseOps::SingleThread(_) => SINGLE_THREAD_TID,
+                    BaseOps::MultiThread(_) => self.current_mem_tid,
+                };
+                let pid = self.thread_pid(target, tid)?;
+                res.write_specific_thread_id(SpecificThreadId {
+                    pid: Some(SpecificIdKind::WithId(pid)),
+                    tid: SpecificIdKind::WithId(tid),
+                })?;
                 HandlerStatus::Handled
             }
             Base::T(cmd) => {
-                let alive = match cmd.thread.tid {
-                    IdKind::WithId(tid) => match target.base_ops() {
-                        BaseOps::SingleThread(_) => tid == SINGLE_THREAD_TID,
-                        BaseOps::MultiThread(ops) => {
-                            ops.is_thread_alive(tid).map_err(Error::TargetError)?
-                        }
-                    },
-                    // TODO: double-check if GDB ever sends other variants
-                    // Even after ample testing, this arm has never been hit...
-                    _ => return Err(Error::PacketUnexpected),
+                let tid = match cmd.thread.tid {
+                    IdKind::WithId(tid) => tid,
+                    // GDB doesn't send these in practice, but a stray query shouldn't end the
+                    // session. "Any" thread is interpreted as the currently selected thread...
+                    IdKind::Any => self.current_mem_tid,
+                    // ...whereas liveness can't be reported for "all" threads at once.
+                    IdKind::All => return Err(Error::NonFatalError(errno::EINVAL)),
+                };
+                let alive = match target.base_ops() {
+                    BaseOps::SingleThread(_) => tid == SINGLE_THREAD_TID,
+                    BaseOps::MultiThread(ops) => {
+                        ops.is_thread_alive(tid).map_err(Error::TargetError)?
+                    }
                 };
                 if alive {
                     HandlerStatus::NeedsOk
                 } else {
                     // any error code will do
-                    return Err(Error::NonFatalError(1));
+                    return Err(Error::NonFatalError(errno::EPERM));
                 }
             }
         };
         Ok(handler_status)
     }
 
+    /// Read `len` bytes of memory starting at `addr` (i.e: the `m` packet),
+    /// using `buf` as scratch space.
+    fn read_memory(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+        addr: &[u8],
+        len: usize,
+        buf: &mut [u8],
+    ) -> Result<(), Error<T::Error, C::Error>> {
+        use num_traits::NumCast;
+
+        let addr = <T::Arch as Arch>::Usize::from_be_bytes(addr).ok_or(Error::TargetMismatch)?;
+
+        let current_mem_tid = self.current_mem_tid;
+
+        // targets which support streaming reads write their memory straight into the
+        // response, skipping the intermediate copy into `buf`.
+        let mut written = 0;
+        let mut write_err = Ok(());
+        let mut write = |data: &[u8]| {
+            let data = &data[..data.len().min(len - written)];
+            if data.is_empty() || write_err.is_err() {
+                return;
+            }
+            write_err = res.write_hex_buf(data);
+            written += data.len();
+        };
+        let streamed = match target.base_ops() {
+            BaseOps::SingleThread(ops) => ops
+                .support_streaming_read()
+                .map(|ops| ops.read_addrs_streaming(addr, len, &mut write)),
+            BaseOps::MultiThread(ops) => ops
+                .support_streaming_read()
+                .map(|ops| ops.read_addrs_streaming(addr, len, &mut write, current_mem_tid)),
+        };
+        if let Some(ret) = streamed {
+            write_err?;
+            return match ret.handle_error() {
+                Ok(()) => Ok(()),
+                // as with `read_addrs`, only report an error if no memory could be read at all
+                Err(Error::NonFatalError(_)) if written != 0 => Ok(()),
+                Err(e) => Err(e),
+            };
+        }
+
+        let mut read_addrs = |addr, data: &mut [u8]| {
+            match target.base_ops() {
+                BaseOps::SingleThread(ops) => ops.read_addrs(addr, data),
+                BaseOps::MultiThread(ops) => ops.read_addrs(addr, data, current_mem_tid),
+            }
+            .handle_error()
+        };
+
+        let mut i = 0;
+        let mut n = len;
+        while n != 0 {
+            let chunk_size = n.min(buf.len());
+
+            let addr = addr + NumCast::from(i).ok_or(Error::TargetMismatch)?;
+            let data = &mut buf[..chunk_size];
+            match read_addrs(addr, data) {
+                Ok(()) => {}
+                Err(Error::NonFatalError(code)) => {
+                    // GDB accepts replies shorter than requested (e.g: when a read
+                    // straddles the end of a mapped region), so bisect for the readable
+                    // prefix of the chunk.
+                    //
+                    // invariant: `data[..valid]` has been read, and `data[..invalid]`
+                    // can't be.
+                    let mut valid = 0;
+                    let mut invalid = chunk_size;
+                    while invalid - valid > 1 {
+                        let mid = valid + (invalid - valid) / 2;
+                        let start = addr + NumCast::from(valid).ok_or(Error::TargetMismatch)?;
+                        match read_addrs(start, &mut data[valid..mid]) {
+                            Ok(()) => valid = mid,
+                            Err(Error::NonFatalError(_)) => invalid = mid,
+                            Err(e) => return Err(e),
+                        }
+                    }
+
+                    // only report an error if no memory could be read at all
+                    if i + valid == 0 {
+                        return Err(Error::NonFatalError(code));
+                    }
+
+                    res.write_hex_buf(&data[..valid])?;
+                    break;
+                }
+                Err(e) => return Err(e),
+            }
+
+            res.write_hex_buf(data)?;
+
+            n -= chunk_size;
+            i += chunk_size;
+        }
+
+        Ok(())
+    }
+
+    /// Determine how a single threaded target should be resumed in response
+    /// to a `vCont` packet.
     #[allow(clippy::type_complexity)]
-    fn do_vcont_single_thread(
+    fn vcont_request_single_thread(
         ops: &mut dyn crate::target::ext::base::singlethread::SingleThreadOps<
             Arch = T::Arch,
             Error = T::Error,
         >,
-        res: &mut ResponseWriter<C>,
         actions: &crate::protocol::commands::_vCont::Actions,
-    ) -> Result<ThreadStopReason<<T::Arch as Arch>::Usize>, Error<T::Error, C::Error>> {
+        current_pid: Pid,
+    ) -> Result<ResumeRequest<<T::Arch as Arch>::Usize>, Error<T::Error, C::Error>> {
         use crate::protocol::commands::_vCont::VContKind;
 
-        let mut err = Ok(());
-        let mut check_gdb_interrupt = || match res.as_conn().peek() {
-            Ok(Some(0x03)) => true, // 0x03 is the interrupt byte
-            Ok(Some(_)) => false,   // it's nothing that can't wait...
-            Ok(None) => false,
-            Err(e) => {
-                err = Err(Error::ConnectionRead(e));
-                true // break ASAP if a connection error occurred
-            }
-        };
+        let actions = ResumeActions::new(actions, current_pid).map_err(Error::PacketParse)?;
 
-        let mut actions = actions.iter();
-        let first_action = actions
-            .next()
-            .ok_or(Error::PacketParse(
-                crate::protocol::PacketParseError::MalformedCommand,
-            ))?
-            .ok_or(Error::PacketParse(
-                crate::protocol::PacketParseError::MalformedCommand,
-            ))?;
-
-        let invalid_second_action = match actions.next() {
-            None => false,
-            Some(act) => match act {
-                None => {
-                    return Err(Error::PacketParse(
-                        crate::protocol::PacketParseError::MalformedCommand,
-                    ))
-                }
-                Some(act) => !matches!(act.kind, VContKind::Continue),
-            },
-        };
-
-        if invalid_second_action || actions.next().is_some() {
-            return Err(Error::PacketUnexpected);
-        }
+        // a single threaded target only cares about the action for its only thread
+        let kind = actions
+            .action_for(SINGLE_THREAD_TID)
+            .ok_or(Error::PacketUnexpected)?;
 
-        let action = match first_action.kind {
-            VContKind::Step => ResumeAction::Step,
-            VContKind::Continue => ResumeAction::Continue,
-            VContKind::StepWithSig(sig) => ResumeAction::StepWithSignal(sig),
-            VContKind::ContinueWithSig(sig) => ResumeAction::ContinueWithSignal(sig),
+        let req = match kind {
             VContKind::RangeStep(start, end) => {
-                if let Some(ops) = ops.support_resume_range_step() {
-                    let start = start.decode().map_err(|_| Error::TargetMismatch)?;
-                    let end = end.decode().map_err(|_| Error::TargetMismatch)?;
-
-                    let ret = ops
-                        .resume_range_step(start, end, GdbInterrupt::new(&mut check_gdb_interrupt))
-                        .map_err(Error::TargetError)?
-                        .into();
-                    err?;
-                    return Ok(ret);
-                } else {
+                if ops.support_resume_range_step().is_none() {
                     return Err(Error::PacketUnexpected);
                 }
+                ResumeRequest::RangeStep {
+                    start: start.decode().map_err(|_| Error::TargetMismatch)?,
+                    end: end.decode().map_err(|_| Error::TargetMismatch)?,
+                }
             }
-            // TODO: update this case when non-stop mode is implemented
+            // threads can't be stopped individually in all-stop mode
             VContKind::Stop => return Err(Error::PacketUnexpected),
+            VContKind::Step | VContKind::StepWithSig(_) if !ops.supports_single_step() => {
+                return Err(Error::PacketUnexpected)
+            }
+            kind => ResumeRequest::Resume(vcont_resume_action(kind)),
         };
 
-        let ret = ops
-            .resume(action, GdbInterrupt::new(&mut check_gdb_interrupt))
-            .map_err(Error::TargetError)?
-            .into();
+        Ok(req)
+    }
+
+    /// Resume the target as requested, blocking until it stops.
+    ///
+    /// GDB interrupts are detected by polling the connection (without
+    /// consuming the `0x03` byte).
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn resume_blocking(
+        &mut self,
+        target: &mut T,
+        conn: &mut C,
+        req: ResumeRequest<<T::Arch as Arch>::Usize>,
+    ) -> Result<ThreadStopReason<<T::Arch as Arch>::Usize>, Error<T::Error, C::Error>> {
+        let mut err = Ok(());
+        let mut check_gdb_interrupt = || match gdb_interrupt_pending(conn) {
+            Ok(pending) => pending,
+            Err(e) => {
+                err = Err(Error::ConnectionRead(e));
+                true // break ASAP if a connection error occurred
+            }
+        };
+        let gdb_interrupt = GdbInterrupt::new(&mut check_gdb_interrupt);
+
+        let ret = match target.base_ops() {
+            BaseOps::SingleThread(ops) => match req {
+                ResumeRequest::Resume(action) => ops.resume(action, gdb_interrupt),
+                ResumeRequest::RangeStep { start, end } => ops
+                    .support_resume_range_step()
+                    .ok_or(Error::PacketUnexpected)?
+                    .resume_range_step(start, end, gdb_interrupt),
+                ResumeRequest::ReverseCont => ops
+                    .support_reverse_cont()
+                    .ok_or(Error::PacketUnexpected)?
+                    .reverse_cont(gdb_interrupt),
+                ResumeRequest::ReverseStep(_) => ops
+                    .support_reverse_step()
+                    .ok_or(Error::PacketUnexpected)?
+                    .reverse_step(gdb_interrupt),
+            }
+            .map(Into::into),
+            BaseOps::MultiThread(ops) => match req {
+                ResumeRequest::Resume(action) => ops.resume(action, gdb_interrupt),
+                // range stepping is set up on a per-thread basis
+                ResumeRequest::RangeStep { .. } => return Err(Error::PacketUnexpected),
+                ResumeRequest::ReverseCont => ops
+                    .support_reverse_cont()
+                    .ok_or(Error::PacketUnexpected)?
+                    .reverse_cont(gdb_interrupt),
+                ResumeRequest::ReverseStep(tid) => ops
+                    .support_reverse_step()
+                    .ok_or(Error::PacketUnexpected)?
+                    .reverse_step(tid, gdb_interrupt),
+            },
+        }
+        .map_err(Error::TargetError)?;
+
         err?;
         Ok(ret)
     }
 
+    /// Pass the thread-specific `vCont` actions along to the target, returning
+    /// the action which applies to all other threads (if any).
+    ///
+    /// See [`ResumeActions`] for details on how conflicting actions are
+    /// resolved.
+    ///
+    /// Stop (i.e: `t`) actions are only valid in non-stop mode, and are passed
+    /// to the target as they are encountered.
     #[allow(clippy::type_complexity)]
-    fn do_vcont_multi_thread(
+    pub(super) fn set_vcont_actions_multi_thread(
         ops: &mut dyn crate::target::ext::base::multithread::MultiThreadOps<
             Arch = T::Arch,
             Error = T::Error,
         >,
-        res: &mut ResponseWriter<C>,
         actions: &crate::protocol::commands::_vCont::Actions,
-    ) -> Result<ThreadStopReason<<T::Arch as Arch>::Usize>, Error<T::Error, C::Error>> {
-        // this is a pretty arbitrary choice, but it seems reasonable for most cases.
-        let mut default_resume_action = ResumeAction::Continue;
+        current_pid: Pid,
+        non_stop: bool,
+    ) -> Result<Option<ResumeAction>, Error<T::Error, C::Error>> {
+        use crate::protocol::commands::_vCont::VContKind;
 
-        ops.clear_resume_actions().map_err(Error::TargetError)?;
+        let actions = ResumeActions::new(actions, current_pid).map_err(Error::PacketParse)?;
 
-        for action in actions.iter() {
-            use crate::protocol::commands::_vCont::VContKind;
+        ops.clear_resume_actions().map_err(Error::TargetError)?;
 
-            let action = action.ok_or(Error::PacketParse(
-                crate::protocol::PacketParseError::MalformedCommand,
-            ))?;
+        for (pid, tid, kind) in actions.specific_actions() {
+            // skip actions naming a thread which doesn't belong to the given process
+            if let SpecificIdKind::WithId(pid) = pid {
+                let owner = match ops.support_multiprocess() {
+                    Some(ops) => ops.thread_pid(tid).map_err(Error::TargetError)?,
+                    None => current_pid,
+                };
+                if owner != pid {
+                    continue;
+                }
+            }
 
-            let resume_action = match action.kind {
-                VContKind::Step => ResumeAction::Step,
-                VContKind::Continue => ResumeAction::Continue,
-                // there seems to be a GDB bug where it doesn't use `vCont` unless
-                // `vCont?` returns support for resuming with a signal.
-                VContKind::StepWithSig(sig) => ResumeAction::StepWithSignal(sig),
-                VContKind::ContinueWithSig(sig) => ResumeAction::ContinueWithSignal(sig),
+            let resume_action = match kind {
                 VContKind::RangeStep(start, end) => {
-                    if let Some(ops) = ops.support_range_step() {
-                        match action.thread.map(|thread| thread.tid) {
-                            // An action with no thread-id matches all threads
-                            None | Some(SpecificIdKind::All) => {
-                                return Err(Error::PacketUnexpected)
-                            }
-                            Some(SpecificIdKind::WithId(tid)) => {
-                                let start = start.decode().map_err(|_| Error::TargetMismatch)?;
-                                let end = end.decode().map_err(|_| Error::TargetMismatch)?;
+                    let ops = ops.support_range_step().ok_or(Error::PacketUnexpected)?;
+                    let start = start.decode().map_err(|_| Error::TargetMismatch)?;
+                    let end = end.decode().map_err(|_| Error::TargetMismatch)?;
 
-                                ops.set_resume_action_range_step(tid, start, end)
-                                    .map_err(Error::TargetError)?;
-                                continue;
-                            }
-                        };
-                    } else {
-                        return Err(Error::PacketUnexpected);
-                    }
+                    ops.set_resume_action_range_step(tid, start, end)
+                        .map_err(Error::TargetError)?;
+                    continue;
+                }
+                VContKind::Stop if non_stop => {
+                    ops.support_non_stop()
+                        .ok_or(Error::PacketUnexpected)?
+                        .request_stop(Some(tid))
+                        .map_err(Error::TargetError)?;
+                    continue;
                 }
-                // TODO: update this case when non-stop mode is implemented
+                // threads can't be stopped individually in all-stop mode
                 VContKind::Stop => return Err(Error::PacketUnexpected),
+                VContKind::Step | VContKind::StepWithSig(_) if !ops.supports_single_step() => {
+                    return Err(Error::PacketUnexpected)
+                }
+                kind => vcont_resume_action(kind),
             };
 
-            match action.thread.map(|thread| thread.tid) {
-                // An action with no thread-id matches all threads
-                None | Some(SpecificIdKind::All) => default_resume_action = resume_action,
-                Some(SpecificIdKind::WithId(tid)) => ops
-                    .set_resume_action(tid, resume_action)
-                    .map_err(Error::TargetError)?,
-            };
+            ops.set_resume_action(tid, resume_action)
+                .map_err(Error::TargetError)?;
         }
 
-        let mut err = Ok(());
-        let mut check_gdb_interrupt = || match res.as_conn().peek() {
-            Ok(Some(0x03)) => true, // 0x03 is the interrupt byte
-            Ok(Some(_)) => false,   // it's nothing that can't wait...
-            Ok(None) => false,
-            Err(e) => {
-                err = Err(Error::ConnectionRead(e));
-                true // break ASAP if a connection error occurred
+        let default_resume_action = match actions.default_action() {
+            None => None,
+            // range stepping only applies to specific threads
+            Some(VContKind::RangeStep(..)) => return Err(Error::PacketUnexpected),
+            Some(VContKind::Stop) if non_stop => {
+                ops.support_non_stop()
+                    .ok_or(Error::PacketUnexpected)?
+                    .request_stop(None)
+                    .map_err(Error::TargetError)?;
+                None
             }
+            Some(VContKind::Stop) => return Err(Error::PacketUnexpected),
+            Some(VContKind::Step | VContKind::StepWithSig(_)) if !ops.supports_single_step() => {
+                return Err(Error::PacketUnexpected)
+            }
+            Some(kind) => Some(vcont_resume_action(kind)),
         };
 
-        let ret = ops
-            .resume(
-                default_resume_action,
-                GdbInterrupt::new(&mut check_gdb_interrupt),
-            )
-            .map_err(Error::TargetError)?;
-
-        err?;
-
-        Ok(ret)
+        Ok(default_resume_action)
     }
 
     fn do_vcont(
         &mut self,
-        res: &mut ResponseWriter<C>,
         target: &mut T,
         actions: crate::protocol::commands::_vCont::Actions,
     ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
-        loop {
-            let stop_reason = match target.base_ops() {
-                BaseOps::SingleThread(ops) => Self::do_vcont_single_thread(ops, res, &actions)?,
-                BaseOps::MultiThread(ops) => Self::do_vcont_multi_thread(ops, res, &actions)?,
-            };
+        // reject malformed action lists (e.g: a `vCont;` without any actions)
+        // up-front, without ending the debugging session.
+        let malformed = {
+            let mut iter = actions.iter();
+            !matches!(iter.next(), Some(Some(_))) || iter.any(|act| act.is_none())
+        };
+        if malformed {
+            return Err(Error::NonFatalError(errno::UNSPECIFIED));
+        }
+
+        // threads can only be stopped individually in non-stop mode
+        let stops = actions
+            .iter()
+            .flatten()
+            .any(|act| matches!(act.kind, crate::protocol::commands::_vCont::VContKind::Stop));
+        if stops && !self.non_stop {
+            return Err(Error::NonFatalError(errno::EINVAL));
+        }
+
+        if self.non_stop {
+            return self.do_vcont_non_stop(target, actions);
+        }
+
+        let req = match target.base_ops() {
+            BaseOps::SingleThread(ops) => {
+                Self::vcont_request_single_thread(ops, &actions, self.current_pid)?
+            }
+            // this is a pretty arbitrary choice, but it seems reasonable for most cases.
+            BaseOps::MultiThread(ops) => ResumeRequest::Resume(
+                Self::set_vcont_actions_multi_thread(ops, &actions, self.current_pid, false)?
+                    .unwrap_or(ResumeAction::Continue),
+            ),
+        };
 
-            match self.finish_exec(res, target, stop_reason)? {
-                Some(status) => break Ok(status),
-                None => continue,
+        self.running = Some(req);
+        Ok(HandlerStatus::Resume)
+    }
+
+    pub(super) fn write_break_common(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+        tid: Tid,
+    ) -> Result<(), Error<T::Error, C::Error>> {
+        self.write_stop_common(res, target, tid, Signal::SIGTRAP)
+    }
+
+    /// Update the PC of the thread selected for resumption (via `Hc`) prior to
+    /// resuming it (i.e: in response to a `c <addr>` / `s <addr>` packet).
+    fn set_resume_addr(
+        &mut self,
+        target: &mut T,
+        addr: &[u8],
+    ) -> Result<(), Error<T::Error, C::Error>> {
+        let addr = <T::Arch as Arch>::Usize::from_be_bytes(addr).ok_or(Error::TargetMismatch)?;
+
+        let tid = match (target.base_ops(), self.current_resume_tid) {
+            (_, SpecificIdKind::WithId(tid)) => tid,
+            // single threaded targets only have the one thread to resume...
+            (BaseOps::SingleThread(_), SpecificIdKind::All) => SINGLE_THREAD_TID,
+            // ...whereas there's no telling which thread's PC should be updated
+            (BaseOps::MultiThread(_), SpecificIdKind::All) => {
+                return Err(Error::NonFatalError(errno::EINVAL))
             }
+        };
+
+        let mut regs: <T::Arch as Arch>::Registers = Default::default();
+        match target.base_ops() {
+            BaseOps::SingleThread(ops) => ops.read_registers(&mut regs),
+            BaseOps::MultiThread(ops) => ops.read_registers(&mut regs, tid),
+        }
+        .handle_error()?;
+
+        match regs.pc_mut() {
+            Some(pc) => *pc = addr,
+            None => return Err(Error::NonFatalError(errno::EINVAL)),
         }
+
+        match target.base_ops() {
+            BaseOps::SingleThread(ops) => ops.write_registers(&regs),
+            BaseOps::MultiThread(ops) => ops.write_registers(&regs, tid),
+        }
+        .handle_error()?;
+
+        Ok(())
     }
 
-    fn write_break_common(
+    /// Switch to the process selected by an `H` packet (or which owns the
+    /// thread that just stopped).
+    ///
+    /// Targets which don't implement extended mode only ever debug a single
+    /// process, so the PID is ignored (leaving it as `FAKE_PID`).
+    fn select_process(
+        &mut self,
+        target: &mut T,
+        pid: Pid,
+    ) -> Result<(), Error<T::Error, C::Error>> {
+        let ops = match target.extended_mode() {
+            Some(ops) => ops,
+            None => return Ok(()),
+        };
+
+        if pid != self.current_pid {
+            ops.select_process(pid).handle_error()?;
+            self.current_pid = pid;
+        }
+
+        Ok(())
+    }
+
+    /// Return the process which owns the thread `tid`.
+    ///
+    /// Targets which don't report the owner of each thread are assumed to
+    /// only debug the currently selected process (which is always `FAKE_PID`
+    /// for non-multiprocess targets).
+    fn thread_pid(&mut self, target: &mut T, tid: Tid) -> Result<Pid, Error<T::Error, C::Error>> {
+        match target.base_ops() {
+            BaseOps::MultiThread(ops) => match ops.support_multiprocess() {
+                Some(ops) => ops.thread_pid(tid).map_err(Error::TargetError),
+                None => Ok(self.current_pid),
+            },
+            BaseOps::SingleThread(_) => Ok(self.current_pid),
+        }
+    }
+
+    /// Write a stop reply reporting that a process was killed (i.e:
+    /// terminated by `SIGKILL`).
+    fn write_killed(
+        res: &mut ResponseWriter<C>,
+        pid: Option<Pid>,
+    ) -> Result<(), Error<T::Error, C::Error>> {
+        res.write_str("X")?;
+        res.write_num(Signal::SIGKILL.as_u8())?;
+        if let Some(pid) = pid {
+            res.write_str(";process:")?;
+            res.write_num(pid.get())?;
+        }
+        Ok(())
+    }
+
+    /// Write the next chunk of active threads in response to a `qfThreadInfo`
+    /// / `qsThreadInfo` packet, picking up after the last thread reported by
+    /// the previous chunk. Writes `l` once all threads have been reported.
+    ///
+    /// Each chunk is sized to fit within GDB's packet buffer, which avoids
+    /// overflowing it on targets with a large number of threads.
+    fn write_thread_info(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+        packet_buffer_len: usize,
+    ) -> Result<(), Error<T::Error, C::Error>> {
+        // worst-case length of a single `p<pid>.<tid>,` entry
+        const MAX_THREAD_ID_LEN: usize = "p01.".len() + core::mem::size_of::<usize>() * 2 + 1;
+        // leave room for the `$m` / `#xx` packet framing
+        let max_threads = (packet_buffer_len.saturating_sub(5) / MAX_THREAD_ID_LEN).max(1);
+
+        let skip = self.thread_info_cursor;
+        let current_pid = self.current_pid;
+        let mut written = 0;
+        let mut write_tid = |res: &mut ResponseWriter<C>, pid: Pid, tid: Tid| {
+            res.write_str(if written == 0 { "m" } else { "," })?;
+            written += 1;
+            res.write_specific_thread_id(SpecificThreadId {
+                pid: Some(SpecificIdKind::WithId(pid)),
+                tid: SpecificIdKind::WithId(tid),
+            })
+        };
+
+        match target.base_ops() {
+            BaseOps::SingleThread(_) => {
+                if skip == 0 {
+                    write_tid(res, current_pid, SINGLE_THREAD_TID)?;
+                }
+            }
+            BaseOps::MultiThread(ops) => {
+                let mut err: Result<_, Error<T::Error, C::Error>> = Ok(());
+                let mut idx = 0;
+                let mut thread_is_active = |pid: Pid, tid: Tid| {
+                    idx += 1;
+                    if idx <= skip || idx > skip + max_threads || err.is_err() {
+                        return;
+                    }
+
+                    if let Err(e) = write_tid(res, pid, tid) {
+                        err = Err(e.into())
+                    }
+                };
+                match ops.support_multiprocess() {
+                    Some(ops) => ops.list_active_process_threads(&mut thread_is_active),
+                    None => ops.list_active_threads(&mut |tid| thread_is_active(current_pid, tid)),
+                }
+                .map_err(Error::TargetError)?;
+                err?;
+            }
+        }
+
+        if written == 0 {
+            res.write_str("l")?;
+        }
+        self.thread_info_cursor += written;
+
+        Ok(())
+    }
+
+    /// Report that the target stopped in response to an asynchronous
+    /// interrupt request from GDB (i.e: a `0x03` byte).
+    pub(crate) fn write_interrupt_common(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+    ) -> Result<(), Error<T::Error, C::Error>> {
+        let tid = self.get_sane_any_tid(target)?;
+        self.write_stop_common(res, target, tid, Signal::SIGINT)
+    }
+
+    fn write_stop_common(
         &mut self,
         res: &mut ResponseWriter<C>,
+        target: &mut T,
         tid: Tid,
+        signal: Signal,
     ) -> Result<(), Error<T::Error, C::Error>> {
+        let pid = self.thread_pid(target, tid)?;
+        self.select_process(target, pid)?;
         self.current_mem_tid = tid;
         self.current_resume_tid = SpecificIdKind::WithId(tid);
 
-        res.write_str("T05")?;
+        res.write_str("T")?;
+        res.write_num(signal.as_u8())?;
 
         res.write_str("thread:")?;
         res.write_specific_thread_id(SpecificThreadId {
-            pid: Some(SpecificIdKind::WithId(FAKE_PID)),
+            pid: Some(SpecificIdKind::WithId(pid)),
             tid: SpecificIdKind::WithId(tid),
         })?;
         res.write_str(";")?;
 
+        if <<T::Arch as Arch>::Registers as Registers>::EXPEDITE {
+            Self::write_expedited_regs(res, target, tid)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_expedited_regs(
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+        tid: Tid,
+    ) -> Result<(), Error<T::Error, C::Error>> {
+        let mut regs: <T::Arch as Arch>::Registers = Default::default();
+        let read = match target.base_ops() {
+            BaseOps::SingleThread(ops) => ops.read_registers(&mut regs),
+            BaseOps::MultiThread(ops) => ops.read_registers(&mut regs, tid),
+        };
+
+        match read.handle_error() {
+            Ok(()) => {}
+            // the GDB client will fall back to fetching the registers via `g`
+            Err(Error::NonFatalError(_)) => return Ok(()),
+            Err(e) => return Err(e),
+        }
+
+        let mut err = Ok(());
+        regs.gdb_expedite(|regnum, val| {
+            if err.is_ok() {
+                err = (|| {
+                    res.write_num(regnum)?;
+                    res.write_str(":")?;
+                    res.write_hex_buf(val)?;
+                    res.write_str(";")
+                })();
+            }
+        });
+        err?;
+
         Ok(())
     }
 
-    pub(super) fn finish_exec(
+    pub(crate) fn finish_exec(
         &mut self,
         res: &mut ResponseWriter<C>,
         target: &mut T,
@@ -655,14 +1384,42 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
             };
         }
 
+        macro_rules! guard_catch {
+            () => {
+                target
+                    .extended_mode()
+                    .and_then(|ops| ops.catch_events())
+                    .is_some()
+            };
+        }
+
         let status = match stop_reason {
-            ThreadStopReason::DoneStep | ThreadStopReason::GdbInterrupt => {
+            ThreadStopReason::DoneStep => {
                 res.write_str("S05")?;
                 HandlerStatus::Handled
             }
+            ThreadStopReason::GdbInterrupt => {
+                self.write_interrupt_common(res, target)?;
+                HandlerStatus::Handled
+            }
             ThreadStopReason::Signal(sig) => {
                 res.write_str("S")?;
-                res.write_num(sig)?;
+                res.write_num(sig.as_u8())?;
+                HandlerStatus::Handled
+            }
+            ThreadStopReason::ThreadSignal { tid, signal } => {
+                self.write_stop_common(res, target, tid, signal)?;
+                HandlerStatus::Handled
+            }
+            ThreadStopReason::SignalWithInfo { tid, signal, addr } => {
+                self.write_stop_common(res, target, tid, signal)?;
+
+                // GDB doesn't have a stop reply field for fault addresses, but
+                // LLDB displays the (hex-encoded) `description` field.
+                res.write_str("description:")?;
+                res.write_hex_buf(b"fault address: 0x")?;
+                res.write_hex_encoded_num(addr)?;
+                res.write_str(";")?;
                 HandlerStatus::Handled
             }
             ThreadStopReason::Exited(code) => {
@@ -672,27 +1429,31 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
             }
             ThreadStopReason::Terminated(sig) => {
                 res.write_str("X")?;
-                res.write_num(sig)?;
+                res.write_num(sig.as_u8())?;
                 HandlerStatus::Disconnect(DisconnectReason::TargetTerminated(sig))
             }
             ThreadStopReason::SwBreak(tid) if guard_break!(sw_breakpoint) => {
                 crate::__dead_code_marker!("sw_breakpoint", "stop_reason");
 
-                self.write_break_common(res, tid)?;
-                res.write_str("swbreak:;")?;
+                self.write_break_common(res, target, tid)?;
+                if self.client_features.swbreak {
+                    res.write_str("swbreak:;")?;
+                }
                 HandlerStatus::Handled
             }
             ThreadStopReason::HwBreak(tid) if guard_break!(hw_breakpoint) => {
                 crate::__dead_code_marker!("hw_breakpoint", "stop_reason");
 
-                self.write_break_common(res, tid)?;
-                res.write_str("hwbreak:;")?;
+                self.write_break_common(res, target, tid)?;
+                if self.client_features.hwbreak {
+                    res.write_str("hwbreak:;")?;
+                }
                 HandlerStatus::Handled
             }
             ThreadStopReason::Watch { tid, kind, addr } if guard_break!(hw_watchpoint) => {
                 crate::__dead_code_marker!("hw_watchpoint", "stop_reason");
 
-                self.write_break_common(res, tid)?;
+                self.write_break_common(res, target, tid)?;
 
                 use crate::target::ext::breakpoints::WatchKind;
                 match kind {
@@ -700,10 +1461,99 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                     WatchKind::Read => res.write_str("rwatch:")?,
                     WatchKind::ReadWrite => res.write_str("awatch:")?,
                 }
-                res.write_num(addr)?;
+                res.write_addr(addr)?;
+                res.write_str(";")?;
+                HandlerStatus::Handled
+            }
+            ThreadStopReason::Stopped(tid) => {
+                self.write_stop_common(res, target, tid, Signal::Other(0))?;
+                HandlerStatus::Handled
+            }
+            // thread events are only reported once GDB has asked for them.
+            // Otherwise, the target is simply resumed.
+            ThreadStopReason::ThreadCreated(_) | ThreadStopReason::ThreadExited { .. }
+                if !self.thread_events =>
+            {
+                return Ok(None)
+            }
+            // likewise, fork / exec events are only reported if GDB asked for
+            // them (via `qSupported`)
+            ThreadStopReason::Fork { .. } if !self.client_features.fork_events => return Ok(None),
+            ThreadStopReason::VFork { .. } | ThreadStopReason::VForkDone(_)
+                if !self.client_features.vfork_events =>
+            {
+                return Ok(None)
+            }
+            ThreadStopReason::Exec(_) if !self.client_features.exec_events => return Ok(None),
+            ThreadStopReason::Fork {
+                tid,
+                new_pid,
+                new_tid,
+            }
+            | ThreadStopReason::VFork {
+                tid,
+                new_pid,
+                new_tid,
+            } if guard_catch!() => {
+                crate::__dead_code_marker!("catch_events", "stop_reason");
+
+                self.write_break_common(res, target, tid)?;
+                match stop_reason {
+                    ThreadStopReason::Fork { .. } => res.write_str("fork:")?,
+                    _ => res.write_str("vfork:")?,
+                }
+                res.write_specific_thread_id(SpecificThreadId {
+                    pid: Some(SpecificIdKind::WithId(new_pid)),
+                    tid: SpecificIdKind::WithId(new_tid),
+                })?;
+                res.write_str(";")?;
+
+                // GDB may detach from the child process (or kill it) once it's
+                // done with it
+                #[cfg(feature = "alloc")]
+                self.attached_pids.insert(new_pid, None);
+
+                HandlerStatus::Handled
+            }
+            ThreadStopReason::VForkDone(tid) if guard_catch!() => {
+                crate::__dead_code_marker!("catch_events", "stop_reason");
+
+                self.write_break_common(res, target, tid)?;
+                res.write_str("vforkdone:;")?;
+                HandlerStatus::Handled
+            }
+            ThreadStopReason::Exec(tid) if guard_catch!() => {
+                crate::__dead_code_marker!("catch_events", "stop_reason");
+
+                let pid = self.thread_pid(target, tid)?;
+                self.write_break_common(res, target, tid)?;
+                let path = target
+                    .extended_mode()
+                    .and_then(|ops| ops.catch_events())
+                    .ok_or(Error::UnsupportedStopReason)?
+                    .exec_path(pid)
+                    .map_err(Error::TargetError)?;
+                res.write_str("exec:")?;
+                res.write_hex_buf(path)?;
                 res.write_str(";")?;
                 HandlerStatus::Handled
             }
+            ThreadStopReason::ThreadCreated(tid) => {
+                self.write_break_common(res, target, tid)?;
+                res.write_str("create:;")?;
+                HandlerStatus::Handled
+            }
+            ThreadStopReason::ThreadExited { tid, status } => {
+                let pid = self.thread_pid(target, tid)?;
+                res.write_str("w")?;
+                res.write_num(status)?;
+                res.write_str(";")?;
+                res.write_specific_thread_id(SpecificThreadId {
+                    pid: Some(SpecificIdKind::WithId(pid)),
+                    tid: SpecificIdKind::WithId(tid),
+                })?;
+                HandlerStatus::Handled
+            }
             ThreadStopReason::ReplayLog(pos) if guard_reverse_exec!() => {
                 crate::__dead_code_marker!("reverse_exec", "stop_reason");
 
@@ -725,6 +1575,22 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
     }
 }
 
+/// Convert one of the "base" `vCont` actions (i.e: `c`, `C`, `s`, and `S`)
+/// into its corresponding `ResumeAction`.
+///
+/// Panics if passed any other kind of action.
+fn vcont_resume_action(kind: crate::protocol::commands::_vCont::VContKind) -> ResumeAction {
+    use crate::protocol::commands::_vCont::VContKind;
+
+    match kind {
+        VContKind::Step => ResumeAction::Step,
+        VContKind::Continue => ResumeAction::Continue,
+        VContKind::StepWithSig(sig) => ResumeAction::StepWithSignal(Signal::from_u8(sig)),
+        VContKind::ContinueWithSig(sig) => ResumeAction::ContinueWithSignal(Signal::from_u8(sig)),
+        VContKind::RangeStep(..) | VContKind::Stop => unreachable!(),
+    }
+}
+
 use crate::target::ext::base::singlethread::StopReason;
 impl<U> From<StopReason<U>> for ThreadStopReason<U> {
     fn from(st_stop_reason: StopReason<U>) -> ThreadStopReason<U> {
@@ -741,6 +1607,11 @@ impl<U> From<StopReason<U>> for ThreadStopReason<U> {
                 addr,
             },
             StopReason::Signal(sig) => ThreadStopReason::Signal(sig),
+            StopReason::SignalWithInfo { signal, addr } => ThreadStopReason::SignalWithInfo {
+                tid: SINGLE_THREAD_TID,
+                signal,
+                addr,
+            },
             StopReason::ReplayLog(pos) => ThreadStopReason::ReplayLog(pos),
         }
     }
@@ -2,6 +2,7 @@ use super::prelude::*;
 use crate::protocol::commands::ext::Breakpoints;
 
 use crate::arch::{Arch, BreakpointKind};
+use num_traits::{FromPrimitive, Zero};
 
 enum CmdKind {
     Add,
@@ -18,18 +19,48 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
     ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
         let addr =
             <T::Arch as Arch>::Usize::from_be_bytes(cmd.addr).ok_or(Error::TargetMismatch)?;
-        let kind =
-            <T::Arch as Arch>::BreakpointKind::from_usize(cmd.kind).ok_or(Error::TargetMismatch)?;
+        let (kind, len) = match cmd.type_ {
+            // a watchpoint's `kind` is the length of the watched region, which
+            // isn't an arch-specific breakpoint kind.
+            2..=4 => (
+                None,
+                <T::Arch as Arch>::Usize::from_usize(cmd.kind)
+                    .ok_or(Error::NonFatalError(errno::EINVAL))?,
+            ),
+            // an unrecognized kind may simply be one this target's `Arch`
+            // doesn't support (e.g: a Thumb-2 breakpoint on an ARMv4T), so it
+            // shouldn't bring down the entire debugging session.
+            _ => {
+                let kind = match <T::Arch as Arch>::BreakpointKind::from_usize(cmd.kind) {
+                    Some(kind) => Some(kind),
+                    // the client didn't specify a kind, so let the arch pick one
+                    None if cmd.kind == 0 => <T::Arch as Arch>::breakpoint_kind(addr),
+                    None => None,
+                };
+                (
+                    Some(kind.ok_or(Error::NonFatalError(errno::EINVAL))?),
+                    Zero::zero(),
+                )
+            }
+        };
 
         let handler_status = match cmd_kind {
             CmdKind::Add => {
                 use crate::target::ext::breakpoints::WatchKind::*;
-                let supported = match cmd.type_ {
-                    0 => (ops.sw_breakpoint()).map(|op| op.add_sw_breakpoint(addr, kind)),
-                    1 => (ops.hw_breakpoint()).map(|op| op.add_hw_breakpoint(addr, kind)),
-                    2 => (ops.hw_watchpoint()).map(|op| op.add_hw_watchpoint(addr, Write)),
-                    3 => (ops.hw_watchpoint()).map(|op| op.add_hw_watchpoint(addr, Read)),
-                    4 => (ops.hw_watchpoint()).map(|op| op.add_hw_watchpoint(addr, ReadWrite)),
+                let supported = match (cmd.type_, kind) {
+                    (0, Some(kind)) => {
+                        (ops.sw_breakpoint()).map(|op| op.add_sw_breakpoint(addr, kind))
+                    }
+                    (1, Some(kind)) => {
+                        (ops.hw_breakpoint()).map(|op| op.add_hw_breakpoint(addr, kind))
+                    }
+                    (2, _) => {
+                        (ops.hw_watchpoint()).map(|op| op.add_hw_watchpoint(addr, len, Write))
+                    }
+                    (3, _) => (ops.hw_watchpoint()).map(|op| op.add_hw_watchpoint(addr, len, Read)),
+                    (4, _) => {
+                        (ops.hw_watchpoint()).map(|op| op.add_hw_watchpoint(addr, len, ReadWrite))
+                    }
                     // only 5 types in the protocol
                     _ => None,
                 };
@@ -41,17 +72,26 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                         HandlerStatus::Handled
                     }
                     Some(Ok(true)) => HandlerStatus::NeedsOk,
-                    Some(Ok(false)) => return Err(Error::NonFatalError(22)),
+                    Some(Ok(false)) => return Err(Error::NonFatalError(errno::EINVAL)),
                 }
             }
             CmdKind::Remove => {
                 use crate::target::ext::breakpoints::WatchKind::*;
-                let supported = match cmd.type_ {
-                    0 => (ops.sw_breakpoint()).map(|op| op.remove_sw_breakpoint(addr, kind)),
-                    1 => (ops.hw_breakpoint()).map(|op| op.remove_hw_breakpoint(addr, kind)),
-                    2 => (ops.hw_watchpoint()).map(|op| op.remove_hw_watchpoint(addr, Write)),
-                    3 => (ops.hw_watchpoint()).map(|op| op.remove_hw_watchpoint(addr, Read)),
-                    4 => (ops.hw_watchpoint()).map(|op| op.remove_hw_watchpoint(addr, ReadWrite)),
+                let supported = match (cmd.type_, kind) {
+                    (0, Some(kind)) => {
+                        (ops.sw_breakpoint()).map(|op| op.remove_sw_breakpoint(addr, kind))
+                    }
+                    (1, Some(kind)) => {
+                        (ops.hw_breakpoint()).map(|op| op.remove_hw_breakpoint(addr, kind))
+                    }
+                    (2, _) => {
+                        (ops.hw_watchpoint()).map(|op| op.remove_hw_watchpoint(addr, len, Write))
+                    }
+                    (3, _) => {
+                        (ops.hw_watchpoint()).map(|op| op.remove_hw_watchpoint(addr, len, Read))
+                    }
+                    (4, _) => (ops.hw_watchpoint())
+                        .map(|op| op.remove_hw_watchpoint(addr, len, ReadWrite)),
                     // only 5 types in the protocol
                     _ => None,
                 };
@@ -63,7 +103,7 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                         HandlerStatus::Handled
                     }
                     Some(Ok(true)) => HandlerStatus::NeedsOk,
-                    Some(Ok(false)) => return Err(Error::NonFatalError(22)),
+                    Some(Ok(false)) => return Err(Error::NonFatalError(errno::EINVAL)),
                 }
             }
         };
@@ -88,8 +128,46 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
             Breakpoints::z(cmd) => self.handle_breakpoint_common(ops, cmd, CmdKind::Remove)?,
             Breakpoints::Z(cmd) => self.handle_breakpoint_common(ops, cmd, CmdKind::Add)?,
             Breakpoints::ZWithBytecode(cmd) => {
-                warn!("Client sent breakpoint packet with bytecode even though target didn't support agent expressions");
-                self.handle_breakpoint_common(ops, cmd.base, CmdKind::Add)?
+                use crate::target::ext::breakpoints::Conditions;
+
+                if cmd.cmds_persist.is_some() {
+                    warn!("Client sent breakpoint commands even though target didn't support them");
+                }
+
+                let (type_, addr) = (cmd.base.type_, cmd.base.addr);
+                let status = self.handle_breakpoint_common(ops, cmd.base, CmdKind::Add)?;
+
+                // conditions only apply to breakpoints which were successfully inserted
+                match (status, type_) {
+                    (HandlerStatus::NeedsOk, 0 | 1) => {
+                        let addr = <T::Arch as Arch>::Usize::from_be_bytes(addr)
+                            .ok_or(Error::TargetMismatch)?;
+
+                        let mut malformed = false;
+                        let mut conds = cmd
+                            .conds
+                            .into_iter()
+                            .flat_map(|conds| conds.into_iter())
+                            .take_while(|cond| {
+                                malformed = cond.is_none();
+                                !malformed
+                            })
+                            .flatten();
+
+                        let ops = target.breakpoints().unwrap();
+                        ops.breakpoint_conditions()
+                            .unwrap()
+                            .set_breakpoint_conditions(addr, Conditions::new(&mut conds))
+                            .handle_error()?;
+                        drop(conds);
+
+                        if malformed {
+                            return Err(Error::NonFatalError(errno::EINVAL));
+                        }
+                        HandlerStatus::NeedsOk
+                    }
+                    (status, _) => status,
+                }
             }
         };
         Ok(handler_status)
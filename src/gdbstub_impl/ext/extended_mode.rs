@@ -18,14 +18,32 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
         let handler_status = match command {
             ExtendedMode::ExclamationMark(_cmd) => {
                 ops.on_start().map_err(Error::TargetError)?;
+                self.extended_mode_enabled = true;
                 HandlerStatus::NeedsOk
             }
+            // the client must have requested extended mode (via `!`) before it
+            // can spawn / restart processes. `R` never gets a reply, so there's no
+            // way to report the error: the packet is simply ignored.
+            //
+            // `vAttach` isn't gated, as LLDB attaches to processes without
+            // sending `!` first.
+            ExtendedMode::R(_cmd) if !self.extended_mode_enabled => HandlerStatus::NoResponse,
+            ExtendedMode::vRun(_) if !self.extended_mode_enabled => {
+                return Err(Error::NonFatalError(errno::EPERM))
+            }
             ExtendedMode::R(_cmd) => {
                 ops.restart().map_err(Error::TargetError)?;
-                HandlerStatus::Handled
+                self.killed = None;
+                // "There is no reply to this packet."
+                HandlerStatus::NoResponse
             }
             ExtendedMode::vAttach(cmd) => {
                 ops.attach(cmd.pid).handle_error()?;
+                self.killed = None;
+                self.current_pid = cmd.pid;
+
+                #[cfg(feature = "alloc")]
+                self.attached_pids.insert(cmd.pid, None);
 
                 // TODO: sends OK when running in Non-Stop mode
                 HandlerStatus::Handled
@@ -33,9 +51,24 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
             ExtendedMode::vRun(cmd) => {
                 use crate::target::ext::extended_mode::Args;
 
-                let _pid = ops
-                    .run(cmd.filename, Args::new(&mut cmd.args.into_iter()))
+                #[cfg(feature = "alloc")]
+                let filename = {
+                    if let Some(filename) = cmd.filename {
+                        self.exec_file = Some(filename.to_vec());
+                    }
+                    self.exec_file.as_deref()
+                };
+                #[cfg(not(feature = "alloc"))]
+                let filename = cmd.filename;
+
+                let pid = ops
+                    .run(filename, Args::new(&mut cmd.args.into_iter()))
                     .handle_error()?;
+                self.killed = None;
+                self.current_pid = pid;
+
+                #[cfg(feature = "alloc")]
+                self.attached_pids.insert(pid, None);
 
                 // TODO: send a more descriptive stop packet?
                 res.write_str("S05")?;
@@ -44,7 +77,8 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
             // --------- ASLR --------- //
             ExtendedMode::QDisableRandomization(cmd) if ops.configure_aslr().is_some() => {
                 let ops = ops.configure_aslr().unwrap();
-                ops.cfg_aslr(cmd.value).handle_error()?;
+                let disable = cmd.value.ok_or(Error::NonFatalError(errno::EINVAL))?;
+                ops.cfg_aslr(!disable).handle_error()?;
                 HandlerStatus::NeedsOk
             }
             // --------- Environment --------- //
@@ -0,0 +1,45 @@
+use super::prelude::*;
+use crate::protocol::commands::ext::Flash;
+
+use crate::arch::Arch;
+
+impl<T: Target, C: Connection> GdbStubImpl<T, C> {
+    pub(crate) fn handle_flash(
+        &mut self,
+        _res: &mut ResponseWriter<C>,
+        target: &mut T,
+        command: Flash,
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        let ops = match target.flash() {
+            Some(ops) => ops,
+            None => return Ok(HandlerStatus::Handled),
+        };
+
+        crate::__dead_code_marker!("flash", "impl");
+
+        let handler_status = match command {
+            Flash::vFlashErase(cmd) => {
+                let addr = <T::Arch as Arch>::Usize::from_be_bytes(cmd.addr)
+                    .ok_or(Error::TargetMismatch)?;
+                let length = <T::Arch as Arch>::Usize::from_be_bytes(cmd.length)
+                    .ok_or(Error::TargetMismatch)?;
+
+                ops.flash_erase(addr, length).handle_error()?;
+                HandlerStatus::NeedsOk
+            }
+            Flash::vFlashWrite(cmd) => {
+                let addr = <T::Arch as Arch>::Usize::from_be_bytes(cmd.addr)
+                    .ok_or(Error::TargetMismatch)?;
+
+                ops.flash_write(addr, cmd.val).handle_error()?;
+                HandlerStatus::NeedsOk
+            }
+            Flash::vFlashDone(_) => {
+                ops.flash_done().handle_error()?;
+                HandlerStatus::NeedsOk
+            }
+        };
+
+        Ok(handler_status)
+    }
+}
@@ -0,0 +1,168 @@
+use super::prelude::*;
+use crate::protocol::commands::ext::HostIo;
+
+use crate::target::ext::host_io::{HostIoErrno, HostIoError, HostIoOpenFlags, HostIoOpenMode};
+
+impl<T: Target, C: Connection> GdbStubImpl<T, C> {
+    pub(crate) fn handle_host_io<'a>(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+        command: HostIo<'a>,
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        let ops = match target.host_io() {
+            Some(ops) => ops,
+            None => return Ok(HandlerStatus::Handled),
+        };
+
+        crate::__dead_code_marker!("host_io", "impl");
+
+        // Writes the `F<retcode>` response corresponding to the result of a Host
+        // I/O operation, with `$ok` called to report successful results.
+        macro_rules! handle_hostio_result {
+            ($ret:expr, |$val:pat| $ok:expr) => {
+                match $ret {
+                    Ok($val) => $ok,
+                    Err(HostIoError::Errno(errno)) => {
+                        res.write_str("F-1,")?;
+                        res.write_num(errno as u32)?;
+                    }
+                    Err(HostIoError::Fatal(e)) => return Err(Error::TargetError(e)),
+                }
+            };
+        }
+
+        // Unimplemented operations report `EINVAL` back to the client.
+        macro_rules! unsupported {
+            () => {{
+                res.write_str("F-1,")?;
+                res.write_num(HostIoErrno::EINVAL as u32)?;
+                return Ok(HandlerStatus::Handled);
+            }};
+        }
+
+        let handler_status = match command {
+            HostIo::vFileOpen(cmd) => {
+                let ret = ops.open(
+                    cmd.filename,
+                    HostIoOpenFlags::from_bits(cmd.flags),
+                    HostIoOpenMode::from_bits(cmd.mode),
+                );
+                handle_hostio_result!(ret, |fd| {
+                    res.write_str("F")?;
+                    res.write_num(fd)?;
+                });
+                HandlerStatus::Handled
+            }
+            HostIo::vFileClose(cmd) => {
+                let ret = ops.close(cmd.fd);
+                handle_hostio_result!(ret, |()| res.write_str("F0")?);
+                HandlerStatus::Handled
+            }
+            HostIo::vFilePread(cmd) => {
+                let count = cmd.count.min(cmd.buf.len());
+                let buf = &mut cmd.buf[..count];
+                let ret = ops.pread(cmd.fd, cmd.offset, buf);
+                handle_hostio_result!(ret, |n| {
+                    let data = &buf[..n.min(count)];
+                    res.write_str("F")?;
+                    res.write_num(data.len())?;
+                    res.write_str(";")?;
+                    res.write_binary(data)?;
+                });
+                HandlerStatus::Handled
+            }
+            HostIo::vFilePwrite(cmd) => {
+                let ops = match ops.support_pwrite() {
+                    Some(ops) => ops,
+                    None => unsupported!(),
+                };
+
+                let ret = ops.pwrite(cmd.fd, cmd.offset, cmd.data);
+                handle_hostio_result!(ret, |n| {
+                    res.write_str("F")?;
+                    res.write_num(n)?;
+                });
+                HandlerStatus::Handled
+            }
+            HostIo::vFileFstat(cmd) => {
+                let ops = match ops.support_fstat() {
+                    Some(ops) => ops,
+                    None => unsupported!(),
+                };
+
+                let ret = ops.fstat(cmd.fd);
+                handle_hostio_result!(ret, |stat| {
+                    // GDB expects a big-endian `struct stat`, as defined by the
+                    // File-I/O protocol extension.
+                    let mut buf = [0; 64];
+                    let fields: [&[u8]; 13] = [
+                        &stat.st_dev.to_be_bytes(),
+                        &stat.st_ino.to_be_bytes(),
+                        &stat.st_mode.to_be_bytes(),
+                        &stat.st_nlink.to_be_bytes(),
+                        &stat.st_uid.to_be_bytes(),
+                        &stat.st_gid.to_be_bytes(),
+                        &stat.st_rdev.to_be_bytes(),
+                        &stat.st_size.to_be_bytes(),
+                        &stat.st_blksize.to_be_bytes(),
+                        &stat.st_blocks.to_be_bytes(),
+                        &stat.st_atime.to_be_bytes(),
+                        &stat.st_mtime.to_be_bytes(),
+                        &stat.st_ctime.to_be_bytes(),
+                    ];
+                    let mut i = 0;
+                    for field in fields.iter() {
+                        buf[i..(i + field.len())].copy_from_slice(field);
+                        i += field.len();
+                    }
+
+                    res.write_str("F")?;
+                    res.write_num(buf.len())?;
+                    res.write_str(";")?;
+                    res.write_binary(&buf)?;
+                });
+                HandlerStatus::Handled
+            }
+            HostIo::vFileUnlink(cmd) => {
+                let ops = match ops.support_unlink() {
+                    Some(ops) => ops,
+                    None => unsupported!(),
+                };
+
+                let ret = ops.unlink(cmd.filename);
+                handle_hostio_result!(ret, |()| res.write_str("F0")?);
+                HandlerStatus::Handled
+            }
+            HostIo::vFileReadlink(cmd) => {
+                let ops = match ops.support_readlink() {
+                    Some(ops) => ops,
+                    None => unsupported!(),
+                };
+
+                let buf = cmd.buf;
+                let ret = ops.readlink(cmd.filename, buf);
+                handle_hostio_result!(ret, |n| {
+                    let data = &buf[..n.min(buf.len())];
+                    res.write_str("F")?;
+                    res.write_num(data.len())?;
+                    res.write_str(";")?;
+                    res.write_binary(data)?;
+                });
+                HandlerStatus::Handled
+            }
+            HostIo::vFileSetfs(cmd) => {
+                let ops = match ops.support_setfs() {
+                    Some(ops) => ops,
+                    None => unsupported!(),
+                };
+
+                let ret = ops.setfs(cmd.pid);
+                handle_hostio_result!(ret, |()| res.write_str("F0")?);
+                HandlerStatus::Handled
+            }
+        };
+
+        Ok(handler_status)
+    }
+}
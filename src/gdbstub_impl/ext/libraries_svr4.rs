@@ -0,0 +1,60 @@
+use super::prelude::*;
+use crate::protocol::commands::ext::LibrariesSvr4;
+
+use managed::ManagedSlice;
+
+use crate::util::managed_vec::ManagedVec;
+
+use super::xml::{push_addr, push_escaped, push_str};
+
+impl<T: Target, C: Connection> GdbStubImpl<T, C> {
+    pub(crate) fn handle_libraries_svr4<'a>(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+        command: LibrariesSvr4<'a>,
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        let ops = match target.libraries_svr4() {
+            Some(ops) => ops,
+            None => return Ok(HandlerStatus::Handled),
+        };
+
+        crate::__dead_code_marker!("libraries_svr4", "impl");
+
+        let handler_status = match command {
+            LibrariesSvr4::qXferLibrariesSvr4Read(cmd) => {
+                let mut buf = ManagedSlice::Borrowed(cmd.buf);
+                let mut xml = ManagedVec::new(&mut buf);
+
+                let mut err: Result<_, Error<T::Error, C::Error>> = Ok(());
+                push_str(
+                    &mut xml,
+                    "<?xml version=\"1.0\"?>\n<library-list-svr4 version=\"1.0\">\n",
+                )?;
+                ops.list_libraries(&mut |lib| {
+                    if err.is_ok() {
+                        err = (|| {
+                            push_str(&mut xml, "<library name=\"")?;
+                            push_escaped(&mut xml, lib.name)?;
+                            push_str(&mut xml, "\" lm=\"")?;
+                            push_addr(&mut xml, lib.lm)?;
+                            push_str(&mut xml, "\" l_addr=\"")?;
+                            push_addr(&mut xml, lib.l_addr)?;
+                            push_str(&mut xml, "\" l_ld=\"")?;
+                            push_addr(&mut xml, lib.l_ld)?;
+                            push_str(&mut xml, "\"/>\n")
+                        })();
+                    }
+                })
+                .map_err(Error::TargetError)?;
+                err?;
+                push_str(&mut xml, "</library-list-svr4>\n")?;
+
+                res.write_qxfer_chunk(xml.as_slice(), cmd.offset, cmd.len)?;
+                HandlerStatus::Handled
+            }
+        };
+
+        Ok(handler_status)
+    }
+}
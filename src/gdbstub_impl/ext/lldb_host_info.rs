@@ -0,0 +1,73 @@
+use super::prelude::*;
+use crate::protocol::commands::ext::LldbHostInfo;
+
+use crate::arch::Arch;
+use crate::target::ext::lldb_host_info::{Endianness, LldbHostInfoOps};
+
+impl<T: Target, C: Connection> GdbStubImpl<T, C> {
+    pub(crate) fn handle_lldb_host_info(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+        command: LldbHostInfo,
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        let ops = match target.lldb_host_info() {
+            Some(ops) => ops,
+            None => return Ok(HandlerStatus::Handled),
+        };
+
+        crate::__dead_code_marker!("lldb_host_info", "impl");
+
+        let handler_status = match command {
+            LldbHostInfo::qHostInfo(_) => {
+                write_host_info::<T, C>(res, ops)?;
+                HandlerStatus::Handled
+            }
+            LldbHostInfo::qProcessInfo(_) => {
+                res.write_str("pid:")?;
+                res.write_num(self.current_pid.get())?;
+                res.write_str(";")?;
+                write_host_info::<T, C>(res, ops)?;
+                HandlerStatus::Handled
+            }
+        };
+
+        Ok(handler_status)
+    }
+}
+
+/// Write the `key:value;` pairs shared by the `qHostInfo` and `qProcessInfo`
+/// replies.
+fn write_host_info<T: Target, C: Connection>(
+    res: &mut ResponseWriter<C>,
+    ops: LldbHostInfoOps<T>,
+) -> Result<(), Error<T::Error, C::Error>> {
+    let info = ops.get_host_info();
+
+    res.write_str("triple:")?;
+    res.write_hex_buf(info.triple.as_bytes())?;
+    res.write_str(";")?;
+    if let Some(vendor) = info.vendor {
+        res.write_str("vendor:")?;
+        res.write_binary(vendor.as_bytes())?;
+        res.write_str(";")?;
+    }
+    if let Some(ostype) = info.ostype {
+        res.write_str("ostype:")?;
+        res.write_binary(ostype.as_bytes())?;
+        res.write_str(";")?;
+    }
+    if let Some(endian) = info.endian {
+        res.write_str("endian:")?;
+        res.write_str(match endian {
+            Endianness::Little => "little",
+            Endianness::Big => "big",
+        })?;
+        res.write_str(";")?;
+    }
+    res.write_str("ptrsize:")?;
+    res.write_dec(core::mem::size_of::<<T::Arch as Arch>::Usize>())?;
+    res.write_str(";")?;
+
+    Ok(())
+}
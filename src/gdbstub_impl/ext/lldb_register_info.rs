@@ -0,0 +1,114 @@
+use super::prelude::*;
+use crate::protocol::commands::ext::LldbRegisterInfo;
+
+use crate::target::ext::lldb_register_info::{Encoding, Format, Generic};
+
+/// The error code LLDB expects once there are no more registers to enumerate.
+const NO_SUCH_REGISTER: u8 = 0x45;
+
+impl<T: Target, C: Connection> GdbStubImpl<T, C> {
+    pub(crate) fn handle_lldb_register_info(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+        command: LldbRegisterInfo,
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        let ops = match target.lldb_register_info() {
+            Some(ops) => ops,
+            None => return Ok(HandlerStatus::Handled),
+        };
+
+        crate::__dead_code_marker!("lldb_register_info", "impl");
+
+        let handler_status = match command {
+            LldbRegisterInfo::qRegisterInfo(cmd) => {
+                let reg = ops
+                    .get_register_info(cmd.reg_id)
+                    .ok_or(Error::NonFatalError(NO_SUCH_REGISTER))?;
+
+                res.write_str("name:")?;
+                res.write_binary(reg.name.as_bytes())?;
+                if let Some(alt_name) = reg.alt_name {
+                    res.write_str(";alt-name:")?;
+                    res.write_binary(alt_name.as_bytes())?;
+                }
+                res.write_str(";bitsize:")?;
+                res.write_dec(reg.bitsize)?;
+                res.write_str(";offset:")?;
+                res.write_dec(reg.offset)?;
+                res.write_str(";encoding:")?;
+                res.write_str(match reg.encoding {
+                    Encoding::Uint => "uint",
+                    Encoding::Sint => "sint",
+                    Encoding::IEEE754 => "ieee754",
+                    Encoding::Vector => "vector",
+                })?;
+                res.write_str(";format:")?;
+                res.write_str(match reg.format {
+                    Format::Binary => "binary",
+                    Format::Decimal => "decimal",
+                    Format::Hex => "hex",
+                    Format::Float => "float",
+                    Format::VectorSInt8 => "vector-sint8",
+                    Format::VectorUInt8 => "vector-uint8",
+                    Format::VectorSInt16 => "vector-sint16",
+                    Format::VectorUInt16 => "vector-uint16",
+                    Format::VectorSInt32 => "vector-sint32",
+                    Format::VectorUInt32 => "vector-uint32",
+                    Format::VectorFloat32 => "vector-float32",
+                    Format::VectorUInt128 => "vector-uint128",
+                })?;
+                res.write_str(";set:")?;
+                res.write_binary(reg.set.as_bytes())?;
+                if let Some(gcc) = reg.gcc {
+                    res.write_str(";gcc:")?;
+                    res.write_dec(gcc)?;
+                }
+                if let Some(dwarf) = reg.dwarf {
+                    res.write_str(";dwarf:")?;
+                    res.write_dec(dwarf)?;
+                }
+                if let Some(generic) = reg.generic {
+                    res.write_str(";generic:")?;
+                    res.write_str(match generic {
+                        Generic::Pc => "pc",
+                        Generic::Sp => "sp",
+                        Generic::Fp => "fp",
+                        Generic::Ra => "ra",
+                        Generic::Flags => "flags",
+                        Generic::Arg1 => "arg1",
+                        Generic::Arg2 => "arg2",
+                        Generic::Arg3 => "arg3",
+                        Generic::Arg4 => "arg4",
+                        Generic::Arg5 => "arg5",
+                        Generic::Arg6 => "arg6",
+                        Generic::Arg7 => "arg7",
+                        Generic::Arg8 => "arg8",
+                    })?;
+                }
+                // register lists are comma-separated hex register numbers
+                for (key, regs) in [
+                    (";container-regs:", reg.container_regs),
+                    (";invalidate-regs:", reg.invalidate_regs),
+                ]
+                .iter()
+                {
+                    if let Some(regs) = regs {
+                        res.write_str(key)?;
+                        for (i, reg_id) in regs.iter().enumerate() {
+                            if i != 0 {
+                                res.write_str(",")?;
+                            }
+                            res.write_num(*reg_id)?;
+                        }
+                    }
+                }
+                res.write_str(";")?;
+
+                HandlerStatus::Handled
+            }
+        };
+
+        Ok(handler_status)
+    }
+}
@@ -18,18 +18,7 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
         let handler_status = match command {
             MemoryMap::qXferMemoryMapRead(cmd) => {
                 let xml = ops.memory_map_xml().trim();
-                if cmd.offset >= xml.len() {
-                    // no more data
-                    res.write_str("l")?;
-                } else if cmd.offset + cmd.len >= xml.len() {
-                    // last little bit of data
-                    res.write_str("l")?;
-                    res.write_binary(&xml.as_bytes()[cmd.offset..])?
-                } else {
-                    // still more data
-                    res.write_str("m")?;
-                    res.write_binary(&xml.as_bytes()[cmd.offset..(cmd.offset + cmd.len)])?
-                }
+                res.write_qxfer_chunk(xml.as_bytes(), cmd.offset, cmd.len)?;
 
                 HandlerStatus::Handled
             }
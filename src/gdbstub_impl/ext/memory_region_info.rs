@@ -0,0 +1,59 @@
+use super::prelude::*;
+use crate::protocol::commands::ext::MemoryRegionInfo;
+
+use crate::arch::Arch;
+
+impl<T: Target, C: Connection> GdbStubImpl<T, C> {
+    pub(crate) fn handle_memory_region_info(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+        command: MemoryRegionInfo,
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        let ops = match target.memory_region_info() {
+            Some(ops) => ops,
+            None => return Ok(HandlerStatus::Handled),
+        };
+
+        crate::__dead_code_marker!("memory_region_info", "impl");
+
+        let handler_status = match command {
+            MemoryRegionInfo::qMemoryRegionInfo(cmd) => {
+                let addr = match cmd.addr {
+                    Some(addr) => addr,
+                    // the client is probing for support
+                    None => return Ok(HandlerStatus::NeedsOk),
+                };
+
+                let addr =
+                    <T::Arch as Arch>::Usize::from_be_bytes(addr).ok_or(Error::TargetMismatch)?;
+                let region = ops.get_memory_region(addr).handle_error()?;
+
+                res.write_str("start:")?;
+                res.write_num(region.start)?;
+                res.write_str(";size:")?;
+                res.write_num(region.size)?;
+                res.write_str(";")?;
+
+                // unmapped regions are reported without any permissions
+                if region.readable || region.writable || region.executable {
+                    res.write_str("permissions:")?;
+                    if region.readable {
+                        res.write_str("r")?;
+                    }
+                    if region.writable {
+                        res.write_str("w")?;
+                    }
+                    if region.executable {
+                        res.write_str("x")?;
+                    }
+                    res.write_str(";")?;
+                }
+
+                HandlerStatus::Handled
+            }
+        };
+
+        Ok(handler_status)
+    }
+}
@@ -8,14 +8,33 @@ mod prelude {
 
     pub(super) use super::super::error::GdbStubError as Error;
     pub(super) use super::super::target_result_ext::TargetResultExt;
-    pub(super) use super::super::{DisconnectReason, GdbStubImpl, HandlerStatus};
+    pub(super) use super::super::{
+        gdb_interrupt_pending, ClientFeatures, DisconnectReason, GdbStubImpl, HandlerStatus,
+        ResumeRequest,
+    };
 }
 
+mod auxv;
 mod base;
 mod breakpoints;
+mod exec_file;
 mod extended_mode;
+mod flash;
+mod host_io;
+mod libraries_svr4;
+mod lldb_host_info;
+mod lldb_register_info;
 mod memory_map;
+mod memory_region_info;
 mod monitor_cmd;
+mod non_stop;
+mod pass_signals;
 mod reverse_exec;
 mod section_offsets;
+mod siginfo;
 mod single_register_access;
+mod thread_extra_info;
+mod thread_list;
+mod tls_access;
+mod tracepoints;
+mod xml;
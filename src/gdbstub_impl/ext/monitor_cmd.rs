@@ -1,48 +1,76 @@
 use super::prelude::*;
-use crate::protocol::commands::ext::MonitorCmd;
+use crate::protocol::commands::_qRcmd::qRcmd;
 
 use crate::protocol::ConsoleOutput;
 
 impl<T: Target, C: Connection> GdbStubImpl<T, C> {
+    /// Handle a `qRcmd` packet.
+    ///
+    /// Unlike most extensions, `qRcmd` is handled regardless of whether the
+    /// target implements `MonitorCmd`, as the stub implements some `monitor`
+    /// commands itself (e.g: `monitor gdbstub version`).
     pub(crate) fn handle_monitor_cmd<'a>(
         &mut self,
         res: &mut ResponseWriter<C>,
         target: &mut T,
-        command: MonitorCmd<'a>,
+        cmd: qRcmd<'a>,
     ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
-        let ops = match target.monitor_cmd() {
-            Some(ops) => ops,
-            None => return Ok(HandlerStatus::Handled),
+        let ops = match (cmd.hex_cmd, target.monitor_cmd()) {
+            (VERSION_CMD, _) => None,
+            (_, Some(ops)) => Some(ops),
+            (_, None) => return Ok(HandlerStatus::Handled),
         };
 
-        crate::__dead_code_marker!("monitor_cmd", "impl");
-
-        let handler_status = match command {
-            MonitorCmd::qRcmd(cmd) => {
-                let mut err: Result<_, Error<T::Error, C::Error>> = Ok(());
-                let mut callback = |msg: &[u8]| {
-                    // TODO: replace this with a try block (once stabilized)
-                    let e = (|| {
-                        let mut res = ResponseWriter::new(res.as_conn());
-                        res.write_str("O")?;
-                        res.write_hex_buf(msg)?;
-                        res.flush()?;
-                        Ok(())
-                    })();
-
-                    if let Err(e) = e {
-                        err = Err(e)
-                    }
-                };
-
-                ops.handle_monitor_cmd(cmd.hex_cmd, ConsoleOutput::new(&mut callback))
-                    .map_err(Error::TargetError)?;
-                err?;
-
-                HandlerStatus::NeedsOk
+        let mut err: Result<_, Error<T::Error, C::Error>> = Ok(());
+        let mut callback = |msg: &[u8]| {
+            // TODO: replace this with a try block (once stabilized)
+            let e = (|| {
+                let mut res = res.reborrow();
+                res.write_str("O")?;
+                res.write_hex_buf(msg)?;
+                res.flush()?;
+                Ok(())
+            })();
+
+            if let Err(e) = e {
+                err = Err(e)
             }
         };
 
-        Ok(handler_status)
+        let out = ConsoleOutput::new(&mut callback);
+        match ops {
+            Some(ops) => {
+                crate::__dead_code_marker!("monitor_cmd", "impl");
+
+                ops.handle_monitor_cmd(cmd.hex_cmd, out)
+                    .map_err(Error::TargetError)?
+            }
+            None => write_version_info(out),
+        }
+        err?;
+
+        Ok(HandlerStatus::NeedsOk)
+    }
+}
+
+/// Built-in `monitor` command which reports the `gdbstub` version, along with
+/// the set of enabled crate features.
+const VERSION_CMD: &[u8] = b"gdbstub version";
+
+fn write_version_info(mut out: ConsoleOutput<'_>) {
+    out.write_raw(b"gdbstub ");
+    out.write_raw(env!("CARGO_PKG_VERSION").as_bytes());
+    out.write_raw(b"\nfeatures:");
+    for &(feature, enabled) in [
+        ("alloc", cfg!(feature = "alloc")),
+        ("std", cfg!(feature = "std")),
+    ]
+    .iter()
+    {
+        if enabled {
+            out.write_raw(b" ");
+            out.write_raw(feature.as_bytes());
+        }
     }
+    out.write_raw(b"\n");
 }
@@ -0,0 +1,246 @@
+//! GDB's non-stop mode.
+//!
+//! In non-stop mode, GDB resumes and stops threads independently of one
+//! another, and keeps sending packets to the stub while threads are running.
+//!
+//! As `gdbstub` services packets in a blocking loop, non-stop mode is modeled
+//! using the following state (stored in `GdbStubImpl`):
+//!
+//! - `non_stop`: toggled via `QNonStop`.
+//! - `threads_running`: set once GDB resumes threads via `vCont`, and cleared
+//!   once the process exits.
+//! - `pending_stop`: the stop which was most recently reported to GDB, and
+//!   which GDB has yet to acknowledge via `vStopped`.
+//!
+//! Transitions:
+//!
+//! ```text
+//!                 vCont                    a thread stops
+//! [all stopped] --------> [running] -----------------------> [stop pending]
+//!                          |     ^        (`%Stop:` sent)           |
+//!          GDB sends a     |     |                                  |
+//!          packet          v     |                                  |
+//!                  [service packet]   <------- vStopped ------------+
+//! ```
+//!
+//! - While threads are running and no stop is pending, the stub calls
+//!   `MultiThreadNonStop::wait_for_stop` prior to reading each packet. The
+//!   target returns `GdbInterrupt` once GDB sends a packet, or the stop reason
+//!   of a single thread. (When driven by a `GdbStubStateMachine`, the host
+//!   reports stops via `report_stop` instead.)
+//! - A thread stopping is sent to GDB as a `%Stop:<stop reply>` notification,
+//!   and becomes the `pending_stop`. The stub doesn't wait for further stops
+//!   until GDB acknowledges the notification via `vStopped`, which avoids
+//!   having to queue any additional stops.
+//! - `?` reports each already-stopped thread in turn: the first in the reply
+//!   to `?` itself, and the rest in the replies to subsequent `vStopped`
+//!   packets (using `pending_stop` to track the next thread to report).
+
+use super::prelude::*;
+use crate::protocol::commands::ext::NonStop;
+
+use crate::protocol::commands::_vCont::Actions;
+use crate::target::ext::base::multithread::ThreadStopReason;
+use crate::target::ext::base::{BaseOps, GdbInterrupt};
+
+use super::super::PendingStop;
+
+impl<T: Target, C: Connection> GdbStubImpl<T, C> {
+    pub(crate) fn handle_non_stop(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+        command: NonStop,
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        crate::__dead_code_marker!("non_stop", "impl");
+
+        let handler_status = match command {
+            NonStop::QNonStop(cmd) => {
+                self.non_stop = cmd.value;
+                // GDB stops all threads prior to switching modes
+                self.threads_running = false;
+                self.pending_stop = None;
+                HandlerStatus::NeedsOk
+            }
+            NonStop::vStopped(_) => match self.pending_stop.take() {
+                Some(PendingStop::Stopped { index, .. }) => {
+                    match Self::nth_active_thread(target, index + 1)? {
+                        Some(tid) => {
+                            let stop = PendingStop::Stopped {
+                                tid,
+                                index: index + 1,
+                            };
+                            self.pending_stop = Some(stop);
+                            self.write_pending_stop(res, target, stop)?;
+                            HandlerStatus::Handled
+                        }
+                        None => HandlerStatus::NeedsOk,
+                    }
+                }
+                // no more stops to report
+                Some(PendingStop::Reason(_)) | None => HandlerStatus::NeedsOk,
+            },
+        };
+
+        Ok(handler_status)
+    }
+
+    /// Report the already-stopped threads in response to `?`.
+    pub(super) fn question_mark_non_stop(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        self.pending_stop = None;
+        if self.threads_running {
+            return Ok(HandlerStatus::NeedsOk);
+        }
+
+        match Self::nth_active_thread(target, 0)? {
+            Some(tid) => {
+                let stop = PendingStop::Stopped { tid, index: 0 };
+                self.pending_stop = Some(stop);
+                self.write_pending_stop(res, target, stop)?;
+                Ok(HandlerStatus::Handled)
+            }
+            None => Ok(HandlerStatus::NeedsOk),
+        }
+    }
+
+    /// Resume threads in response to `vCont`, without waiting for them to
+    /// stop.
+    pub(super) fn do_vcont_non_stop(
+        &mut self,
+        target: &mut T,
+        actions: Actions,
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        let ops = match target.base_ops() {
+            BaseOps::MultiThread(ops) => ops,
+            BaseOps::SingleThread(_) => return Err(Error::PacketUnexpected),
+        };
+
+        let default_resume_action =
+            Self::set_vcont_actions_multi_thread(ops, &actions, self.current_pid, true)?;
+
+        ops.support_non_stop()
+            .ok_or(Error::PacketUnexpected)?
+            .resume_threads(default_resume_action)
+            .map_err(Error::TargetError)?;
+
+        self.threads_running = true;
+        Ok(HandlerStatus::NeedsOk)
+    }
+
+    /// Wait for a thread to stop (sending GDB a `%Stop` notification when one
+    /// does), returning once GDB has sent a packet.
+    pub(crate) fn wait_for_stop_non_stop(
+        &mut self,
+        target: &mut T,
+        conn: &mut C,
+    ) -> Result<(), Error<T::Error, C::Error>> {
+        let ops = match target.base_ops() {
+            BaseOps::MultiThread(ops) => match ops.support_non_stop() {
+                Some(ops) => ops,
+                None => return Ok(()),
+            },
+            BaseOps::SingleThread(_) => return Ok(()),
+        };
+
+        // in non-stop mode, GDB sends regular packets (as opposed to `0x03`) while
+        // threads are running, so any incoming data warrants a response.
+        let mut err = Ok(());
+        let mut check_gdb_interrupt = || match conn.poll_readable() {
+            Ok(readable) => readable,
+            Err(e) => {
+                err = Err(Error::ConnectionRead(e));
+                true // break ASAP if a connection error occurred
+            }
+        };
+
+        let stop_reason = ops
+            .wait_for_stop(GdbInterrupt::new(&mut check_gdb_interrupt))
+            .map_err(Error::TargetError)?;
+        err?;
+
+        self.report_stop_non_stop(target, conn, stop_reason)
+    }
+
+    /// Send GDB a `%Stop` notification for a thread which stopped.
+    pub(crate) fn report_stop_non_stop(
+        &mut self,
+        target: &mut T,
+        conn: &mut C,
+        stop_reason: ThreadStopReason<<T::Arch as crate::arch::Arch>::Usize>,
+    ) -> Result<(), Error<T::Error, C::Error>> {
+        match stop_reason {
+            ThreadStopReason::GdbInterrupt => return Ok(()),
+            // see `finish_exec`
+            ThreadStopReason::ThreadCreated(_) | ThreadStopReason::ThreadExited { .. }
+                if !self.thread_events =>
+            {
+                return Ok(())
+            }
+            ThreadStopReason::Fork { .. } if !self.client_features.fork_events => return Ok(()),
+            ThreadStopReason::VFork { .. } | ThreadStopReason::VForkDone(_)
+                if !self.client_features.vfork_events =>
+            {
+                return Ok(())
+            }
+            ThreadStopReason::Exec(_) if !self.client_features.exec_events => return Ok(()),
+            ThreadStopReason::Exited(_) | ThreadStopReason::Terminated(_) => {
+                self.threads_running = false
+            }
+            _ => {}
+        }
+
+        let stop = PendingStop::Reason(stop_reason);
+        let mut out = core::mem::take(&mut self.out_buf);
+        let mut res = ResponseWriter::new_notification(conn, &mut out, self.rle_enabled)
+            .with_tracer(self.tracer);
+        res.write_str("Stop:")?;
+        self.write_pending_stop(&mut res, target, stop)?;
+        res.flush()?;
+        self.out_buf = out;
+
+        self.pending_stop = Some(stop);
+        Ok(())
+    }
+
+    fn write_pending_stop(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+        stop: PendingStop<<T::Arch as crate::arch::Arch>::Usize>,
+    ) -> Result<(), Error<T::Error, C::Error>> {
+        match stop {
+            // in non-stop mode, the process exiting doesn't end the session
+            PendingStop::Reason(stop_reason) => {
+                self.finish_exec(res, target, stop_reason)?;
+            }
+            PendingStop::Stopped { tid, .. } => self.write_break_common(res, target, tid)?,
+        }
+        Ok(())
+    }
+
+    fn nth_active_thread(
+        target: &mut T,
+        n: usize,
+    ) -> Result<Option<Tid>, Error<T::Error, C::Error>> {
+        let ops = match target.base_ops() {
+            BaseOps::MultiThread(ops) => ops,
+            BaseOps::SingleThread(_) => return Ok(None),
+        };
+
+        let mut i = 0;
+        let mut nth = None;
+        ops.list_active_threads(&mut |tid| {
+            if i == n {
+                nth = Some(tid)
+            }
+            i += 1;
+        })
+        .map_err(Error::TargetError)?;
+
+        Ok(nth)
+    }
+}
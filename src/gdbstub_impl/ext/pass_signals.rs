@@ -0,0 +1,39 @@
+use super::prelude::*;
+use crate::protocol::commands::ext::PassSignals;
+
+impl<T: Target, C: Connection> GdbStubImpl<T, C> {
+    pub(crate) fn handle_pass_signals(
+        &mut self,
+        _res: &mut ResponseWriter<C>,
+        target: &mut T,
+        command: PassSignals,
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        let ops = match target.pass_signals() {
+            Some(ops) => ops,
+            None => return Ok(HandlerStatus::Handled),
+        };
+
+        crate::__dead_code_marker!("pass_signals", "impl");
+
+        let handler_status = match command {
+            PassSignals::QPassSignals(cmd) => {
+                ops.set_pass_signals(&mut cmd.signals.iter().map(Signal::from_u8))
+                    .handle_error()?;
+                HandlerStatus::NeedsOk
+            }
+            PassSignals::QProgramSignals(cmd) => {
+                let ops = match ops.support_program_signals() {
+                    Some(ops) => ops,
+                    None => return Ok(HandlerStatus::Handled),
+                };
+
+                ops.set_program_signals(&mut cmd.signals.iter().map(Signal::from_u8))
+                    .handle_error()?;
+
+                HandlerStatus::NeedsOk
+            }
+        };
+
+        Ok(handler_status)
+    }
+}
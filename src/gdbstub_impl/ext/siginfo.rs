@@ -0,0 +1,42 @@
+use super::prelude::*;
+use crate::protocol::commands::ext::Siginfo;
+
+impl<T: Target, C: Connection> GdbStubImpl<T, C> {
+    pub(crate) fn handle_siginfo(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+        command: Siginfo,
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        let ops = match target.siginfo() {
+            Some(ops) => ops,
+            None => return Ok(HandlerStatus::Handled),
+        };
+
+        crate::__dead_code_marker!("siginfo", "impl");
+
+        let handler_status = match command {
+            Siginfo::qXferSiginfoRead(cmd) => {
+                let siginfo = ops.get_siginfo(self.current_mem_tid).handle_error()?;
+                res.write_qxfer_chunk(siginfo, cmd.offset, cmd.len)?;
+
+                HandlerStatus::Handled
+            }
+            Siginfo::qXferSiginfoWrite(cmd) => {
+                let ops = match ops.support_siginfo_write() {
+                    Some(ops) => ops,
+                    None => return Ok(HandlerStatus::Handled),
+                };
+
+                ops.set_siginfo(self.current_mem_tid, cmd.offset, cmd.data)
+                    .handle_error()?;
+
+                // reply with the number of bytes written
+                res.write_num(cmd.data.len())?;
+                HandlerStatus::Handled
+            }
+        };
+
+        Ok(handler_status)
+    }
+}
@@ -1,7 +1,7 @@
 use super::prelude::*;
 use crate::protocol::commands::ext::SingleRegisterAccess;
 
-use crate::arch::{Arch, RegId};
+use crate::arch::{Arch, DeserializeError, RegId, Registers};
 use crate::target::ext::base::BaseOps;
 
 impl<T: Target, C: Connection> GdbStubImpl<T, C> {
@@ -13,14 +13,33 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
     ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
         let handler_status = match command {
             SingleRegisterAccess::p(p) => {
-                let mut dst = [0u8; 32]; // enough for 256-bit registers
                 let reg = <T::Arch as Arch>::RegId::from_raw_id(p.reg_id);
                 let (reg_id, reg_size) = match reg {
                     // empty packet indicates unrecognized query
                     None => return Ok(HandlerStatus::Handled),
                     Some(v) => v,
                 };
-                let dst = &mut dst[0..reg_size];
+
+                let mut buf = [0u8; 64]; // enough for 512-bit registers (e.g: AVX-512 `zmm`)
+                #[cfg(feature = "alloc")]
+                let mut heap_buf;
+                let dst = if reg_size <= buf.len() {
+                    &mut buf[..reg_size]
+                } else {
+                    #[cfg(feature = "alloc")]
+                    {
+                        heap_buf = alloc::vec![0u8; reg_size];
+                        &mut heap_buf[..]
+                    }
+                    #[cfg(not(feature = "alloc"))]
+                    {
+                        warn!(
+                            "Cannot read {}-byte register without the `alloc` feature",
+                            reg_size
+                        );
+                        return Err(Error::NonFatalError(errno::ENOMEM));
+                    }
+                };
                 ops.read_register(id, reg_id, dst).handle_error()?;
 
                 res.write_hex_buf(dst)?;
@@ -31,6 +50,9 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
                 match reg {
                     // empty packet indicates unrecognized query
                     None => return Ok(HandlerStatus::Handled),
+                    Some((_, reg_size)) if reg_size != p.val.len() => {
+                        return Err(Error::NonFatalError(errno::EINVAL))
+                    }
                     Some((reg_id, _)) => ops.write_register(id, reg_id, p.val).handle_error()?,
                 }
                 HandlerStatus::NeedsOk
@@ -40,6 +62,62 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
         Ok(handler_status)
     }
 
+    /// Service `p` / `P` packets using the target's register file, via
+    /// `Registers::gdb_read_register` / `Registers::gdb_write_register`.
+    fn indexed(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+        command: SingleRegisterAccess<'_>,
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        let mut regs: <T::Arch as Arch>::Registers = Default::default();
+        match target.base_ops() {
+            BaseOps::SingleThread(ops) => ops.read_registers(&mut regs),
+            BaseOps::MultiThread(ops) => ops.read_registers(&mut regs, self.current_mem_tid),
+        }
+        .handle_error()?;
+
+        let handler_status = match command {
+            SingleRegisterAccess::p(p) => {
+                let mut err = Ok(());
+                // empty packet indicates unrecognized query
+                regs.gdb_read_register(p.reg_id, |val| {
+                    let res = match val {
+                        Some(b) => res.write_hex_buf(&[b]),
+                        None => res.write_str("xx"),
+                    };
+                    if let Err(e) = res {
+                        err = Err(e);
+                    }
+                });
+                err?;
+                HandlerStatus::Handled
+            }
+            SingleRegisterAccess::P(p) => {
+                if !regs.gdb_read_register(p.reg_id, |_| {}) {
+                    // empty packet indicates unrecognized query
+                    return Ok(HandlerStatus::Handled);
+                }
+
+                regs.gdb_write_register(p.reg_id, p.val)
+                    .map_err(|e| match e {
+                        DeserializeError::SizeMismatch => Error::NonFatalError(errno::EINVAL),
+                        DeserializeError::InvalidValue => Error::NonFatalError(errno::ERANGE),
+                    })?;
+
+                match target.base_ops() {
+                    BaseOps::SingleThread(ops) => ops.write_registers(&regs),
+                    BaseOps::MultiThread(ops) => ops.write_registers(&regs, self.current_mem_tid),
+                }
+                .handle_error()?;
+
+                HandlerStatus::NeedsOk
+            }
+        };
+
+        Ok(handler_status)
+    }
+
     pub(crate) fn handle_single_register_access<'a>(
         &mut self,
         res: &mut ResponseWriter<C>,
@@ -48,11 +126,11 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
     ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
         match target.base_ops() {
             BaseOps::SingleThread(ops) => match ops.single_register_access() {
-                None => Ok(HandlerStatus::Handled),
+                None => self.indexed(res, target, command),
                 Some(ops) => Self::inner(res, ops, command, ()),
             },
             BaseOps::MultiThread(ops) => match ops.single_register_access() {
-                None => Ok(HandlerStatus::Handled),
+                None => self.indexed(res, target, command),
                 Some(ops) => Self::inner(res, ops, command, self.current_mem_tid),
             },
         }
@@ -0,0 +1,43 @@
+use super::prelude::*;
+use crate::protocol::commands::ext::ThreadExtraInfo;
+
+use crate::protocol::IdKind;
+use crate::target::ext::base::BaseOps;
+
+impl<T: Target, C: Connection> GdbStubImpl<T, C> {
+    pub(crate) fn handle_thread_extra_info<'a>(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+        command: ThreadExtraInfo<'a>,
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        let ops = match target.base_ops() {
+            BaseOps::SingleThread(_) => return Ok(HandlerStatus::Handled),
+            BaseOps::MultiThread(ops) => match ops.support_thread_extra_info() {
+                Some(ops) => ops,
+                None => return Ok(HandlerStatus::Handled),
+            },
+        };
+
+        crate::__dead_code_marker!("thread_extra_info", "impl");
+
+        let handler_status = match command {
+            ThreadExtraInfo::qThreadExtraInfo(cmd) => {
+                // GDB only ever asks about specific threads. Any other selector
+                // simply gets an empty description.
+                if let IdKind::WithId(tid) = cmd.id.tid {
+                    // the description is hex-encoded, so it can only take up half of the
+                    // packet buffer
+                    let half = cmd.buf.len() / 2;
+                    let buf = &mut cmd.buf[..half];
+                    let len = ops.thread_extra_info(tid, buf).handle_error()?;
+                    res.write_hex_buf(&buf[..len.min(buf.len())])?;
+                }
+
+                HandlerStatus::Handled
+            }
+        };
+
+        Ok(handler_status)
+    }
+}
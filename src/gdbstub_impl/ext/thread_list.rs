@@ -0,0 +1,83 @@
+use super::prelude::*;
+use crate::protocol::commands::ext::ThreadList;
+
+use managed::ManagedSlice;
+
+use crate::target::ext::base::multithread::ThreadInfo;
+use crate::target::ext::base::BaseOps;
+use crate::util::managed_vec::ManagedVec;
+
+use super::xml::{push_escaped, push_num, push_str};
+
+impl<T: Target, C: Connection> GdbStubImpl<T, C> {
+    pub(crate) fn handle_thread_list<'a>(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+        command: ThreadList<'a>,
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        let ops = match target.base_ops() {
+            BaseOps::SingleThread(_) => return Ok(HandlerStatus::Handled),
+            BaseOps::MultiThread(ops) => match ops.support_thread_list() {
+                Some(ops) => ops,
+                None => return Ok(HandlerStatus::Handled),
+            },
+        };
+
+        crate::__dead_code_marker!("thread_list", "impl");
+
+        let handler_status = match command {
+            ThreadList::qXferThreadsRead(cmd) => {
+                let mut buf = ManagedSlice::Borrowed(cmd.buf);
+                let mut xml = ManagedVec::new(&mut buf);
+
+                // threads are assumed to belong to the current process, unless stated
+                // otherwise
+                let pid = self.current_pid;
+                let mut err: Result<_, Error<T::Error, C::Error>> = Ok(());
+                push_str(&mut xml, "<?xml version=\"1.0\"?>\n<threads>\n")?;
+                ops.list_threads(&mut |info| {
+                    if err.is_ok() {
+                        err = write_thread_xml(&mut xml, info.pid.unwrap_or(pid), info);
+                    }
+                })
+                .map_err(Error::TargetError)?;
+                err?;
+                push_str(&mut xml, "</threads>\n")?;
+
+                res.write_qxfer_chunk(xml.as_slice(), cmd.offset, cmd.len)?;
+                HandlerStatus::Handled
+            }
+        };
+
+        Ok(handler_status)
+    }
+}
+
+/// Append a `<thread>` element describing `info` to the thread list.
+fn write_thread_xml<T, C>(
+    xml: &mut ManagedVec<'_, '_, u8>,
+    pid: Pid,
+    info: ThreadInfo<'_>,
+) -> Result<(), Error<T, C>> {
+    push_str(xml, "<thread id=\"p")?;
+    push_num(xml, pid.get(), 16)?;
+    push_str(xml, ".")?;
+    push_num(xml, info.tid.get(), 16)?;
+    push_str(xml, "\"")?;
+
+    if let Some(core) = info.core {
+        push_str(xml, " core=\"")?;
+        push_num(xml, core, 10)?;
+        push_str(xml, "\"")?;
+    }
+
+    if let Some(name) = info.name {
+        push_str(xml, " name=\"")?;
+        push_escaped(xml, name.as_bytes())?;
+        push_str(xml, "\"")?;
+    }
+
+    push_str(xml, "/>\n")?;
+    Ok(())
+}
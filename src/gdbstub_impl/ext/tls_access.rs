@@ -0,0 +1,43 @@
+use super::prelude::*;
+use crate::protocol::commands::ext::TlsAccess;
+
+use crate::arch::Arch;
+use crate::protocol::IdKind;
+
+impl<T: Target, C: Connection> GdbStubImpl<T, C> {
+    pub(crate) fn handle_tls_access(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+        command: TlsAccess,
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        let ops = match target.tls_access() {
+            Some(ops) => ops,
+            None => return Ok(HandlerStatus::Handled),
+        };
+
+        crate::__dead_code_marker!("tls_access", "impl");
+
+        let handler_status = match command {
+            TlsAccess::qGetTLSAddr(cmd) => {
+                let tid = match cmd.id.tid {
+                    IdKind::WithId(tid) => tid,
+                    IdKind::Any => self.current_mem_tid,
+                    // a variable's address can only be resolved for a single thread
+                    IdKind::All => return Err(Error::NonFatalError(errno::EINVAL)),
+                };
+
+                let offset = <T::Arch as Arch>::Usize::from_be_bytes(cmd.offset)
+                    .ok_or(Error::TargetMismatch)?;
+                let lm =
+                    <T::Arch as Arch>::Usize::from_be_bytes(cmd.lm).ok_or(Error::TargetMismatch)?;
+
+                let addr = ops.get_tls_addr(tid, offset, lm).handle_error()?;
+                res.write_num(addr)?;
+                HandlerStatus::Handled
+            }
+        };
+
+        Ok(handler_status)
+    }
+}
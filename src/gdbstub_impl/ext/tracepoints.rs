@@ -0,0 +1,146 @@
+use super::prelude::*;
+use crate::protocol::commands::ext::Tracepoints;
+
+use crate::arch::Arch;
+use crate::protocol::commands::_QTFrame::QTFrame;
+use crate::protocol::commands::_QTDP::{TracepointAction as RawAction, QTDP};
+use crate::target::ext::tracepoints::{
+    ExperimentStatus, FrameRequest, NewTracepoint, Tracepoint, TracepointAction,
+};
+
+impl<T: Target, C: Connection> GdbStubImpl<T, C> {
+    pub(crate) fn handle_tracepoints(
+        &mut self,
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+        command: Tracepoints,
+    ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
+        let ops = match target.tracepoints() {
+            Some(ops) => ops,
+            None => return Ok(HandlerStatus::Handled),
+        };
+
+        crate::__dead_code_marker!("tracepoints", "impl");
+
+        let from_be_bytes =
+            |buf: &[u8]| <T::Arch as Arch>::Usize::from_be_bytes(buf).ok_or(Error::TargetMismatch);
+
+        let handler_status = match command {
+            Tracepoints::QTinit(_) => {
+                ops.trace_init().handle_error()?;
+                HandlerStatus::NeedsOk
+            }
+            Tracepoints::QTDP(QTDP::Create(cmd)) => {
+                self.tracepoint_stepping = false;
+
+                let tp = NewTracepoint {
+                    number: Tracepoint(cmd.number),
+                    addr: from_be_bytes(cmd.addr)?,
+                    enabled: cmd.enable,
+                    step_count: cmd.step,
+                    pass_count: cmd.pass,
+                    condition: cmd.condition,
+                };
+                ops.tracepoint_create(tp).handle_error()?;
+                HandlerStatus::NeedsOk
+            }
+            Tracepoints::QTDP(QTDP::Define(cmd)) => {
+                self.tracepoint_stepping |= cmd.while_stepping;
+
+                let tp = Tracepoint(cmd.number);
+                let addr = from_be_bytes(cmd.addr)?;
+                for action in cmd.actions {
+                    let action = match action.ok_or(Error::NonFatalError(errno::EINVAL))? {
+                        RawAction::Registers { mask } => TracepointAction::Registers { mask },
+                        RawAction::Memory {
+                            basereg,
+                            offset,
+                            length,
+                        } => {
+                            // relative offsets may be negative (i.e: sign-extended to the
+                            // width of GDB's `ULONGEST`), so truncate them to the target's
+                            // address width.
+                            let width = core::mem::size_of::<<T::Arch as Arch>::Usize>();
+                            let offset = &offset[offset.len().saturating_sub(width)..];
+                            TracepointAction::Memory {
+                                basereg,
+                                offset: from_be_bytes(offset)?,
+                                length,
+                            }
+                        }
+                        RawAction::Expression { expr } => TracepointAction::Expression { expr },
+                    };
+
+                    ops.tracepoint_add_action(tp, addr, action, self.tracepoint_stepping)
+                        .handle_error()?;
+                }
+                HandlerStatus::NeedsOk
+            }
+            Tracepoints::QTStart(_) => {
+                ops.trace_start().handle_error()?;
+                HandlerStatus::NeedsOk
+            }
+            Tracepoints::QTStop(_) => {
+                ops.trace_stop().handle_error()?;
+                HandlerStatus::NeedsOk
+            }
+            Tracepoints::QTFrame(cmd) => {
+                let req = match cmd {
+                    QTFrame::Clear => {
+                        ops.deselect_frame().handle_error()?;
+                        return Ok(HandlerStatus::NeedsOk);
+                    }
+                    QTFrame::Number(n) => FrameRequest::Number(n),
+                    QTFrame::Pc(addr) => FrameRequest::Pc(from_be_bytes(addr)?),
+                    QTFrame::Tdp(tp) => FrameRequest::Tracepoint(Tracepoint(tp)),
+                    QTFrame::Range(start, end) => {
+                        FrameRequest::InRange(from_be_bytes(start)?, from_be_bytes(end)?)
+                    }
+                    QTFrame::Outside(start, end) => {
+                        FrameRequest::OutsideRange(from_be_bytes(start)?, from_be_bytes(end)?)
+                    }
+                };
+
+                match ops.select_frame(req).handle_error()? {
+                    Some(frame) => {
+                        res.write_str("F")?;
+                        res.write_num(frame.number)?;
+                        res.write_str("T")?;
+                        res.write_num(frame.tracepoint.0)?;
+                    }
+                    None => res.write_str("F-1")?,
+                }
+                HandlerStatus::Handled
+            }
+        };
+
+        Ok(handler_status)
+    }
+
+    /// Reply to a `qTStatus` packet.
+    ///
+    /// Targets which don't implement the `Tracepoints` extension report that
+    /// no trace experiment has been run.
+    pub(super) fn write_trace_status(
+        res: &mut ResponseWriter<C>,
+        target: &mut T,
+    ) -> Result<(), Error<T::Error, C::Error>> {
+        let status = match target.tracepoints() {
+            Some(ops) => ops.trace_status().handle_error()?,
+            None => ExperimentStatus::NotRun,
+        };
+
+        match status {
+            ExperimentStatus::Running => res.write_str("T1")?,
+            ExperimentStatus::NotRun => res.write_str("T0;tnotrun:0")?,
+            ExperimentStatus::Stopped => res.write_str("T0;tstop:0")?,
+            ExperimentStatus::BufferFull => res.write_str("T0;tfull:0")?,
+            ExperimentStatus::PassCount(tp) => {
+                res.write_str("T0;tpasscount:")?;
+                res.write_num(tp.0)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+//! Helpers for assembling XML documents (e.g: for `qXfer` responses) in the
+//! packet buffer.
+
+use super::prelude::*;
+use crate::util::managed_vec::ManagedVec;
+
+pub(super) fn push_str<T, C>(xml: &mut ManagedVec<'_, '_, u8>, s: &str) -> Result<(), Error<T, C>> {
+    for b in s.bytes() {
+        xml.push(b)?;
+    }
+    Ok(())
+}
+
+/// Append `s` to `xml`, escaping any characters which aren't valid within an
+/// attribute value.
+pub(super) fn push_escaped<T, C>(
+    xml: &mut ManagedVec<'_, '_, u8>,
+    s: &[u8],
+) -> Result<(), Error<T, C>> {
+    for &b in s {
+        match b {
+            b'&' => push_str(xml, "&amp;")?,
+            b'<' => push_str(xml, "&lt;")?,
+            b'>' => push_str(xml, "&gt;")?,
+            b'"' => push_str(xml, "&quot;")?,
+            b'\'' => push_str(xml, "&apos;")?,
+            b => xml.push(b)?,
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn push_num<T, C>(
+    xml: &mut ManagedVec<'_, '_, u8>,
+    mut n: usize,
+    radix: usize,
+) -> Result<(), Error<T, C>> {
+    // large enough to fit a 128-bit usize in decimal
+    let mut digits = [0; 40];
+    let mut i = digits.len();
+    loop {
+        i -= 1;
+        digits[i] = b"0123456789abcdef"[n % radix];
+        n /= radix;
+        if n == 0 {
+            break;
+        }
+    }
+
+    for &b in &digits[i..] {
+        xml.push(b)?;
+    }
+    Ok(())
+}
+
+/// Append a target address to `xml`, as a `0x`-prefixed hex number.
+pub(super) fn push_addr<T, C, U: BeBytes + num_traits::PrimInt>(
+    xml: &mut ManagedVec<'_, '_, u8>,
+    addr: U,
+) -> Result<(), Error<T, C>> {
+    push_str(xml, "0x")?;
+
+    let mut buf = [0; 16];
+    // infallible (unless addr is a >128 bit number)
+    let len = addr.to_be_bytes(&mut buf).unwrap();
+    let digits = buf[..len]
+        .iter()
+        .flat_map(|b| core::iter::once(b >> 4).chain(core::iter::once(b & 0xf)))
+        .skip_while(|&d| d == 0);
+
+    let mut empty = true;
+    for d in digits {
+        xml.push(b"0123456789abcdef"[d as usize])?;
+        empty = false;
+    }
+    if empty {
+        xml.push(b'0')?;
+    }
+    Ok(())
+}
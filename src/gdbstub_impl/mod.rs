@@ -1,37 +1,74 @@
 use core::marker::PhantomData;
+use core::time::Duration;
 
 use managed::ManagedSlice;
 
+use crate::arch::Arch;
 use crate::common::*;
-use crate::connection::Connection;
-use crate::protocol::{commands::Command, Packet, ResponseWriter, SpecificIdKind};
+use crate::connection::{Connection, ConnectionExt};
+use crate::protocol::{
+    commands::Command, OutBuf, Packet, PacketParseError, ResponseWriter, SpecificIdKind,
+};
+use crate::target::ext::base::multithread::ThreadStopReason;
 use crate::target::Target;
-use crate::util::managed_vec::ManagedVec;
-use crate::SINGLE_THREAD_TID;
+use crate::util::managed_vec::{CapacityError, ManagedVec};
+use crate::{FAKE_PID, SINGLE_THREAD_TID};
 
 mod builder;
 mod error;
 mod ext;
+mod state_machine;
 mod target_result_ext;
 
 pub use builder::{GdbStubBuilder, GdbStubBuilderError};
 pub use error::GdbStubError;
+pub use state_machine::{GdbStubStateMachine, ResumeRequest, StateMachineAction};
+
+use state_machine::PumpResult;
 
 use GdbStubError as Error;
 
 /// Describes why the GDB session ended.
+///
+/// Each of these represents an orderly, protocol-level end to the session.
+/// Sessions which end due to a failure (e.g: the connection dropping
+/// mid-stream) are instead reported as a [`GdbStubError`]. See
+/// [`GdbStubError::is_connection_error`].
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum DisconnectReason {
     /// Target exited with given status code
     TargetExited(u8),
     /// Target terminated with given signal
-    TargetTerminated(u8),
+    TargetTerminated(Signal),
     /// GDB issued a disconnect command
     Disconnect,
     /// GDB issued a kill command
     Kill,
 }
 
+/// The direction of a packet passed to a [`PacketTracer`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PacketDirection {
+    /// Sent by the GDB client.
+    Incoming,
+    /// Sent by `gdbstub`.
+    Outgoing,
+}
+
+/// A callback which is invoked with the raw bytes of each packet sent or
+/// received by `gdbstub`. Registered via
+/// [`GdbStubBuilder::packet_tracer`].
+///
+/// Incoming packets are reported once they have been received in their
+/// entirety (including acks, and interrupt packets), while outgoing packets
+/// are reported exactly as they're written to the connection (i.e: including
+/// framing, with run-length encoding applied). Large outgoing packets may be
+/// reported across several calls.
+///
+/// This is a plain function pointer (as opposed to a closure) so that it can
+/// be used in `no_std` environments without heap allocation.
+pub type PacketTracer = fn(PacketDirection, &[u8]);
+
 /// Debug a [`Target`] using the GDB Remote Serial Protocol over a given
 /// [`Connection`].
 pub struct GdbStub<'a, T: Target, C: Connection> {
@@ -65,20 +102,145 @@ impl<'a, T: Target, C: Connection> GdbStub<'a, T, C> {
         self.state
             .run(target, &mut self.conn, &mut self.packet_buffer)
     }
+
+    /// Same as [`run`](Self::run), except that reads from the connection give
+    /// up once `timeout` has elapsed.
+    ///
+    /// If the GDB client stops sending data partway through a packet (e.g: due
+    /// to a flaky serial link), the partially received packet is discarded,
+    /// and the client is asked to retransmit it (unless no-ack mode is
+    /// enabled, in which case the packet is simply dropped). Timeouts between
+    /// packets are expected (e.g: while the user is idle), and are ignored.
+    pub fn run_with_read_timeout(
+        &mut self,
+        target: &mut T,
+        timeout: Duration,
+    ) -> Result<DisconnectReason, Error<T::Error, C::Error>>
+    where
+        C: ConnectionExt,
+    {
+        self.state
+            .run_with(target, &mut self.conn, &mut self.packet_buffer, |conn| {
+                conn.read_with_timeout(timeout)
+            })
+    }
+
+    /// Convert the `GdbStub` into a [`GdbStubStateMachine`], which is driven
+    /// by passing it bytes from the GDB client as they arrive (instead of
+    /// blocking on [`Connection::read`]).
+    pub fn into_state_machine(self) -> GdbStubStateMachine<'a, T, C> {
+        GdbStubStateMachine::new(self.conn, self.packet_buffer, self.state)
+    }
 }
 
 struct GdbStubImpl<T: Target, C: Connection> {
     _target: PhantomData<T>,
     _connection: PhantomData<C>,
 
+    /// The process selected via the `H` packet (or most recently spawned /
+    /// attached to). Always `FAKE_PID` for non-multiprocess targets.
+    current_pid: Pid,
     current_mem_tid: Tid,
     current_resume_tid: SpecificIdKind,
     no_ack_mode: bool,
+    rle_enabled: bool,
+    tracer: Option<PacketTracer>,
+    /// Buffers each outgoing packet, and is reused between packets.
+    out_buf: OutBuf,
+    recv_packet: RecvPacketStateMachine,
+    /// Features advertised by the GDB client via `qSupported`.
+    client_features: ClientFeatures,
+
+    /// Set via `QThreadEvents`. Whether thread creation / exit events are
+    /// reported to GDB.
+    thread_events: bool,
+    /// (all-stop mode) The request which most recently resumed the target, if
+    /// it has yet to stop.
+    running: Option<ResumeRequest<<T::Arch as Arch>::Usize>>,
+    /// Set via `QNonStop`. See the `ext::non_stop` module for details.
+    non_stop: bool,
+    /// (non-stop mode) Whether any threads may currently be running.
+    threads_running: bool,
+    /// (non-stop mode) The stop which GDB has yet to acknowledge via
+    /// `vStopped`.
+    pending_stop: Option<PendingStop<<T::Arch as Arch>::Usize>>,
+
+    /// The number of threads which have already been reported to GDB as part
+    /// of the current `qfThreadInfo` / `qsThreadInfo` sequence.
+    thread_info_cursor: usize,
+
+    /// Whether the target is in the middle of looking up symbols via
+    /// `qSymbol`.
+    symbol_lookup_active: bool,
+
+    /// Whether subsequent `QTDP` actions are to be performed while stepping
+    /// (i.e: after the tracepoint's action list contained an `S`).
+    tracepoint_stepping: bool,
+
+    /// Whether the client requested extended mode (via `!`), which is
+    /// required before processes can be spawned / attached to / restarted.
+    extended_mode_enabled: bool,
+    /// (extended mode) Set once a process has been killed (along with the PID
+    /// GDB asked to kill, if any), so that subsequent `?` packets report its
+    /// termination. Cleared once a new process is spawned / attached to.
+    killed: Option<Option<Pid>>,
+
+    /// Processes spawned / attached to during the current extended-mode
+    /// session, alongside a cache of the target's `query_if_attached` response
+    /// for each process (populated by `qAttached`).
+    #[cfg(feature = "alloc")]
+    attached_pids: alloc::collections::BTreeMap<Pid, Option<bool>>,
+    /// The most recent non-empty `vRun` filename (i.e: GDB's `remote
+    /// exec-file`), reused when GDB sends a `vRun` without a filename.
+    #[cfg(feature = "alloc")]
+    exec_file: Option<alloc::vec::Vec<u8>>,
+}
+
+/// A stop event reported to GDB in non-stop mode, which has yet to be
+/// acknowledged via `vStopped`.
+#[derive(Clone, Copy)]
+enum PendingStop<U> {
+    /// A thread stopped while running.
+    Reason(ThreadStopReason<U>),
+    /// The `index`th active thread is stopped (reported in response to `?`).
+    Stopped { tid: Tid, index: usize },
+}
+
+/// Features advertised by the GDB client via `qSupported`.
+///
+/// Until `qSupported` is received, the client is assumed to support none of
+/// these features.
+#[derive(Debug, Default, Clone, Copy)]
+struct ClientFeatures {
+    /// `multiprocess+`
+    // not yet consulted, as thread IDs are always reported in multiprocess form
+    #[allow(dead_code)]
+    multiprocess: bool,
+    /// `swbreak+`: the client understands the `swbreak` stop reason.
+    swbreak: bool,
+    /// `hwbreak+`: the client understands the `hwbreak` stop reason.
+    hwbreak: bool,
+    /// `fork-events+`: the client understands the `fork` stop reason.
+    fork_events: bool,
+    /// `vfork-events+`: the client understands the `vfork` and `vforkdone`
+    /// stop reasons.
+    vfork_events: bool,
+    /// `exec-events+`: the client understands the `exec` stop reason.
+    exec_events: bool,
+    /// `xmlRegisters=<arch>,...`
+    // not yet consulted, as gdbstub always reports a target description
+    #[allow(dead_code)]
+    xml_registers: bool,
 }
 
 enum HandlerStatus {
     Handled,
     NeedsOk,
+    /// The packet doesn't warrant a reply (not even an empty one).
+    NoResponse,
+    /// The target was resumed (as described by `GdbStubImpl::running`). The
+    /// reply is sent once the target stops.
+    Resume,
     Disconnect(DisconnectReason),
 }
 
@@ -96,9 +258,66 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
             //
             // Plus, even if the GDB client is acting strangely and doesn't overwrite these values,
             // the target will simply return a non-fatal error, which is totally fine.
+            current_pid: FAKE_PID,
             current_mem_tid: SINGLE_THREAD_TID,
             current_resume_tid: SpecificIdKind::WithId(SINGLE_THREAD_TID),
             no_ack_mode: false,
+            rle_enabled: false,
+            out_buf: OutBuf::new(),
+            tracer: None,
+            recv_packet: RecvPacketStateMachine::new(),
+            client_features: ClientFeatures::default(),
+
+            thread_events: false,
+            running: None,
+            non_stop: false,
+            threads_running: false,
+            pending_stop: None,
+
+            thread_info_cursor: 0,
+            symbol_lookup_active: false,
+            tracepoint_stepping: false,
+            extended_mode_enabled: false,
+            killed: None,
+
+            #[cfg(feature = "alloc")]
+            attached_pids: alloc::collections::BTreeMap::new(),
+            #[cfg(feature = "alloc")]
+            exec_file: None,
+        }
+    }
+
+    /// Carry the session's state over to a different connection type (i.e:
+    /// the connection wrapper used by [`GdbStubStateMachine`]).
+    fn with_connection<C2: Connection>(self) -> GdbStubImpl<T, C2> {
+        GdbStubImpl {
+            _target: PhantomData,
+            _connection: PhantomData,
+
+            current_pid: self.current_pid,
+            current_mem_tid: self.current_mem_tid,
+            current_resume_tid: self.current_resume_tid,
+            no_ack_mode: self.no_ack_mode,
+            rle_enabled: self.rle_enabled,
+            tracer: self.tracer,
+            out_buf: self.out_buf,
+            recv_packet: self.recv_packet,
+            client_features: self.client_features,
+            thread_events: self.thread_events,
+            running: self.running,
+            non_stop: self.non_stop,
+            threads_running: self.threads_running,
+            pending_stop: self.pending_stop,
+            thread_info_cursor: self.thread_info_cursor,
+            symbol_lookup_active: self.symbol_lookup_active,
+            tracepoint_stepping: self.tracepoint_stepping,
+            extended_mode_enabled: self.extended_mode_enabled,
+            killed: self.killed,
+
+            #[cfg(feature = "alloc")]
+            attached_pids: self.attached_pids,
+            #[cfg(feature = "alloc")]
+            exec_file: self.exec_file,
         }
     }
 
@@ -107,100 +326,284 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
         target: &mut T,
         conn: &mut C,
         packet_buffer: &mut ManagedSlice<u8>,
+    ) -> Result<DisconnectReason, Error<T::Error, C::Error>> {
+        self.run_with(target, conn, packet_buffer, |conn| conn.read().map(Some))
+    }
+
+    /// Run the debugging session, reading bytes via `read_byte` (which
+    /// returns `None` if the read timed out).
+    ///
+    /// This drives the same machinery as [`GdbStubStateMachine`], resuming the
+    /// target on the host's behalf using the target's blocking resume methods.
+    fn run_with(
+        &mut self,
+        target: &mut T,
+        conn: &mut C,
+        packet_buffer: &mut ManagedSlice<u8>,
+        mut read_byte: impl FnMut(&mut C) -> Result<Option<u8>, C::Error>,
     ) -> Result<DisconnectReason, Error<T::Error, C::Error>> {
         conn.on_session_start().map_err(Error::ConnectionRead)?;
 
+        // discard any partially received packet left over from a previous session
+        self.recv_packet = RecvPacketStateMachine::new();
+
         loop {
-            match Self::recv_packet(conn, target, packet_buffer)? {
-                Packet::Ack => {}
-                Packet::Nack => return Err(Error::ClientSentNack),
-                Packet::Interrupt => {
-                    debug!("<-- interrupt packet");
-                    let mut res = ResponseWriter::new(conn);
-                    res.write_str("S05")?;
-                    res.flush()?;
-                }
-                Packet::Command(command) => {
-                    // Acknowledge the command
-                    if !self.no_ack_mode {
-                        conn.write(b'+').map_err(Error::ConnectionRead)?;
-                    }
+            // the target is only polled between packets, as once GDB has started
+            // sending a packet, the rest of it is sure to follow shortly.
+            if self.non_stop
+                && self.threads_running
+                && self.pending_stop.is_none()
+                && !self.recv_packet.is_partial()
+            {
+                self.wait_for_stop_non_stop(target, conn)?;
+            }
 
-                    let mut res = ResponseWriter::new(conn);
-                    let disconnect = match self.handle_command(&mut res, target, command) {
-                        Ok(HandlerStatus::Handled) => None,
-                        Ok(HandlerStatus::NeedsOk) => {
-                            res.write_str("OK")?;
-                            None
+            let byte = match read_byte(conn).map_err(Error::ConnectionRead)? {
+                Some(byte) => byte,
+                None => {
+                    if self.recv_packet.is_partial() {
+                        debug!("timed out partway through a packet, discarding it");
+                        self.recv_packet = RecvPacketStateMachine::new();
+                        if !self.no_ack_mode {
+                            conn.write(b'-').map_err(Error::ConnectionWrite)?;
+                            conn.flush().map_err(Error::ConnectionWrite)?;
                         }
-                        Ok(HandlerStatus::Disconnect(reason)) => Some(reason),
-                        // HACK: handling this "dummy" error is required as part of the
-                        // `TargetResultExt::handle_error()` machinery.
-                        Err(Error::NonFatalError(code)) => {
-                            res.write_str("E")?;
-                            res.write_num(code)?;
-                            None
-                        }
-                        Err(Error::TargetError(e)) => {
-                            // unlike all other errors which are "unrecoverable" in the sense that
-                            // the GDB session cannot continue, there's still a chance that a target
-                            // might want to keep the debugging session alive to do a "post-mortem"
-                            // analysis. As such, we simply report a standard TRAP stop reason.
-                            let mut res = ResponseWriter::new(conn);
-                            res.write_str("S05")?;
-                            res.flush()?;
-                            return Err(Error::TargetError(e));
-                        }
-                        Err(e) => return Err(e),
-                    };
+                    }
+                    continue;
+                }
+            };
 
-                    // HACK: this could be more elegant...
-                    if disconnect != Some(DisconnectReason::Kill) {
+            let mut action = match self.pump(target, conn, packet_buffer, byte) {
+                Ok(action) => action,
+                // the oversized packet has already been discarded (and rejected), so the
+                // session can carry on as usual
+                Err(Error::PacketTooLarge) => continue,
+                Err(e) => return Err(e),
+            };
+            while let Some(StateMachineAction::Resume(req)) = action {
+                let stop_reason = match self.resume_blocking(target, conn, req) {
+                    Ok(stop_reason) => stop_reason,
+                    Err(Error::TargetError(e)) => {
+                        // see `finish_response`
+                        let mut res =
+                            ResponseWriter::new(conn, &mut self.out_buf, self.rle_enabled)
+                                .with_tracer(self.tracer);
+                        res.write_str("S05")?;
                         res.flush()?;
+                        return Err(Error::TargetError(e));
                     }
+                    Err(e) => return Err(e),
+                };
 
-                    if let Some(disconnect_reason) = disconnect {
-                        return Ok(disconnect_reason);
+                // The interrupt has been serviced, so consume the pending `0x03`
+                // to avoid it being mistaken for a separate interrupt request.
+                if let ThreadStopReason::GdbInterrupt = stop_reason {
+                    if gdb_interrupt_pending(conn).map_err(Error::ConnectionRead)? {
+                        read_byte(conn).map_err(Error::ConnectionRead)?;
                     }
                 }
-            };
+
+                action = self.report_stop(target, conn, stop_reason)?;
+            }
+
+            match action {
+                Some(StateMachineAction::Disconnect(reason)) => return Ok(reason),
+                // the connection isn't read from while the target is running
+                Some(StateMachineAction::Interrupt) => return Err(Error::PacketUnexpected),
+                // outgoing bytes are only handed back by `GdbStubStateMachine`
+                Some(StateMachineAction::Resume(_)) | Some(StateMachineAction::Send(_)) | None => {}
+            }
         }
     }
 
-    fn recv_packet<'a>(
-        conn: &mut C,
+    /// Process a single byte sent by the GDB client, handling the incoming
+    /// packet once it has been received in its entirety.
+    fn pump(
+        &mut self,
         target: &mut T,
-        pkt_buf: &'a mut ManagedSlice<u8>,
-    ) -> Result<Packet<'a>, Error<T::Error, C::Error>> {
-        let header_byte = conn.read().map_err(Error::ConnectionRead)?;
-
-        // Wrap the buf in a `ManagedVec` to keep the code readable.
-        let mut buf = ManagedVec::new(pkt_buf);
-
-        buf.clear();
-        buf.push(header_byte)?;
-        if header_byte == b'$' {
-            // read the packet body
-            loop {
-                let c = conn.read().map_err(Error::ConnectionRead)?;
-                buf.push(c)?;
-                if c == b'#' {
-                    break;
+        conn: &mut C,
+        packet_buffer: &mut ManagedSlice<u8>,
+        byte: u8,
+    ) -> PumpResult<'static, T, C> {
+        let len = match self.recv_packet.pump(packet_buffer, byte) {
+            Ok(Some(len)) => len,
+            Ok(None) => return Ok(None),
+            Err(_) => {
+                // The packet arrived intact, so it mustn't be nack'd, as GDB would just
+                // re-send the exact same packet. Instead, it's rejected with an error.
+                debug!("received a packet larger than the packet buffer, discarding it");
+                if !self.no_ack_mode {
+                    conn.write(b'+').map_err(Error::ConnectionWrite)?;
+                    conn.flush().map_err(Error::ConnectionWrite)?;
                 }
+                let mut res = ResponseWriter::new(conn, &mut self.out_buf, self.rle_enabled)
+                    .with_tracer(self.tracer);
+                res.write_str("E")?;
+                res.write_error_code(crate::common::errno::ENOMEM)?;
+                res.flush()?;
+                return Err(Error::PacketTooLarge);
             }
-            // read the checksum as well
-            buf.push(conn.read().map_err(Error::ConnectionRead)?)?;
-            buf.push(conn.read().map_err(Error::ConnectionRead)?)?;
-        }
+        };
 
         trace!(
             "<-- {}",
-            core::str::from_utf8(buf.as_slice()).unwrap_or("<invalid packet>")
+            core::str::from_utf8(&packet_buffer[..len]).unwrap_or("<invalid packet>")
         );
+        if let Some(tracer) = self.tracer {
+            tracer(PacketDirection::Incoming, &packet_buffer[..len]);
+        }
+
+        let packet = match Packet::from_buf(target, packet_buffer.as_mut()) {
+            Ok(packet) => packet,
+            // A corrupted packet can simply be re-sent by GDB. In no-ack mode, GDB never
+            // re-sends packets, so there's no way to recover.
+            Err(PacketParseError::ChecksumMismatched {
+                checksum,
+                calculated,
+            }) => {
+                warn!(
+                    "packet checksum mismatch (expected {:#04x}, calculated {:#04x})",
+                    checksum, calculated
+                );
+                if self.no_ack_mode {
+                    return Err(Error::ChecksumMismatch);
+                }
+                conn.write(b'-').map_err(Error::ConnectionWrite)?;
+                conn.flush().map_err(Error::ConnectionWrite)?;
+                return Ok(None);
+            }
+            Err(e) => return Err(Error::PacketParse(e)),
+        };
+        match packet {
+            Packet::Ack => Ok(None),
+            Packet::Nack => Err(Error::ClientSentNack),
+            // It's up to the host to stop the running target.
+            Packet::Interrupt if self.running.is_some() => {
+                debug!("<-- interrupt packet (target running)");
+                Ok(Some(StateMachineAction::Interrupt))
+            }
+            // Interrupts which arrive between packets (e.g: when the user hits ^C just
+            // as the target stops by itself) are still answered with a stop reply, as
+            // GDB is waiting for the target to stop.
+            Packet::Interrupt => {
+                debug!("<-- interrupt packet");
+                self.with_response(conn, |this, mut res| {
+                    this.write_interrupt_common(&mut res, target)?;
+                    res.flush()?;
+                    Ok(None)
+                })
+            }
+            // in all-stop mode, GDB doesn't send any packets while the target is
+            // running (other than interrupts)
+            Packet::Command(_) if self.running.is_some() => Err(Error::PacketUnexpected),
+            Packet::Command(command) => {
+                // Acknowledge the command. This is done _before_ handling the
+                // command, which ensures that `QStartNoAckMode` itself is still
+                // acknowledged. The ack is flushed right away, as commands which
+                // resume the target may not send a response for some time.
+                if !self.no_ack_mode {
+                    conn.write(b'+').map_err(Error::ConnectionWrite)?;
+                    conn.flush().map_err(Error::ConnectionWrite)?;
+                }
+
+                self.with_response(conn, |this, mut res| {
+                    let status = this.handle_command(&mut res, target, command);
+                    this.finish_response(res, status)
+                })
+            }
+        }
+    }
+
+    /// Report that the (all-stop mode) target stopped after being resumed, or
+    /// that a thread stopped while in non-stop mode.
+    fn report_stop(
+        &mut self,
+        target: &mut T,
+        conn: &mut C,
+        stop_reason: ThreadStopReason<<T::Arch as Arch>::Usize>,
+    ) -> PumpResult<'static, T, C> {
+        if self.non_stop {
+            self.report_stop_non_stop(target, conn, stop_reason)?;
+            return Ok(None);
+        }
 
-        drop(buf);
+        let req = match self.running {
+            Some(req) => req,
+            None => {
+                warn!("ignoring a stop reported while the target wasn't running");
+                return Ok(None);
+            }
+        };
+
+        self.with_response(conn, |this, mut res| {
+            let status = match this.finish_exec(&mut res, target, stop_reason) {
+                Ok(Some(status)) => Ok(status),
+                // GDB isn't interested in this stop, so resume the target once again
+                Ok(None) => return Ok(Some(StateMachineAction::Resume(req))),
+                Err(e) => Err(e),
+            };
+
+            this.running = None;
+            this.finish_response(res, status)
+        })
+    }
+
+    /// Run `f` with a [`ResponseWriter`] for a new packet, which reuses the
+    /// stub's outgoing packet buffer.
+    fn with_response<R>(
+        &mut self,
+        conn: &mut C,
+        f: impl FnOnce(&mut Self, ResponseWriter<'_, C>) -> R,
+    ) -> R {
+        let mut out = core::mem::take(&mut self.out_buf);
+        let res = ResponseWriter::new(conn, &mut out, self.rle_enabled).with_tracer(self.tracer);
+        let ret = f(self, res);
+        self.out_buf = out;
+        ret
+    }
+
+    /// Send the response to a command (or a stop reply), based on how it was
+    /// handled.
+    fn finish_response(
+        &mut self,
+        mut res: ResponseWriter<C>,
+        status: Result<HandlerStatus, Error<T::Error, C::Error>>,
+    ) -> PumpResult<'static, T, C> {
+        let disconnect = match status {
+            Ok(HandlerStatus::Handled) => None,
+            Ok(HandlerStatus::NoResponse) => return Ok(None),
+            Ok(HandlerStatus::Resume) => return Ok(self.running.map(StateMachineAction::Resume)),
+            Ok(HandlerStatus::NeedsOk) => {
+                res.write_str("OK")?;
+                None
+            }
+            Ok(HandlerStatus::Disconnect(reason)) => Some(reason),
+            // HACK: handling this "dummy" error is required as part of the
+            // `TargetResultExt::handle_error()` machinery.
+            Err(Error::NonFatalError(code)) => {
+                res.write_str("E")?;
+                res.write_error_code(code)?;
+                None
+            }
+            Err(Error::TargetError(e)) => {
+                // unlike all other errors which are "unrecoverable" in the sense that
+                // the GDB session cannot continue, there's still a chance that a target
+                // might want to keep the debugging session alive to do a "post-mortem"
+                // analysis. As such, we simply report a standard TRAP stop reason.
+                let mut res = res.reborrow();
+                res.write_str("S05")?;
+                res.flush()?;
+                return Err(Error::TargetError(e));
+            }
+            Err(e) => return Err(e),
+        };
+
+        // HACK: this could be more elegant...
+        if disconnect != Some(DisconnectReason::Kill) {
+            res.flush()?;
+        }
 
-        Packet::from_buf(target, pkt_buf.as_mut()).map_err(Error::PacketParse)
+        Ok(disconnect.map(StateMachineAction::Disconnect))
     }
 
     fn handle_command(
@@ -211,9 +614,10 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
     ) -> Result<HandlerStatus, Error<T::Error, C::Error>> {
         match cmd {
             Command::Unknown(cmd) => {
-                // cmd must be ASCII, as the slice originated from a PacketBuf, which checks for
-                // ASCII as part of the initial validation.
-                info!("Unknown command: {}", core::str::from_utf8(cmd).unwrap());
+                info!(
+                    "Unknown command: {}",
+                    core::str::from_utf8(cmd).unwrap_or("<non-ascii command>")
+                );
                 Ok(HandlerStatus::Handled)
             }
             // `handle_X` methods are defined in the `ext` module
@@ -223,11 +627,125 @@ impl<T: Target, C: Connection> GdbStubImpl<T, C> {
             }
             Command::Breakpoints(cmd) => self.handle_breakpoints(res, target, cmd),
             Command::ExtendedMode(cmd) => self.handle_extended_mode(res, target, cmd),
-            Command::MonitorCmd(cmd) => self.handle_monitor_cmd(res, target, cmd),
+            Command::PassSignals(cmd) => self.handle_pass_signals(res, target, cmd),
             Command::SectionOffsets(cmd) => self.handle_section_offsets(res, target, cmd),
+            Command::TlsAccess(cmd) => self.handle_tls_access(res, target, cmd),
             Command::ReverseCont(cmd) => self.handle_reverse_cont(res, target, cmd),
             Command::ReverseStep(cmd) => self.handle_reverse_step(res, target, cmd),
             Command::MemoryMap(cmd) => self.handle_memory_map(res, target, cmd),
+            Command::MemoryRegionInfo(cmd) => self.handle_memory_region_info(res, target, cmd),
+            Command::LldbHostInfo(cmd) => self.handle_lldb_host_info(res, target, cmd),
+            Command::LldbRegisterInfo(cmd) => self.handle_lldb_register_info(res, target, cmd),
+            Command::Flash(cmd) => self.handle_flash(res, target, cmd),
+            Command::Auxv(cmd) => self.handle_auxv(res, target, cmd),
+            Command::ExecFile(cmd) => self.handle_exec_file(res, target, cmd),
+            Command::Siginfo(cmd) => self.handle_siginfo(res, target, cmd),
+            Command::LibrariesSvr4(cmd) => self.handle_libraries_svr4(res, target, cmd),
+            Command::HostIo(cmd) => self.handle_host_io(res, target, cmd),
+            Command::ThreadExtraInfo(cmd) => self.handle_thread_extra_info(res, target, cmd),
+            Command::ThreadList(cmd) => self.handle_thread_list(res, target, cmd),
+            Command::NonStop(cmd) => self.handle_non_stop(res, target, cmd),
+            Command::Tracepoints(cmd) => self.handle_tracepoints(res, target, cmd),
         }
     }
 }
+
+/// Assembles incoming packets, as their bytes are received one at a time.
+struct RecvPacketStateMachine {
+    state: RecvPacketState,
+    len: usize,
+    /// The first byte which didn't fit in the packet buffer, if any. The rest
+    /// of the packet is discarded as it arrives.
+    overflow: Option<u8>,
+}
+
+enum RecvPacketState {
+    /// Waiting for the first byte of a packet.
+    Ready,
+    /// Reading the body of a `$` packet, up to and including the `#`.
+    Body,
+    /// Reading the checksum, with the given number of bytes remaining.
+    Checksum(u8),
+}
+
+impl RecvPacketStateMachine {
+    fn new() -> RecvPacketStateMachine {
+        RecvPacketStateMachine {
+            state: RecvPacketState::Ready,
+            len: 0,
+            overflow: None,
+        }
+    }
+
+    /// Check if a packet has only been partially received.
+    fn is_partial(&self) -> bool {
+        !matches!(self.state, RecvPacketState::Ready)
+    }
+
+    /// Append `byte` to the packet buffer, returning the length of the packet
+    /// once it has been received in its entirety.
+    ///
+    /// If the packet doesn't fit in the packet buffer, the remainder of the
+    /// packet is discarded, and an error is returned once the packet ends.
+    fn pump(
+        &mut self,
+        packet_buffer: &mut ManagedSlice<u8>,
+        byte: u8,
+    ) -> Result<Option<usize>, CapacityError<u8>> {
+        use RecvPacketState::*;
+
+        // `$` never appears unescaped within a packet, so if one shows up before the
+        // previous packet was terminated, the previous packet must have been truncated
+        // (e.g: GDB gave up on it after a timeout). Start over from the new packet.
+        if byte == b'$' && self.is_partial() {
+            debug!("received '$' partway through a packet, discarding the partial packet");
+            self.state = Ready;
+        }
+
+        if let Ready = self.state {
+            self.len = 0;
+            self.overflow = None;
+        }
+
+        if self.overflow.is_none() {
+            let mut buf = ManagedVec::new_with_len(packet_buffer, self.len);
+            if let Err(CapacityError(byte)) = buf.push(byte) {
+                self.overflow = Some(byte);
+            }
+            self.len = buf.len();
+        }
+
+        self.state = match self.state {
+            Ready if byte == b'$' => Body,
+            // every other kind of packet (i.e: acks and interrupts) is a single byte
+            Ready => return self.finish(),
+            Body if byte == b'#' => Checksum(2),
+            Body => Body,
+            Checksum(1) => {
+                self.state = Ready;
+                return self.finish();
+            }
+            Checksum(n) => Checksum(n - 1),
+        };
+
+        Ok(None)
+    }
+
+    fn finish(&self) -> Result<Option<usize>, CapacityError<u8>> {
+        match self.overflow {
+            Some(byte) => Err(CapacityError(byte)),
+            None => Ok(Some(self.len)),
+        }
+    }
+}
+
+/// Check if GDB has sent an interrupt (i.e: the `0x03` byte), without
+/// consuming it.
+fn gdb_interrupt_pending<C: Connection>(conn: &mut C) -> Result<bool, C::Error> {
+    if !conn.poll_readable()? {
+        return Ok(false);
+    }
+
+    // anything other than an interrupt can wait until the target stops
+    Ok(conn.peek()? == Some(0x03))
+}
@@ -0,0 +1,305 @@
+use managed::ManagedSlice;
+
+use super::{Connection, DisconnectReason, Error, GdbStubImpl, Target};
+use crate::arch::Arch;
+use crate::common::Tid;
+use crate::target::ext::base::multithread::ThreadStopReason;
+use crate::target::ext::base::ResumeAction;
+
+/// A non-blocking interface to a [`GdbStub`](super::GdbStub), driven by
+/// feeding it bytes from the GDB client as they arrive.
+///
+/// Unlike [`GdbStub::run`](super::GdbStub::run), the state machine never
+/// reads from (or polls) the connection, and never blocks waiting for the
+/// target to stop, making it possible to integrate `gdbstub` into an existing
+/// event loop (e.g: one built around `poll`/`epoll`), or a cooperative
+/// `no_std` scheduler.
+///
+/// Created via [`GdbStub::into_state_machine`](super::GdbStub::into_state_machine).
+///
+/// # Sending responses
+///
+/// Rather than being written to the connection, outgoing bytes are handed
+/// back to the host via [`StateMachineAction::Send`], leaving it up to the
+/// host to send them to the GDB client (however, and whenever, it sees fit).
+///
+/// A single call to `pump` may result in several actions (e.g: acknowledging
+/// a packet which resumes the target). The first action is returned right
+/// away, and once it has been taken care of, any subsequent actions are
+/// retrieved via [`next_action`](Self::next_action):
+///
+/// ```text
+/// let mut action = gdb.pump(&mut target, byte);
+/// loop {
+///     match action? {
+///         None => break,
+///         Some(StateMachineAction::Send(bytes)) => sock.write_all(bytes)?,
+///         Some(StateMachineAction::Resume(req)) => { /* resume the target */ }
+///         // ...
+///     }
+///     action = gdb.next_action();
+/// }
+/// ```
+///
+/// _Note:_ Outgoing bytes are buffered on the heap, so `Send` actions are
+/// only returned when the `alloc` feature is enabled. Otherwise, responses
+/// are written straight to the connection (using its `write` and `flush`
+/// methods).
+///
+/// # Resuming the target
+///
+/// When GDB resumes the target, [`pump`](Self::pump) returns a
+/// [`StateMachineAction::Resume`], and it's up to the host to resume the
+/// target as requested. Once the target stops, the host must report why via
+/// [`report_stop`](Self::report_stop).
+///
+/// While the target is running, the host should keep passing incoming bytes
+/// to `pump`. If GDB asks for the target to be interrupted, `pump` returns a
+/// [`StateMachineAction::Interrupt`], and the host should stop the target,
+/// reporting [`ThreadStopReason::GdbInterrupt`] (or whichever stop reason
+/// the target happened to stop with in the meantime).
+///
+/// In non-stop mode, threads are resumed via
+/// [`MultiThreadNonStop::resume_threads`](crate::target::ext::base::multithread::MultiThreadNonStop::resume_threads)
+/// as usual, and stops reported via `report_stop` are sent to GDB as
+/// asynchronous notifications.
+pub struct GdbStubStateMachine<'a, T: Target, C: Connection> {
+    conn: Outgoing<C>,
+    packet_buffer: ManagedSlice<'a, u8>,
+    state: GdbStubImpl<T, Outgoing<C>>,
+    started: bool,
+    /// The result to hand back via `next_action`, once the bytes returned by
+    /// the preceding `Send` action have been sent.
+    deferred: Option<PumpResult<'static, T, C>>,
+}
+
+/// An action which the host must take on behalf of a
+/// [`GdbStubStateMachine`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum StateMachineAction<'b, U> {
+    /// Send the given bytes to the GDB client.
+    ///
+    /// Once they have been sent, the host should call
+    /// [`GdbStubStateMachine::next_action`] to retrieve the next action (if
+    /// any).
+    Send(&'b [u8]),
+    /// GDB resumed the target.
+    ///
+    /// The host should resume the target as requested, and call
+    /// [`GdbStubStateMachine::report_stop`] once it stops.
+    Resume(ResumeRequest<U>),
+    /// GDB asked for the running target to be interrupted.
+    ///
+    /// The host should stop the target, and call
+    /// [`GdbStubStateMachine::report_stop`] with
+    /// [`ThreadStopReason::GdbInterrupt`].
+    Interrupt,
+    /// The debugging session ended.
+    Disconnect(DisconnectReason),
+}
+
+/// The result of passing data (or a stop) to the state machine.
+pub(super) type PumpResult<'b, T, C> = Result<
+    Option<StateMachineAction<'b, <<T as Target>::Arch as Arch>::Usize>>,
+    Error<<T as Target>::Error, <C as Connection>::Error>,
+>;
+
+/// Wraps the host's connection, capturing outgoing bytes so that they can be
+/// handed back to the host via [`StateMachineAction::Send`].
+struct Outgoing<C> {
+    inner: C,
+    #[cfg(feature = "alloc")]
+    buf: alloc::vec::Vec<u8>,
+}
+
+impl<C> Outgoing<C> {
+    fn new(inner: C) -> Outgoing<C> {
+        Outgoing {
+            inner,
+            #[cfg(feature = "alloc")]
+            buf: alloc::vec::Vec::new(),
+        }
+    }
+
+    /// Bytes which have been written, but have yet to be handed to the host.
+    #[cfg(feature = "alloc")]
+    fn pending(&self) -> &[u8] {
+        &self.buf
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn pending(&self) -> &[u8] {
+        &[]
+    }
+
+    fn clear(&mut self) {
+        #[cfg(feature = "alloc")]
+        self.buf.clear();
+    }
+}
+
+impl<C: Connection> Connection for Outgoing<C> {
+    type Error = C::Error;
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        self.inner.read()
+    }
+
+    #[cfg(feature = "alloc")]
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+        self.buf.push(byte);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+        self.inner.write(byte)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.buf.extend_from_slice(buf);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        self.inner.write_all(buf)
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+        self.inner.peek()
+    }
+
+    fn poll_readable(&mut self) -> Result<bool, Self::Error> {
+        self.inner.poll_readable()
+    }
+
+    #[cfg(feature = "alloc")]
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+
+    fn on_session_start(&mut self) -> Result<(), Self::Error> {
+        self.inner.on_session_start()
+    }
+}
+
+/// Describes how GDB asked for the target to be resumed.
+///
+/// Each variant corresponds to one of the target's (blocking) resume methods,
+/// which [`GdbStub::run`](super::GdbStub::run) calls on the host's behalf.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ResumeRequest<U> {
+    /// Resume the target using the given action (i.e: `resume`).
+    ///
+    /// For multi threaded targets, this is the default resume action. Any
+    /// thread-specific actions have already been passed to the target via
+    /// `set_resume_action`.
+    Resume(ResumeAction),
+    /// (single threaded targets) Step the target until its PC leaves the given
+    /// range (i.e: `resume_range_step`).
+    RangeStep {
+        /// Start of the range (inclusive).
+        start: U,
+        /// End of the range (exclusive).
+        end: U,
+    },
+    /// Run the target backwards (i.e: `reverse_cont`).
+    ReverseCont,
+    /// Step the given thread backwards (i.e: `reverse_step`). Single threaded
+    /// targets can ignore the thread id.
+    ReverseStep(Tid),
+}
+
+impl<'a, T: Target, C: Connection> GdbStubStateMachine<'a, T, C> {
+    pub(super) fn new(
+        conn: C,
+        packet_buffer: ManagedSlice<'a, u8>,
+        state: GdbStubImpl<T, C>,
+    ) -> GdbStubStateMachine<'a, T, C> {
+        GdbStubStateMachine {
+            conn: Outgoing::new(conn),
+            packet_buffer,
+            state: state.with_connection(),
+            started: false,
+            deferred: None,
+        }
+    }
+
+    /// Pass a byte sent by the GDB client to the state machine.
+    ///
+    /// Once a complete packet has been received, it is handled immediately.
+    ///
+    /// Returns an action if the host needs to act on the packet (or
+    /// interrupt) which was received.
+    pub fn pump(&mut self, target: &mut T, byte: u8) -> PumpResult<'_, T, C> {
+        self.start()?;
+        let res = self
+            .state
+            .pump(target, &mut self.conn, &mut self.packet_buffer, byte);
+        self.send_first(res)
+    }
+
+    /// Report that the target stopped after being resumed (in response to a
+    /// [`StateMachineAction::Resume`]).
+    ///
+    /// The stop is reported to GDB, unless it's one which GDB didn't ask to be
+    /// notified of (e.g: a `ThreadCreated` event, without GDB having sent
+    /// `QThreadEvents`), in which case the same `Resume` action is returned,
+    /// and the host should resume the target once again.
+    pub fn report_stop(
+        &mut self,
+        target: &mut T,
+        stop_reason: ThreadStopReason<<T::Arch as Arch>::Usize>,
+    ) -> PumpResult<'_, T, C> {
+        self.start()?;
+        let res = self.state.report_stop(target, &mut self.conn, stop_reason);
+        self.send_first(res)
+    }
+
+    /// Retrieve the action which follows a [`StateMachineAction::Send`].
+    ///
+    /// This must be called once the bytes have been sent, and before passing
+    /// any more data to the state machine, as otherwise the action is lost.
+    pub fn next_action(&mut self) -> PumpResult<'static, T, C> {
+        self.deferred.take().unwrap_or(Ok(None))
+    }
+
+    /// Return a mutable reference to the underlying connection.
+    pub fn borrow_conn(&mut self) -> &mut C {
+        &mut self.conn.inner
+    }
+
+    fn start(&mut self) -> Result<(), Error<T::Error, C::Error>> {
+        // any bytes handed out by the previous `Send` action have been sent
+        self.conn.clear();
+        self.deferred = None;
+
+        if !self.started {
+            self.conn
+                .on_session_start()
+                .map_err(Error::ConnectionRead)?;
+            self.started = true;
+        }
+        Ok(())
+    }
+
+    /// If there are bytes to send, return them first, deferring `res` until
+    /// the host calls `next_action`.
+    fn send_first(&mut self, res: PumpResult<'static, T, C>) -> PumpResult<'_, T, C> {
+        if self.conn.pending().is_empty() {
+            return res;
+        }
+
+        self.deferred = Some(res);
+        Ok(Some(StateMachineAction::Send(self.conn.pending())))
+    }
+}
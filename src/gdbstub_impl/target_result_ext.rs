@@ -1,3 +1,4 @@
+use crate::common::errno;
 use crate::target::TargetError;
 use crate::GdbStubError;
 
@@ -15,11 +16,10 @@ impl<V, T, C> TargetResultExt<V, T, C> for Result<V, TargetError<T>> {
             Ok(v) => return Ok(v),
             Err(TargetError::Fatal(e)) => return Err(GdbStubError::TargetError(e)),
             // Recoverable errors:
-            // Error code 121 corresponds to `EREMOTEIO` lol
-            Err(TargetError::NonFatal) => 121,
+            Err(TargetError::NonFatal) => errno::EREMOTEIO,
             Err(TargetError::Errno(code)) => code,
             #[cfg(feature = "std")]
-            Err(TargetError::Io(e)) => e.raw_os_error().unwrap_or(121) as u8,
+            Err(TargetError::Io(e)) => e.raw_os_error().unwrap_or(errno::EREMOTEIO as i32) as u8,
         };
 
         Err(GdbStubError::NonFatalError(code))
@@ -31,7 +31,9 @@
 //!
 //! `Connection` is automatically implemented for common `std` types such as
 //! [`TcpStream`](std::net::TcpStream) and
-//! [`UnixStream`](std::os::unix::net::UnixStream).
+//! [`UnixStream`](std::os::unix::net::UnixStream). Any other
+//! `std::io::Read + std::io::Write` type (e.g: a serial port) can be used by
+//! wrapping it in an `IoConnection`.
 //!
 //! If you're using `gdbstub` in a `#![no_std]` environment, `Connection` will
 //! most likely need to be manually implemented on top of whatever in-order,
@@ -99,7 +101,8 @@
 //! match debugger.run(&mut target) {
 //!     Ok(disconnect_reason) => match disconnect_reason {
 //!         DisconnectReason::Disconnect => println!("GDB client disconnected."),
-//!         DisconnectReason::TargetHalted => println!("Target halted!"),
+//!         DisconnectReason::TargetExited(code) => println!("Target exited: {}", code),
+//!         DisconnectReason::TargetTerminated(sig) => println!("Target terminated: {:?}", sig),
 //!         DisconnectReason::Kill => println!("GDB client sent a kill command!"),
 //!     }
 //!     // Handle any target-specific errors
@@ -130,8 +133,11 @@
 //!         - (Monitor Command) Use a heap-allocated output buffer in
 //!           `ConsoleOutput`.
 //! - `std` (implies `alloc`)
-//!     - Implement `Connection` for [`TcpStream`](std::net::TcpStream) and
+//!     - Implement `Connection` and `ConnectionExt` for
+//!       [`TcpStream`](std::net::TcpStream) and
 //!       [`UnixStream`](std::os::unix::net::UnixStream).
+//!     - Provide `IoConnection`, which implements `Connection` for any
+//!       `std::io::Read + std::io::Write` type.
 //!     - Implement [`std::error::Error`] for `gdbstub::Error`.
 //!     - Add a `TargetError::Io` error variant to simplify I/O Error handling
 //!       from `Target` methods.
@@ -157,7 +163,9 @@ pub mod arch;
 pub mod common;
 pub mod target;
 
-pub use connection::Connection;
+#[cfg(feature = "std")]
+pub use connection::IoConnection;
+pub use connection::{BufferedConnection, Connection, ConnectionExt};
 pub use gdbstub_impl::*;
 
 /// (Internal) The fake Tid that's used when running in single-threaded mode.
@@ -6,7 +6,10 @@ use crate::target::Target;
 pub(self) mod prelude {
     pub use super::ParseCommand;
     pub use crate::common::*;
-    pub use crate::protocol::common::hex::{decode_hex, decode_hex_buf, is_hex, HexString};
+    pub use crate::protocol::common::hex::{
+        decode_bin_buf, decode_hex, decode_hex_buf, is_hex, HexString,
+    };
+    pub use crate::protocol::common::signal_list::SignalList;
     pub use crate::protocol::common::thread_id::{
         IdKind, SpecificIdKind, SpecificThreadId, ThreadId,
     };
@@ -21,7 +24,8 @@ pub trait ParseCommand<'a>: Sized {
 }
 
 // Breakpoint packets are special-cased, as the "Z" packet is parsed differently
-// depending on whether or not the target implements the `Agent` extension.
+// depending on whether or not the target implements the `BreakpointConditions`
+// extension.
 //
 // While it's entirely possible to eagerly parse the "Z" packet for bytecode,
 // doing so would unnecessary bloat implementations that do not support
@@ -36,14 +40,14 @@ macro_rules! commands {
         )*
     ) => {paste! {
         $($(
-            #[allow(non_snake_case, non_camel_case_types)]
+            #[allow(non_snake_case, non_camel_case_types, clippy::upper_case_acronyms)]
             pub mod $mod;
         )*)*
         pub mod breakpoint;
 
         pub mod ext {
             $(
-                #[allow(non_camel_case_types)]
+                #[allow(non_camel_case_types, clippy::enum_variant_names, clippy::upper_case_acronyms)]
                 pub enum [<$ext:camel>] $(<$lt>)? {
                     $($command(super::$mod::$command<$($lifetime)?>),)*
                 }
@@ -81,6 +85,9 @@ macro_rules! commands {
                     fn single_register_access(&mut self) -> Option<()>;
                     fn reverse_step(&mut self) -> Option<()>;
                     fn reverse_cont(&mut self) -> Option<()>;
+                    fn thread_extra_info(&mut self) -> Option<()>;
+                    fn thread_list(&mut self) -> Option<()>;
+                    fn non_stop(&mut self) -> Option<()>;
                 }
 
                 impl<T: Target> Hack for T {
@@ -89,11 +96,14 @@ macro_rules! commands {
                     }
 
                     fn single_register_access(&mut self) -> Option<()> {
+                        use crate::arch::{Arch, Registers};
                         use crate::target::ext::base::BaseOps;
-                        match self.base_ops() {
+                        let ext = match self.base_ops() {
                             BaseOps::SingleThread(ops) => ops.single_register_access().map(drop),
                             BaseOps::MultiThread(ops) => ops.single_register_access().map(drop),
-                        }
+                        };
+                        let indexed = <T::Arch as Arch>::Registers::INDEXED_ACCESS;
+                        ext.or(if indexed { Some(()) } else { None })
                     }
 
                     fn reverse_step(&mut self) -> Option<()> {
@@ -111,6 +121,30 @@ macro_rules! commands {
                             BaseOps::MultiThread(ops) => ops.support_reverse_cont().map(drop),
                         }
                     }
+
+                    fn thread_extra_info(&mut self) -> Option<()> {
+                        use crate::target::ext::base::BaseOps;
+                        match self.base_ops() {
+                            BaseOps::SingleThread(_) => None,
+                            BaseOps::MultiThread(ops) => ops.support_thread_extra_info().map(drop),
+                        }
+                    }
+
+                    fn thread_list(&mut self) -> Option<()> {
+                        use crate::target::ext::base::BaseOps;
+                        match self.base_ops() {
+                            BaseOps::SingleThread(_) => None,
+                            BaseOps::MultiThread(ops) => ops.support_thread_list().map(drop),
+                        }
+                    }
+
+                    fn non_stop(&mut self) -> Option<()> {
+                        use crate::target::ext::base::BaseOps;
+                        match self.base_ops() {
+                            BaseOps::SingleThread(_) => None,
+                            BaseOps::MultiThread(ops) => ops.support_non_stop().map(drop),
+                        }
+                    }
                 }
 
                 // TODO?: use tries for more efficient longest prefix matching
@@ -134,7 +168,7 @@ macro_rules! commands {
                 }
                 )*
 
-                if let Some(_breakpoint_ops) = target.breakpoints() {
+                if let Some(breakpoint_ops) = target.breakpoints() {
                     use breakpoint::{BasicBreakpoint, BytecodeBreakpoint};
 
                     if buf.strip_prefix(b"z") {
@@ -143,9 +177,9 @@ macro_rules! commands {
                     }
 
                     if buf.strip_prefix(b"Z") {
-                        // TODO: agent bytecode currently unimplemented
-                        if true {
-                           let cmd = BasicBreakpoint::from_slice(buf.into_body())?;
+                        // only targets which evaluate breakpoint conditions need the bytecode
+                        if breakpoint_ops.breakpoint_conditions().is_none() {
+                            let cmd = BasicBreakpoint::from_slice(buf.into_body())?;
                             return Some(Command::Breakpoints(ext::Breakpoints::Z(cmd)))
                         } else {
                             let cmd = BytecodeBreakpoint::from_slice(buf.into_body())?;
@@ -165,22 +199,36 @@ commands! {
         "?" => question_mark::QuestionMark,
         "c" => _c::c<'a>,
         "D" => _d_upcase::D,
-        "g" => _g::g,
+        "g" => _g::g<'a>,
         "G" => _g_upcase::G<'a>,
         "H" => _h_upcase::H,
         "k" => _k::k,
         "m" => _m::m<'a>,
         "M" => _m_upcase::M<'a>,
         "qAttached" => _qAttached::qAttached,
+        "qCRC" => _qCRC::qCRC<'a>,
+        // NOTE: must come after "qCRC", as commands are prefix-matched in order
+        "qC" => _qC::qC,
         "qfThreadInfo" => _qfThreadInfo::qfThreadInfo,
+        // NOTE: handled even without the `MonitorCmd` extension, as the stub
+        // implements some `monitor` commands itself
+        "qRcmd" => _qRcmd::qRcmd<'a>,
+        "qSearch:memory" => _qSearch_memory::qSearchMemory<'a>,
         "QStartNoAckMode" => _QStartNoAckMode::QStartNoAckMode,
         "qsThreadInfo" => _qsThreadInfo::qsThreadInfo,
         "qSupported" => _qSupported::qSupported<'a>,
-        "qXfer:features:read" => _qXfer_features_read::qXferFeaturesRead,
+        "qSymbol" => _qSymbol::qSymbol<'a>,
+        "QThreadEvents" => _QThreadEvents::QThreadEvents,
+        // NOTE: handled even without the `Tracepoints` extension, as GDB
+        // probes the trace status when connecting
+        "qTStatus" => _qTStatus::qTStatus,
+        "qXfer:features:read" => _qXfer_features_read::qXferFeaturesRead<'a>,
         "s" => _s::s<'a>,
         "T" => _t_upcase::T,
         "vCont" => _vCont::vCont<'a>,
+        "vCtrlC" => _vCtrlC::vCtrlC,
         "vKill" => _vKill::vKill,
+        "vMustReplyEmpty" => _vMustReplyEmpty::vMustReplyEmpty,
     }
 
     single_register_access use 'a {
@@ -201,14 +249,19 @@ commands! {
         "vRun" => _vRun::vRun<'a>,
     }
 
-    monitor_cmd use 'a {
-        "qRcmd" => _qRcmd::qRcmd<'a>,
+    pass_signals use 'a {
+        "QPassSignals" => _QPassSignals::QPassSignals<'a>,
+        "QProgramSignals" => _QProgramSignals::QProgramSignals<'a>,
     }
 
     section_offsets {
         "qOffsets" => _qOffsets::qOffsets,
     }
 
+    tls_access use 'a {
+        "qGetTLSAddr" => _qGetTLSAddr::qGetTLSAddr<'a>,
+    }
+
     reverse_cont {
         "bc" => _bc::bc,
     }
@@ -217,7 +270,75 @@ commands! {
         "bs" => _bs::bs,
     }
 
+    thread_extra_info use 'a {
+        "qThreadExtraInfo" => _qThreadExtraInfo::qThreadExtraInfo<'a>,
+    }
+
+    thread_list use 'a {
+        "qXfer:threads:read" => _qXfer_threads::qXferThreadsRead<'a>,
+    }
+
+    non_stop {
+        "QNonStop" => _QNonStop::QNonStop,
+        "vStopped" => _vStopped::vStopped,
+    }
+
+    host_io use 'a {
+        "vFile:close" => _vFile_close::vFileClose,
+        "vFile:fstat" => _vFile_fstat::vFileFstat,
+        "vFile:open" => _vFile_open::vFileOpen<'a>,
+        "vFile:pread" => _vFile_pread::vFilePread<'a>,
+        "vFile:pwrite" => _vFile_pwrite::vFilePwrite<'a>,
+        "vFile:readlink" => _vFile_readlink::vFileReadlink<'a>,
+        "vFile:setfs" => _vFile_setfs::vFileSetfs,
+        "vFile:unlink" => _vFile_unlink::vFileUnlink<'a>,
+    }
+
     memory_map {
         "qXfer:memory-map:read" => _qXfer_memory_map::qXferMemoryMapRead,
     }
+
+    memory_region_info use 'a {
+        "qMemoryRegionInfo" => _qMemoryRegionInfo::qMemoryRegionInfo<'a>,
+    }
+
+    lldb_host_info {
+        "qHostInfo" => _qHostInfo::qHostInfo,
+        "qProcessInfo" => _qProcessInfo::qProcessInfo,
+    }
+
+    lldb_register_info {
+        "qRegisterInfo" => _qRegisterInfo::qRegisterInfo,
+    }
+
+    flash use 'a {
+        "vFlashDone" => _vFlashDone::vFlashDone,
+        "vFlashErase" => _vFlashErase::vFlashErase<'a>,
+        "vFlashWrite" => _vFlashWrite::vFlashWrite<'a>,
+    }
+
+    auxv {
+        "qXfer:auxv:read" => _qXfer_auxv_read::qXferAuxvRead,
+    }
+
+    exec_file {
+        "qXfer:exec-file:read" => _qXfer_exec_file::qXferExecFileRead,
+    }
+
+    siginfo use 'a {
+        "qXfer:siginfo:read" => _qXfer_siginfo_read::qXferSiginfoRead,
+        "qXfer:siginfo:write" => _qXfer_siginfo_write::qXferSiginfoWrite<'a>,
+    }
+
+    libraries_svr4 use 'a {
+        "qXfer:libraries-svr4:read" => _qXfer_libraries_svr4_read::qXferLibrariesSvr4Read<'a>,
+    }
+
+    tracepoints use 'a {
+        "QTDP" => _QTDP::QTDP<'a>,
+        "QTFrame" => _QTFrame::QTFrame<'a>,
+        "QTinit" => _QTinit::QTinit,
+        "QTStart" => _QTStart::QTStart,
+        "QTStop" => _QTStop::QTStop,
+    }
 }
@@ -2,15 +2,18 @@ use super::prelude::*;
 
 #[derive(Debug)]
 pub struct QDisableRandomization {
-    pub value: bool,
+    /// `None` if GDB sent a value other than `0` or `1`.
+    pub value: Option<bool>,
 }
 
 impl<'a> ParseCommand<'a> for QDisableRandomization {
     fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
         let body = buf.into_body();
         let value = match body as &[u8] {
-            b":0" => false,
-            b":1" => true,
+            b":0" => Some(false),
+            b":1" => Some(true),
+            // reported back to GDB as an invalid argument
+            [b':', ..] => None,
             _ => return None,
         };
         Some(QDisableRandomization { value })
@@ -27,3 +27,39 @@ impl<'a> ParseCommand<'a> for QEnvironmentHexEncoded<'a> {
         Some(QEnvironmentHexEncoded { key, value })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(body: &[u8]) -> Option<(Vec<u8>, Option<Vec<u8>>)> {
+        let mut body = body.to_vec();
+        let buf = PacketBuf::new_with_raw_body(&mut body).unwrap();
+        let pkt = QEnvironmentHexEncoded::from_packet(buf)?;
+        Some((pkt.key.to_vec(), pkt.value.map(|v| v.to_vec())))
+    }
+
+    #[test]
+    fn key_value() {
+        // "FOO=bar=baz"
+        let (key, value) = parse(b":464f4f3d6261723d62617a").unwrap();
+        assert_eq!(key, b"FOO");
+        assert_eq!(value.unwrap(), b"bar=baz");
+    }
+
+    #[test]
+    fn empty_value() {
+        // "FOO="
+        let (key, value) = parse(b":464f4f3d").unwrap();
+        assert_eq!(key, b"FOO");
+        assert_eq!(value, None);
+    }
+
+    #[test]
+    fn malformed() {
+        // "FOO"
+        assert!(parse(b":464f4f").is_none());
+        assert!(parse(b":464f4").is_none());
+        assert!(parse(b"464f4f3d").is_none());
+    }
+}
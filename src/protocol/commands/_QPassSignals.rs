@@ -0,0 +1,17 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub struct QPassSignals<'a> {
+    pub signals: SignalList<'a>,
+}
+
+impl<'a> ParseCommand<'a> for QPassSignals<'a> {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let signals = match buf.into_body() {
+            [b':', signals @ ..] => SignalList::parse(signals)?,
+            _ => return None,
+        };
+
+        Some(QPassSignals { signals })
+    }
+}
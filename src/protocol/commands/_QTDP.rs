@@ -0,0 +1,302 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub enum QTDP<'a> {
+    Create(QTDPCreate<'a>),
+    Define(QTDPDefine<'a>),
+}
+
+/// `QTDP:n:addr:ena:step:pass[:Xlen,bytes][-]`
+#[derive(Debug)]
+pub struct QTDPCreate<'a> {
+    pub number: usize,
+    pub addr: &'a [u8],
+    pub enable: bool,
+    pub step: u64,
+    pub pass: u64,
+    pub condition: Option<&'a [u8]>,
+}
+
+/// `QTDP:-n:addr:[S]action...[-]`
+#[derive(Debug)]
+pub struct QTDPDefine<'a> {
+    pub number: usize,
+    pub addr: &'a [u8],
+    pub while_stepping: bool,
+    pub actions: Actions<'a>,
+}
+
+impl<'a> ParseCommand<'a> for QTDP<'a> {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let body = match buf.into_body() {
+            [b':', body @ ..] => body,
+            _ => return None,
+        };
+
+        // a trailing '-' indicates that more packets will follow for the same
+        // tracepoint, which isn't something the target needs to know about.
+        let body = match body {
+            [body @ .., b'-'] => body,
+            body => body,
+        };
+
+        match body {
+            [b'-', body @ ..] => {
+                let mut body = body.splitn_mut(3, |b| *b == b':');
+                let number = decode_hex(body.next()?).ok()?;
+                let addr = decode_hex_buf(body.next()?).ok()?;
+                let (while_stepping, actions) = match body.next()? {
+                    [b'S', actions @ ..] => (true, actions),
+                    actions => (false, actions),
+                };
+
+                Some(QTDP::Define(QTDPDefine {
+                    number,
+                    addr,
+                    while_stepping,
+                    actions: Actions(actions),
+                }))
+            }
+            body => {
+                let mut body = body.split_mut(|b| *b == b':');
+                let number = decode_hex(body.next()?).ok()?;
+                let addr = decode_hex_buf(body.next()?).ok()?;
+                let enable = match body.next()? {
+                    [b'E'] => true,
+                    [b'D'] => false,
+                    _ => return None,
+                };
+                let step = decode_hex(body.next()?).ok()?;
+                let pass = decode_hex(body.next()?).ok()?;
+
+                let condition = match body.next() {
+                    None => None,
+                    Some([b'X', cond @ ..]) => Some(parse_bytecode(cond)?.0),
+                    // fast tracepoints (i.e: `F`) are not supported
+                    Some(_) => return None,
+                };
+
+                if body.next().is_some() {
+                    return None;
+                }
+
+                Some(QTDP::Create(QTDPCreate {
+                    number,
+                    addr,
+                    enable,
+                    step,
+                    pass,
+                    condition: condition.map(|c| c as &[u8]),
+                }))
+            }
+        }
+    }
+}
+
+/// Parse a `len,bytes` agent expression, returning the decoded expression and
+/// any remaining data.
+fn parse_bytecode(buf: &mut [u8]) -> Option<(&mut [u8], &mut [u8])> {
+    let sep = buf.iter().position(|b| *b == b',')?;
+    let (len, buf) = buf.split_at_mut(sep);
+    let len: usize = decode_hex(len).ok()?;
+    let buf = &mut buf[1..];
+    if buf.len() < len * 2 {
+        return None;
+    }
+    let (expr, rest) = buf.split_at_mut(len * 2);
+    Some((decode_hex_buf(expr).ok()?, rest))
+}
+
+/// Split off the leading hex digits of `buf`.
+fn split_hex(buf: &mut [u8]) -> (&mut [u8], &mut [u8]) {
+    let len = buf.iter().take_while(|b| b.is_ascii_hexdigit()).count();
+    buf.split_at_mut(len)
+}
+
+#[derive(Debug)]
+pub enum TracepointAction<'a> {
+    Registers {
+        mask: &'a [u8],
+    },
+    Memory {
+        basereg: Option<u64>,
+        offset: &'a [u8],
+        length: u64,
+    },
+    Expression {
+        expr: &'a [u8],
+    },
+}
+
+/// A list of tracepoint actions, which are lazily decoded (in place) as they
+/// are iterated over.
+///
+/// Yields `None` (and stops iterating) if an action is malformed.
+#[derive(Debug)]
+pub struct Actions<'a>(&'a mut [u8]);
+
+impl<'a> Iterator for Actions<'a> {
+    type Item = Option<TracepointAction<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (kind, buf) = core::mem::take(&mut self.0).split_first_mut()?;
+        Some(Self::parse_action(*kind, buf).map(|(action, rest)| {
+            self.0 = rest;
+            action
+        }))
+    }
+}
+
+impl<'a> Actions<'a> {
+    fn parse_action(
+        kind: u8,
+        buf: &'a mut [u8],
+    ) -> Option<(TracepointAction<'a>, &'a mut [u8])> {
+        let res = match kind {
+            b'R' => {
+                let (mask, rest) = split_hex(buf);
+                if mask.is_empty() {
+                    return None;
+                }
+                let mask = decode_hex_buf(mask).ok()?;
+                (TracepointAction::Registers { mask }, rest)
+            }
+            b'M' => {
+                let mut fields = buf.splitn_mut(3, |b| *b == b',');
+                let basereg = match fields.next()? as &[u8] {
+                    b"-1" => None,
+                    // GDB sends `-1` as a 32-bit hex value
+                    basereg => match decode_hex(basereg).ok()? {
+                        0xffff_ffff => None,
+                        basereg => Some(basereg),
+                    },
+                };
+                let offset = decode_hex_buf(fields.next()?).ok()?;
+                let (length, rest) = split_hex(fields.next()?);
+                let length = decode_hex(length).ok()?;
+                let action = TracepointAction::Memory {
+                    basereg,
+                    offset,
+                    length,
+                };
+                (action, rest)
+            }
+            b'X' => {
+                let (expr, rest) = parse_bytecode(buf)?;
+                (TracepointAction::Expression { expr }, rest)
+            }
+            _ => return None,
+        };
+
+        Some(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! test_buf {
+        ($bufname:ident, $body:literal) => {
+            let mut test = $body.to_vec();
+            let mut buf = PacketBuf::new_with_raw_body(&mut test).unwrap();
+            if !buf.strip_prefix(b"QTDP") {
+                panic!("invalid test");
+            }
+            let $bufname = buf;
+        };
+    }
+
+    #[test]
+    fn create() {
+        test_buf!(buf, b"QTDP:1:0000000000401000:E:0:3:X2,2601-");
+
+        let pkt = match QTDP::from_packet(buf).unwrap() {
+            QTDP::Create(pkt) => pkt,
+            _ => panic!("expected QTDP::Create"),
+        };
+        assert_eq!(pkt.number, 1);
+        assert_eq!(pkt.addr, [0, 0, 0, 0, 0, 0x40, 0x10, 0]);
+        assert!(pkt.enable);
+        assert_eq!(pkt.step, 0);
+        assert_eq!(pkt.pass, 3);
+        assert_eq!(pkt.condition, Some(&[0x26, 0x01][..]));
+    }
+
+    #[test]
+    fn create_malformed() {
+        // fast tracepoints are not supported
+        test_buf!(buf, b"QTDP:1:401000:E:0:0:F4");
+        assert!(QTDP::from_packet(buf).is_none());
+
+        test_buf!(buf, b"QTDP:1:401000:Y:0:0");
+        assert!(QTDP::from_packet(buf).is_none());
+
+        // truncated condition
+        test_buf!(buf, b"QTDP:1:401000:E:0:0:X3,2601");
+        assert!(QTDP::from_packet(buf).is_none());
+
+        test_buf!(buf, b"QTDP:1:401000:E:0:0:X1,26:");
+        assert!(QTDP::from_packet(buf).is_none());
+    }
+
+    #[test]
+    fn define() {
+        test_buf!(buf, b"QTDP:-1:401000:SR03M-1,10,4Mffffffff,20,8M2,30,4X1,27-");
+
+        let pkt = match QTDP::from_packet(buf).unwrap() {
+            QTDP::Define(pkt) => pkt,
+            _ => panic!("expected QTDP::Define"),
+        };
+        assert_eq!(pkt.number, 1);
+        assert_eq!(pkt.addr, [0x40, 0x10, 0x00]);
+        assert!(pkt.while_stepping);
+
+        let actions = pkt.actions.map(Option::unwrap).collect::<Vec<_>>();
+        assert_eq!(actions.len(), 5);
+        assert!(matches!(actions[0], TracepointAction::Registers { mask: [0x03] }));
+        assert!(matches!(
+            actions[1],
+            TracepointAction::Memory {
+                basereg: None,
+                offset: [0x10],
+                length: 4
+            }
+        ));
+        assert!(matches!(
+            actions[2],
+            TracepointAction::Memory { basereg: None, .. }
+        ));
+        assert!(matches!(
+            actions[3],
+            TracepointAction::Memory {
+                basereg: Some(2),
+                offset: [0x30],
+                length: 4
+            }
+        ));
+        assert!(matches!(
+            actions[4],
+            TracepointAction::Expression { expr: [0x27] }
+        ));
+    }
+
+    #[test]
+    fn define_malformed_action() {
+        test_buf!(buf, b"QTDP:-1:401000:R03Q");
+
+        let pkt = match QTDP::from_packet(buf).unwrap() {
+            QTDP::Define(pkt) => pkt,
+            _ => panic!("expected QTDP::Define"),
+        };
+        assert!(!pkt.while_stepping);
+
+        let mut actions = pkt.actions;
+        assert!(matches!(
+            actions.next(),
+            Some(Some(TracepointAction::Registers { .. }))
+        ));
+        assert!(matches!(actions.next(), Some(None)));
+        assert!(actions.next().is_none());
+    }
+}
@@ -0,0 +1,49 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub enum QTFrame<'a> {
+    Number(usize),
+    /// Stop inspecting trace frames (i.e: `QTFrame:-1`)
+    Clear,
+    Pc(&'a [u8]),
+    Tdp(usize),
+    Range(&'a [u8], &'a [u8]),
+    Outside(&'a [u8], &'a [u8]),
+}
+
+impl<'a> ParseCommand<'a> for QTFrame<'a> {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let body = match buf.into_body() {
+            [b':', body @ ..] => body,
+            _ => return None,
+        };
+
+        let mut body = body.split_mut(|b| *b == b':');
+        let kind = body.next()?;
+        let frame = match kind as &[u8] {
+            b"pc" => QTFrame::Pc(decode_hex_buf(body.next()?).ok()?),
+            b"tdp" => QTFrame::Tdp(decode_hex(body.next()?).ok()?),
+            b"range" => QTFrame::Range(
+                decode_hex_buf(body.next()?).ok()?,
+                decode_hex_buf(body.next()?).ok()?,
+            ),
+            b"outside" => QTFrame::Outside(
+                decode_hex_buf(body.next()?).ok()?,
+                decode_hex_buf(body.next()?).ok()?,
+            ),
+            b"-1" => QTFrame::Clear,
+            n => match decode_hex::<u64>(n).ok()? {
+                // GDB sends the frame number as a 32-bit hex value, so `-1`
+                // is typically sent as `ffffffff`
+                0xffff_ffff => QTFrame::Clear,
+                n => QTFrame::Number(n.try_into().ok()?),
+            },
+        };
+
+        if body.next().is_some() {
+            return None;
+        }
+
+        Some(frame)
+    }
+}
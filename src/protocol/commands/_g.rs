@@ -1,13 +1,18 @@
 use super::prelude::*;
 
 #[derive(Debug)]
-pub struct g;
+pub struct g<'a> {
+    pub buf: &'a mut [u8],
+}
 
-impl<'a> ParseCommand<'a> for g {
+impl<'a> ParseCommand<'a> for g<'a> {
     fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
-        if !buf.into_body().is_empty() {
+        let (buf, body_range) = buf.into_raw_buf();
+        if !body_range.is_empty() {
             return None;
         }
-        Some(g)
+        // the packet itself carries no data, so the entire packet buffer can
+        // be used as scratch space when serializing the register file
+        Some(g { buf })
     }
 }
@@ -0,0 +1,29 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub struct qCRC<'a> {
+    pub addr: &'a [u8],
+    pub len: &'a [u8],
+
+    pub buf: &'a mut [u8],
+}
+
+impl<'a> ParseCommand<'a> for qCRC<'a> {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        // the addr and len are decoded in-place, with the empty space following the
+        // body used as scratch space when reading target memory.
+        let (buf, body_range) = buf.into_raw_buf();
+        let (body, buf) = buf[body_range.start..].split_at_mut(body_range.len());
+
+        let body = match body {
+            [b':', body @ ..] => body,
+            _ => return None,
+        };
+
+        let mut body = body.split_mut(|b| *b == b',');
+        let addr = decode_hex_buf(body.next()?).ok()?;
+        let len = decode_hex_buf(body.next()?).ok()?;
+
+        Some(qCRC { addr, len, buf })
+    }
+}
@@ -0,0 +1,28 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub struct qGetTLSAddr<'a> {
+    pub id: ThreadId,
+    pub offset: &'a [u8],
+    pub lm: &'a [u8],
+}
+
+impl<'a> ParseCommand<'a> for qGetTLSAddr<'a> {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let body = match buf.into_body() {
+            [b':', body @ ..] => body,
+            _ => return None,
+        };
+
+        let mut body = body.split_mut(|b| *b == b',');
+        let id = ThreadId::try_from(&*body.next()?).ok()?;
+        let offset = decode_hex_buf(body.next()?).ok()?;
+        let lm = decode_hex_buf(body.next()?).ok()?;
+
+        if body.next().is_some() {
+            return None;
+        }
+
+        Some(qGetTLSAddr { id, offset, lm })
+    }
+}
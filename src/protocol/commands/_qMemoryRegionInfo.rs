@@ -0,0 +1,18 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub struct qMemoryRegionInfo<'a> {
+    /// `None` when the client is probing for support.
+    pub addr: Option<&'a [u8]>,
+}
+
+impl<'a> ParseCommand<'a> for qMemoryRegionInfo<'a> {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let addr = match buf.into_body() {
+            [] => None,
+            [b':', addr @ ..] => Some(decode_hex_buf(addr).ok()? as &[u8]),
+            _ => return None,
+        };
+        Some(qMemoryRegionInfo { addr })
+    }
+}
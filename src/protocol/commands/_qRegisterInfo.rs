@@ -0,0 +1,13 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub struct qRegisterInfo {
+    pub reg_id: usize,
+}
+
+impl<'a> ParseCommand<'a> for qRegisterInfo {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let reg_id = decode_hex(buf.into_body()).ok()?;
+        Some(qRegisterInfo { reg_id })
+    }
+}
@@ -0,0 +1,78 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub struct qSearchMemory<'a> {
+    pub addr: &'a [u8],
+    pub len: &'a [u8],
+    pub pattern: &'a [u8],
+
+    pub buf: &'a mut [u8],
+}
+
+impl<'a> ParseCommand<'a> for qSearchMemory<'a> {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        // the total packet buffer currently looks like:
+        //
+        // +-----------------------+------+-----+---------+-------+-----------------+
+        // | "$qSearch:memory:"    | addr | len | pattern | "#XX" | empty space ... |
+        // +-----------------------+------+-----+---------+-------+-----------------+
+        //
+        // Each field is decoded in-place, with the empty space following the body
+        // used as scratch space when reading target memory.
+
+        let (buf, body_range) = buf.into_raw_buf();
+        let (body, buf) = buf[body_range.start..].split_at_mut(body_range.len());
+
+        let body = match body {
+            [b':', body @ ..] => body,
+            _ => return None,
+        };
+
+        // the pattern may itself contain ';' characters
+        let mut body = body.splitn_mut(3, |b| *b == b';');
+        let addr = decode_hex_buf(body.next()?).ok()?;
+        let len = decode_hex_buf(body.next()?).ok()?;
+        let pattern = decode_bin_buf(body.next()?).ok()?;
+
+        Some(qSearchMemory {
+            addr,
+            len,
+            pattern,
+            buf,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pattern_is_unescaped() {
+        // the pattern contains a ';', and an escaped '#'
+        let body = b"qSearch:memory:100;20;a;}\x03b";
+        let checksum = body.iter().fold(0u8, |a, x| a.wrapping_add(*x));
+        let mut buf = b"$".to_vec();
+        buf.extend_from_slice(body);
+        buf.extend_from_slice(format!("#{:02x}", checksum).as_bytes());
+        let pkt_len = buf.len();
+        buf.resize(pkt_len + 16, 0);
+
+        let mut buf = PacketBuf::new(&mut buf).unwrap();
+        assert!(buf.strip_prefix(b"qSearch:memory"));
+        let pkt = qSearchMemory::from_packet(buf).unwrap();
+
+        assert_eq!(pkt.addr, [0x01, 0x00]);
+        assert_eq!(pkt.len, [0x20]);
+        assert_eq!(pkt.pattern, b"a;#b");
+        // everything following the body is available as scratch space
+        assert_eq!(pkt.buf.len(), 3 + 16);
+    }
+
+    #[test]
+    fn malformed() {
+        let mut body = b":100;20".to_vec();
+        let buf = PacketBuf::new_with_raw_body(&mut body).unwrap();
+        assert!(qSearchMemory::from_packet(buf).is_none());
+    }
+}
@@ -10,14 +10,16 @@ impl<'a> ParseCommand<'a> for qSupported<'a> {
     fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
         let packet_buffer_len = buf.full_len();
 
-        let body = buf.into_body();
-        if body.is_empty() {
-            return None;
-        }
+        // the list of client features is optional
+        let features: &[u8] = match buf.into_body() {
+            [] => &[],
+            [b':', features @ ..] => features,
+            _ => return None,
+        };
 
         Some(qSupported {
             packet_buffer_len,
-            features: Features(body),
+            features: Features(features),
         })
     }
 }
@@ -51,7 +53,7 @@ impl<'a> Features<'a> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum FeatureSupported {
     Yes,
     No,
@@ -60,7 +62,7 @@ pub enum FeatureSupported {
 
 #[derive(Debug)]
 pub struct Feature<'a> {
-    name: Bstr<'a>,
-    val: Option<Bstr<'a>>,
-    status: FeatureSupported,
+    pub name: Bstr<'a>,
+    pub val: Option<Bstr<'a>>,
+    pub status: FeatureSupported,
 }
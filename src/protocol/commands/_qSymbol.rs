@@ -0,0 +1,38 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub struct qSymbol<'a> {
+    /// `None` when GDB is ready to serve symbol lookups (i.e: `qSymbol::`).
+    pub name: Option<&'a [u8]>,
+    /// `None` if GDB could not resolve the symbol.
+    pub value: Option<&'a [u8]>,
+}
+
+impl<'a> ParseCommand<'a> for qSymbol<'a> {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let body = match buf.into_body() {
+            [b':', body @ ..] => body,
+            _ => return None,
+        };
+
+        let mut body = body.split_mut(|b| *b == b':');
+        let value = body.next()?;
+        let name = body.next()?;
+
+        if body.next().is_some() {
+            return None;
+        }
+
+        let value = match value {
+            [] => None,
+            value => Some(decode_hex_buf(value).ok()? as &[u8]),
+        };
+        let name = match name {
+            [] if value.is_some() => return None,
+            [] => None,
+            name => Some(decode_hex_buf(name).ok()? as &[u8]),
+        };
+
+        Some(qSymbol { name, value })
+    }
+}
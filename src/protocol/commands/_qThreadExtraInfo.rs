@@ -0,0 +1,22 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub struct qThreadExtraInfo<'a> {
+    pub id: ThreadId,
+
+    pub buf: &'a mut [u8],
+}
+
+impl<'a> ParseCommand<'a> for qThreadExtraInfo<'a> {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let (buf, body_range) = buf.into_raw_buf();
+        let id = match &buf[body_range] {
+            [b',', id @ ..] => ThreadId::try_from(id).ok()?,
+            _ => return None,
+        };
+
+        // the thread-id has been parsed, so the entire packet buffer can be
+        // re-used as scratch space
+        Some(qThreadExtraInfo { id, buf })
+    }
+}
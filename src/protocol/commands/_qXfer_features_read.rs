@@ -1,14 +1,15 @@
 use super::prelude::*;
 
 #[derive(Debug)]
-pub struct qXferFeaturesRead {
+pub struct qXferFeaturesRead<'a> {
+    pub annex: &'a [u8],
     pub offset: usize,
     pub len: usize,
 }
 
-impl<'a> ParseCommand<'a> for qXferFeaturesRead {
+impl<'a> ParseCommand<'a> for qXferFeaturesRead<'a> {
     fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
-        let body = buf.into_body();
+        let body: &'a [u8] = buf.into_body();
 
         if body.is_empty() {
             return None;
@@ -16,14 +17,15 @@ impl<'a> ParseCommand<'a> for qXferFeaturesRead {
 
         let mut body = body.split(|b| *b == b':').skip(1);
         let annex = body.next()?;
-        if annex != b"target.xml" {
-            return None;
-        }
 
         let mut body = body.next()?.split(|b| *b == b',');
         let offset = decode_hex(body.next()?).ok()?;
         let len = decode_hex(body.next()?).ok()?;
 
-        Some(qXferFeaturesRead { offset, len })
+        Some(qXferFeaturesRead {
+            annex,
+            offset,
+            len,
+        })
     }
 }
@@ -0,0 +1,34 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub struct qXferLibrariesSvr4Read<'a> {
+    pub offset: usize,
+    pub len: usize,
+
+    pub buf: &'a mut [u8],
+}
+
+impl<'a> ParseCommand<'a> for qXferLibrariesSvr4Read<'a> {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let (buf, body_range) = buf.into_raw_buf();
+        let body = &buf[body_range];
+
+        if body.is_empty() {
+            return None;
+        }
+
+        let mut body = body.split(|b| *b == b':').skip(1);
+        let annex = body.next()?;
+        if annex != b"" {
+            return None;
+        }
+
+        let mut body = body.next()?.split(|b| *b == b',');
+        let offset = decode_hex(body.next()?).ok()?;
+        let len = decode_hex(body.next()?).ok()?;
+
+        // offset and len have been parsed, so the entire packet buffer can be
+        // re-used to assemble the library list
+        Some(qXferLibrariesSvr4Read { offset, len, buf })
+    }
+}
@@ -0,0 +1,28 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub struct qXferSiginfoWrite<'a> {
+    pub offset: usize,
+    pub data: &'a [u8],
+}
+
+impl<'a> ParseCommand<'a> for qXferSiginfoWrite<'a> {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let body = match buf.into_body() {
+            [b':', body @ ..] => body,
+            _ => return None,
+        };
+
+        // the data may itself contain ':' characters
+        let mut body = body.splitn_mut(3, |b| *b == b':');
+        let annex = body.next()?;
+        if annex != b"" {
+            return None;
+        }
+
+        let offset = decode_hex(body.next()?).ok()?;
+        let data = decode_bin_buf(body.next()?).ok()?;
+
+        Some(qXferSiginfoWrite { offset, data })
+    }
+}
@@ -1,13 +1,16 @@
 use super::prelude::*;
 
 #[derive(Debug)]
-pub struct qsThreadInfo;
+pub struct qsThreadInfo {
+    pub packet_buffer_len: usize,
+}
 
 impl<'a> ParseCommand<'a> for qsThreadInfo {
     fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let packet_buffer_len = buf.full_len();
         if !buf.into_body().is_empty() {
             return None;
         }
-        Some(qsThreadInfo)
+        Some(qsThreadInfo { packet_buffer_len })
     }
 }
@@ -1,4 +1,5 @@
 use super::prelude::*;
+use crate::protocol::PacketParseError;
 
 // TODO?: instead of parsing lazily when invoked, parse the strings into a
 // compressed binary representations that can be stuffed back into the packet
@@ -117,6 +118,146 @@ impl<'a> VContKind<'a> {
     }
 }
 
+/// Which threads a `vCont` action applies to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TidSelector {
+    /// All threads (i.e: an action without a thread-id, or with a thread-id
+    /// of `-1`).
+    All,
+    /// A specific thread, along with the process it belongs to (which is the
+    /// currently selected process, if none was specified).
+    WithId { pid: SpecificIdKind, tid: Tid },
+}
+
+impl TidSelector {
+    fn from_action(action: &VContAction<'_>, default_pid: Pid) -> TidSelector {
+        match action.thread {
+            None => TidSelector::All,
+            Some(thread) => match thread.tid {
+                SpecificIdKind::All => TidSelector::All,
+                SpecificIdKind::WithId(tid) => TidSelector::WithId {
+                    pid: thread.pid.unwrap_or(SpecificIdKind::WithId(default_pid)),
+                    tid,
+                },
+            },
+        }
+    }
+
+    /// A totally-ordered key identifying a specific thread.
+    fn key(self) -> Option<(usize, usize)> {
+        match self {
+            TidSelector::All => None,
+            TidSelector::WithId { pid, tid } => {
+                let pid = match pid {
+                    // `-1` can't be confused with a real pid
+                    SpecificIdKind::All => usize::MAX,
+                    SpecificIdKind::WithId(pid) => pid.get(),
+                };
+                Some((pid, tid.get()))
+            }
+        }
+    }
+}
+
+/// A validated list of `vCont` actions, keyed by the threads they apply to.
+///
+/// Actions are resolved using the following precedence:
+///
+/// 1. An action for a specific thread applies to that thread, overriding any
+///    wildcard (i.e: `TidSelector::All`) action.
+/// 2. The wildcard action applies to all threads without a specific action.
+/// 3. If there are multiple wildcard actions, the leftmost one wins.
+///
+/// Multiple actions for the same specific thread (i.e: the same pid + tid
+/// pair, where a thread-id without a pid belongs to the currently selected
+/// process) are rejected, as there's no way to tell which of them the client
+/// intended.
+///
+/// e.g: `vCont;s:p1.2;c` steps thread 2, and continues all other threads.
+#[derive(Debug)]
+pub struct ResumeActions<'a, 'b> {
+    actions: &'b Actions<'a>,
+    default_pid: Pid,
+}
+
+impl<'a, 'b> ResumeActions<'a, 'b> {
+    /// Validate the list of actions, rejecting malformed actions, and
+    /// multiple actions which name the same specific thread.
+    ///
+    /// Thread-ids without a pid are resolved against `default_pid`.
+    pub fn new(
+        actions: &'b Actions<'a>,
+        default_pid: Pid,
+    ) -> Result<ResumeActions<'a, 'b>, PacketParseError> {
+        #[cfg(feature = "alloc")]
+        let mut seen = alloc::collections::BTreeSet::new();
+
+        for action in actions.iter() {
+            let action = action.ok_or(PacketParseError::MalformedCommand)?;
+            let selector = TidSelector::from_action(&action, default_pid);
+            let key = match selector.key() {
+                Some(key) => key,
+                None => continue,
+            };
+
+            #[cfg(feature = "alloc")]
+            let conflicting = !seen.insert(key);
+            // without a heap, the other actions have to be re-parsed instead
+            #[cfg(not(feature = "alloc"))]
+            let conflicting = actions
+                .iter()
+                .flatten()
+                .filter(|act| TidSelector::from_action(act, default_pid).key() == Some(key))
+                .count()
+                > 1;
+
+            if conflicting {
+                warn!("vCont contains conflicting actions for {:?}", selector);
+                return Err(PacketParseError::MalformedCommand);
+            }
+        }
+
+        Ok(ResumeActions {
+            actions,
+            default_pid,
+        })
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (TidSelector, VContKind<'a>)> + '_ {
+        let default_pid = self.default_pid;
+        self.actions
+            .iter()
+            .flatten()
+            .map(move |act| (TidSelector::from_action(&act, default_pid), act.kind))
+    }
+
+    /// The action which applies to all threads without a specific action.
+    pub fn default_action(&self) -> Option<VContKind<'a>> {
+        self.iter()
+            .find(|(selector, _)| *selector == TidSelector::All)
+            .map(|(_, kind)| kind)
+    }
+
+    /// The actions which apply to specific threads, along with the process
+    /// each thread belongs to. Each pid + tid pair is yielded at most once.
+    pub fn specific_actions(
+        &self,
+    ) -> impl Iterator<Item = (SpecificIdKind, Tid, VContKind<'a>)> + '_ {
+        self.iter().filter_map(|(selector, kind)| match selector {
+            TidSelector::All => None,
+            TidSelector::WithId { pid, tid } => Some((pid, tid, kind)),
+        })
+    }
+
+    /// The action which applies to the thread `tid` (if any).
+    pub fn action_for(&self, tid: Tid) -> Option<VContKind<'a>> {
+        self.iter()
+            .find(|(selector, _)| matches!(selector, TidSelector::WithId { tid: t, .. } if *t == tid))
+            .map(|(_, kind)| kind)
+            .or_else(|| self.default_action())
+    }
+}
+
 /// Helper type to unify iterators that output the same type. Returned as an
 /// opaque type from `Actions::iter()`.
 enum EitherIter<A, B> {
@@ -137,3 +278,69 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(n: usize) -> core::num::NonZeroUsize {
+        core::num::NonZeroUsize::new(n).unwrap()
+    }
+
+    #[test]
+    fn specific_action_overrides_wildcard() {
+        let actions = Actions::new_from_buf(b";s:p1.2;c");
+        let actions = ResumeActions::new(&actions, id(1)).unwrap();
+
+        assert!(matches!(actions.default_action(), Some(VContKind::Continue)));
+        assert!(matches!(actions.action_for(id(2)), Some(VContKind::Step)));
+        assert!(matches!(actions.action_for(id(3)), Some(VContKind::Continue)));
+
+        let specific = actions.specific_actions().collect::<Vec<_>>();
+        assert_eq!(specific.len(), 1);
+        assert!(matches!(
+            specific[0],
+            (SpecificIdKind::WithId(pid), tid, VContKind::Step) if pid == id(1) && tid == id(2)
+        ));
+    }
+
+    #[test]
+    fn leftmost_wildcard_wins() {
+        let actions = Actions::new_from_buf(b";C0b;s;c:-1");
+        let actions = ResumeActions::new(&actions, id(1)).unwrap();
+
+        assert!(matches!(
+            actions.default_action(),
+            Some(VContKind::ContinueWithSig(0x0b))
+        ));
+        assert_eq!(actions.specific_actions().count(), 0);
+    }
+
+    #[test]
+    fn no_wildcard() {
+        let actions = Actions::new_from_buf(b";s:2");
+        let actions = ResumeActions::new(&actions, id(1)).unwrap();
+
+        assert!(actions.default_action().is_none());
+        assert!(actions.action_for(id(3)).is_none());
+    }
+
+    #[test]
+    fn conflicting_actions() {
+        let actions = Actions::new_from_buf(b";s:p1.2;c:2");
+        assert!(ResumeActions::new(&actions, id(1)).is_err());
+        // ...but a thread-id without a pid belongs to the selected process
+        assert!(ResumeActions::new(&actions, id(2)).is_ok());
+
+        let actions = Actions::new_from_buf(b";s:p-1.2;c:p-1.2");
+        assert!(ResumeActions::new(&actions, id(1)).is_err());
+    }
+
+    #[test]
+    fn malformed_actions() {
+        for buf in [&b";x"[..], b";c:zz", b";C", b";r100"].iter() {
+            let actions = Actions::new_from_buf(buf);
+            assert!(ResumeActions::new(&actions, id(1)).is_err());
+        }
+    }
+}
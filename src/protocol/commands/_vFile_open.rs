@@ -0,0 +1,29 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub struct vFileOpen<'a> {
+    pub filename: &'a [u8],
+    pub flags: u32,
+    pub mode: u32,
+}
+
+impl<'a> ParseCommand<'a> for vFileOpen<'a> {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let body = buf.into_body();
+        let body = match body {
+            [b':', body @ ..] => body,
+            _ => return None,
+        };
+
+        let mut body = body.split_mut(|b| *b == b',');
+        let filename = decode_hex_buf(body.next()?).ok()?;
+        let flags = decode_hex(body.next()?).ok()?;
+        let mode = decode_hex(body.next()?).ok()?;
+
+        Some(vFileOpen {
+            filename,
+            flags,
+            mode,
+        })
+    }
+}
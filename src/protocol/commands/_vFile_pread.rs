@@ -0,0 +1,34 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub struct vFilePread<'a> {
+    pub fd: usize,
+    pub count: usize,
+    pub offset: usize,
+
+    pub buf: &'a mut [u8],
+}
+
+impl<'a> ParseCommand<'a> for vFilePread<'a> {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let (buf, body_range) = buf.into_raw_buf();
+        let body = match &buf[body_range] {
+            [b':', body @ ..] => body,
+            _ => return None,
+        };
+
+        let mut body = body.split(|b| *b == b',');
+        let fd = decode_hex(body.next()?).ok()?;
+        let count = decode_hex(body.next()?).ok()?;
+        let offset = decode_hex(body.next()?).ok()?;
+
+        // all arguments have been parsed, so the entire packet buffer can be
+        // re-used to hold the data read from the file
+        Some(vFilePread {
+            fd,
+            count,
+            offset,
+            buf,
+        })
+    }
+}
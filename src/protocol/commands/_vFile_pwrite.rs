@@ -0,0 +1,26 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub struct vFilePwrite<'a> {
+    pub fd: usize,
+    pub offset: usize,
+    pub data: &'a [u8],
+}
+
+impl<'a> ParseCommand<'a> for vFilePwrite<'a> {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let body = buf.into_body();
+        let body = match body {
+            [b':', body @ ..] => body,
+            _ => return None,
+        };
+
+        // the data may itself contain ',' characters
+        let mut body = body.splitn_mut(3, |b| *b == b',');
+        let fd = decode_hex(body.next()?).ok()?;
+        let offset = decode_hex(body.next()?).ok()?;
+        let data = decode_bin_buf(body.next()?).ok()?;
+
+        Some(vFilePwrite { fd, offset, data })
+    }
+}
@@ -0,0 +1,24 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub struct vFileReadlink<'a> {
+    pub filename: &'a [u8],
+
+    pub buf: &'a mut [u8],
+}
+
+impl<'a> ParseCommand<'a> for vFileReadlink<'a> {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        // the filename is decoded in-place, with the rest of the packet buffer
+        // used to hold the link's target
+        let (buf, body_range) = buf.into_raw_buf();
+        let (body, buf) = buf[body_range.start..].split_at_mut(body_range.len());
+
+        let filename = match body {
+            [b':', filename @ ..] => decode_hex_buf(filename).ok()?,
+            _ => return None,
+        };
+
+        Some(vFileReadlink { filename, buf })
+    }
+}
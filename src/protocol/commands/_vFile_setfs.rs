@@ -0,0 +1,19 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub struct vFileSetfs {
+    pub pid: Option<Pid>,
+}
+
+impl<'a> ParseCommand<'a> for vFileSetfs {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let body = buf.into_body();
+        match body {
+            // a pid of `0` selects the stub's own filesystem
+            [b':', pid @ ..] => Some(vFileSetfs {
+                pid: Pid::new(decode_hex(pid).ok()?),
+            }),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub struct vFlashWrite<'a> {
+    pub addr: &'a [u8],
+    pub val: &'a [u8],
+}
+
+impl<'a> ParseCommand<'a> for vFlashWrite<'a> {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        let body = match buf.into_body() {
+            [b':', body @ ..] => body,
+            _ => return None,
+        };
+
+        // the data may itself contain ':' characters
+        let mut body = body.splitn_mut(2, |b| *b == b':');
+        let addr = decode_hex_buf(body.next()?).ok()?;
+        let val = decode_bin_buf(body.next()?).ok()?;
+
+        Some(vFlashWrite { addr, val })
+    }
+}
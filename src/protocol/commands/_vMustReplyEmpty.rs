@@ -0,0 +1,13 @@
+use super::prelude::*;
+
+#[derive(Debug)]
+pub struct vMustReplyEmpty;
+
+impl<'a> ParseCommand<'a> for vMustReplyEmpty {
+    fn from_packet(buf: PacketBuf<'a>) -> Option<Self> {
+        if !buf.into_body().is_empty() {
+            return None;
+        }
+        Some(vMustReplyEmpty)
+    }
+}
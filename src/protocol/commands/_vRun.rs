@@ -11,13 +11,16 @@ pub struct Args<'a>(&'a mut [u8]);
 
 impl<'a> Args<'a> {
     pub fn into_iter(self) -> impl Iterator<Item = &'a [u8]> + 'a {
-        self.0
-            .split_mut(|b| *b == b';')
+        // an empty list of args is distinct from a list containing a single empty arg (which GDB
+        // sends as a trailing ';')
+        let args = if self.0.is_empty() { None } else { Some(self.0) };
+
+        args.into_iter()
+            .flat_map(|args| args.split_mut(|b| *b == b';'))
             // the `from_packet` method guarantees that the args are valid hex ascii, so this should
             // method should never fail.
             .map(|raw| decode_hex_buf(raw).unwrap_or(&mut []))
             .map(|s| s as &[u8])
-            .filter(|s| !s.is_empty())
     }
 }
 
@@ -106,6 +109,17 @@ mod tests {
         assert_eq!(args, &[b"test"]);
     }
 
+    #[test]
+    fn valid_vRun_empty_args() {
+        test_buf!(buf, b"vRun;74657374;;666f6f;");
+
+        let pkt = vRun::from_packet(buf).unwrap();
+        let args = pkt.args.into_iter().collect::<Vec<_>>();
+
+        assert_eq!(pkt.filename, Some(&b"test"[..]));
+        assert_eq!(args, &[&b""[..], &b"foo"[..], &b""[..]]);
+    }
+
     #[test]
     fn invalid_vRun_args() {
         test_buf!(buf, b"vRun;74657374;nothex");
@@ -13,7 +13,7 @@ pub use core::convert::{TryFrom, TryInto};
 //                          |
 //                  BytecodeBreakpoint
 //
-// If the target does not implement the `Agent` extension, only the
+// If the target does not implement the `BreakpointConditions` extension, only the
 // `BasicBreakpoint` part is parsed, which helps cut down on binary bloat.
 
 #[derive(Debug)]
@@ -54,14 +54,9 @@ impl<'a> BytecodeBreakpoint<'a> {
         if let Some(rest) = body.next() {
             let mut s = rest.split_mut(|b| *b == b':');
             let (raw_conds, raw_cmds) = match (s.next(), s.next()) {
+                (Some(a), Some(b)) if a == b"cmds" => (None, Some(b)),
                 (Some(a), Some(b)) => (Some(strip_suffix_mut(a, b";cmds")?), Some(b)),
-                (Some(a), None) => {
-                    if a.starts_with(b"cmds") {
-                        (None, Some(a))
-                    } else {
-                        (Some(a), None)
-                    }
-                }
+                (Some(a), None) => (Some(a), None),
                 _ => return None,
             };
 
@@ -70,7 +65,8 @@ impl<'a> BytecodeBreakpoint<'a> {
             }
 
             if let Some(raw_cmds) = raw_cmds {
-                let mut raw_cmds = raw_cmds.split_mut(|b| *b == b',');
+                // the command list itself contains ',' characters
+                let mut raw_cmds = raw_cmds.splitn_mut(2, |b| *b == b',');
                 let raw_persist = decode_hex::<u8>(raw_cmds.next()?).ok()? != 0;
                 let raw_cmds = raw_cmds.next()?;
 
@@ -115,3 +111,40 @@ impl<'a> BytecodeList<'a> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect(list: BytecodeList<'_>) -> Vec<Vec<u8>> {
+        list.into_iter().map(|code| code.unwrap().to_vec()).collect()
+    }
+
+    #[test]
+    fn condition_and_commands() {
+        let mut body = b"0,1000,4;X3,220027X1,01;cmds:1,X2,2627X1,00".to_vec();
+        let bp = BytecodeBreakpoint::from_slice(&mut body).unwrap();
+
+        assert_eq!(bp.base.type_, 0);
+        assert_eq!(bp.base.addr, [0x10, 0x00]);
+        assert_eq!(bp.base.kind, 4);
+
+        let conds = collect(bp.conds.unwrap());
+        assert_eq!(conds, [vec![0x22, 0x00, 0x27], vec![0x01]]);
+
+        let (cmds, persist) = bp.cmds_persist.unwrap();
+        assert_eq!(collect(cmds), [vec![0x26, 0x27], vec![0x00]]);
+        assert!(persist);
+    }
+
+    #[test]
+    fn commands_only() {
+        let mut body = b"1,1000,4;cmds:0,X2,2627".to_vec();
+        let bp = BytecodeBreakpoint::from_slice(&mut body).unwrap();
+
+        assert!(bp.conds.is_none());
+        let (cmds, persist) = bp.cmds_persist.unwrap();
+        assert_eq!(collect(cmds), [vec![0x26, 0x27]]);
+        assert!(!persist);
+    }
+}
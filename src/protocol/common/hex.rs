@@ -101,6 +101,44 @@ pub fn decode_hex_buf(base_buf: &mut [u8]) -> Result<&mut [u8], DecodeHexBufErro
     Ok(&mut base_buf[..decoded_len + odd_adust])
 }
 
+/// Check if the byte `c` must be escaped when sent using the binary protocol
+/// (i.e: `#`, `$`, `}`, and `*`).
+pub fn is_bin_escaped(c: u8) -> bool {
+    matches!(c, b'#' | b'$' | b'}' | b'*')
+}
+
+#[derive(Debug)]
+pub enum DecodeBinBufError {
+    UnexpectedEnd,
+}
+
+/// Decode GDB escaped binary data into a byte slice _in place_.
+///
+/// Bytes which would otherwise be misinterpreted by the protocol (i.e: `#`,
+/// `$`, `}`, and `*`) are sent as a `}` followed by the original byte XOR'd
+/// with `0x20`.
+pub fn decode_bin_buf(buf: &mut [u8]) -> Result<&mut [u8], DecodeBinBufError> {
+    use DecodeBinBufError::*;
+
+    let mut i = 0;
+    let mut len = 0;
+    while i < buf.len() {
+        if buf[i] == b'}' {
+            if i + 1 >= buf.len() {
+                return Err(UnexpectedEnd);
+            }
+            buf[len] = buf[i + 1] ^ 0x20;
+            i += 2;
+        } else {
+            buf[len] = buf[i];
+            i += 1;
+        }
+        len += 1;
+    }
+
+    Ok(&mut buf[..len])
+}
+
 #[allow(dead_code)]
 #[derive(Debug)]
 pub enum EncodeHexBufError {
@@ -181,6 +219,19 @@ mod tests {
         assert_eq!(out, expect.as_bytes())
     }
 
+    #[test]
+    fn decode_bin_buf_escapes() {
+        let mut payload = b"a}\x03b}\x04}]}\x0ac".to_vec();
+        let res = decode_bin_buf(&mut payload).unwrap();
+        assert_eq!(res, b"a#b$}*c");
+    }
+
+    #[test]
+    fn decode_bin_buf_truncated_escape() {
+        let mut payload = b"abc}".to_vec();
+        assert!(decode_bin_buf(&mut payload).is_err());
+    }
+
     #[test]
     fn decode_hex_buf_odd() {
         let mut payload = b"ffffff4".to_vec();
@@ -1,4 +1,5 @@
 pub mod hex;
+pub mod signal_list;
 pub mod thread_id;
 
 /// Lightweight wrapper around `&[u8]` which denotes that the contained data is
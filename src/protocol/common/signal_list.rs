@@ -0,0 +1,50 @@
+use super::hex::decode_hex;
+
+/// A `;`-separated list of hex-encoded signal numbers (as sent by the
+/// `QPassSignals` and `QProgramSignals` packets).
+#[derive(Debug, Clone, Copy)]
+pub struct SignalList<'a>(&'a [u8]);
+
+impl<'a> SignalList<'a> {
+    /// Validate the list, returning `None` if any of the signals are
+    /// malformed. An empty list is valid.
+    pub fn parse(list: &'a [u8]) -> Option<SignalList<'a>> {
+        for sig in list.split(|b| *b == b';').filter(|sig| !sig.is_empty()) {
+            decode_hex::<u8>(sig).ok()?;
+        }
+        Some(SignalList(list))
+    }
+
+    /// Iterate over the signal numbers in the list.
+    pub fn iter(&self) -> impl Iterator<Item = u8> + 'a {
+        self.0
+            .split(|b| *b == b';')
+            .filter(|sig| !sig.is_empty())
+            // signals were validated as part of `parse`
+            .map(|sig| decode_hex(sig).unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signals() {
+        let list = SignalList::parse(b"0e;f;1b;").unwrap();
+        assert_eq!(list.iter().collect::<Vec<_>>(), [0x0e, 0x0f, 0x1b]);
+    }
+
+    #[test]
+    fn empty() {
+        let list = SignalList::parse(b"").unwrap();
+        assert_eq!(list.iter().count(), 0);
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(SignalList::parse(b"0e;zz").is_none());
+        // signal numbers must fit in a byte
+        assert!(SignalList::parse(b"100").is_none());
+    }
+}
@@ -36,6 +36,9 @@ impl TryFrom<&[u8]> for ThreadId {
                     Some(s) => s.try_into()?,
                     None => IdKind::All, // sending only p<pid> is valid
                 };
+                if s.next().is_some() {
+                    return Err(());
+                }
 
                 Ok(ThreadId {
                     pid: Some(pid),
@@ -58,8 +61,11 @@ impl TryFrom<&[u8]> for IdKind {
     fn try_from(s: &[u8]) -> Result<Self, ()> {
         Ok(match s {
             b"-1" => IdKind::All,
-            b"0" => IdKind::Any,
-            id => IdKind::WithId(NonZeroUsize::new(decode_hex(id).map_err(drop)?).ok_or(())?),
+            // ids may be zero-padded (e.g: `00` is still "any" thread)
+            id => match NonZeroUsize::new(decode_hex(id).map_err(drop)?) {
+                Some(id) => IdKind::WithId(id),
+                None => IdKind::Any,
+            },
         })
     }
 }
@@ -92,7 +98,7 @@ pub enum SpecificIdKind {
 
 /// Like [`ThreadId`], without the `Any` variants. Typically used when working
 /// with vCont (i.e: where the `Any` variant wouldn't be valid).
-#[derive(Debug, Copy, Clone)]
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
 pub struct SpecificThreadId {
     /// Process ID (may or may not be present).
     pub pid: Option<SpecificIdKind>,
@@ -125,3 +131,82 @@ impl TryFrom<ThreadId> for SpecificThreadId {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(n: usize) -> IdKind {
+        IdKind::WithId(NonZeroUsize::new(n).unwrap())
+    }
+
+    fn parse(s: &str) -> Result<ThreadId, ()> {
+        ThreadId::try_from(s.as_bytes())
+    }
+
+    #[test]
+    fn special_ids() {
+        let cases = [
+            ("-1", None, IdKind::All),
+            ("0", None, IdKind::Any),
+            ("00", None, IdKind::Any),
+            ("p-1.-1", Some(IdKind::All), IdKind::All),
+            ("p1.0", Some(id(1)), IdKind::Any),
+            ("p0.-1", Some(IdKind::Any), IdKind::All),
+            ("p2a", Some(id(0x2a)), IdKind::All),
+            ("p01.02", Some(id(1)), id(2)),
+        ];
+        for (s, pid, tid) in cases.iter().copied() {
+            assert_eq!(parse(s), Ok(ThreadId { pid, tid }), "{}", s);
+        }
+    }
+
+    #[test]
+    fn large_ids() {
+        assert_eq!(
+            parse("pffff.7fffffff"),
+            Ok(ThreadId {
+                pid: Some(id(0xffff)),
+                tid: id(0x7fffffff),
+            })
+        );
+
+        let max = format!("{:x}", usize::MAX);
+        assert_eq!(
+            parse(&max),
+            Ok(ThreadId {
+                pid: None,
+                tid: id(usize::MAX)
+            })
+        );
+        // one hex digit too many
+        assert_eq!(parse(&format!("{}0", max)), Err(()));
+    }
+
+    #[test]
+    fn malformed_ids() {
+        for s in [
+            "", "p", "p.1", "p1.", "p1.2.3", "-2", "-", "1-", "g", "p1;2",
+        ]
+        .iter()
+        {
+            assert_eq!(parse(s), Err(()), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn specific_ids() {
+        let specific = |s: &str| SpecificThreadId::try_from(parse(s).unwrap());
+
+        assert!(specific("0").is_err());
+        assert!(specific("p0.1").is_err());
+        assert!(specific("p1.0").is_err());
+        assert_eq!(
+            specific("p-1.-1"),
+            Ok(SpecificThreadId {
+                pid: Some(SpecificIdKind::All),
+                tid: SpecificIdKind::All,
+            })
+        );
+    }
+}
@@ -74,7 +74,7 @@ impl Drop for ConsoleOutput<'_> {
 #[macro_export]
 macro_rules! output {
     ($console_output:expr, $($args:tt)*) => {{
-        use std::fmt::Write;
+        use core::fmt::Write;
         let _ = write!($console_output, $($args)*);
     }};
 }
@@ -89,7 +89,7 @@ macro_rules! outputln {
         let _ = writeln!($console_output);
     }};
     ($console_output:expr,) => {
-        outputln!($console_output)
+        $crate::outputln!($console_output)
     };
     ($console_output:expr, $($args:tt)*) => {{
         use core::fmt::Write;
@@ -7,7 +7,7 @@ pub(crate) mod commands;
 
 pub(crate) use common::thread_id::{IdKind, SpecificIdKind, SpecificThreadId};
 pub(crate) use packet::Packet;
-pub(crate) use response_writer::{Error as ResponseWriterError, ResponseWriter};
+pub(crate) use response_writer::{Error as ResponseWriterError, OutBuf, ResponseWriter};
 
 // These types end up a part of the public interface.
 pub use console_output::ConsoleOutput;
@@ -10,6 +10,7 @@ pub enum PacketParseError {
     MissingChecksum,
     MalformedChecksum,
     MalformedCommand,
+    MalformedRle,
     NotAscii,
     UnexpectedHeader(u8),
 }
@@ -29,7 +30,7 @@ pub struct PacketBuf<'a> {
 
 impl<'a> PacketBuf<'a> {
     /// Validate the contents of the raw packet buffer, checking for checksum
-    /// consistency, structural correctness, and ASCII validation.
+    /// consistency and structural correctness.
     pub fn new(pkt_buf: &'a mut [u8]) -> Result<PacketBuf<'a>, PacketParseError> {
         if pkt_buf.is_empty() {
             return Err(PacketParseError::EmptyBuf);
@@ -45,10 +46,8 @@ impl<'a> PacketBuf<'a> {
             .get(..2)
             .ok_or(PacketParseError::MalformedChecksum)?;
 
-        // validate that the body is valid ASCII
-        if !body.is_ascii() {
-            return Err(PacketParseError::NotAscii);
-        }
+        // NOTE: the body is _not_ validated as being ASCII, as certain packets
+        // (e.g: `qSearch:memory`) carry escaped binary data.
 
         // validate the checksum
         let checksum = decode_hex(checksum).map_err(|_| PacketParseError::MalformedChecksum)?;
@@ -62,9 +61,12 @@ impl<'a> PacketBuf<'a> {
 
         let end_of_body = 1 + body.len();
 
+        // expand any run-length encoded sequences in the (now validated) body
+        let body_range = decode_rle(pkt_buf, 1..end_of_body)?;
+
         Ok(PacketBuf {
             buf: pkt_buf,
-            body_range: 1..end_of_body,
+            body_range,
         })
     }
 
@@ -84,13 +86,26 @@ impl<'a> PacketBuf<'a> {
         })
     }
 
+    /// Strip `prefix` from the start of the body, returning `false` if the
+    /// body doesn't start with `prefix`.
+    ///
+    /// `v` packets are matched by their full name (i.e: the prefix must be
+    /// followed by a `;`, `:`, `?`, or the end of the packet), as GDB expects
+    /// an empty reply to any `v` packet the stub doesn't recognize.
     pub fn strip_prefix(&mut self, prefix: &[u8]) -> bool {
-        if self.buf[self.body_range.clone()].starts_with(prefix) {
-            self.body_range = (self.body_range.start + prefix.len())..self.body_range.end;
-            true
-        } else {
-            false
+        let body = &self.buf[self.body_range.clone()];
+        if !body.starts_with(prefix) {
+            return false;
+        }
+
+        if prefix.first() == Some(&b'v')
+            && !matches!(body.get(prefix.len()), None | Some(b';' | b':' | b'?'))
+        {
+            return false;
         }
+
+        self.body_range = (self.body_range.start + prefix.len())..self.body_range.end;
+        true
     }
 
     /// Return a mut reference to slice of the packet buffer corresponding to
@@ -114,6 +129,78 @@ impl<'a> PacketBuf<'a> {
     }
 }
 
+/// Expand any run-length encoded sequences within `buf[body]` _in place_,
+/// returning the range of the expanded body.
+///
+/// A `*` indicates that the preceding byte should be repeated `n - 29` times,
+/// where `n` is the byte immediately following the `*` (e.g: `0* ` expands to
+/// `0000`).
+///
+/// As expansion may grow the body past its original end, the remainder of
+/// `buf` (i.e: the checksum, and any unused buffer space) is clobbered in the
+/// process. As such, this method should only be called _after_ the packet's
+/// checksum has been validated.
+fn decode_rle(
+    buf: &mut [u8],
+    body: core::ops::Range<usize>,
+) -> Result<core::ops::Range<usize>, PacketParseError> {
+    // fast path: most incoming packets aren't compressed
+    if !buf[body.clone()].contains(&b'*') {
+        return Ok(body);
+    }
+
+    // validate the encoding, and calculate the length of the expanded body
+    let mut expanded_len = 0;
+    let mut i = body.start;
+    while i < body.end {
+        if buf[i] == b'*' {
+            if i == body.start {
+                return Err(PacketParseError::MalformedRle);
+            }
+            let count = *buf[..body.end]
+                .get(i + 1)
+                .ok_or(PacketParseError::MalformedRle)?;
+            expanded_len += count
+                .checked_sub(29)
+                .ok_or(PacketParseError::MalformedRle)? as usize;
+            i += 2;
+        } else {
+            expanded_len += 1;
+            i += 1;
+        }
+    }
+
+    if body.start + expanded_len > buf.len() {
+        return Err(PacketParseError::MalformedRle);
+    }
+
+    // Shift the compressed body to the very end of the buffer, and expand it
+    // back towards the front. The write cursor can never overtake the read
+    // cursor, as the expanded body is guaranteed to fit within the buffer.
+    let compressed_start = buf.len() - body.len();
+    buf.copy_within(body.clone(), compressed_start);
+
+    let mut r = compressed_start;
+    let mut w = body.start;
+    while r < buf.len() {
+        if buf[r] == b'*' {
+            let c = buf[w - 1];
+            let count = (buf[r + 1] - 29) as usize;
+            for b in &mut buf[w..w + count] {
+                *b = c;
+            }
+            w += count;
+            r += 2;
+        } else {
+            buf[w] = buf[r];
+            w += 1;
+            r += 1;
+        }
+    }
+
+    Ok(body.start..body.start + expanded_len)
+}
+
 impl<'a> Packet<'a> {
     pub fn from_buf(
         target: &mut impl Target,
@@ -136,3 +223,100 @@ impl<'a> Packet<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(raw: &[u8], buf_len: usize) -> Result<Vec<u8>, PacketParseError> {
+        let mut buf = vec![0; buf_len];
+        buf[..raw.len()].copy_from_slice(raw);
+        let body = decode_rle(&mut buf, 0..raw.len())?;
+        Ok(buf[body].to_vec())
+    }
+
+    #[test]
+    fn rle_passthrough() {
+        assert_eq!(expand(b"deadbeef", 8).unwrap(), b"deadbeef");
+    }
+
+    #[test]
+    fn rle_middle() {
+        assert_eq!(expand(b"a0* b", 16).unwrap(), b"a0000b");
+    }
+
+    #[test]
+    fn rle_start_of_buffer() {
+        assert_eq!(expand(b"0* 12", 16).unwrap(), b"000012");
+    }
+
+    #[test]
+    fn rle_end_of_buffer() {
+        assert_eq!(expand(b"12f*\"", 16).unwrap(), b"12ffffff");
+    }
+
+    #[test]
+    fn rle_multiple_runs() {
+        assert_eq!(expand(b"0* 1* 2", 16).unwrap(), b"000011112");
+    }
+
+    #[test]
+    fn rle_exactly_fills_buffer() {
+        assert_eq!(expand(b"x0*!", 6).unwrap(), b"x00000");
+    }
+
+    #[test]
+    fn rle_overflows_buffer() {
+        assert!(expand(b"x0*!", 5).is_err());
+    }
+
+    #[test]
+    fn rle_missing_run_char() {
+        assert!(expand(b"* 00", 16).is_err());
+    }
+
+    #[test]
+    fn rle_missing_count() {
+        assert!(expand(b"00*", 16).is_err());
+    }
+
+    #[test]
+    fn rle_full_packet() {
+        // "$M0,4:0* #" w/ checksum
+        let body = b"M0,4:0* ";
+        let checksum = body.iter().fold(0u8, |a, x| a.wrapping_add(*x));
+        let mut buf =
+            format!("${}#{:02x}", core::str::from_utf8(body).unwrap(), checksum).into_bytes();
+        buf.resize(32, 0);
+
+        let pkt = PacketBuf::new(&mut buf).unwrap();
+        assert_eq!(pkt.into_body(), b"M0,4:0000");
+    }
+
+    #[test]
+    fn checksum_mismatch() {
+        let mut buf = b"$?#00".to_vec();
+        assert!(matches!(
+            PacketBuf::new(&mut buf),
+            Err(PacketParseError::ChecksumMismatched {
+                checksum: 0x00,
+                calculated: 0x3f
+            })
+        ));
+    }
+
+    #[test]
+    fn missing_checksum() {
+        let mut buf = b"$?".to_vec();
+        assert!(matches!(
+            PacketBuf::new(&mut buf),
+            Err(PacketParseError::MissingChecksum)
+        ));
+
+        let mut buf = b"$?#3".to_vec();
+        assert!(matches!(
+            PacketBuf::new(&mut buf),
+            Err(PacketParseError::MalformedChecksum)
+        ));
+    }
+}
@@ -1,8 +1,199 @@
 use num_traits::PrimInt;
 
 use crate::internal::BeBytes;
+use crate::protocol::common::hex::is_bin_escaped;
 use crate::protocol::{SpecificIdKind, SpecificThreadId};
-use crate::Connection;
+use crate::{Connection, PacketDirection, PacketTracer};
+
+/// Convert a nibble into its (lowercase) ascii hex digit.
+fn hex_digit(nibble: u8) -> u8 {
+    match nibble {
+        0..=9 => b'0' + nibble,
+        10..=15 => b'a' + nibble - 10,
+        _ => unreachable!(),
+    }
+}
+
+/// The char used to encode a run of `n` identical bytes.
+///
+/// i.e: a run of 4 bytes is encoded as `X* `, as `' ' - 29 == 3` additional
+/// repetitions of `X`.
+fn rle_count_char(n: u8) -> u8 {
+    n + 28
+}
+
+/// Report a malformed outgoing packet.
+///
+/// Violations panic in `gdbstub`'s own unit tests, and are otherwise logged, as
+/// a packet containing (e.g) a `$` written by the target shouldn't bring down
+/// the entire debugging session.
+#[cfg(debug_assertions)]
+macro_rules! invalid_packet {
+    ($($args:tt)*) => {
+        if cfg!(test) {
+            panic!("invalid packet: {}", format_args!($($args)*));
+        } else {
+            error!("invalid packet: {}", format_args!($($args)*));
+        }
+    };
+}
+
+/// Debug-build sanity checks on outgoing packets.
+///
+/// Catches handlers (or encoder bugs) which produce packets that would be
+/// misinterpreted by the GDB client, e.g: by emitting an unescaped `#` in the
+/// middle of a packet body. See `invalid_packet!` for how violations are
+/// reported.
+#[cfg(debug_assertions)]
+#[derive(Default)]
+struct FramingCheck {
+    /// The previous byte on the wire was a `}` escape.
+    escape_next: bool,
+    /// The previous byte on the wire was a `*` run-length marker.
+    count_next: bool,
+    /// The previous byte on the wire was a literal byte, which may be repeated.
+    can_repeat: bool,
+    /// The packet body has been completed (i.e: the trailer is being written).
+    finished: bool,
+}
+
+#[cfg(debug_assertions)]
+impl FramingCheck {
+    /// Check a byte of the packet body, prior to escaping / RLE.
+    fn check_raw(byte: u8) {
+        if is_bin_escaped(byte) {
+            invalid_packet!(
+                "unescaped {:?} in packet body (use `write_binary`)",
+                byte as char
+            );
+        }
+    }
+
+    /// Check a byte of the packet body, as it is sent over the wire.
+    fn check_encoded(&mut self, byte: u8) {
+        if self.finished {
+            return;
+        }
+
+        if self.escape_next {
+            // GDB repeats the _decoded_ byte, so escaped bytes can't be repeated
+            self.escape_next = false;
+            self.can_repeat = false;
+            return;
+        }
+
+        if self.count_next {
+            if !(b' '..=b'~').contains(&byte) || matches!(byte, b'#' | b'$' | b'}') {
+                invalid_packet!("bad run-length count {:?}", byte as char);
+            }
+            self.count_next = false;
+            self.can_repeat = false;
+            return;
+        }
+
+        match byte {
+            b'#' | b'$' => invalid_packet!("unescaped {:?}", byte as char),
+            b'}' => self.escape_next = true,
+            b'*' => {
+                if !self.can_repeat {
+                    invalid_packet!("'*' doesn't follow a repeatable byte");
+                }
+                self.count_next = true;
+            }
+            _ => self.can_repeat = true,
+        }
+    }
+
+    /// Check that the packet body doesn't end mid-sequence.
+    fn finish(&mut self) {
+        if self.escape_next || self.count_next {
+            invalid_packet!("body ends with an incomplete escape / run-length sequence");
+        }
+        self.finished = true;
+    }
+}
+
+/// The number of outgoing bytes buffered by a [`ResponseWriter`] before they
+/// are written to the underlying connection (when `alloc` isn't available).
+#[cfg(not(feature = "alloc"))]
+const OUT_BUF_LEN: usize = 256;
+
+/// Storage for outgoing packets. A single `OutBuf` is kept by the stub, and is
+/// reused by each [`ResponseWriter`].
+///
+/// When the `alloc` feature is enabled, the buffer grows to fit the entire
+/// framed packet (i.e: `$<body>#<checksum>`), which is always sent using a
+/// single call to [`Connection::write_all`]. Otherwise, the buffer has a fixed
+/// size of `OUT_BUF_LEN` bytes, and larger packets are sent in several chunks
+/// (though most responses fit in a single chunk).
+pub struct OutBuf {
+    #[cfg(feature = "alloc")]
+    buf: alloc::vec::Vec<u8>,
+    #[cfg(not(feature = "alloc"))]
+    buf: [u8; OUT_BUF_LEN],
+    #[cfg(not(feature = "alloc"))]
+    len: usize,
+}
+
+impl Default for OutBuf {
+    fn default() -> OutBuf {
+        OutBuf::new()
+    }
+}
+
+impl OutBuf {
+    /// Creates a new (empty) `OutBuf`.
+    ///
+    /// When `alloc` is enabled, nothing is allocated until the first packet is
+    /// written, after which the buffer's capacity is retained between packets.
+    pub fn new() -> OutBuf {
+        OutBuf {
+            #[cfg(feature = "alloc")]
+            buf: alloc::vec::Vec::new(),
+            #[cfg(not(feature = "alloc"))]
+            buf: [0; OUT_BUF_LEN],
+            #[cfg(not(feature = "alloc"))]
+            len: 0,
+        }
+    }
+
+    /// Append a byte to the buffer. Returns `false` if the buffer is full
+    /// (which only happens when `alloc` isn't available).
+    fn push(&mut self, byte: u8) -> bool {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "alloc")] {
+                self.buf.push(byte);
+            } else {
+                if self.len == OUT_BUF_LEN {
+                    return false;
+                }
+                self.buf[self.len] = byte;
+                self.len += 1;
+            }
+        }
+        true
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "alloc")] {
+                self.buf.as_slice()
+            } else {
+                &self.buf[..self.len]
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "alloc")] {
+                self.buf.clear();
+            } else {
+                self.len = 0;
+            }
+        }
+    }
+}
 
 /// Newtype around a Connection error. Having a newtype allows implementing a
 /// `From<ResponseWriterError<C>> for crate::Error<T, C>`, which greatly
@@ -13,63 +204,116 @@ pub struct Error<C>(pub C);
 /// A wrapper around [`Connection`] that computes the single-byte checksum of
 /// incoming / outgoing data.
 pub struct ResponseWriter<'a, C: Connection + 'a> {
-    // TODO: add `write_all` method to Connection, and allow user to optionally pass outgoing
-    // packet buffer? This could improve performance (instead of writing a single byte at a time)
     inner: &'a mut C,
+    out: &'a mut OutBuf,
+    start_char: u8,
     started: bool,
     checksum: u8,
-    // TODO?: Make using RLE configurable by the target?
-    // if implemented correctly, targets that disable RLE entirely could have all RLE code
-    // dead-code-eliminated.
+    rle_enabled: bool,
     rle_char: u8,
     rle_repeat: u8,
+    tracer: Option<PacketTracer>,
     // buffer to log outgoing packets. only allocates if logging is enabled.
     #[cfg(feature = "std")]
     msg: Vec<u8>,
+    #[cfg(debug_assertions)]
+    framing: FramingCheck,
 }
 
 impl<'a, C: Connection + 'a> ResponseWriter<'a, C> {
-    /// Creates a new ResponseWriter
-    pub fn new(inner: &'a mut C) -> Self {
+    /// Creates a new ResponseWriter, which buffers the outgoing packet in
+    /// `out`.
+    ///
+    /// If `rle_enabled` is set, runs of 4 or more identical bytes are
+    /// compressed using GDB's run-length encoding scheme.
+    pub fn new(inner: &'a mut C, out: &'a mut OutBuf, rle_enabled: bool) -> Self {
+        Self::new_with_start_char(inner, out, rle_enabled, b'$')
+    }
+
+    /// Creates a new ResponseWriter for an asynchronous notification packet
+    /// (i.e: `%<body>#<checksum>`), which GDB does not acknowledge.
+    pub fn new_notification(inner: &'a mut C, out: &'a mut OutBuf, rle_enabled: bool) -> Self {
+        Self::new_with_start_char(inner, out, rle_enabled, b'%')
+    }
+
+    fn new_with_start_char(
+        inner: &'a mut C,
+        out: &'a mut OutBuf,
+        rle_enabled: bool,
+        start_char: u8,
+    ) -> Self {
+        // discard anything left over from a packet which was never sent
+        out.clear();
         Self {
             inner,
+            out,
+            start_char,
             started: false,
             checksum: 0,
+            rle_enabled,
             rle_char: 0,
             rle_repeat: 0,
+            tracer: None,
             #[cfg(feature = "std")]
             msg: Vec::new(),
+            #[cfg(debug_assertions)]
+            framing: FramingCheck::default(),
         }
     }
 
+    /// Report the outgoing packet to the given [`PacketTracer`] as it's
+    /// written to the connection.
+    pub fn with_tracer(mut self, tracer: Option<PacketTracer>) -> Self {
+        self.tracer = tracer;
+        self
+    }
+
     /// Consumes self, writing out the final '#' and checksum
     pub fn flush(mut self) -> Result<(), Error<C::Error>> {
-        self.write(b'#')?;
+        self.flush_rle()?;
+
+        #[cfg(debug_assertions)]
+        self.framing.finish();
 
         // don't include the '#' in checksum calculation
-        // (note: even though `self.write` was called, the the '#' char hasn't been
-        // added to the checksum, and is just sitting in the RLE buffer)
         let checksum = self.checksum;
 
         #[cfg(feature = "std")]
         trace!(
-            "--> ${}#{:02x?}",
-            core::str::from_utf8(&self.msg).unwrap(), // buffers are always ascii
+            "--> {}{}#{:02x?}",
+            self.start_char as char,
+            // binary responses may not be valid utf-8
+            core::str::from_utf8(&self.msg).unwrap_or("<binary data>"),
             checksum
         );
 
-        self.write_hex(checksum)?;
-        // HACK: "write" a dummy char to force an RLE flush
-        self.write(0)?;
+        self.inner_write(b'#')?;
+        for digit in [(checksum & 0xf0) >> 4, checksum & 0x0f].iter() {
+            self.inner_write(hex_digit(*digit))?;
+        }
 
+        self.flush_out()?;
         self.inner.flush().map_err(Error)?;
 
         Ok(())
     }
 
-    /// Get a mutable reference to the underlying connection.
-    pub fn as_conn(&mut self) -> &mut C {
-        self.inner
+    /// Start a new packet on the same connection, reusing the same buffer.
+    ///
+    /// Any part of the current packet which hasn't been sent yet is discarded,
+    /// and the current packet is restarted from scratch.
+    pub fn reborrow(&mut self) -> ResponseWriter<'_, C> {
+        self.started = false;
+        self.checksum = 0;
+        self.rle_repeat = 0;
+        #[cfg(feature = "std")]
+        self.msg.clear();
+        #[cfg(debug_assertions)]
+        {
+            self.framing = FramingCheck::default();
+        }
+
+        ResponseWriter::new(self.inner, self.out, self.rle_enabled).with_tracer(self.tracer)
     }
 
     fn inner_write(&mut self, byte: u8) -> Result<(), Error<C::Error>> {
@@ -87,53 +331,87 @@ impl<'a, C: Connection + 'a> ResponseWriter<'a, C> {
             }
         }
 
+        #[cfg(debug_assertions)]
+        self.framing.check_encoded(byte);
+
         if !self.started {
             self.started = true;
-            self.inner.write(b'$').map_err(Error)?;
+            self.push_out(self.start_char)?;
         }
 
         self.checksum = self.checksum.wrapping_add(byte);
-        self.inner.write(byte).map_err(Error)
+        self.push_out(byte)
     }
 
-    fn write(&mut self, byte: u8) -> Result<(), Error<C::Error>> {
-        const ASCII_FIRST_PRINT: u8 = b' ';
-        const ASCII_LAST_PRINT: u8 = b'~';
+    /// Append a byte to the outgoing buffer, writing out the buffer if it's
+    /// full (which only happens when `alloc` isn't available).
+    fn push_out(&mut self, byte: u8) -> Result<(), Error<C::Error>> {
+        if !self.out.push(byte) {
+            self.flush_out()?;
+            self.out.push(byte);
+        }
+        Ok(())
+    }
 
-        // handle RLE
-        let rle_printable = (ASCII_FIRST_PRINT - 4 + (self.rle_repeat + 1)) <= ASCII_LAST_PRINT;
-        if byte == self.rle_char && rle_printable {
-            self.rle_repeat += 1;
-            Ok(())
-        } else {
-            loop {
-                match self.rle_repeat {
-                    0 => {} // happens once, after the first char is written
-                    // RLE doesn't win, just output the byte
-                    1 | 2 | 3 => {
-                        for _ in 0..self.rle_repeat {
-                            self.inner_write(self.rle_char)?
-                        }
-                    }
-                    // RLE would output an invalid char ('#' or '$')
-                    6 | 7 => {
-                        self.inner_write(self.rle_char)?;
-                        self.rle_repeat -= 1;
-                        continue;
-                    }
-                    // RLE wins for repetitions >4
-                    _ => {
-                        self.inner_write(self.rle_char)?;
-                        self.inner_write(b'*')?;
-                        self.inner_write(ASCII_FIRST_PRINT - 4 + self.rle_repeat)?;
+    /// Write out the contents of the outgoing buffer.
+    fn flush_out(&mut self) -> Result<(), Error<C::Error>> {
+        let out = self.out.as_slice();
+        if let Some(tracer) = self.tracer {
+            tracer(PacketDirection::Outgoing, out);
+        }
+        self.inner.write_all(out).map_err(Error)?;
+        self.out.clear();
+        Ok(())
+    }
+
+    /// Write out any pending run of repeated bytes.
+    fn flush_rle(&mut self) -> Result<(), Error<C::Error>> {
+        loop {
+            match self.rle_repeat {
+                0 => {} // happens once, after the first char is written
+                // RLE doesn't win, just output the byte
+                1 | 2 | 3 => {
+                    for _ in 0..self.rle_repeat {
+                        self.inner_write(self.rle_char)?
                     }
                 }
+                // RLE would output an invalid repeat count char ('#', '$', or '}')
+                n if matches!(rle_count_char(n), b'#' | b'$' | b'}') => {
+                    self.inner_write(self.rle_char)?;
+                    self.rle_repeat -= 1;
+                    continue;
+                }
+                // RLE wins for repetitions >=4
+                n => {
+                    self.inner_write(self.rle_char)?;
+                    self.inner_write(b'*')?;
+                    self.inner_write(rle_count_char(n))?;
+                }
+            }
 
-                self.rle_char = byte;
-                self.rle_repeat = 1;
+            self.rle_repeat = 0;
+            break Ok(());
+        }
+    }
 
-                break Ok(());
-            }
+    fn write(&mut self, byte: u8) -> Result<(), Error<C::Error>> {
+        #[cfg(debug_assertions)]
+        FramingCheck::check_raw(byte);
+
+        if !self.rle_enabled {
+            return self.inner_write(byte);
+        }
+
+        // the repeat count is sent as a single printable ascii char
+        let rle_printable = rle_count_char(self.rle_repeat + 1) <= b'~';
+        if self.rle_repeat != 0 && byte == self.rle_char && rle_printable {
+            self.rle_repeat += 1;
+            Ok(())
+        } else {
+            self.flush_rle()?;
+            self.rle_char = byte;
+            self.rle_repeat = 1;
+            Ok(())
         }
     }
 
@@ -148,12 +426,7 @@ impl<'a, C: Connection + 'a> ResponseWriter<'a, C> {
     /// Write a single byte as a hex string (two ascii chars)
     fn write_hex(&mut self, byte: u8) -> Result<(), Error<C::Error>> {
         for digit in [(byte & 0xf0) >> 4, byte & 0x0f].iter() {
-            let c = match digit {
-                0..=9 => b'0' + digit,
-                10..=15 => b'a' + digit - 10,
-                _ => unreachable!(),
-            };
-            self.write(c)?;
+            self.write(hex_digit(*digit))?;
         }
         Ok(())
     }
@@ -167,19 +440,76 @@ impl<'a, C: Connection + 'a> ResponseWriter<'a, C> {
     }
 
     /// Write data using the binary protocol.
+    ///
+    /// Binary data bypasses run-length encoding entirely, so that it can be
+    /// decoded by simply un-escaping it.
     pub fn write_binary(&mut self, data: &[u8]) -> Result<(), Error<C::Error>> {
+        self.flush_rle()?;
         for &b in data.iter() {
-            match b {
-                b'#' | b'$' | b'}' | b'*' => {
-                    self.write(b'}')?;
-                    self.write(b ^ 0x20)?
-                }
-                _ => self.write(b)?,
+            if is_bin_escaped(b) {
+                // i.e: `}` followed by `byte ^ 0x20`
+                self.inner_write(b'}')?;
+                self.inner_write(b ^ 0x20)?;
+            } else {
+                self.inner_write(b)?;
             }
         }
         Ok(())
     }
 
+    /// Write a single chunk of a `qXfer` transfer (i.e: up to `len` bytes of
+    /// `data`, starting at `offset`) using the binary protocol.
+    ///
+    /// The chunk is prefixed with `m` if there is more data left to transfer,
+    /// or `l` if it is the last chunk.
+    pub fn write_qxfer_chunk(
+        &mut self,
+        data: &[u8],
+        offset: usize,
+        len: usize,
+    ) -> Result<(), Error<C::Error>> {
+        self.write_qxfer_chunk_parts(core::iter::once(data), offset, len)
+    }
+
+    /// Same as [`write_qxfer_chunk`](Self::write_qxfer_chunk), except that the
+    /// transferred data is the concatenation of `parts`.
+    pub fn write_qxfer_chunk_parts<'b>(
+        &mut self,
+        parts: impl Iterator<Item = &'b [u8]> + Clone,
+        offset: usize,
+        len: usize,
+    ) -> Result<(), Error<C::Error>> {
+        let total_len: usize = parts.clone().map(|part| part.len()).sum();
+
+        if offset >= total_len {
+            // no more data
+            return self.write_str("l");
+        }
+
+        let end = if len >= total_len - offset {
+            // last little bit of data
+            self.write_str("l")?;
+            total_len
+        } else {
+            // still more data
+            self.write_str("m")?;
+            offset + len
+        };
+
+        let mut part_start = 0;
+        for part in parts {
+            let part_end = part_start + part.len();
+            if part_end > offset && part_start < end {
+                let lo = offset.saturating_sub(part_start);
+                let hi = part.len() - part_end.saturating_sub(end);
+                self.write_binary(&part[lo..hi])?;
+            }
+            part_start = part_end;
+        }
+
+        Ok(())
+    }
+
     /// Write a number as a big-endian hex string using the most compact
     /// representation possible (i.e: trimming leading zeros).
     pub fn write_num<D: BeBytes + PrimInt>(&mut self, digit: D) -> Result<(), Error<C::Error>> {
@@ -197,6 +527,59 @@ impl<'a, C: Connection + 'a> ResponseWriter<'a, C> {
         Ok(())
     }
 
+    /// Write a number as a decimal string (e.g: `0x1f` is written as `31`).
+    pub fn write_dec(&mut self, mut digit: usize) -> Result<(), Error<C::Error>> {
+        // usize::MAX has at most 20 decimal digits
+        let mut buf = [0; 20];
+        let mut start = buf.len();
+        loop {
+            start -= 1;
+            buf[start] = b'0' + (digit % 10) as u8;
+            digit /= 10;
+            if digit == 0 {
+                break;
+            }
+        }
+        for &b in buf[start..].iter() {
+            self.write(b)?;
+        }
+        Ok(())
+    }
+
+    /// Write an error code as exactly two hex digits (i.e: the `NN` in an
+    /// `E NN` reply).
+    pub fn write_error_code(&mut self, code: u8) -> Result<(), Error<C::Error>> {
+        self.write_hex(code)
+    }
+
+    /// Write an address as a big-endian hex string, zero-padded to the full
+    /// width of the address type (e.g: `0x1000u32` is written as `00001000`).
+    pub fn write_addr<D: BeBytes + PrimInt>(&mut self, addr: D) -> Result<(), Error<C::Error>> {
+        let mut buf = [0; 16];
+        // infallible (unless addr is a >128 bit number)
+        let len = addr.to_be_bytes(&mut buf).unwrap();
+        self.write_hex_buf(&buf[..len])
+    }
+
+    /// Write a number as a hex-encoded string of its big-endian hex digits
+    /// (e.g: `0x1f` is written as `3166`, the hex encoding of `"1f"`).
+    ///
+    /// Leading zeros are trimmed in the same manner as [`write_num`](Self::write_num).
+    pub fn write_hex_encoded_num<D: BeBytes + PrimInt>(
+        &mut self,
+        digit: D,
+    ) -> Result<(), Error<C::Error>> {
+        let mut buf = [0; 16];
+        // infallible (unless digit is a >128 bit number)
+        let len = digit.to_be_bytes(&mut buf).unwrap();
+        let buf = &buf[..len];
+        let start = buf.iter().position(|&b| b != 0).unwrap_or(len - 1);
+        for b in buf[start..].iter() {
+            self.write_hex_buf(&[hex_digit(b >> 4), hex_digit(b & 0x0f)])?;
+        }
+        Ok(())
+    }
+
     fn write_specific_id_kind(&mut self, tid: SpecificIdKind) -> Result<(), Error<C::Error>> {
         match tid {
             SpecificIdKind::All => self.write_str("-1")?,
@@ -218,3 +601,392 @@ impl<'a, C: Connection + 'a> ResponseWriter<'a, C> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MockConnection(Vec<u8>);
+
+    impl Connection for MockConnection {
+        type Error = ();
+
+        fn read(&mut self) -> Result<u8, ()> {
+            Err(())
+        }
+
+        fn write(&mut self, byte: u8) -> Result<(), ()> {
+            self.0.push(byte);
+            Ok(())
+        }
+
+        fn peek(&mut self) -> Result<Option<u8>, ()> {
+            Ok(None)
+        }
+
+        fn flush(&mut self) -> Result<(), ()> {
+            Ok(())
+        }
+    }
+
+    /// Decode a packet according to the rules used by the GDB client,
+    /// returning the expanded body.
+    fn gdb_decode(pkt: &[u8]) -> Vec<u8> {
+        assert_eq!(pkt[0], b'$');
+        let hash = pkt.iter().position(|b| *b == b'#').unwrap();
+        let (body, checksum) = (&pkt[1..hash], &pkt[hash + 1..]);
+
+        let calculated = body.iter().fold(0u8, |a, b| a.wrapping_add(*b));
+        assert_eq!(format!("{:02x}", calculated).as_bytes(), checksum);
+
+        let mut out: Vec<u8> = Vec::new();
+        let mut body = body.iter().copied();
+        while let Some(b) = body.next() {
+            assert!(b != b'$' && b != b'#');
+            if b == b'}' {
+                out.push(body.next().unwrap() ^ 0x20);
+            } else if b == b'*' {
+                let count = body.next().unwrap();
+                assert!(!matches!(count, b'#' | b'$' | b'}'));
+                assert!((b' '..=b'~').contains(&count));
+                let c = *out.last().unwrap();
+                out.extend(core::iter::repeat(c).take((count - 29) as usize));
+            } else {
+                out.push(b);
+            }
+        }
+        out
+    }
+
+    fn write_packet(data: &[u8], rle_enabled: bool) -> Vec<u8> {
+        let mut conn = MockConnection(Vec::new());
+        let mut out = OutBuf::new();
+        let mut res = ResponseWriter::new(&mut conn, &mut out, rle_enabled);
+        res.write_hex_buf(data).unwrap();
+        res.flush().unwrap();
+        conn.0
+    }
+
+    fn hex(data: &[u8]) -> Vec<u8> {
+        data.iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    #[test]
+    fn rle_round_trip_all_run_lengths() {
+        for len in 0..=300 {
+            let data = vec![0; len];
+            let pkt = write_packet(&data, true);
+            assert_eq!(gdb_decode(&pkt), hex(&data), "run length {}", len);
+        }
+    }
+
+    #[test]
+    fn rle_round_trip_mixed() {
+        let mut data = vec![0x11; 7];
+        data.extend_from_slice(&[0; 48]);
+        data.extend_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+        data.extend_from_slice(&[0xff; 49]);
+
+        let pkt = write_packet(&data, true);
+        assert!(pkt.len() < hex(&data).len());
+        assert_eq!(gdb_decode(&pkt), hex(&data));
+    }
+
+    #[test]
+    fn thread_id_round_trip() {
+        use crate::protocol::common::thread_id::ThreadId;
+        use core::convert::TryFrom;
+        use core::num::NonZeroUsize;
+
+        let mut ids = vec![SpecificIdKind::All];
+        let values = (1..=0x1000)
+            .chain((4..usize::BITS).map(|shift| 1 << shift))
+            .chain((4..usize::BITS).map(|shift| (1 << shift) - 1))
+            .chain(core::iter::once(usize::MAX));
+        ids.extend(values.map(|n| SpecificIdKind::WithId(NonZeroUsize::new(n).unwrap())));
+
+        let pids = [
+            None,
+            Some(SpecificIdKind::All),
+            Some(ids[1]),
+            Some(ids[0x2a]),
+        ];
+        for &pid in pids.iter() {
+            for &tid in ids.iter() {
+                let id = SpecificThreadId { pid, tid };
+
+                let mut conn = MockConnection(Vec::new());
+                let mut out = OutBuf::new();
+                let mut res = ResponseWriter::new(&mut conn, &mut out, false);
+                res.write_specific_thread_id(id).unwrap();
+                res.flush().unwrap();
+
+                let body = gdb_decode(&conn.0);
+                let parsed = ThreadId::try_from(body.as_slice())
+                    .and_then(SpecificThreadId::try_from)
+                    .unwrap_or_else(|_| {
+                        panic!("failed to parse {:?}", String::from_utf8_lossy(&body))
+                    });
+                assert_eq!(parsed, id);
+            }
+        }
+    }
+
+    #[test]
+    fn rle_compresses_register_dump() {
+        let pkt = write_packet(&[0; 32 * 4], true);
+        assert!(pkt.len() < 32);
+    }
+
+    #[test]
+    fn writes_are_batched() {
+        /// Counts calls to `write` and `write_all`.
+        #[derive(Default)]
+        struct CountingConnection {
+            data: Vec<u8>,
+            writes: usize,
+            write_alls: usize,
+        }
+
+        impl Connection for CountingConnection {
+            type Error = ();
+
+            fn read(&mut self) -> Result<u8, ()> {
+                Err(())
+            }
+
+            fn write(&mut self, byte: u8) -> Result<(), ()> {
+                self.writes += 1;
+                self.data.push(byte);
+                Ok(())
+            }
+
+            fn write_all(&mut self, buf: &[u8]) -> Result<(), ()> {
+                self.write_alls += 1;
+                self.data.extend_from_slice(buf);
+                Ok(())
+            }
+
+            fn peek(&mut self) -> Result<Option<u8>, ()> {
+                Ok(None)
+            }
+
+            fn flush(&mut self) -> Result<(), ()> {
+                Ok(())
+            }
+        }
+
+        let write = |data: &[u8]| {
+            let mut conn = CountingConnection::default();
+            let mut out = OutBuf::new();
+            let mut res = ResponseWriter::new(&mut conn, &mut out, true);
+            res.write_hex_buf(data).unwrap();
+            res.flush().unwrap();
+            conn
+        };
+
+        // a short response is sent all at once
+        let conn = write(b"OK");
+        assert_eq!((conn.writes, conn.write_alls), (0, 1));
+
+        // an incompressible armv4t `g` response (i.e: 168 bytes of registers,
+        // or 340 bytes on the wire) used to require 340 single-byte writes, and
+        // is now sent all at once as well
+        let regs = (0..168).collect::<Vec<u8>>();
+        let conn = write(&regs);
+        assert_eq!(conn.data.len(), 340);
+        assert_eq!((conn.writes, conn.write_alls), (0, 1));
+        assert_eq!(gdb_decode(&conn.data), hex(&regs));
+
+        // even packets much larger than the initial buffer
+        let mem = (0..4096).map(|i| (i * 7) as u8).collect::<Vec<u8>>();
+        let conn = write(&mem);
+        assert_eq!((conn.writes, conn.write_alls), (0, 1));
+        assert_eq!(gdb_decode(&conn.data), hex(&mem));
+    }
+
+    #[test]
+    fn exact_framing() {
+        let mut conn = MockConnection(Vec::new());
+        let mut out = OutBuf::new();
+        let mut res = ResponseWriter::new(&mut conn, &mut out, true);
+        res.write_str("OK").unwrap();
+        res.flush().unwrap();
+        // 'O' + 'K' == 0x9a, using a lowercase hex digit
+        assert_eq!(conn.0, b"$OK#9a");
+
+        let mut conn = MockConnection(Vec::new());
+        let mut out = OutBuf::new();
+        let mut res = ResponseWriter::new(&mut conn, &mut out, true);
+        res.write_hex_buf(&[0xde, 0xad, 0xbe, 0xef]).unwrap();
+        res.flush().unwrap();
+        assert_eq!(conn.0, b"$deadbeef#20");
+
+        let mut conn = MockConnection(Vec::new());
+        let mut out = OutBuf::new();
+        ResponseWriter::new(&mut conn, &mut out, true)
+            .flush()
+            .unwrap();
+        assert_eq!(conn.0, b"$#00");
+    }
+
+    #[test]
+    fn rle_disabled() {
+        let data = [0; 16];
+        let pkt = write_packet(&data, false);
+        assert!(!pkt.contains(&b'*'));
+        assert_eq!(&pkt[1..pkt.len() - 3], &hex(&data)[..]);
+        assert_eq!(gdb_decode(&pkt), hex(&data));
+    }
+
+    #[test]
+    fn qxfer_chunk_parts_match_contiguous_data() {
+        let parts: [&[u8]; 4] = [b"abc", b"", b"de", b"fghij"];
+        let data = parts.concat();
+
+        let write_chunk = |f: &dyn Fn(&mut ResponseWriter<MockConnection>)| {
+            let mut conn = MockConnection(Vec::new());
+            let mut out = OutBuf::new();
+            let mut res = ResponseWriter::new(&mut conn, &mut out, false);
+            f(&mut res);
+            res.flush().unwrap();
+            conn.0
+        };
+
+        for offset in 0..=data.len() + 1 {
+            for len in 0..=data.len() + 1 {
+                assert_eq!(
+                    write_chunk(&|res| res
+                        .write_qxfer_chunk_parts(parts.iter().copied(), offset, len)
+                        .unwrap()),
+                    write_chunk(&|res| res.write_qxfer_chunk(&data, offset, len).unwrap()),
+                    "offset {}, len {}",
+                    offset,
+                    len
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn addr_is_zero_padded() {
+        let mut conn = MockConnection(Vec::new());
+        let mut out = OutBuf::new();
+        let mut res = ResponseWriter::new(&mut conn, &mut out, false);
+        res.write_addr(0x1000u32).unwrap();
+        res.write_str(",").unwrap();
+        res.write_addr(0x1000u64).unwrap();
+        res.flush().unwrap();
+        assert_eq!(gdb_decode(&conn.0), b"00001000,0000000000001000");
+    }
+
+    #[test]
+    fn error_code_is_two_hex_digits() {
+        let mut conn = MockConnection(Vec::new());
+        let mut out = OutBuf::new();
+        let mut res = ResponseWriter::new(&mut conn, &mut out, false);
+        for code in [0, 1, 22, 121].iter().copied() {
+            res.write_str("E").unwrap();
+            res.write_error_code(code).unwrap();
+        }
+        res.flush().unwrap();
+        assert_eq!(gdb_decode(&conn.0), b"E00E01E16E79");
+    }
+
+    #[test]
+    fn hex_encoded_num() {
+        let write = |n: u32| {
+            let mut conn = MockConnection(Vec::new());
+            let mut out = OutBuf::new();
+            let mut res = ResponseWriter::new(&mut conn, &mut out, false);
+            res.write_hex_encoded_num(n).unwrap();
+            res.flush().unwrap();
+            gdb_decode(&conn.0)
+        };
+
+        assert_eq!(write(0), b"3030");
+        assert_eq!(write(0x1f), b"3166");
+        assert_eq!(write(0xdead_beef), b"6465616462656566");
+    }
+
+    #[test]
+    fn dec() {
+        let write = |n: usize| {
+            let mut conn = MockConnection(Vec::new());
+            let mut out = OutBuf::new();
+            let mut res = ResponseWriter::new(&mut conn, &mut out, false);
+            res.write_dec(n).unwrap();
+            res.flush().unwrap();
+            gdb_decode(&conn.0)
+        };
+
+        assert_eq!(write(0), b"0");
+        assert_eq!(write(31), b"31");
+        assert_eq!(write(1000), b"1000");
+        assert_eq!(write(usize::MAX), usize::MAX.to_string().as_bytes());
+    }
+
+    #[test]
+    fn empty_packet() {
+        assert_eq!(write_packet(&[], true), b"$#00");
+        assert_eq!(write_packet(&[], false), b"$#00");
+    }
+
+    #[test]
+    fn binary_escapes_round_trip() {
+        let mut data = vec![b'#', 3, 3, 3, 3, 3, 3];
+        data.extend_from_slice(&[b'}'; 8]);
+        data.extend_from_slice(b"$*ab");
+
+        for &rle_enabled in [true, false].iter() {
+            let mut conn = MockConnection(Vec::new());
+            let mut out = OutBuf::new();
+            let mut res = ResponseWriter::new(&mut conn, &mut out, rle_enabled);
+            res.write_binary(&data).unwrap();
+            res.flush().unwrap();
+            assert_eq!(gdb_decode(&conn.0), data);
+        }
+    }
+
+    #[test]
+    fn binary_escapes_every_byte() {
+        use crate::protocol::common::hex::decode_bin_buf;
+
+        // every byte value, both on its own and as part of a run
+        let mut data = (0..=255).collect::<Vec<u8>>();
+        for b in 0..=255 {
+            data.extend(core::iter::repeat(b).take(5));
+        }
+
+        for &rle_enabled in [true, false].iter() {
+            let mut conn = MockConnection(Vec::new());
+            let mut out = OutBuf::new();
+            let mut res = ResponseWriter::new(&mut conn, &mut out, rle_enabled);
+            res.write_binary(&data).unwrap();
+            res.flush().unwrap();
+
+            let pkt = conn.0;
+            assert_eq!(gdb_decode(&pkt), data, "rle_enabled: {}", rle_enabled);
+
+            // binary data is never run-length encoded, so the body must also
+            // round-trip through the decoder used for incoming binary data
+            let mut body = pkt[1..pkt.len() - 3].to_vec();
+            assert!(!body.iter().any(|&b| b == b'#' || b == b'$'));
+            assert_eq!(decode_bin_buf(&mut body).unwrap(), &data[..]);
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "unescaped '#'")]
+    fn framing_check_catches_unescaped_bytes() {
+        let mut conn = MockConnection(Vec::new());
+        let mut out = OutBuf::new();
+        let mut res = ResponseWriter::new(&mut conn, &mut out, true);
+        res.write_str("OK#00").unwrap();
+        res.flush().unwrap();
+    }
+}
@@ -0,0 +1,19 @@
+//! Provide the ELF auxiliary vector of the target process.
+//!
+//! The auxiliary vector is passed to Linux-like userspace processes by the
+//! kernel, and describes various properties of the process (e.g: its entry
+//! point, the system page size, and the location of the program headers).
+//! GDB uses it to locate the dynamic linker, and to implement `info auxv`.
+use crate::target::{Target, TargetResult};
+
+/// Target Extension - Provide the ELF auxiliary vector of the target process.
+pub trait Auxv: Target {
+    /// Return the raw contents of the auxiliary vector, as found in the target
+    /// process's memory (i.e: a sequence of target-endian `(AT_*, value)`
+    /// pairs, terminated by an `AT_NULL` entry).
+    ///
+    /// On Linux, this corresponds to the contents of `/proc/<pid>/auxv`.
+    fn get_auxv(&mut self) -> TargetResult<&[u8], Self>;
+}
+
+define_ext!(AuxvOps, Auxv);
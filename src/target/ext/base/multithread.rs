@@ -0,0 +1,712 @@
+//! Base debugging operations for multi threaded targets.
+
+use crate::arch::Arch;
+use crate::common::*;
+use crate::target::ext::breakpoints::WatchKind;
+use crate::target::{Target, TargetResult};
+
+use super::{ReplayLogPosition, SingleRegisterAccessOps};
+
+// Convenient re-exports
+pub use super::{GdbInterrupt, ResumeAction};
+
+/// Base debugging operations for multi threaded targets.
+#[allow(clippy::type_complexity)]
+pub trait MultiThreadOps: Target {
+    /// Resume execution on the target.
+    ///
+    /// Prior to calling `resume`, `gdbstub` will call `clear_resume_actions`,
+    /// followed by zero or more calls to `set_resume_action`, specifying any
+    /// thread-specific resume actions.
+    ///
+    /// The `default_action` parameter specifies the "fallback" resume action
+    /// for any threads that did not have a specific resume action set via
+    /// `set_resume_action`. The GDB client typically sets this to
+    /// `ResumeAction::Continue`, though this is not guaranteed.
+    ///
+    /// The `check_gdb_interrupt` callback can be invoked to check if GDB sent
+    /// an Interrupt packet (i.e: the user pressed Ctrl-C). It's recommended to
+    /// invoke this callback every-so-often while the system is running (e.g:
+    /// every X cycles/milliseconds). Periodically checking for incoming
+    /// interrupt packets is _not_ required, but it is _recommended_.
+    ///
+    /// # Implementation requirements
+    ///
+    /// These requirements cannot be satisfied by `gdbstub` internally, and must
+    /// be handled on a per-target basis.
+    ///
+    /// ### Adjusting PC after a breakpoint is hit
+    ///
+    /// The [GDB remote serial protocol documentation](https://sourceware.org/gdb/current/onlinedocs/gdb/Stop-Reply-Packets.html#swbreak-stop-reason)
+    /// notes the following:
+    ///
+    /// > On some architectures, such as x86, at the architecture level, when a
+    /// > breakpoint instruction executes the program counter points at the
+    /// > breakpoint address plus an offset. On such targets, the stub is
+    /// > responsible for adjusting the PC to point back at the breakpoint
+    /// > address.
+    ///
+    /// Omitting PC adjustment may result in unexpected execution flow and/or
+    /// breakpoints not working correctly.
+    ///
+    /// # Additional Considerations
+    ///
+    /// ### Bare-Metal Targets
+    ///
+    /// On bare-metal targets (such as microcontrollers or emulators), it's
+    /// common to treat individual _CPU cores_ as a separate "threads". e.g:
+    /// in a dual-core system, [CPU0, CPU1] might be mapped to [TID1, TID2]
+    /// (note that TIDs cannot be zero).
+    ///
+    /// In this case, the `Tid` argument of `read/write_addrs` becomes quite
+    /// relevant, as different cores may have different memory maps.
+    ///
+    /// ### Running in "Non-stop" mode
+    ///
+    /// By default, `gdbstub` runs in GDB's
+    /// ["All-Stop" mode](https://sourceware.org/gdb/current/onlinedocs/gdb/All_002dStop-Mode.html),
+    /// whereby _all_ threads must be stopped when returning from `resume`
+    /// (not just the thread associated with the `ThreadStopReason`).
+    ///
+    /// Targets which implement [`support_non_stop`](Self::support_non_stop)
+    /// can also be debugged in GDB's
+    /// ["Non-Stop" mode](https://sourceware.org/gdb/current/onlinedocs/gdb/Non_002dStop-Mode.html).
+    /// While in non-stop mode, `resume` is _not_ called. See
+    /// [`MultiThreadNonStop`] for details.
+    fn resume(
+        &mut self,
+        default_resume_action: ResumeAction,
+        gdb_interrupt: GdbInterrupt<'_>,
+    ) -> Result<ThreadStopReason<<Self::Arch as Arch>::Usize>, Self::Error>;
+
+    /// Clear all previously set resume actions.
+    fn clear_resume_actions(&mut self) -> Result<(), Self::Error>;
+
+    /// Specify what action each thread should take when
+    /// [`resume`](Self::resume) is called.
+    ///
+    /// A simple implementation of this method would simply update an internal
+    /// `HashMap<Tid, ResumeAction>`.
+    ///
+    /// Aside from the four "base" resume actions handled by this method (i.e:
+    /// `Step`, `Continue`, `StepWithSignal`, and `ContinueWithSignal`),
+    /// there are also two additional resume actions which are only set if the
+    /// target implements their corresponding protocol extension:
+    ///
+    /// Action                     | Protocol Extension
+    /// ---------------------------|---------------------------
+    /// Optimized [Range Stepping] | See [`support_range_step()`]
+    /// "Stop"                     | See [`support_non_stop()`] \*
+    ///
+    /// \* Requests to stop individual threads in "Non-Stop" mode are passed
+    /// to [`MultiThreadNonStop::request_stop`] instead.
+    ///
+    /// [Range Stepping]: https://sourceware.org/gdb/current/onlinedocs/gdb/Continuing-and-Stepping.html#range-stepping
+    /// [`support_range_step()`]: Self::support_range_step
+    /// [`support_non_stop()`]: Self::support_non_stop
+    fn set_resume_action(&mut self, tid: Tid, action: ResumeAction) -> Result<(), Self::Error>;
+
+    /// Whether the target supports resuming with a signal, i.e: handles the
+    /// [`ResumeAction::StepWithSignal`] and
+    /// [`ResumeAction::ContinueWithSignal`] resume actions.
+    ///
+    /// When `false` (the default), `gdbstub` will not advertise the `C` and `S`
+    /// `vCont` actions to the GDB client. See [`ResumeAction`] for details on
+    /// how this affects the GDB client's behavior.
+    #[inline(always)]
+    fn supports_resume_with_signal(&self) -> bool {
+        false
+    }
+
+    /// Whether the target supports single-stepping, i.e: handles the
+    /// [`ResumeAction::Step`] and [`ResumeAction::StepWithSignal`] resume
+    /// actions.
+    ///
+    /// When `false`, `gdbstub` will not advertise the `s` and `S` `vCont`
+    /// actions to the GDB client, which will then emulate single-stepping
+    /// using temporary breakpoints (if it's able to do so on the target's
+    /// architecture). See [`Arch::single_step_gdb_behavior`] for more details.
+    ///
+    /// Targets which emulate single-stepping internally should keep this as
+    /// `true` (the default), and report the emulated step as `DoneStep`.
+    #[inline(always)]
+    fn supports_single_step(&self) -> bool {
+        true
+    }
+
+    /// Support for the optimized [range stepping] resume action.
+    ///
+    /// [range stepping]: https://sourceware.org/gdb/current/onlinedocs/gdb/Continuing-and-Stepping.html#range-stepping
+    #[inline(always)]
+    fn support_range_step(&mut self) -> Option<MultiThreadRangeSteppingOps<Self>> {
+        None
+    }
+
+    /// Support for [reverse stepping] a target.
+    ///
+    /// [reverse stepping]: https://sourceware.org/gdb/current/onlinedocs/gdb/Reverse-Execution.html
+    #[inline(always)]
+    fn support_reverse_step(&mut self) -> Option<MultiThreadReverseStepOps<Self>> {
+        None
+    }
+
+    /// Support for [reverse continuing] a target.
+    ///
+    /// [reverse continuing]: https://sourceware.org/gdb/current/onlinedocs/gdb/Reverse-Execution.html
+    #[inline(always)]
+    fn support_reverse_cont(&mut self) -> Option<MultiThreadReverseContOps<Self>> {
+        None
+    }
+
+    /// Support for GDB's [non-stop mode], whereby threads are stopped and
+    /// resumed independently of one another.
+    ///
+    /// [non-stop mode]: https://sourceware.org/gdb/current/onlinedocs/gdb/Non_002dStop-Mode.html
+    #[inline(always)]
+    fn support_non_stop(&mut self) -> Option<MultiThreadNonStopOps<Self>> {
+        None
+    }
+
+    /// Support for providing a human-readable description of each thread
+    /// (shown alongside each thread in GDB's `info threads` output).
+    #[inline(always)]
+    fn support_thread_extra_info(&mut self) -> Option<MultiThreadExtraInfoOps<Self>> {
+        None
+    }
+
+    /// Support for reporting detailed information about each active thread
+    /// (such as its name, and the core it is running on).
+    #[inline(always)]
+    fn support_thread_list(&mut self) -> Option<ThreadListOps<Self>> {
+        None
+    }
+
+    /// Support for reporting which process owns each thread, for targets
+    /// which debug multiple processes at once (see
+    /// [`ExtendedMode`](crate::target::ext::extended_mode::ExtendedMode)).
+    #[inline(always)]
+    fn support_multiprocess(&mut self) -> Option<MultiThreadMultiprocessOps<Self>> {
+        None
+    }
+
+    /// Read the target's registers.
+    ///
+    /// If the registers could not be accessed, an appropriate non-fatal error
+    /// should be returned.
+    fn read_registers(
+        &mut self,
+        regs: &mut <Self::Arch as Arch>::Registers,
+        tid: Tid,
+    ) -> TargetResult<(), Self>;
+
+    /// Write the target's registers.
+    ///
+    /// If the registers could not be accessed, an appropriate non-fatal error
+    /// should be returned.
+    fn write_registers(
+        &mut self,
+        regs: &<Self::Arch as Arch>::Registers,
+        tid: Tid,
+    ) -> TargetResult<(), Self>;
+
+    /// Support for single-register access.
+    /// See [`SingleRegisterAccess`](super::SingleRegisterAccess) for more
+    /// details.
+    ///
+    /// While this is an optional feature, it is **highly recommended** to
+    /// implement it when possible, as it can significantly improve performance
+    /// on certain architectures.
+    #[inline(always)]
+    fn single_register_access(&mut self) -> Option<SingleRegisterAccessOps<Tid, Self>> {
+        None
+    }
+
+    /// Read bytes from the specified address range.
+    ///
+    /// If the requested address range could not be accessed (e.g: due to
+    /// MMU protection, unhanded page fault, etc...), an appropriate non-fatal
+    /// error should be returned.
+    ///
+    /// _Note:_ When a read partially overlaps an inaccessible region, `gdbstub`
+    /// will retry the failing portion of the read byte-by-byte, and report
+    /// the accessible prefix back to GDB.
+    fn read_addrs(
+        &mut self,
+        start_addr: <Self::Arch as Arch>::Usize,
+        data: &mut [u8],
+        tid: Tid,
+    ) -> TargetResult<(), Self>;
+
+    /// Write bytes to the specified address range.
+    ///
+    /// If the requested address range could not be accessed (e.g: due to
+    /// MMU protection, unhanded page fault, etc...), an appropriate non-fatal
+    /// error should be returned.
+    fn write_addrs(
+        &mut self,
+        start_addr: <Self::Arch as Arch>::Usize,
+        data: &[u8],
+        tid: Tid,
+    ) -> TargetResult<(), Self>;
+
+    /// Support for streaming memory reads straight into the response packet.
+    /// See [`MultiThreadStreamingRead`] for more details.
+    #[inline(always)]
+    fn support_streaming_read(&mut self) -> Option<MultiThreadStreamingReadOps<'_, Self>> {
+        None
+    }
+
+    /// List all currently active threads.
+    ///
+    /// See [the section above](#bare-metal-targets) on implementing
+    /// thread-related methods on bare-metal (threadless) targets.
+    fn list_active_threads(
+        &mut self,
+        thread_is_active: &mut dyn FnMut(Tid),
+    ) -> Result<(), Self::Error>;
+
+    /// Check if the specified thread is alive.
+    ///
+    /// As a convenience, this method provides a default implementation which
+    /// uses `list_active_threads` to do a linear-search through all active
+    /// threads. On thread-heavy systems, it may be more efficient
+    /// to override this method with a more direct query.
+    fn is_thread_alive(&mut self, tid: Tid) -> Result<bool, Self::Error> {
+        let mut found = false;
+        self.list_active_threads(&mut |active_tid| {
+            if tid == active_tid {
+                found = true;
+            }
+        })?;
+        Ok(found)
+    }
+}
+
+/// Target Extension - [Reverse continue] for multi threaded targets.
+///
+/// Reverse continue allows the target to run backwards until it reaches the end
+/// of the replay log.
+///
+/// [Reverse continue]: https://sourceware.org/gdb/current/onlinedocs/gdb/Reverse-Execution.html
+pub trait MultiThreadReverseCont: Target + MultiThreadOps {
+    /// Reverse-continue the target.
+    fn reverse_cont(
+        &mut self,
+        gdb_interrupt: GdbInterrupt<'_>,
+    ) -> Result<ThreadStopReason<<Self::Arch as Arch>::Usize>, Self::Error>;
+}
+
+define_ext!(MultiThreadReverseContOps, MultiThreadReverseCont);
+
+/// Target Extension - [Reverse stepping] for multi threaded targets.
+///
+/// Reverse stepping allows the target to run backwards by one step.
+///
+/// [Reverse stepping]: https://sourceware.org/gdb/current/onlinedocs/gdb/Reverse-Execution.html
+pub trait MultiThreadReverseStep: Target + MultiThreadOps {
+    /// Reverse-step the specified [`Tid`].
+    fn reverse_step(
+        &mut self,
+        tid: Tid,
+        gdb_interrupt: GdbInterrupt<'_>,
+    ) -> Result<ThreadStopReason<<Self::Arch as Arch>::Usize>, Self::Error>;
+}
+
+define_ext!(MultiThreadReverseStepOps, MultiThreadReverseStep);
+
+/// Target Extension - Optimized [range stepping] for multi threaded targets.
+/// See [`MultiThreadOps::support_range_step`].
+///
+/// Range Stepping will step the target once, and keep stepping the target as
+/// long as execution remains between the specified start (inclusive) and end
+/// (exclusive) addresses, or another stop condition is met (e.g: a breakpoint
+/// it hit).
+///
+/// If the range is empty (`start` == `end`), then the action becomes
+/// equivalent to the ‘s’ action. In other words, single-step once, and
+/// report the stop (even if the stepped instruction jumps to start).
+///
+/// _Note:_ A stop reply may be sent at any point even if the PC is still
+/// within the stepping range; for example, it is valid to implement range
+/// stepping in a degenerate way as a single instruction step operation.
+///
+/// [range stepping]: https://sourceware.org/gdb/current/onlinedocs/gdb/Continuing-and-Stepping.html#range-stepping
+pub trait MultiThreadRangeStepping: Target + MultiThreadOps {
+    /// See [`MultiThreadOps::set_resume_action`].
+    fn set_resume_action_range_step(
+        &mut self,
+        tid: Tid,
+        start: <Self::Arch as Arch>::Usize,
+        end: <Self::Arch as Arch>::Usize,
+    ) -> Result<(), Self::Error>;
+}
+
+define_ext!(MultiThreadRangeSteppingOps, MultiThreadRangeStepping);
+
+/// Target Extension - Stream memory reads straight into the response packet.
+/// See [`MultiThreadOps::support_streaming_read`].
+///
+/// By default, `gdbstub` services memory reads by having the target copy the
+/// requested bytes into a scratch buffer (via [`MultiThreadOps::read_addrs`]), and then
+/// encoding that buffer into the response. Targets which can access their
+/// memory directly (e.g: emulators) can use this extension to skip the
+/// intermediate copy, and hand their memory to `gdbstub` as-is.
+///
+/// This also lets a single call service the entire read, instead of it being
+/// split into `read_addrs` calls no larger than the packet buffer.
+pub trait MultiThreadStreamingRead: Target + MultiThreadOps {
+    /// Read `len` bytes starting at `start_addr`, passing them to `write` (in
+    /// order, across as many calls as is convenient).
+    ///
+    /// Any bytes past the first `len` are ignored.
+    ///
+    /// If part of the requested address range could not be accessed, the
+    /// target should write out the accessible prefix, and then return an
+    /// appropriate non-fatal error. As with `read_addrs`, the error is only
+    /// reported to GDB if no bytes were written at all, and the read is
+    /// otherwise reported as having been cut short.
+    fn read_addrs_streaming(
+        &mut self,
+        start_addr: <Self::Arch as Arch>::Usize,
+        len: usize,
+        write: &mut dyn FnMut(&[u8]),
+        tid: Tid,
+    ) -> TargetResult<(), Self>;
+}
+
+define_ext!(MultiThreadStreamingReadOps, MultiThreadStreamingRead);
+
+/// Target Extension - Support GDB's non-stop mode.
+/// See [`MultiThreadOps::support_non_stop`].
+///
+/// In non-stop mode, GDB resumes and inspects threads independently of one
+/// another: a thread stopping (e.g: due to hitting a breakpoint) does _not_
+/// stop any other threads, and GDB can keep sending packets to the target
+/// while threads are running.
+///
+/// Instead of calling [`MultiThreadOps::resume`] (which blocks until the
+/// target stops), `gdbstub` splits resumption into two steps:
+///
+/// 1. [`resume_threads`](Self::resume_threads) starts executing threads,
+///    without waiting for any of them to stop.
+/// 2. [`wait_for_stop`](Self::wait_for_stop) is called whenever `gdbstub` is
+///    idle, and reports threads stopping one at a time.
+///
+/// Any stops are sent to GDB as asynchronous `%Stop` notifications.
+///
+/// _Note:_ Stop reasons that don't include a thread id (i.e:
+/// `ThreadStopReason::DoneStep` and `ThreadStopReason::Signal`) are reported
+/// without one, in which case GDB attributes them to its currently selected
+/// thread. Prefer reporting stop reasons that include a thread id.
+pub trait MultiThreadNonStop: Target + MultiThreadOps {
+    /// Start executing threads, _without_ waiting for any of them to stop.
+    ///
+    /// Prior to calling `resume_threads`, `gdbstub` will call
+    /// `clear_resume_actions`, followed by zero or more calls to
+    /// `set_resume_action`, specifying which threads to resume (and how).
+    ///
+    /// If `default_resume_action` is `Some`, the action applies to every
+    /// _stopped_ thread which did not have a specific resume action set.
+    ///
+    /// Threads that are not being resumed should be left in their current
+    /// state (i.e: already running threads should keep running, and stopped
+    /// threads should remain stopped).
+    fn resume_threads(
+        &mut self,
+        default_resume_action: Option<ResumeAction>,
+    ) -> Result<(), Self::Error>;
+
+    /// Keep executing any running threads until one of them stops, or until
+    /// GDB sends a packet.
+    ///
+    /// Only the thread associated with the returned `ThreadStopReason` should
+    /// be stopped. All other running threads should keep running (e.g: the
+    /// next call to `wait_for_stop` should pick up where this one left off).
+    ///
+    /// The `gdb_interrupt` callback reports whether GDB has sent a packet.
+    /// When it does, this method should return `ThreadStopReason::GdbInterrupt`
+    /// as soon as possible, _without_ stopping any threads, so that `gdbstub`
+    /// can service the incoming packet.
+    ///
+    /// If no threads are running, this method should simply wait until a
+    /// packet arrives.
+    fn wait_for_stop(
+        &mut self,
+        gdb_interrupt: GdbInterrupt<'_>,
+    ) -> Result<ThreadStopReason<<Self::Arch as Arch>::Usize>, Self::Error>;
+
+    /// Request that the thread `tid` (or all threads, if `tid` is `None`)
+    /// stop, _without_ waiting for it to do so.
+    ///
+    /// This corresponds to GDB's `vCont;t` action. Once a running thread has
+    /// stopped, it should be reported via `wait_for_stop` as
+    /// [`ThreadStopReason::Stopped`]. Threads which are already stopped
+    /// should be left as-is, and should _not_ be reported again.
+    fn request_stop(&mut self, tid: Option<Tid>) -> Result<(), Self::Error>;
+}
+
+define_ext!(MultiThreadNonStopOps, MultiThreadNonStop);
+
+/// Target Extension - Provide a human-readable description of each thread.
+/// See [`MultiThreadOps::support_thread_extra_info`].
+///
+/// This description is displayed alongside the thread's ID in the output of
+/// GDB's `info threads` command (e.g: "idle task", "ISR", "CPU1").
+pub trait MultiThreadExtraInfo: Target + MultiThreadOps {
+    /// Write a short, human-readable description of the thread `tid` into
+    /// `buf`, returning the number of bytes written.
+    ///
+    /// The description is not required to be valid UTF-8, though GDB will
+    /// typically display it as such.
+    ///
+    /// _Note:_ As the description is sent to GDB hex-encoded, `buf` is only
+    /// half the size of the packet buffer. Longer descriptions should be
+    /// truncated.
+    ///
+    /// If the thread doesn't exist (or its description couldn't be
+    /// retrieved), an appropriate non-fatal error should be returned.
+    fn thread_extra_info(&mut self, tid: Tid, buf: &mut [u8]) -> TargetResult<usize, Self>;
+}
+
+define_ext!(MultiThreadExtraInfoOps, MultiThreadExtraInfo);
+
+/// Information about an active thread, as reported by [`ThreadList`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ThreadInfo<'a> {
+    /// The thread's id.
+    pub tid: Tid,
+    /// A human-readable name for the thread.
+    pub name: Option<&'a str>,
+    /// The id of the processor core the thread is running on.
+    pub core: Option<usize>,
+    /// The process which owns the thread.
+    ///
+    /// If `None`, the thread is assumed to belong to the currently selected
+    /// process.
+    pub pid: Option<Pid>,
+}
+
+/// Target Extension - Report detailed information about each active thread.
+/// See [`MultiThreadOps::support_thread_list`].
+///
+/// When implemented, GDB uses this extension (via the `qXfer:threads:read`
+/// packet) instead of [`MultiThreadOps::list_active_threads`] to enumerate
+/// the target's threads.
+///
+/// _Note:_ The resulting thread list is assembled in the `GdbStub`'s packet
+/// buffer, which must be large enough to hold the entire list.
+pub trait ThreadList: Target + MultiThreadOps {
+    /// Call `thread_info` with information about each active thread.
+    fn list_threads(
+        &mut self,
+        thread_info: &mut dyn FnMut(ThreadInfo<'_>),
+    ) -> Result<(), Self::Error>;
+}
+
+define_ext!(ThreadListOps, ThreadList);
+
+/// Target Extension - Report which process owns each thread.
+/// See [`MultiThreadOps::support_multiprocess`].
+///
+/// Thread ids are always reported to GDB alongside the id of the process
+/// which owns them (e.g: `p2.1`). Without this extension, every thread is
+/// assumed to belong to the currently selected process, which is only correct
+/// when debugging a single process at a time.
+pub trait MultiThreadMultiprocess: Target + MultiThreadOps {
+    /// Return the process which owns the thread `tid`.
+    ///
+    /// This may be called for a thread which has just exited (i.e: when
+    /// reporting a [`ThreadStopReason::ThreadExited`] stop reason).
+    fn thread_pid(&mut self, tid: Tid) -> Result<Pid, Self::Error>;
+
+    /// List all currently active threads, along with the process which owns
+    /// each of them.
+    ///
+    /// Used instead of [`MultiThreadOps::list_active_threads`].
+    fn list_active_process_threads(
+        &mut self,
+        thread_is_active: &mut dyn FnMut(Pid, Tid),
+    ) -> Result<(), Self::Error>;
+}
+
+define_ext!(MultiThreadMultiprocessOps, MultiThreadMultiprocess);
+
+/// Describes why a thread stopped.
+///
+/// Targets MUST only respond with stop reasons that correspond to IDETs that
+/// target has implemented.
+///
+/// e.g: A target which has not implemented the [`HwBreakpoint`] IDET must not
+/// return a `HwBreak` stop reason. While this is not enforced at compile time,
+/// doing so will result in a runtime `UnsupportedStopReason` error.
+///
+/// [`HwBreakpoint`]: crate::target::ext::breakpoints::HwBreakpoint
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ThreadStopReason<U> {
+    /// Completed the single-step request.
+    DoneStep,
+    /// `check_gdb_interrupt` returned `true`.
+    ///
+    /// Reported to GDB as the target stopping with `SIGINT`.
+    GdbInterrupt,
+    /// The process exited with the specified exit status.
+    Exited(u8),
+    /// The process terminated with the specified signal.
+    Terminated(Signal),
+    /// The program received a signal.
+    ///
+    /// Reported to GDB without a thread ID (i.e: as `S<signal>`), leaving GDB
+    /// to assume the signal was received by the current thread. Prefer
+    /// [`ThreadSignal`](Self::ThreadSignal) when the receiving thread is
+    /// known.
+    Signal(Signal),
+    /// A specific thread received a signal.
+    ///
+    /// Reported to GDB as a `T<signal>thread:<tid>;` stop reply, which
+    /// ensures GDB selects the thread which received the signal.
+    ThreadSignal {
+        /// Which thread received the signal
+        tid: Tid,
+        /// The signal
+        signal: Signal,
+    },
+    /// A thread received a signal caused by a memory access (e.g: `SIGSEGV`
+    /// or `SIGBUS`).
+    ///
+    /// The faulting address is reported alongside the signal. As GDB has no
+    /// dedicated stop reply field for fault addresses, it is sent as a
+    /// `description` field (which is displayed by LLDB, and ignored by GDB).
+    SignalWithInfo {
+        /// Which thread received the signal
+        tid: Tid,
+        /// The signal
+        signal: Signal,
+        /// Address of the faulting memory access
+        addr: U,
+    },
+    /// A thread hit a software breakpoint (e.g. due to a trap instruction).
+    ///
+    /// Requires: [`SwBreakpoint`].
+    ///
+    /// NOTE: If the GDB client didn't advertise support for the `swbreak`
+    /// stop reason (via `qSupported`), this is reported as a plain `SIGTRAP`.
+    ///
+    /// NOTE: This does not necessarily have to be a breakpoint configured by
+    /// the client/user of the current GDB session.
+    ///
+    /// [`SwBreakpoint`]: crate::target::ext::breakpoints::SwBreakpoint
+    SwBreak(Tid),
+    /// A thread hit a hardware breakpoint.
+    ///
+    /// Requires: [`HwBreakpoint`].
+    ///
+    /// NOTE: If the GDB client didn't advertise support for the `hwbreak`
+    /// stop reason (via `qSupported`), this is reported as a plain `SIGTRAP`.
+    ///
+    /// [`HwBreakpoint`]: crate::target::ext::breakpoints::HwBreakpoint
+    HwBreak(Tid),
+    /// A thread hit a watchpoint.
+    ///
+    /// Requires: [`HwWatchpoint`].
+    ///
+    /// [`HwWatchpoint`]: crate::target::ext::breakpoints::HwWatchpoint
+    Watch {
+        /// Which thread hit the watchpoint
+        tid: Tid,
+        /// Kind of watchpoint that was hit
+        kind: WatchKind,
+        /// Address of the memory access which triggered the watchpoint. This
+        /// may be anywhere within the watched region, and is used by GDB to
+        /// determine which watchpoint was hit.
+        addr: U,
+    },
+    /// The program has reached the end of the logged replay events.
+    ///
+    /// Requires: [`MultiThreadReverseCont`] or [`MultiThreadReverseStep`].
+    ///
+    /// This is used for GDB's reverse execution. When playing back a recording,
+    /// you may hit the end of the buffer of recorded events, and as such no
+    /// further execution can be done. This stop reason tells GDB that this has
+    /// occurred.
+    ReplayLog(ReplayLogPosition),
+    /// A thread stopped at GDB's request.
+    ///
+    /// Requires: [`MultiThreadNonStop`].
+    ///
+    /// Reported to GDB as the thread stopping with signal `0`, as is expected
+    /// of threads stopped via [`MultiThreadNonStop::request_stop`].
+    Stopped(Tid),
+    /// A new thread was created.
+    ///
+    /// Reported to GDB as a `create` stop reason, which lets GDB add the
+    /// thread to its thread list right away (instead of discovering it the
+    /// next time it lists the active threads). The new thread should remain
+    /// stopped until GDB resumes it.
+    ///
+    /// NOTE: GDB must request thread events (via `QThreadEvents`) before they
+    /// are reported. Until it does, `gdbstub` ignores this stop reason and
+    /// resumes the target using the same resume actions (or in non-stop mode,
+    /// doesn't report it at all).
+    ThreadCreated(Tid),
+    /// A thread exited, while the rest of the process is still running.
+    ///
+    /// Reported to GDB as a `w` stop reply. The same caveats apply as for
+    /// [`ThreadCreated`](Self::ThreadCreated).
+    ThreadExited {
+        /// Which thread exited
+        tid: Tid,
+        /// The thread's exit status
+        status: u8,
+    },
+    /// A thread called `fork`, creating the new process `new_pid`.
+    ///
+    /// Requires: [`CatchEvents`].
+    ///
+    /// Both the parent and the child process should remain stopped until GDB
+    /// resumes (or detaches from) them.
+    ///
+    /// [`CatchEvents`]: crate::target::ext::extended_mode::CatchEvents
+    Fork {
+        /// Which thread called `fork`
+        tid: Tid,
+        /// The new (child) process
+        new_pid: Pid,
+        /// The child process's thread
+        new_tid: Tid,
+    },
+    /// A thread called `vfork`, creating the new process `new_pid`.
+    ///
+    /// Requires: [`CatchEvents`].
+    ///
+    /// Once the child process calls `exec` or exits (i.e: the parent and the
+    /// child no longer share an address space), the parent should report
+    /// [`VForkDone`](Self::VForkDone).
+    ///
+    /// [`CatchEvents`]: crate::target::ext::extended_mode::CatchEvents
+    VFork {
+        /// Which thread called `vfork`
+        tid: Tid,
+        /// The new (child) process
+        new_pid: Pid,
+        /// The child process's thread
+        new_tid: Tid,
+    },
+    /// The child process created by a thread's `vfork` called `exec` or
+    /// exited.
+    ///
+    /// Requires: [`CatchEvents`].
+    ///
+    /// [`CatchEvents`]: crate::target::ext::extended_mode::CatchEvents
+    VForkDone(Tid),
+    /// A thread called `exec`.
+    ///
+    /// Requires: [`CatchEvents`].
+    ///
+    /// The path of the new executable is obtained via
+    /// [`CatchEvents::exec_path`].
+    ///
+    /// [`CatchEvents`]: crate::target::ext::extended_mode::CatchEvents
+    /// [`CatchEvents::exec_path`]: crate::target::ext::extended_mode::CatchEvents::exec_path
+    Exec(Tid),
+}
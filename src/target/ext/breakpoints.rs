@@ -0,0 +1,220 @@
+//! Add/Remove various kinds of breakpoints.
+
+use crate::arch::Arch;
+use crate::target::{Target, TargetResult};
+
+/// Target Extension - Set/Remove Breakpoints.
+pub trait Breakpoints: Target {
+    /// Set/Remote software breakpoints.
+    #[inline(always)]
+    fn sw_breakpoint(&mut self) -> Option<SwBreakpointOps<Self>> {
+        None
+    }
+
+    /// Set/Remote hardware breakpoints.
+    #[inline(always)]
+    fn hw_breakpoint(&mut self) -> Option<HwBreakpointOps<Self>> {
+        None
+    }
+
+    /// Set/Remote hardware watchpoints.
+    #[inline(always)]
+    fn hw_watchpoint(&mut self) -> Option<HwWatchpointOps<Self>> {
+        None
+    }
+
+    /// Evaluate breakpoint conditions on the target.
+    #[inline(always)]
+    fn breakpoint_conditions(&mut self) -> Option<BreakpointConditionsOps<'_, Self>> {
+        None
+    }
+}
+
+define_ext!(BreakpointsOps, Breakpoints);
+
+/// Nested Target Extension - Set/Remove Software Breakpoints.
+///
+/// See [this stackoverflow discussion](https://stackoverflow.com/questions/8878716/what-is-the-difference-between-hardware-and-software-breakpoints)
+/// about the differences between hardware and software breakpoints.
+///
+/// _Recommendation:_ If you're implementing `Target` for an emulator that's
+/// using an _interpreted_ CPU (as opposed to a JIT), the simplest way to
+/// implement "software" breakpoints would be to check the `PC` value after each
+/// CPU cycle, ignoring the specified breakpoint `kind` entirely.
+pub trait SwBreakpoint: Target + Breakpoints {
+    /// Add a new software breakpoint.
+    /// Return `Ok(false)` if the operation could not be completed.
+    ///
+    /// `kind` is the architecture-specific breakpoint kind sent by GDB. On
+    /// most architectures, this is simply the size of the breakpoint
+    /// instruction, though some (such as ARM, which uses it to differentiate
+    /// ARM and Thumb breakpoints) assign it an additional meaning. See
+    /// [`BreakpointKind`](crate::arch::BreakpointKind) for more details.
+    fn add_sw_breakpoint(
+        &mut self,
+        addr: <Self::Arch as Arch>::Usize,
+        kind: <Self::Arch as Arch>::BreakpointKind,
+    ) -> TargetResult<bool, Self>;
+
+    /// Remove an existing software breakpoint.
+    /// Return `Ok(false)` if the operation could not be completed.
+    ///
+    /// `kind` is the same value that was passed when adding the breakpoint.
+    fn remove_sw_breakpoint(
+        &mut self,
+        addr: <Self::Arch as Arch>::Usize,
+        kind: <Self::Arch as Arch>::BreakpointKind,
+    ) -> TargetResult<bool, Self>;
+}
+
+define_ext!(SwBreakpointOps, SwBreakpoint);
+
+/// Nested Target Extension - Set/Remove Hardware Breakpoints.
+///
+/// See [this stackoverflow discussion](https://stackoverflow.com/questions/8878716/what-is-the-difference-between-hardware-and-software-breakpoints)
+/// about the differences between hardware and software breakpoints.
+///
+/// _Recommendation:_ If you're implementing `Target` for an emulator that's
+/// using an _interpreted_ CPU (as opposed to a JIT), there shouldn't be any
+/// reason to implement this extension (as software breakpoints are likely to be
+/// just-as-fast).
+pub trait HwBreakpoint: Target + Breakpoints {
+    /// Add a new hardware breakpoint.
+    /// Return `Ok(false)` if the operation could not be completed.
+    ///
+    /// `kind` is the architecture-specific breakpoint kind sent by GDB. On
+    /// most architectures, this is simply the size of the breakpoint
+    /// instruction, though some (such as ARM, which uses it to differentiate
+    /// ARM and Thumb breakpoints) assign it an additional meaning. See
+    /// [`BreakpointKind`](crate::arch::BreakpointKind) for more details.
+    fn add_hw_breakpoint(
+        &mut self,
+        addr: <Self::Arch as Arch>::Usize,
+        kind: <Self::Arch as Arch>::BreakpointKind,
+    ) -> TargetResult<bool, Self>;
+
+    /// Remove an existing hardware breakpoint.
+    /// Return `Ok(false)` if the operation could not be completed.
+    ///
+    /// `kind` is the same value that was passed when adding the breakpoint.
+    fn remove_hw_breakpoint(
+        &mut self,
+        addr: <Self::Arch as Arch>::Usize,
+        kind: <Self::Arch as Arch>::BreakpointKind,
+    ) -> TargetResult<bool, Self>;
+}
+
+define_ext!(HwBreakpointOps, HwBreakpoint);
+
+/// The kind of watchpoint that should be set/removed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WatchKind {
+    /// Fire when the memory location is written to.
+    Write,
+    /// Fire when the memory location is read from.
+    Read,
+    /// Fire when the memory location is written to and/or read from.
+    ReadWrite,
+}
+
+/// Nested Target Extension - Set/Remove Hardware Watchpoints.
+///
+/// See the [GDB documentation](https://sourceware.org/gdb/current/onlinedocs/gdb/Set-Watchpoints.html)
+/// regarding watchpoints for how they're supposed to work.
+///
+/// _Note:_ If this extension isn't implemented, GDB will default to using
+/// _software watchpoints_, which tend to be excruciatingly slow (as hey are
+/// implemented by single-stepping the system, and reading the watched memory
+/// location after each step).
+pub trait HwWatchpoint: Target + Breakpoints {
+    /// Add a new hardware watchpoint.
+    /// Return `Ok(false)` if the operation could not be completed.
+    ///
+    /// The watchpoint covers the `len` bytes of memory starting at `addr`. GDB
+    /// will typically set this to the size of the watched expression, but may
+    /// also request a larger region (e.g: when watching a struct or an array).
+    fn add_hw_watchpoint(
+        &mut self,
+        addr: <Self::Arch as Arch>::Usize,
+        len: <Self::Arch as Arch>::Usize,
+        kind: WatchKind,
+    ) -> TargetResult<bool, Self>;
+
+    /// Remove an existing hardware watchpoint.
+    /// Return `Ok(false)` if the operation could not be completed.
+    ///
+    /// `len` is the same value that was passed when adding the watchpoint.
+    fn remove_hw_watchpoint(
+        &mut self,
+        addr: <Self::Arch as Arch>::Usize,
+        len: <Self::Arch as Arch>::Usize,
+        kind: WatchKind,
+    ) -> TargetResult<bool, Self>;
+}
+
+define_ext!(HwWatchpointOps, HwWatchpoint);
+
+/// Nested Target Extension - Evaluate breakpoint conditions on the target.
+///
+/// Implementing this extension advertises `ConditionalBreakpoints+` to the GDB
+/// client, which (depending on the `set breakpoint condition-evaluation`
+/// setting) will then send each breakpoint's conditions alongside the `Z0` /
+/// `Z1` packet used to insert it. Each condition is an [agent
+/// expression](https://sourceware.org/gdb/current/onlinedocs/gdb/Agent-Expressions.html),
+/// and the target should only report a stop at the breakpoint if _any_ of its
+/// conditions evaluates to a non-zero value.
+///
+/// Conditions are evaluated _either_ by the target or by GDB, never both: GDB
+/// won't re-check conditions it has sent to the target, and won't send any
+/// conditions if this extension isn't implemented. Breakpoint command lists
+/// (i.e: `BreakpointCommands+`) are a separate feature, and are not currently
+/// supported.
+///
+/// _Note:_ Hit counts and ignore counts are never sent to the target. GDB
+/// keeps track of both itself, counting each stop reported at the breakpoint
+/// as a hit. As such, a target that evaluates conditions will see GDB's hit
+/// counts reflect the number of times the condition was satisfied, exactly as
+/// if GDB had evaluated the condition itself.
+pub trait BreakpointConditions: Target + Breakpoints {
+    /// Set the conditions for the software / hardware breakpoint at `addr`,
+    /// replacing any conditions which were previously set.
+    ///
+    /// This method is called immediately after the breakpoint has been
+    /// successfully inserted. GDB re-inserts existing breakpoints whenever
+    /// their conditions change, and an empty list of `conds` means the
+    /// breakpoint is unconditional. Any conditions should be discarded once
+    /// the breakpoint is removed.
+    fn set_breakpoint_conditions(
+        &mut self,
+        addr: <Self::Arch as Arch>::Usize,
+        conds: Conditions<'_, '_>,
+    ) -> TargetResult<(), Self>;
+}
+
+define_ext!(BreakpointConditionsOps, BreakpointConditions);
+
+/// Iterator over the agent expression bytecode of each of a breakpoint's
+/// conditions (used in `BreakpointConditions::set_breakpoint_conditions`).
+pub struct Conditions<'a, 'cond> {
+    inner: &'a mut dyn Iterator<Item = &'cond [u8]>,
+}
+
+impl core::fmt::Debug for Conditions<'_, '_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Conditions {{ .. }}")
+    }
+}
+
+impl<'a, 'b> Conditions<'a, 'b> {
+    pub(crate) fn new(inner: &'a mut dyn Iterator<Item = &'b [u8]>) -> Conditions<'a, 'b> {
+        Conditions { inner }
+    }
+}
+
+impl<'cond> Iterator for Conditions<'_, 'cond> {
+    type Item = &'cond [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
@@ -0,0 +1,20 @@
+//! Provide the on-disk path of a process's executable.
+//!
+//! GDB uses this path to automatically load the executable's symbols when
+//! attaching to a process (i.e: without the user having to specify the
+//! executable via `file`).
+use crate::common::Pid;
+use crate::target::{Target, TargetResult};
+
+/// Target Extension - Provide the on-disk path of a process's executable.
+pub trait ExecFile: Target {
+    /// Return the absolute path of the executable of process `pid`, or of the
+    /// current process if `pid` is `None`.
+    ///
+    /// The path is interpreted by GDB using the remote filesystem (i.e: it
+    /// may be fetched via the [`HostIo`](crate::target::ext::host_io)
+    /// extension, unless the user has configured a local `sysroot`).
+    fn get_exec_file(&mut self, pid: Option<Pid>) -> TargetResult<&[u8], Self>;
+}
+
+define_ext!(ExecFileOps, ExecFile);
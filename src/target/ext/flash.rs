@@ -0,0 +1,51 @@
+//! Program the target's flash memory.
+//!
+//! When loading a program into a region of memory which is marked as flash
+//! in the target's memory map, GDB uses a dedicated set of packets instead of
+//! regular memory writes: the affected flash blocks are first erased
+//! ([`Flash::flash_erase`]), the new contents are then written
+//! ([`Flash::flash_write`]), and finally, GDB signals that flashing is
+//! complete ([`Flash::flash_done`]).
+//!
+//! _Note:_ GDB will only use these packets if the target reports its flash
+//! regions via the [`MemoryMap`](super::memory_map::MemoryMap) extension,
+//! e.g:
+//!
+//! ```xml
+//! <memory type="flash" start="0x08000000" length="0x100000">
+//!   <property name="blocksize">0x800</property>
+//! </memory>
+//! ```
+use crate::arch::Arch;
+use crate::target::{Target, TargetResult};
+
+/// Target Extension - Program the target's flash memory.
+pub trait Flash: Target {
+    /// Erase `length` bytes of flash, starting at `start_addr`.
+    ///
+    /// GDB only issues erases which are aligned to the flash region's
+    /// `blocksize` (as reported in the target's memory map).
+    fn flash_erase(
+        &mut self,
+        start_addr: <Self::Arch as Arch>::Usize,
+        length: <Self::Arch as Arch>::Usize,
+    ) -> TargetResult<(), Self>;
+
+    /// Write `data` to flash, starting at `start_addr`.
+    ///
+    /// Writes may be buffered until [`flash_done`](Self::flash_done) is
+    /// called, and GDB will only write to flash which was previously erased.
+    fn flash_write(
+        &mut self,
+        start_addr: <Self::Arch as Arch>::Usize,
+        data: &[u8],
+    ) -> TargetResult<(), Self>;
+
+    /// Commit any pending flash operations.
+    ///
+    /// GDB won't assume that any flash operations have completed until this
+    /// method has returned.
+    fn flash_done(&mut self) -> TargetResult<(), Self>;
+}
+
+define_ext!(FlashOps, Flash);
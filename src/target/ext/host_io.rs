@@ -0,0 +1,336 @@
+//! Provide Host I/O operations for the target.
+//!
+//! The GDB client uses the `vFile` family of packets to access files on the
+//! target's filesystem (e.g: to fetch the shared libraries loaded by a
+//! dynamically linked program).
+//!
+//! All values (flags, modes, and errno values) use the encodings defined by
+//! GDB's [File-I/O protocol](https://sourceware.org/gdb/onlinedocs/gdb/File_002dI_002fO-Remote-Protocol-Extension.html),
+//! which may differ from those used by the target's host OS.
+//!
+//! _Note:_ There is no `qSupported` feature for Host I/O. Instead, the GDB
+//! client probes for support by sending the `vFile` packets directly, with
+//! `gdbstub` replying with an empty response when this extension isn't
+//! implemented.
+use crate::common::Pid;
+use crate::target::Target;
+
+/// Flags used when opening a file. See [`HostIo::open`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HostIoOpenFlags(u32);
+
+impl HostIoOpenFlags {
+    /// Open for reading only.
+    pub const O_RDONLY: Self = HostIoOpenFlags(0x0);
+    /// Open for writing only.
+    pub const O_WRONLY: Self = HostIoOpenFlags(0x1);
+    /// Open for reading and writing.
+    pub const O_RDWR: Self = HostIoOpenFlags(0x2);
+    /// Append to the end of the file on each write.
+    pub const O_APPEND: Self = HostIoOpenFlags(0x8);
+    /// Create the file if it doesn't exist.
+    pub const O_CREAT: Self = HostIoOpenFlags(0x200);
+    /// Truncate the file to zero length.
+    pub const O_TRUNC: Self = HostIoOpenFlags(0x400);
+    /// Fail if the file already exists (when used with `O_CREAT`).
+    pub const O_EXCL: Self = HostIoOpenFlags(0x800);
+
+    /// Create a set of flags from its raw representation.
+    pub fn from_bits(bits: u32) -> HostIoOpenFlags {
+        HostIoOpenFlags(bits)
+    }
+
+    /// Return the raw representation of the flags.
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Return the file access mode (i.e: one of `O_RDONLY`, `O_WRONLY`, or
+    /// `O_RDWR`).
+    pub fn access_mode(self) -> HostIoOpenFlags {
+        HostIoOpenFlags(self.0 & 0x3)
+    }
+
+    /// Check if all of the flags in `other` are set.
+    ///
+    /// _Note:_ As `O_RDONLY` is `0`, use [`access_mode`](Self::access_mode) to
+    /// check the file access mode instead.
+    pub fn contains(self, other: HostIoOpenFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+/// The permission bits to use when creating a file. See [`HostIo::open`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HostIoOpenMode(u32);
+
+impl HostIoOpenMode {
+    /// Regular file.
+    pub const S_IFREG: Self = HostIoOpenMode(0o100000);
+    /// Directory.
+    pub const S_IFDIR: Self = HostIoOpenMode(0o40000);
+    /// Read permission, owner.
+    pub const S_IRUSR: Self = HostIoOpenMode(0o400);
+    /// Write permission, owner.
+    pub const S_IWUSR: Self = HostIoOpenMode(0o200);
+    /// Execute permission, owner.
+    pub const S_IXUSR: Self = HostIoOpenMode(0o100);
+    /// Read permission, group.
+    pub const S_IRGRP: Self = HostIoOpenMode(0o40);
+    /// Write permission, group.
+    pub const S_IWGRP: Self = HostIoOpenMode(0o20);
+    /// Execute permission, group.
+    pub const S_IXGRP: Self = HostIoOpenMode(0o10);
+    /// Read permission, others.
+    pub const S_IROTH: Self = HostIoOpenMode(0o4);
+    /// Write permission, others.
+    pub const S_IWOTH: Self = HostIoOpenMode(0o2);
+    /// Execute permission, others.
+    pub const S_IXOTH: Self = HostIoOpenMode(0o1);
+
+    /// Create a mode from its raw representation.
+    pub fn from_bits(bits: u32) -> HostIoOpenMode {
+        HostIoOpenMode(bits)
+    }
+
+    /// Return the raw representation of the mode.
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Check if all of the bits in `other` are set.
+    pub fn contains(self, other: HostIoOpenMode) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+/// Errno values reported back to the GDB client.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum HostIoErrno {
+    /// Operation not permitted.
+    EPERM = 1,
+    /// No such file or directory.
+    ENOENT = 2,
+    /// Interrupted system call.
+    EINTR = 4,
+    /// Bad file number.
+    EBADF = 9,
+    /// Permission denied.
+    EACCES = 13,
+    /// Bad address.
+    EFAULT = 14,
+    /// Device or resource busy.
+    EBUSY = 16,
+    /// File exists.
+    EEXIST = 17,
+    /// No such device.
+    ENODEV = 19,
+    /// Not a directory.
+    ENOTDIR = 20,
+    /// Is a directory.
+    EISDIR = 21,
+    /// Invalid argument.
+    EINVAL = 22,
+    /// File table overflow.
+    ENFILE = 23,
+    /// Too many open files.
+    EMFILE = 24,
+    /// File too large.
+    EFBIG = 27,
+    /// No space left on device.
+    ENOSPC = 28,
+    /// Illegal seek.
+    ESPIPE = 29,
+    /// Read-only file system.
+    EROFS = 30,
+    /// File name too long.
+    ENAMETOOLONG = 91,
+    /// Unknown errno.
+    EUNKNOWN = 9999,
+}
+
+/// An error which may occur during a Host I/O operation.
+#[derive(Debug)]
+pub enum HostIoError<E> {
+    /// An operation-specific non-fatal error, which is reported back to the
+    /// GDB client.
+    Errno(HostIoErrno),
+    /// A target-specific fatal error.
+    ///
+    /// **WARNING:** Returning this error will immediately halt the target's
+    /// execution and return a `GdbStubError::TargetError` from `GdbStub::run`!
+    Fatal(E),
+}
+
+impl<E> From<HostIoErrno> for HostIoError<E> {
+    fn from(errno: HostIoErrno) -> HostIoError<E> {
+        HostIoError::Errno(errno)
+    }
+}
+
+/// Converts a [`std::io::Error`] into a `HostIoError::Errno`, using the
+/// closest matching errno value.
+#[cfg(feature = "std")]
+impl<E> From<std::io::Error> for HostIoError<E> {
+    fn from(e: std::io::Error) -> HostIoError<E> {
+        use std::io::ErrorKind::*;
+        let errno = match e.kind() {
+            NotFound => HostIoErrno::ENOENT,
+            PermissionDenied => HostIoErrno::EACCES,
+            AlreadyExists => HostIoErrno::EEXIST,
+            Interrupted => HostIoErrno::EINTR,
+            InvalidInput | InvalidData => HostIoErrno::EINVAL,
+            _ => HostIoErrno::EUNKNOWN,
+        };
+        HostIoError::Errno(errno)
+    }
+}
+
+/// A specialized `Result` type for Host I/O operations. Supports reporting
+/// non-fatal errors back to the GDB client.
+///
+/// See [`HostIoError`] for more details.
+pub type HostIoResult<T, Tgt> = Result<T, HostIoError<<Tgt as Target>::Error>>;
+
+/// File metadata, as reported by [`HostIoFstat::fstat`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HostIoStat {
+    /// The device.
+    pub st_dev: u32,
+    /// The inode.
+    pub st_ino: u32,
+    /// Protection bits (see [`HostIoOpenMode`]).
+    pub st_mode: u32,
+    /// The number of hard links.
+    pub st_nlink: u32,
+    /// The user id of the owner.
+    pub st_uid: u32,
+    /// The group id of the owner.
+    pub st_gid: u32,
+    /// The device type, if an inode device.
+    pub st_rdev: u32,
+    /// The size of the file (in bytes).
+    pub st_size: u64,
+    /// The blocksize for the filesystem.
+    pub st_blksize: u64,
+    /// The number of blocks allocated.
+    pub st_blocks: u64,
+    /// The time of last access.
+    pub st_atime: u32,
+    /// The time of last modification.
+    pub st_mtime: u32,
+    /// The time of last change.
+    pub st_ctime: u32,
+}
+
+/// Target Extension - Perform I/O operations on the target's filesystem.
+///
+/// At minimum, targets must support opening, reading, and closing files. The
+/// remaining operations are optional, and will report `EINVAL` to the GDB
+/// client when left unimplemented.
+pub trait HostIo: Target {
+    /// Open a file at `filename`, returning a file descriptor on success.
+    ///
+    /// `mode` is only used when a new file is created (i.e: when `flags`
+    /// contains `O_CREAT`).
+    ///
+    /// Filenames are _not_ guaranteed to be valid UTF-8, hence the use of
+    /// `&[u8]` as opposed to `&str`.
+    fn open(
+        &mut self,
+        filename: &[u8],
+        flags: HostIoOpenFlags,
+        mode: HostIoOpenMode,
+    ) -> HostIoResult<usize, Self>;
+
+    /// Close the open file corresponding to `fd`.
+    fn close(&mut self, fd: usize) -> HostIoResult<(), Self>;
+
+    /// Read data from the open file corresponding to `fd`, starting at
+    /// `offset`.
+    ///
+    /// Up to `buf.len()` bytes should be read into `buf`, returning the number
+    /// of bytes read. A return value of `0` indicates the end of the file.
+    fn pread(&mut self, fd: usize, offset: usize, buf: &mut [u8]) -> HostIoResult<usize, Self>;
+
+    /// Support for writing to open files.
+    #[inline(always)]
+    fn support_pwrite(&mut self) -> Option<HostIoPwriteOps<Self>> {
+        None
+    }
+
+    /// Support for querying the metadata of open files.
+    #[inline(always)]
+    fn support_fstat(&mut self) -> Option<HostIoFstatOps<Self>> {
+        None
+    }
+
+    /// Support for deleting files.
+    #[inline(always)]
+    fn support_unlink(&mut self) -> Option<HostIoUnlinkOps<Self>> {
+        None
+    }
+
+    /// Support for reading the target of symbolic links.
+    #[inline(always)]
+    fn support_readlink(&mut self) -> Option<HostIoReadlinkOps<Self>> {
+        None
+    }
+
+    /// Support for selecting the filesystem that subsequent operations act
+    /// upon.
+    #[inline(always)]
+    fn support_setfs(&mut self) -> Option<HostIoSetfsOps<Self>> {
+        None
+    }
+}
+
+define_ext!(HostIoOps, HostIo);
+
+/// Nested Target Extension - Write to open files.
+pub trait HostIoPwrite: HostIo {
+    /// Write `data` to the open file corresponding to `fd`, starting at
+    /// `offset`.
+    ///
+    /// Return the number of bytes written.
+    fn pwrite(&mut self, fd: usize, offset: usize, data: &[u8]) -> HostIoResult<usize, Self>;
+}
+
+define_ext!(HostIoPwriteOps, HostIoPwrite);
+
+/// Nested Target Extension - Query the metadata of open files.
+pub trait HostIoFstat: HostIo {
+    /// Return the metadata of the open file corresponding to `fd`.
+    fn fstat(&mut self, fd: usize) -> HostIoResult<HostIoStat, Self>;
+}
+
+define_ext!(HostIoFstatOps, HostIoFstat);
+
+/// Nested Target Extension - Delete files.
+pub trait HostIoUnlink: HostIo {
+    /// Delete the file at `filename`.
+    fn unlink(&mut self, filename: &[u8]) -> HostIoResult<(), Self>;
+}
+
+define_ext!(HostIoUnlinkOps, HostIoUnlink);
+
+/// Nested Target Extension - Read the target of symbolic links.
+pub trait HostIoReadlink: HostIo {
+    /// Read the target of the symbolic link at `filename` into `buf`,
+    /// returning the number of bytes written.
+    fn readlink(&mut self, filename: &[u8], buf: &mut [u8]) -> HostIoResult<usize, Self>;
+}
+
+define_ext!(HostIoReadlinkOps, HostIoReadlink);
+
+/// Nested Target Extension - Select the filesystem that subsequent operations
+/// act upon.
+pub trait HostIoSetfs: HostIo {
+    /// Select the filesystem as seen by process `pid`.
+    ///
+    /// `None` selects the filesystem as seen by the `gdbstub` itself.
+    fn setfs(&mut self, pid: Option<Pid>) -> HostIoResult<(), Self>;
+}
+
+define_ext!(HostIoSetfsOps, HostIoSetfs);
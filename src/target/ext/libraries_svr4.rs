@@ -0,0 +1,42 @@
+//! List the shared libraries loaded by an SVR4 (e.g: Linux) process.
+//!
+//! GDB uses this list to implement `info sharedlibrary`, and to automatically
+//! load the symbols of shared libraries as they are loaded by the dynamic
+//! linker.
+use crate::arch::Arch;
+use crate::target::Target;
+
+/// Describes a shared library loaded by the process.
+///
+/// Each library corresponds to an entry in the dynamic linker's list of
+/// `link_map` structures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LibrarySvr4<'a, U> {
+    /// The library's path (i.e: `link_map.l_name`).
+    ///
+    /// Paths are _not_ guaranteed to be valid UTF-8, hence the use of `&[u8]`
+    /// as opposed to `&str`.
+    pub name: &'a [u8],
+    /// The address of the library's `link_map` structure.
+    pub lm: U,
+    /// The difference between the library's load address and the address
+    /// it was linked at (i.e: `link_map.l_addr`).
+    pub l_addr: U,
+    /// The address of the library's dynamic section (i.e: `link_map.l_ld`).
+    pub l_ld: U,
+}
+
+/// Target Extension - List the shared libraries loaded by an SVR4 process.
+///
+/// _Note:_ The resulting library list is assembled in the `GdbStub`'s packet
+/// buffer, which must be large enough to hold the entire list.
+pub trait LibrariesSvr4: Target {
+    /// Call `library` with a description of each loaded shared library.
+    #[allow(clippy::type_complexity)]
+    fn list_libraries(
+        &mut self,
+        library: &mut dyn FnMut(LibrarySvr4<'_, <Self::Arch as Arch>::Usize>),
+    ) -> Result<(), Self::Error>;
+}
+
+define_ext!(LibrariesSvr4Ops, LibrariesSvr4);
@@ -0,0 +1,49 @@
+//! Describe the target's platform to LLDB.
+//!
+//! When connecting to a remote stub, LLDB sends `qHostInfo` and `qProcessInfo`
+//! packets to learn the target's triple, byte order, pointer size, and OS
+//! before it starts reading registers and memory.
+//!
+//! `gdbstub` fills in the pointer size using the target's
+//! [`Arch::Usize`](crate::arch::Arch::Usize), and the process ID using the
+//! currently selected process. Everything else is provided by the target.
+//!
+//! _Note:_ These packets are LLDB-specific, and are never sent by GDB, so
+//! implementing this extension has no effect on GDB sessions. When it isn't
+//! implemented, both packets get an empty reply, and LLDB infers what it can
+//! from the target description XML.
+use crate::target::Target;
+
+/// The target's byte order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endianness {
+    /// Little-endian.
+    Little,
+    /// Big-endian.
+    Big,
+}
+
+/// Describes the target's platform.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HostInfo<'a> {
+    /// The target's LLVM triple (e.g: `armv4t-none-eabi`).
+    pub triple: &'a str,
+    /// The target's vendor (e.g: `apple`), if any.
+    pub vendor: Option<&'a str>,
+    /// The target's OS type (e.g: `linux`), if any.
+    pub ostype: Option<&'a str>,
+    /// The target's byte order, if it isn't implied by `triple`.
+    pub endian: Option<Endianness>,
+}
+
+/// Target Extension - Describe the target's platform to LLDB.
+pub trait LldbHostInfo: Target {
+    /// Describe the target's platform.
+    ///
+    /// The same description is used to answer both `qHostInfo` and
+    /// `qProcessInfo`, as a debugged target generally _is_ the host from the
+    /// client's point of view.
+    fn get_host_info(&mut self) -> HostInfo<'_>;
+}
+
+define_ext!(LldbHostInfoOps, LldbHostInfo);
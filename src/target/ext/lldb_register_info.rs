@@ -0,0 +1,133 @@
+//! Describe the target's registers to LLDB.
+//!
+//! Unlike GDB, LLDB doesn't rely on the target description XML to learn the
+//! target's register layout. Instead, it enumerates the target's registers one
+//! at a time using `qRegisterInfo<hex>` packets, starting at register `0` and
+//! stopping at the first register the target reports as non-existent.
+//!
+//! Register numbers are the same as those used by the `p` / `P` packets (see
+//! [`SingleRegisterAccess`](super::base::SingleRegisterAccess)), and
+//! `offset`s index into the buffer used by the `g` / `G` packets.
+//!
+//! _Note:_ This extension is entirely optional. When it isn't implemented,
+//! `qRegisterInfo` gets an empty reply, and LLDB falls back to reading the
+//! target description XML.
+use crate::target::Target;
+
+/// The encoding of a register's value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// Unsigned integer.
+    Uint,
+    /// Signed integer.
+    Sint,
+    /// IEEE 754 floating point.
+    IEEE754,
+    /// Vector register.
+    Vector,
+}
+
+/// The preferred format for displaying a register's value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Binary.
+    Binary,
+    /// Decimal.
+    Decimal,
+    /// Hexadecimal.
+    Hex,
+    /// Floating point.
+    Float,
+    /// Vector of signed 8-bit integers.
+    VectorSInt8,
+    /// Vector of unsigned 8-bit integers.
+    VectorUInt8,
+    /// Vector of signed 16-bit integers.
+    VectorSInt16,
+    /// Vector of unsigned 16-bit integers.
+    VectorUInt16,
+    /// Vector of signed 32-bit integers.
+    VectorSInt32,
+    /// Vector of unsigned 32-bit integers.
+    VectorUInt32,
+    /// Vector of 32-bit floats.
+    VectorFloat32,
+    /// Vector of unsigned 128-bit integers.
+    VectorUInt128,
+}
+
+/// The architecture-independent role of a register.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Generic {
+    /// Program counter.
+    Pc,
+    /// Stack pointer.
+    Sp,
+    /// Frame pointer.
+    Fp,
+    /// Return address.
+    Ra,
+    /// Flags register.
+    Flags,
+    /// Function argument 1.
+    Arg1,
+    /// Function argument 2.
+    Arg2,
+    /// Function argument 3.
+    Arg3,
+    /// Function argument 4.
+    Arg4,
+    /// Function argument 5.
+    Arg5,
+    /// Function argument 6.
+    Arg6,
+    /// Function argument 7.
+    Arg7,
+    /// Function argument 8.
+    Arg8,
+}
+
+/// Describes a single register.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Register<'a> {
+    /// The register's name.
+    pub name: &'a str,
+    /// An alternative name for the register (e.g: `fp` for `r11` on ARM).
+    pub alt_name: Option<&'a str>,
+    /// The register's size, in bits.
+    pub bitsize: usize,
+    /// The register's offset (in bytes) in the `g` / `G` packet's buffer.
+    pub offset: usize,
+    /// The encoding of the register's value.
+    pub encoding: Encoding,
+    /// The preferred format for displaying the register's value.
+    pub format: Format,
+    /// The name of the register set the register belongs to (e.g: "General
+    /// Purpose Registers").
+    pub set: &'a str,
+    /// The register's GCC (i.e: EH frame) register number, if any.
+    pub gcc: Option<usize>,
+    /// The register's DWARF register number, if any.
+    pub dwarf: Option<usize>,
+    /// The register's architecture-independent role, if any.
+    pub generic: Option<Generic>,
+    /// The registers this register is a part of (e.g: `rax` for `eax`), if
+    /// any.
+    pub container_regs: Option<&'a [usize]>,
+    /// The registers whose values are changed by writing to this register, if
+    /// any.
+    pub invalidate_regs: Option<&'a [usize]>,
+}
+
+/// Target Extension - Describe the target's registers to LLDB.
+pub trait LldbRegisterInfo: Target {
+    /// Describe the register with the given register number, or return `None`
+    /// if the target has no such register.
+    ///
+    /// LLDB stops enumerating registers at the first register number for
+    /// which `None` is returned, so registers must be numbered contiguously
+    /// starting from `0`.
+    fn get_register_info(&mut self, reg_id: usize) -> Option<Register<'_>>;
+}
+
+define_ext!(LldbRegisterInfoOps, LldbRegisterInfo);
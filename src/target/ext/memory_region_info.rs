@@ -0,0 +1,43 @@
+//! Describe the memory region containing a given address.
+//!
+//! LLDB queries the attributes of memory regions to implement its `memory
+//! region` command, and to avoid reading memory which it believes to be
+//! unmapped.
+//!
+//! _Note:_ There is no `qSupported` feature for memory region info. Instead,
+//! the client probes for support by sending a bare `qMemoryRegionInfo`
+//! packet, which `gdbstub` replies to with `OK` when this extension is
+//! implemented.
+use crate::arch::Arch;
+use crate::target::{Target, TargetResult};
+
+/// Describes a region of memory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryRegion<U> {
+    /// The region's start address.
+    pub start: U,
+    /// The region's size, in bytes.
+    pub size: U,
+    /// Whether the region is readable.
+    pub readable: bool,
+    /// Whether the region is writable.
+    pub writable: bool,
+    /// Whether the region is executable.
+    pub executable: bool,
+}
+
+/// Target Extension - Describe the memory region containing a given address.
+pub trait MemoryRegionInfo: Target {
+    /// Return the memory region containing `addr`.
+    ///
+    /// If `addr` is unmapped, return the unmapped region containing it (i.e:
+    /// the gap between the surrounding mapped regions), with all permissions
+    /// cleared. The client relies on this to step from one region to the next
+    /// when enumerating the target's memory map.
+    fn get_memory_region(
+        &mut self,
+        addr: <Self::Arch as Arch>::Usize,
+    ) -> TargetResult<MemoryRegion<<Self::Arch as Arch>::Usize>, Self>;
+}
+
+define_ext!(MemoryRegionInfoOps, MemoryRegionInfo);
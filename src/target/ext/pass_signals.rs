@@ -0,0 +1,71 @@
+//! Control how signals are delivered to the target.
+//!
+//! GDB's `handle <signal>` command configures two distinct sets of signals:
+//!
+//! - **Passed signals** (`handle <signal> nostop noprint pass`): signals which
+//!   aren't of interest to the user (e.g: a flood of `SIGUSR1`s used for IPC).
+//!   Instead of stopping and reporting each one to GDB (only to be resumed with
+//!   the very same signal), the target should deliver these signals to the
+//!   process directly. Configured via [`PassSignals`].
+//! - **Program signals** (`handle <signal> nopass`): the signals which the
+//!   process is allowed to see when they arrive while it's running. Any other
+//!   signal should be discarded instead of being delivered to the process.
+//!   Configured via [`ProgramSignals`].
+//!
+//! Put another way: the passed signals determine whether a signal _stops_ the
+//! target, whereas the program signals determine whether a signal which
+//! arrives while the target is running is _delivered_ to the process.
+//!
+//! Neither set influences the signal GDB explicitly resumes the target with
+//! (e.g: via `signal SIGUSR1`, or when passing along a signal which stopped
+//! the target). Such signals are always handed to the target as part of the
+//! [`ResumeAction`](crate::target::ext::base::ResumeAction).
+use crate::common::Signal;
+use crate::target::{Target, TargetResult};
+
+/// Target Extension - Pass signals directly to the target, without stopping.
+pub trait PassSignals: Target {
+    /// Set the signals which should be passed directly to the process,
+    /// replacing any previously set signals.
+    ///
+    /// The target should store this set, and consult it whenever the process
+    /// receives a signal while resumed: signals in the set should be delivered
+    /// to the process _without_ stopping execution, and without reporting a
+    /// stop reason to GDB.
+    ///
+    /// See [`Signal`] for details on how GDB numbers signals.
+    fn set_pass_signals(
+        &mut self,
+        signals: &mut dyn Iterator<Item = Signal>,
+    ) -> TargetResult<(), Self>;
+
+    /// Support for restricting which signals are delivered to the process.
+    #[inline(always)]
+    fn support_program_signals(&mut self) -> Option<ProgramSignalsOps<Self>> {
+        None
+    }
+}
+
+define_ext!(PassSignalsOps, PassSignals);
+
+/// Nested Target Extension - Restrict which signals are delivered to the
+/// process.
+pub trait ProgramSignals: PassSignals {
+    /// Set the signals which the process is allowed to receive, replacing any
+    /// previously set signals. Until this method is called, all signals should
+    /// be delivered to the process.
+    ///
+    /// The target should store this set, and consult it whenever it would
+    /// deliver a signal to the process: signals which aren't in the set should
+    /// be silently discarded.
+    ///
+    /// This set does _not_ apply to signals which GDB explicitly resumes the
+    /// target with (i.e: `ResumeAction::ContinueWithSignal` and
+    /// `ResumeAction::StepWithSignal`), which should always be delivered.
+    fn set_program_signals(
+        &mut self,
+        signals: &mut dyn Iterator<Item = Signal>,
+    ) -> TargetResult<(), Self>;
+}
+
+define_ext!(ProgramSignalsOps, ProgramSignals);
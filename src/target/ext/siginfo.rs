@@ -0,0 +1,47 @@
+//! Provide (and optionally modify) the signal information of the stopped
+//! thread.
+//!
+//! When a thread stops due to a signal, GDB fetches the signal's `siginfo_t`
+//! to populate the `$_siginfo` convenience variable, which describes the
+//! signal in more detail (e.g: the faulting address of a `SIGSEGV`, or the
+//! PID of the process which sent a `SIGTERM`). Writing to `$_siginfo` (e.g:
+//! to tweak the details of a signal before it's delivered to the process)
+//! requires the [`SigInfoWrite`] nested extension.
+//!
+//! GDB interprets the raw siginfo bytes using the layout of the target
+//! platform's `siginfo_t` (as determined by the architecture and OS ABI).
+use crate::common::Tid;
+use crate::target::{Target, TargetResult};
+
+/// Target Extension - Provide the signal information of the stopped thread.
+pub trait SigInfo: Target {
+    /// Return the raw contents of the `siginfo_t` describing the last signal
+    /// received by thread `tid`.
+    ///
+    /// Single threaded targets will always receive the `Tid` of their only
+    /// thread (i.e: `1`).
+    ///
+    /// Return an error if the thread didn't stop due to a signal.
+    fn get_siginfo(&mut self, tid: Tid) -> TargetResult<&[u8], Self>;
+
+    /// Support for modifying the signal information of the stopped thread.
+    #[inline(always)]
+    fn support_siginfo_write(&mut self) -> Option<SigInfoWriteOps<'_, Self>> {
+        None
+    }
+}
+
+define_ext!(SigInfoOps, SigInfo);
+
+/// Nested Target Extension - Modify the signal information of the stopped
+/// thread.
+pub trait SigInfoWrite: SigInfo {
+    /// Overwrite the raw contents of thread `tid`'s `siginfo_t`, starting at
+    /// byte `offset`, with `data`.
+    ///
+    /// The updated signal information should be delivered to the process
+    /// alongside the signal once the thread is resumed.
+    fn set_siginfo(&mut self, tid: Tid, offset: usize, data: &[u8]) -> TargetResult<(), Self>;
+}
+
+define_ext!(SigInfoWriteOps, SigInfoWrite);
@@ -0,0 +1,47 @@
+//! Look up the addresses of symbols in the debugged program.
+//!
+//! Some targets need to know the address of certain symbols in order to
+//! function (e.g: a stub which tracks shared libraries might need to locate
+//! the dynamic linker's `_dl_debug_state`), but have no way to resolve them
+//! on their own. Instead, the target can ask GDB to look them up.
+//!
+//! Whenever GDB is ready to serve symbol lookups (e.g: after connecting, or
+//! after loading a new object file), it notifies the target via
+//! [`SymbolLookup::start_symbol_lookup`]. `gdbstub` then repeatedly asks the
+//! target for the next symbol it needs ([`SymbolLookup::next_symbol`]), and
+//! passes GDB's answer back to the target
+//! ([`SymbolLookup::symbol_resolved`]), until the target doesn't need any more
+//! symbols.
+use crate::arch::Arch;
+use crate::target::{Target, TargetResult};
+
+/// Target Extension - Look up the addresses of symbols in the debugged
+/// program.
+pub trait SymbolLookup: Target {
+    /// GDB is ready to serve symbol lookups.
+    ///
+    /// This may be called multiple times over the course of a debugging
+    /// session (e.g: each time a new object file is loaded), so targets should
+    /// (re)request any symbols that they still need, including ones GDB was
+    /// previously unable to resolve.
+    fn start_symbol_lookup(&mut self) -> TargetResult<(), Self>;
+
+    /// Return the name of the next symbol whose address the target needs, or
+    /// `None` if the target doesn't need any more symbols.
+    ///
+    /// Targets must not request the same symbol more than once per lookup (as
+    /// started by [`start_symbol_lookup`](Self::start_symbol_lookup)), as GDB
+    /// will keep failing to resolve symbols it doesn't know about.
+    fn next_symbol(&mut self) -> TargetResult<Option<&[u8]>, Self>;
+
+    /// Report the address of a symbol previously requested via
+    /// [`next_symbol`](Self::next_symbol), or `None` if GDB could not resolve
+    /// it.
+    fn symbol_resolved(
+        &mut self,
+        name: &[u8],
+        addr: Option<<Self::Arch as Arch>::Usize>,
+    ) -> TargetResult<(), Self>;
+}
+
+define_ext!(SymbolLookupOps, SymbolLookup);
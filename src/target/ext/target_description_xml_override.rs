@@ -0,0 +1,348 @@
+//! Override the target description XML specified by `Target::Arch`.
+use core::fmt::{self, Display, Write};
+
+use crate::arch::TargetDescriptionFeature;
+use crate::target::Target;
+
+/// Target Extension - Override the target description XML specified by
+/// `Target::Arch`.
+///
+/// _Note:_ Unless you're working with a particularly dynamic,
+/// runtime-configurable target, it's unlikely that you'll need to implement
+/// this extension.
+///
+/// Targets with a runtime-configurable register set can use a
+/// [`TargetDescriptionBuilder`] to generate their description XML.
+pub trait TargetDescriptionXmlOverride: Target {
+    /// Return the target's description XML file (`target.xml`).
+    ///
+    /// Refer to the
+    /// [target_description_xml](crate::arch::Arch::target_description_xml)
+    /// docs for more info.
+    fn target_description_xml(&self) -> &str;
+
+    /// Return any additional target description features, which are served
+    /// to GDB as separate XML files.
+    ///
+    /// Refer to the
+    /// [target_description_xml_features](crate::arch::Arch::target_description_xml_features)
+    /// docs for more info.
+    fn target_description_xml_features(&self) -> &[TargetDescriptionFeature<'_>] {
+        &[]
+    }
+}
+
+define_ext!(
+    TargetDescriptionXmlOverrideOps,
+    TargetDescriptionXmlOverride
+);
+
+/// An error which may occur when building a target description via
+/// [`TargetDescriptionBuilder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetDescriptionBuilderError {
+    /// The provided buffer is too small to fit the target description.
+    BufferTooSmall,
+}
+
+impl Display for TargetDescriptionBuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use self::TargetDescriptionBuilderError::*;
+        match self {
+            BufferTooSmall => write!(
+                f,
+                "The provided buffer is too small to fit the target description."
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TargetDescriptionBuilderError {}
+
+/// A register declared via [`TargetDescriptionFeatureBuilder::reg`].
+///
+/// Corresponds to a `<reg>` element in the target description. See the [GDB
+/// docs](https://sourceware.org/gdb/current/onlinedocs/gdb/Target-Description-Format.html#Registers)
+/// for details on each attribute.
+#[derive(Debug, Clone, Copy)]
+pub struct TargetDescriptionReg<'a, N> {
+    name: N,
+    bitsize: usize,
+    regnum: Option<usize>,
+    ty: Option<&'a str>,
+    group: Option<&'a str>,
+}
+
+impl<'a, N: Display> TargetDescriptionReg<'a, N> {
+    /// Declare a register with the given name and size (in bits).
+    ///
+    /// The name can be any `Display` type, making it possible to generate
+    /// register names without allocating (e.g: `format_args!("r{}", i)`).
+    pub fn new(name: N, bitsize: usize) -> Self {
+        TargetDescriptionReg {
+            name,
+            bitsize,
+            regnum: None,
+            ty: None,
+            group: None,
+        }
+    }
+
+    /// Set the register's number. If omitted, GDB numbers the register one
+    /// greater than the preceding register.
+    pub fn regnum(mut self, regnum: usize) -> Self {
+        self.regnum = Some(regnum);
+        self
+    }
+
+    /// Set the register's type (e.g: `int`, `code_ptr`, `ieee_double`).
+    pub fn ty(mut self, ty: &'a str) -> Self {
+        self.ty = Some(ty);
+        self
+    }
+
+    /// Set the register group the register belongs to (e.g: `general`,
+    /// `float`, `vector`).
+    pub fn group(mut self, group: &'a str) -> Self {
+        self.group = Some(group);
+        self
+    }
+}
+
+/// Helper to generate target description XML at runtime, without allocating.
+///
+/// The XML is written into a caller-provided buffer. The resulting string can
+/// then be returned from
+/// [`TargetDescriptionXmlOverride::target_description_xml`].
+///
+/// _Note:_ The builder only generates the XML. It is still up to the target
+/// to ensure its `Registers` and `RegId` implementations match the declared
+/// registers.
+///
+/// # Example
+///
+/// ```
+/// use gdbstub::target::ext::target_description_xml_override::{
+///     TargetDescriptionBuilder, TargetDescriptionReg,
+/// };
+///
+/// let num_gprs = 16;
+///
+/// let mut buf = [0; 1024];
+/// let mut builder = TargetDescriptionBuilder::new(&mut buf);
+/// builder
+///     .architecture("riscv:rv32")
+///     .feature("org.gnu.gdb.riscv.cpu", |f| {
+///         for i in 0..num_gprs {
+///             f.reg(TargetDescriptionReg::new(format_args!("x{}", i), 32));
+///         }
+///         f.reg(TargetDescriptionReg::new("pc", 32).ty("code_ptr"));
+///     });
+/// let xml = builder.finish().unwrap();
+///
+/// assert!(xml.starts_with(r#"<target version="1.0"><architecture>riscv:rv32</architecture>"#));
+/// assert!(xml.contains(r#"<reg name="x15" bitsize="32"/>"#));
+/// assert!(xml.ends_with("</feature></target>"));
+/// ```
+pub struct TargetDescriptionBuilder<'a> {
+    out: XmlBuf<'a>,
+}
+
+impl<'a> TargetDescriptionBuilder<'a> {
+    /// Create a new `TargetDescriptionBuilder`, which writes into `buf`.
+    pub fn new(buf: &'a mut [u8]) -> TargetDescriptionBuilder<'a> {
+        let mut out = XmlBuf {
+            buf,
+            len: 0,
+            overflow: false,
+        };
+        let _ = out.write_str(r#"<target version="1.0">"#);
+        TargetDescriptionBuilder { out }
+    }
+
+    /// Set the target's architecture (e.g: `armv4t`, `i386:x86-64`).
+    pub fn architecture(&mut self, arch: &str) -> &mut Self {
+        let _ = write!(self.out, "<architecture>{}</architecture>", Escaped(arch));
+        self
+    }
+
+    /// Declare a feature with the given name (e.g: `org.gnu.gdb.arm.core`),
+    /// whose registers are declared via `f`.
+    pub fn feature(
+        &mut self,
+        name: &str,
+        f: impl FnOnce(&mut TargetDescriptionFeatureBuilder<'_, 'a>),
+    ) -> &mut Self {
+        let _ = write!(self.out, r#"<feature name="{}">"#, Escaped(name));
+        f(&mut TargetDescriptionFeatureBuilder { out: &mut self.out });
+        let _ = self.out.write_str("</feature>");
+        self
+    }
+
+    /// Finish the target description, returning the generated XML.
+    pub fn finish(self) -> Result<&'a str, TargetDescriptionBuilderError> {
+        let mut out = self.out;
+        let _ = out.write_str("</target>");
+        if out.overflow {
+            return Err(TargetDescriptionBuilderError::BufferTooSmall);
+        }
+
+        let buf: &'a [u8] = out.buf;
+        // infallible, as only whole `str`s are ever written into the buffer
+        Ok(core::str::from_utf8(&buf[..out.len]).unwrap())
+    }
+}
+
+/// Declares the registers of a single feature. See
+/// [`TargetDescriptionBuilder::feature`].
+pub struct TargetDescriptionFeatureBuilder<'b, 'a> {
+    out: &'b mut XmlBuf<'a>,
+}
+
+impl TargetDescriptionFeatureBuilder<'_, '_> {
+    /// Declare a register.
+    pub fn reg<N: Display>(&mut self, reg: TargetDescriptionReg<'_, N>) -> &mut Self {
+        let _ = write!(
+            self.out,
+            r#"<reg name="{}" bitsize="{}""#,
+            Escaped(reg.name),
+            reg.bitsize
+        );
+        if let Some(regnum) = reg.regnum {
+            let _ = write!(self.out, r#" regnum="{}""#, regnum);
+        }
+        if let Some(ty) = reg.ty {
+            let _ = write!(self.out, r#" type="{}""#, Escaped(ty));
+        }
+        if let Some(group) = reg.group {
+            let _ = write!(self.out, r#" group="{}""#, Escaped(group));
+        }
+        let _ = self.out.write_str("/>");
+        self
+    }
+}
+
+/// Fixed-size output buffer, which records (instead of panicking on) overflow.
+struct XmlBuf<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+    overflow: bool,
+}
+
+impl Write for XmlBuf<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let dst = self.buf[self.len..].get_mut(..s.len());
+        match dst {
+            Some(dst) if !self.overflow => {
+                dst.copy_from_slice(s.as_bytes());
+                self.len += s.len();
+                Ok(())
+            }
+            _ => {
+                self.overflow = true;
+                Err(fmt::Error)
+            }
+        }
+    }
+}
+
+/// Escapes XML special characters when displaying the wrapped value.
+struct Escaped<T>(T);
+
+impl<T: Display> Display for Escaped<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct EscapingWriter<'a, 'b>(&'a mut fmt::Formatter<'b>);
+
+        impl Write for EscapingWriter<'_, '_> {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                for c in s.chars() {
+                    match c {
+                        '&' => self.0.write_str("&amp;")?,
+                        '<' => self.0.write_str("&lt;")?,
+                        '>' => self.0.write_str("&gt;")?,
+                        '"' => self.0.write_str("&quot;")?,
+                        '\'' => self.0.write_str("&apos;")?,
+                        c => self.0.write_char(c)?,
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        write!(EscapingWriter(f), "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_target_description() {
+        let mut buf = [0; 512];
+        let mut builder = TargetDescriptionBuilder::new(&mut buf);
+        builder
+            .architecture("arm")
+            .feature("org.gnu.gdb.arm.core", |f| {
+                f.reg(TargetDescriptionReg::new(format_args!("r{}", 0), 32).ty("uint32"))
+                    .reg(TargetDescriptionReg::new("cpsr", 32).regnum(25));
+            })
+            .feature("org.gnu.gdb.arm.vfp", |f| {
+                f.reg(
+                    TargetDescriptionReg::new("fpscr", 32)
+                        .ty("int")
+                        .group("float"),
+                );
+            });
+
+        assert_eq!(
+            builder.finish().unwrap(),
+            concat!(
+                r#"<target version="1.0"><architecture>arm</architecture>"#,
+                r#"<feature name="org.gnu.gdb.arm.core">"#,
+                r#"<reg name="r0" bitsize="32" type="uint32"/>"#,
+                r#"<reg name="cpsr" bitsize="32" regnum="25"/>"#,
+                r#"</feature>"#,
+                r#"<feature name="org.gnu.gdb.arm.vfp">"#,
+                r#"<reg name="fpscr" bitsize="32" type="int" group="float"/>"#,
+                r#"</feature></target>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn escapes_special_chars() {
+        let mut buf = [0; 512];
+        let mut builder = TargetDescriptionBuilder::new(&mut buf);
+        builder.feature("a&b", |f| {
+            f.reg(TargetDescriptionReg::new("<\"r\">", 8));
+        });
+
+        assert_eq!(
+            builder.finish().unwrap(),
+            concat!(
+                r#"<target version="1.0"><feature name="a&amp;b">"#,
+                r#"<reg name="&lt;&quot;r&quot;&gt;" bitsize="8"/>"#,
+                r#"</feature></target>"#,
+            )
+        );
+    }
+
+    #[test]
+    fn buffer_too_small() {
+        let expected = r#"<target version="1.0"><architecture>arm</architecture></target>"#;
+
+        let mut buf = [0; 128];
+        for len in 0..=expected.len() {
+            let mut builder = TargetDescriptionBuilder::new(&mut buf[..len]);
+            builder.architecture("arm");
+            let res = builder.finish();
+            if len == expected.len() {
+                assert_eq!(res, Ok(expected));
+            } else {
+                assert_eq!(res, Err(TargetDescriptionBuilderError::BufferTooSmall));
+            }
+        }
+    }
+}
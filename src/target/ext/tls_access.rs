@@ -0,0 +1,38 @@
+//! Resolve the addresses of thread-local variables.
+//!
+//! When the GDB client can't compute the address of a thread-local variable
+//! on its own (e.g: because it doesn't know how the target's dynamic linker
+//! lays out TLS blocks), it asks the target to resolve the address instead.
+//!
+//! _Note:_ There is no `qSupported` feature for TLS access. Instead, the GDB
+//! client probes for support by sending the `qGetTLSAddr` packet directly,
+//! with `gdbstub` replying with an empty response when this extension isn't
+//! implemented.
+use crate::arch::Arch;
+use crate::common::Tid;
+use crate::target::{Target, TargetResult};
+
+/// Target Extension - Resolve the addresses of thread-local variables.
+pub trait TlsAccess: Target {
+    /// Return the address of the thread-local variable at `offset` within the
+    /// TLS block of thread `tid`.
+    ///
+    /// `load_module` identifies the object file (i.e: executable or shared
+    /// library) which the variable belongs to. On SVR4 systems, this is the
+    /// address of the object's `link_map` structure, as maintained by the
+    /// dynamic linker.
+    ///
+    /// Single threaded targets will always receive the `Tid` of their only
+    /// thread (i.e: `1`).
+    ///
+    /// Return an error if the address cannot be resolved (e.g: if the thread
+    /// hasn't allocated its TLS block yet).
+    fn get_tls_addr(
+        &mut self,
+        tid: Tid,
+        offset: <Self::Arch as Arch>::Usize,
+        load_module: <Self::Arch as Arch>::Usize,
+    ) -> TargetResult<<Self::Arch as Arch>::Usize, Self>;
+}
+
+define_ext!(TlsAccessOps, TlsAccess);
@@ -0,0 +1,180 @@
+//! Define and run tracepoints.
+//!
+//! Tracepoints allow GDB to collect data (e.g: registers, memory, or the
+//! result of evaluating an expression) whenever execution reaches a given
+//! address, _without_ stopping the target. The collected data is stored in a
+//! "trace frame", which GDB can later select and inspect (e.g: via `tfind`)
+//! as though it were a regular stopped target.
+//!
+//! The typical lifecycle of a "trace experiment" looks like:
+//!
+//! 1. GDB clears any existing trace state ([`Tracepoints::trace_init`])
+//! 2. GDB downloads each tracepoint ([`Tracepoints::tracepoint_create`]),
+//!    followed by its actions ([`Tracepoints::tracepoint_add_action`])
+//! 3. The experiment is started ([`Tracepoints::trace_start`]), and the
+//!    target is resumed
+//! 4. The experiment is stopped ([`Tracepoints::trace_stop`]), at which point
+//!    GDB can select collected trace frames via
+//!    [`Tracepoints::select_frame`].
+//!
+//! While a trace frame is selected, the target should service register and
+//! memory reads using the data collected in the selected frame. Once GDB is
+//! done inspecting trace frames, it will call
+//! [`Tracepoints::deselect_frame`].
+//!
+//! _Note:_ Trace state variables, fast tracepoints, and uploading tracepoints
+//! from the target are not currently supported.
+use crate::arch::Arch;
+use crate::target::{Target, TargetResult};
+
+/// A tracepoint number, as assigned by GDB.
+///
+/// A single tracepoint may be set at multiple locations (e.g: when tracing an
+/// inlined function), in which case GDB will define the tracepoint once for
+/// each location, using the same number each time. As such, tracepoints
+/// should be identified by both their number _and_ address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tracepoint(pub usize);
+
+/// A new tracepoint, as defined by GDB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NewTracepoint<'a, U> {
+    /// The tracepoint's number.
+    pub number: Tracepoint,
+    /// The address to collect data at.
+    pub addr: U,
+    /// Whether the tracepoint is initially enabled.
+    pub enabled: bool,
+    /// The number of instructions to single-step (collecting data at each
+    /// step) after the tracepoint is hit.
+    pub step_count: u64,
+    /// The number of times the tracepoint may be hit before the experiment is
+    /// automatically stopped, where `0` indicates no limit.
+    pub pass_count: u64,
+    /// An agent expression which must evaluate to a non-zero value in order
+    /// for data to be collected.
+    pub condition: Option<&'a [u8]>,
+}
+
+/// An action to perform when a tracepoint is hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TracepointAction<'a, U> {
+    /// Collect the registers specified in `mask`.
+    ///
+    /// `mask` is a big-endian bitmask, where bit `n` (counting from the
+    /// least significant bit of the last byte) corresponds to register `n`.
+    Registers {
+        /// The set of registers to collect.
+        mask: &'a [u8],
+    },
+    /// Collect `length` bytes of memory.
+    Memory {
+        /// The register whose value `offset` is relative to, or `None` if
+        /// `offset` is an absolute address.
+        basereg: Option<u64>,
+        /// The offset (or address) to collect memory from. Relative offsets
+        /// may be negative, and should be added using wrapping arithmetic.
+        offset: U,
+        /// The number of bytes to collect.
+        length: u64,
+    },
+    /// Evaluate an agent expression, collecting any memory it references.
+    Expression {
+        /// The agent expression's bytecode.
+        expr: &'a [u8],
+    },
+}
+
+/// The current status of the trace experiment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExperimentStatus {
+    /// The trace experiment is currently running.
+    Running,
+    /// No trace experiment has been run yet.
+    NotRun,
+    /// The trace experiment was stopped by GDB.
+    Stopped,
+    /// The trace experiment was stopped because the trace buffer filled up.
+    BufferFull,
+    /// The trace experiment was stopped because a tracepoint's pass count was
+    /// reached.
+    PassCount(Tracepoint),
+}
+
+/// Describes which trace frame GDB wants to select.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameRequest<U> {
+    /// Select the trace frame with the given number.
+    Number(usize),
+    /// Select the next trace frame collected at the given address.
+    Pc(U),
+    /// Select the next trace frame collected by the given tracepoint.
+    Tracepoint(Tracepoint),
+    /// Select the next trace frame whose PC is within `start..=end`.
+    InRange(U, U),
+    /// Select the next trace frame whose PC is outside `start..=end`.
+    OutsideRange(U, U),
+}
+
+/// A trace frame, as selected by [`Tracepoints::select_frame`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceFrame {
+    /// The trace frame's number.
+    pub number: usize,
+    /// The tracepoint which collected the trace frame.
+    pub tracepoint: Tracepoint,
+}
+
+/// Target Extension - Define and run tracepoints.
+///
+/// Implementing this extension will advertise support for conditional
+/// tracepoints to GDB.
+pub trait Tracepoints: Target {
+    /// Clear all tracepoints and collected trace frames, in preparation for
+    /// a new trace experiment.
+    fn trace_init(&mut self) -> TargetResult<(), Self>;
+
+    /// Define a new tracepoint.
+    fn tracepoint_create(
+        &mut self,
+        tp: NewTracepoint<'_, <Self::Arch as Arch>::Usize>,
+    ) -> TargetResult<(), Self>;
+
+    /// Add an action to the tracepoint at `addr`.
+    ///
+    /// If `while_stepping` is set, the action should be performed at each
+    /// step _after_ the tracepoint is hit (as opposed to when the tracepoint
+    /// itself is hit).
+    fn tracepoint_add_action(
+        &mut self,
+        tp: Tracepoint,
+        addr: <Self::Arch as Arch>::Usize,
+        action: TracepointAction<'_, <Self::Arch as Arch>::Usize>,
+        while_stepping: bool,
+    ) -> TargetResult<(), Self>;
+
+    /// Return the current status of the trace experiment.
+    fn trace_status(&mut self) -> TargetResult<ExperimentStatus, Self>;
+
+    /// Start the trace experiment, discarding any previously collected trace
+    /// frames.
+    fn trace_start(&mut self) -> TargetResult<(), Self>;
+
+    /// Stop the trace experiment.
+    fn trace_stop(&mut self) -> TargetResult<(), Self>;
+
+    /// Select a trace frame, returning `None` if no matching frame exists.
+    ///
+    /// Searches (i.e: all requests other than [`FrameRequest::Number`])
+    /// should begin _after_ the currently selected frame, or from the first
+    /// frame if no frame is currently selected.
+    fn select_frame(
+        &mut self,
+        req: FrameRequest<<Self::Arch as Arch>::Usize>,
+    ) -> TargetResult<Option<TraceFrame>, Self>;
+
+    /// Stop inspecting trace frames, returning to the target's live state.
+    fn deselect_frame(&mut self) -> TargetResult<(), Self>;
+}
+
+define_ext!(TracepointsOps, Tracepoints);
@@ -0,0 +1,43 @@
+/// Update a running CRC-32 checksum with the contents of `data`.
+///
+/// This is the CRC-32 variant used by GDB's `qCRC` packet (and the binutils
+/// `xcrc32` function): polynomial `0x04c11db7`, processed MSB-first, with no
+/// final XOR. GDB seeds the checksum with `0xffffffff`.
+pub fn crc32(mut crc: u32, data: &[u8]) -> u32 {
+    for &b in data {
+        crc ^= (b as u32) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04c1_1db7
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_vector() {
+        assert_eq!(crc32(0xffff_ffff, b"123456789"), 0x0376_e6e7);
+    }
+
+    #[test]
+    fn incremental() {
+        let data = b"the quick brown fox jumps over the lazy dog";
+        let (a, b) = data.split_at(17);
+        assert_eq!(
+            crc32(crc32(0xffff_ffff, a), b),
+            crc32(0xffff_ffff, &data[..])
+        );
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(crc32(0xffff_ffff, &[]), 0xffff_ffff);
+    }
+}
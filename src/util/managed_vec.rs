@@ -15,11 +15,14 @@ impl<'a, 'b, T> ManagedVec<'a, 'b, T> {
         ManagedVec { buf, len: 0 }
     }
 
-    pub fn clear(&mut self) {
-        // While it's very tempting to just call `Vec::clear` in the `Owned` case, doing
-        // so would modify the length of the underlying `ManagedSlice`, which isn't
-        // desirable.
-        self.len = 0;
+    /// Resume pushing onto a buffer whose first `len` elements are already
+    /// populated.
+    pub fn new_with_len(buf: &'b mut ManagedSlice<'a, T>, len: usize) -> Self {
+        ManagedVec { buf, len }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
     }
 
     pub fn push(&mut self, value: T) -> Result<(), CapacityError<T>> {
@@ -0,0 +1,1476 @@
+//! Shared test harness: an in-memory `Connection`, and mock targets which can
+//! be driven by canned GDB packets.
+
+#![allow(dead_code)]
+
+use std::collections::VecDeque;
+
+use gdbstub::arch::Arch;
+use gdbstub::arch::TargetDescriptionFeature;
+use gdbstub::common::{errno, tid_from_zero_based, tid_to_zero_based, Pid, Signal, Tid};
+use gdbstub::target;
+use gdbstub::target::ext::base::multithread::{MultiThreadOps, ThreadStopReason};
+use gdbstub::target::ext::base::singlethread::{
+    GdbInterrupt, ResumeAction, SingleThreadOps, StopReason,
+};
+use gdbstub::target::ext::breakpoints::{Conditions, WatchKind};
+use gdbstub::target::ext::extended_mode::{Args, AttachKind, ShouldTerminate};
+use gdbstub::target::ext::host_io::{
+    HostIo, HostIoErrno, HostIoError, HostIoOpenFlags, HostIoOpenMode, HostIoResult, HostIoStat,
+};
+use gdbstub::target::ext::lldb_host_info::HostInfo;
+use gdbstub::target::ext::lldb_register_info::{Encoding, Format, Generic, Register};
+use gdbstub::target::ext::memory_region_info::MemoryRegion;
+use gdbstub::target::ext::section_offsets::Offsets;
+use gdbstub::target::ext::tracepoints::{
+    ExperimentStatus, FrameRequest, NewTracepoint, TraceFrame, Tracepoint, TracepointAction,
+};
+use gdbstub::target::{Target, TargetError, TargetResult};
+use gdbstub::{
+    Connection, DisconnectReason, GdbStub, GdbStubError, GdbStubStateMachine, StateMachineAction,
+};
+use gdbstub_arch::arm::reg::ArmCoreRegs;
+use gdbstub_arch::arm::ArmBreakpointKind;
+
+/// Error returned by [`MockConnection`] once all incoming data has been
+/// consumed.
+pub const EOF: &str = "eof";
+
+/// A `Connection` backed by in-memory buffers.
+pub struct MockConnection {
+    pub rx: VecDeque<u8>,
+    pub tx: Vec<u8>,
+}
+
+impl MockConnection {
+    pub fn new(rx: &[u8]) -> MockConnection {
+        MockConnection {
+            rx: rx.iter().copied().collect(),
+            tx: Vec::new(),
+        }
+    }
+}
+
+impl Connection for MockConnection {
+    type Error = &'static str;
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        self.rx.pop_front().ok_or(EOF)
+    }
+
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+        self.tx.push(byte);
+        Ok(())
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+        Ok(self.rx.front().copied())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Frame `body` as a GDB packet (i.e: `$<body>#<checksum>`).
+pub fn packet(body: &str) -> Vec<u8> {
+    let checksum = body.bytes().fold(0u8, |a, b| a.wrapping_add(b));
+    format!("${}#{:02x}", body, checksum).into_bytes()
+}
+
+/// Hex-encode `s` (as done by GDB for filenames, monitor commands, etc...).
+pub fn hex(s: &[u8]) -> String {
+    s.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The result of driving a [`GdbStubStateMachine`] via [`pump_all`] /
+/// [`report_stop_all`].
+pub type DriveResult<T, C> = Result<
+    Option<StateMachineAction<'static, <<T as Target>::Arch as Arch>::Usize>>,
+    GdbStubError<<T as Target>::Error, <C as Connection>::Error>,
+>;
+
+/// Feed `bytes` to a state machine one at a time, appending any bytes it asks
+/// to send to `tx`.
+///
+/// Returns the last action (other than `Send`) requested by the state machine.
+pub fn pump_all<T: Target, C: Connection>(
+    gdb: &mut GdbStubStateMachine<'_, T, C>,
+    target: &mut T,
+    bytes: &[u8],
+    tx: &mut Vec<u8>,
+) -> DriveResult<T, C> {
+    let mut last = None;
+    for &b in bytes {
+        let mut more = take_action(gdb.pump(target, b)?, tx, &mut last);
+        while more {
+            more = take_action(gdb.next_action()?, tx, &mut last);
+        }
+    }
+    Ok(last)
+}
+
+/// Report a stop to a state machine, appending any bytes it asks to send to
+/// `tx`.
+///
+/// Returns the last action (other than `Send`) requested by the state machine.
+pub fn report_stop_all<T: Target, C: Connection>(
+    gdb: &mut GdbStubStateMachine<'_, T, C>,
+    target: &mut T,
+    stop_reason: ThreadStopReason<<T::Arch as Arch>::Usize>,
+    tx: &mut Vec<u8>,
+) -> DriveResult<T, C> {
+    let mut last = None;
+    let mut more = take_action(gdb.report_stop(target, stop_reason)?, tx, &mut last);
+    while more {
+        more = take_action(gdb.next_action()?, tx, &mut last);
+    }
+    Ok(last)
+}
+
+/// Returns whether there may be further actions to take.
+fn take_action<U>(
+    action: Option<StateMachineAction<'_, U>>,
+    tx: &mut Vec<u8>,
+    last: &mut Option<StateMachineAction<'static, U>>,
+) -> bool {
+    *last = match action {
+        None => return false,
+        Some(StateMachineAction::Send(bytes)) => {
+            tx.extend_from_slice(bytes);
+            return true;
+        }
+        Some(StateMachineAction::Resume(req)) => Some(StateMachineAction::Resume(req)),
+        Some(StateMachineAction::Interrupt) => Some(StateMachineAction::Interrupt),
+        Some(StateMachineAction::Disconnect(reason)) => {
+            Some(StateMachineAction::Disconnect(reason))
+        }
+        Some(_) => unreachable!(),
+    };
+    true
+}
+
+/// Split the raw bytes sent by the stub into individual (RLE-expanded)
+/// response bodies, skipping any acks.
+///
+/// Notification packets are included with their leading `%`.
+pub fn responses(tx: &[u8]) -> Vec<String> {
+    let mut res = Vec::new();
+    let mut i = 0;
+    while i < tx.len() {
+        match tx[i] {
+            b'+' | b'-' => i += 1,
+            start @ (b'$' | b'%') => {
+                let end = i + tx[i..]
+                    .iter()
+                    .position(|b| *b == b'#')
+                    .expect("missing '#'");
+                let mut body: Vec<u8> = Vec::new();
+                if start == b'%' {
+                    body.push(start);
+                }
+                let mut j = i + 1;
+                while j < end {
+                    if tx[j] == b'*' {
+                        let c = *body.last().unwrap();
+                        for _ in 0..(tx[j + 1] - 29) {
+                            body.push(c);
+                        }
+                        j += 2;
+                    } else {
+                        body.push(tx[j]);
+                        j += 1;
+                    }
+                }
+                // map each byte to a char, as binary responses needn't be UTF-8
+                res.push(body.into_iter().map(char::from).collect());
+                // skip the '#' and the two checksum bytes
+                i = end + 3;
+            }
+            c => panic!("unexpected byte from stub: {:#x?}", c),
+        }
+    }
+    res
+}
+
+/// The result of a session run via [`run`] / [`run_with_buffer_size`], along
+/// with the stub's responses.
+pub type RunResult<T> = (
+    Result<DisconnectReason, GdbStubError<<T as Target>::Error, &'static str>>,
+    Vec<String>,
+);
+
+/// Feed each of the provided packet bodies to a fresh `GdbStub`, returning the
+/// session's result, and the stub's responses.
+pub fn run<T: Target>(target: &mut T, packets: &[&str]) -> RunResult<T> {
+    run_with_buffer_size(target, packets, 4096)
+}
+
+/// Same as [`run`], but using a packet buffer of the given size.
+pub fn run_with_buffer_size<T: Target>(
+    target: &mut T,
+    packets: &[&str],
+    packet_buffer_size: usize,
+) -> RunResult<T> {
+    let rx = packets.iter().flat_map(|p| packet(p)).collect::<Vec<_>>();
+    let mut conn = MockConnection::new(&rx);
+    // use a caller-provided packet buffer, so that the tests also work without the
+    // `alloc` feature
+    let mut packet_buffer = vec![0; packet_buffer_size];
+    let result = GdbStub::builder(&mut conn as &mut dyn Connection<Error = _>)
+        .with_packet_buffer(&mut packet_buffer)
+        .build()
+        .unwrap()
+        .run(target);
+    (result, responses(&conn.tx))
+}
+
+/// Same as [`run`], but asserts that the session only ended because the
+/// connection ran out of data.
+pub fn run_ok<T: Target>(target: &mut T, packets: &[&str]) -> Vec<String>
+where
+    T::Error: core::fmt::Debug,
+{
+    check_ok(run(target, packets))
+}
+
+/// Asserts that a session only ended because the connection ran out of data,
+/// returning the stub's responses.
+pub fn check_ok<E: core::fmt::Debug>(
+    (result, res): (
+        Result<DisconnectReason, GdbStubError<E, &'static str>>,
+        Vec<String>,
+    ),
+) -> Vec<String> {
+    match result {
+        Err(GdbStubError::ConnectionRead(EOF)) => {}
+        other => panic!("unexpected session result: {:?}", other),
+    }
+    res
+}
+
+/// The auxiliary vector reported via `qXfer:auxv:read`.
+pub const AUXV: &[u8] = &[
+    0x06, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, // AT_PAGESZ = 0x1000
+    0x09, 0x00, 0x00, 0x00, 0x24, 0x00, 0x00, 0x08, // AT_ENTRY = 0x0800_0024
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // AT_NULL
+];
+
+/// The pid and path of the executable reported via `qXfer:exec-file:read`.
+pub const EXEC_FILE: (usize, &[u8]) = (0x2a, b"/usr/bin/true");
+
+/// The initial siginfo reported via `qXfer:siginfo:read`.
+pub const SIGINFO: &[u8] = &[
+    0x0b, 0x00, 0x00, 0x00, // si_signo = SIGSEGV
+    0x00, 0x00, 0x00, 0x00, // si_errno
+    0x01, 0x00, 0x00, 0x00, // si_code = SEGV_MAPERR
+    0x00, 0x10, 0x00, 0x00, // si_addr = 0x1000
+];
+
+/// `(filename, contents)` pairs accessible via Host I/O. Each file's index
+/// doubles as its fd.
+pub const FILES: &[(&[u8], &[u8])] = &[
+    (b"/lib/libfoo.so", b"\x7fELF$#}*"),
+    (b"/etc/motd", b"hello"),
+];
+
+/// The shared libraries reported via `qXfer:libraries-svr4:read`, as `(name,
+/// lm, l_addr, l_ld)` tuples.
+pub const LIBRARIES: &[(&[u8], u32, u32, u32)] = &[
+    (b"/lib/libc.so.6", 0x1000, 0, 0x7f8a_3e18),
+    (b"/tmp/a&b.so", 0x1040, 0x2000_0000, 0x2000_1f00),
+];
+
+/// The target description XML (and additional features) reported via
+/// `qXfer:features:read`.
+pub const TARGET_XML: &str = r#"<target version="1.0"><architecture>arm</architecture></target>"#;
+pub const TARGET_XML_FEATURES: &[TargetDescriptionFeature<'static>] = &[
+    TargetDescriptionFeature {
+        annex: "arm-core.xml",
+        xml: r#"<feature name="org.gnu.gdb.arm.core"><reg name="r0" bitsize="32"/></feature>"#,
+    },
+    TargetDescriptionFeature {
+        annex: "arm-vfp.xml",
+        xml: r#"<feature name="org.gnu.gdb.arm.vfp"><reg name="d0" bitsize="64" type="ieee_double"/></feature>"#,
+    },
+];
+
+/// The target's mapped memory regions, as `(start, size, permissions)`.
+pub const MEMORY_REGIONS: &[(u32, u32, &str)] = &[(0x1000, 0x2000, "rx"), (0x8000, 0x1000, "rw")];
+
+/// `(load_module, tls_block)` pairs used to resolve the addresses of
+/// thread-local variables.
+pub const TLS_BLOCKS: &[(u32, u32)] = &[(0x4000_0100, 0x2000_0000), (0x4000_0200, 0x2000_1000)];
+
+/// The platform described to LLDB via `qHostInfo` / `qProcessInfo`.
+pub const HOST_INFO: HostInfo<'static> = HostInfo {
+    triple: "arm-none-eabi",
+    vendor: Some("unknown"),
+    ostype: None,
+    endian: None,
+};
+
+/// The registers described to LLDB via `qRegisterInfo`.
+pub const LLDB_REGISTERS: &[Register<'static>] = &[
+    Register {
+        name: "r0",
+        alt_name: None,
+        bitsize: 32,
+        offset: 0,
+        encoding: Encoding::Uint,
+        format: Format::Hex,
+        set: "General Purpose Registers",
+        gcc: None,
+        dwarf: None,
+        generic: None,
+        container_regs: None,
+        invalidate_regs: None,
+    },
+    Register {
+        name: "r11",
+        alt_name: Some("fp"),
+        bitsize: 32,
+        offset: 4,
+        encoding: Encoding::Uint,
+        format: Format::Hex,
+        set: "General Purpose Registers",
+        gcc: Some(11),
+        dwarf: Some(11),
+        generic: Some(Generic::Fp),
+        container_regs: None,
+        invalidate_regs: None,
+    },
+    Register {
+        name: "r0b",
+        alt_name: None,
+        bitsize: 8,
+        offset: 0,
+        encoding: Encoding::Sint,
+        format: Format::Decimal,
+        set: "General Purpose Registers",
+        gcc: None,
+        dwarf: None,
+        generic: None,
+        container_regs: Some(&[0]),
+        invalidate_regs: Some(&[0, 0x10]),
+    },
+];
+
+/// The pid of processes spawned via `vRun`.
+pub const RUN_PID: usize = 0x539;
+
+/// A tracepoint defined via `QTDP`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockTracepoint {
+    pub number: usize,
+    pub addr: u32,
+    pub enabled: bool,
+    pub step_count: u64,
+    pub pass_count: u64,
+    pub condition: Option<Vec<u8>>,
+    /// `(action, while_stepping)` pairs, with each action recorded via its
+    /// `Debug` representation.
+    pub actions: Vec<(String, bool)>,
+}
+
+/// A single-threaded ARM target, backed by a flat chunk of memory.
+///
+/// Targets created via [`MockTarget::new`] implement every optional extension
+/// (backed by the canned data above), whereas those created via
+/// [`MockTarget::minimal`] only implement the required target methods.
+pub struct MockTarget {
+    minimal: bool,
+    pub regs: ArmCoreRegs,
+    pub mem: Vec<u8>,
+    /// The address at which `mem` is mapped.
+    pub mem_base: u32,
+    /// The number of calls made to `read_addrs`.
+    pub mem_reads: usize,
+    /// Stop reasons to report from subsequent resumes. Once empty, `DoneStep`
+    /// is reported.
+    pub stop_reasons: VecDeque<StopReason<u32>>,
+    pub resume_actions: Vec<ResumeAction>,
+    /// Reverse resumes, recorded as `"bc"` or `"bs"`.
+    pub reverse_resumes: Vec<&'static str>,
+    /// When set, `resume` runs until GDB sends an interrupt.
+    pub run_until_interrupt: bool,
+
+    pub sw_breakpoints: Vec<(u32, ArmBreakpointKind)>,
+    /// The conditions most recently set for each breakpoint.
+    pub breakpoint_conditions: Vec<(u32, Vec<Vec<u8>>)>,
+    pub hw_watchpoints: Vec<(u32, u32, WatchKind)>,
+    /// `monitor` commands forwarded to the target.
+    pub monitor_cmds: Vec<Vec<u8>>,
+    /// Signals which are delivered without stopping (as set via
+    /// `QPassSignals`).
+    pub passed_signals: Vec<Signal>,
+    /// Signals which may be delivered to the program (as set via
+    /// `QProgramSignals`).
+    pub program_signals: Vec<Signal>,
+    pub siginfo: Vec<u8>,
+    /// Erased `(start, length)` flash ranges.
+    pub flash_erased: Vec<(u32, u32)>,
+    /// Flash writes which have yet to be committed to `mem`.
+    pub flash_pending: Vec<(u32, Vec<u8>)>,
+    pub tracepoints: Vec<MockTracepoint>,
+    pub trace_status: ExperimentStatus,
+    /// Collected trace frames, as `(tracepoint, pc)` pairs.
+    pub trace_frames: Vec<(usize, u32)>,
+    pub selected_frame: Option<usize>,
+    /// The symbols the target needs GDB to look up, alongside their addresses
+    /// (once resolved).
+    pub symbols: Vec<(&'static str, Option<u32>)>,
+    /// Symbols yet to be requested as part of the current `qSymbol` lookup.
+    pending_symbols: Vec<&'static str>,
+
+    /// `(filename, args)` of each spawned process.
+    pub ran: Vec<(Option<Vec<u8>>, Vec<Vec<u8>>)>,
+    pub detached: Vec<Option<Pid>>,
+    /// Processes selected via `H` packets.
+    pub selected: Vec<Pid>,
+    pub restarts: usize,
+    pub startup_with_shell: bool,
+    pub aslr: bool,
+    /// The working directory for spawned processes, where `None` indicates
+    /// the default working directory.
+    pub working_dir: Option<Vec<u8>>,
+    pub env: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+}
+
+impl MockTarget {
+    pub fn new() -> MockTarget {
+        MockTarget {
+            minimal: false,
+            regs: ArmCoreRegs::default(),
+            mem: vec![0; 0x1000],
+            mem_base: 0,
+            mem_reads: 0,
+            stop_reasons: VecDeque::new(),
+            resume_actions: Vec::new(),
+            reverse_resumes: Vec::new(),
+            run_until_interrupt: false,
+
+            sw_breakpoints: Vec::new(),
+            breakpoint_conditions: Vec::new(),
+            hw_watchpoints: Vec::new(),
+            monitor_cmds: Vec::new(),
+            passed_signals: Vec::new(),
+            program_signals: Vec::new(),
+            siginfo: SIGINFO.to_vec(),
+            flash_erased: Vec::new(),
+            flash_pending: Vec::new(),
+            tracepoints: Vec::new(),
+            trace_status: ExperimentStatus::NotRun,
+            trace_frames: Vec::new(),
+            selected_frame: None,
+            symbols: vec![("_dl_debug_state", None), ("main", None)],
+            pending_symbols: Vec::new(),
+
+            ran: Vec::new(),
+            detached: Vec::new(),
+            selected: Vec::new(),
+            restarts: 0,
+            startup_with_shell: true,
+            aslr: true,
+            working_dir: None,
+            env: Vec::new(),
+        }
+    }
+
+    pub fn minimal() -> MockTarget {
+        MockTarget {
+            minimal: true,
+            ..MockTarget::new()
+        }
+    }
+}
+
+/// Implement each of the given IDET methods, returning `None` for `minimal`
+/// targets.
+macro_rules! extensions {
+    ($($method:ident: $ops:ty;)*) => {
+        $(
+            fn $method(&mut self) -> Option<$ops> {
+                if self.minimal {
+                    None
+                } else {
+                    Some(self)
+                }
+            }
+        )*
+    };
+}
+
+impl Target for MockTarget {
+    type Arch = gdbstub_arch::arm::Armv4t;
+    type Error = &'static str;
+
+    fn base_ops(&mut self) -> target::ext::base::BaseOps<Self::Arch, Self::Error> {
+        target::ext::base::BaseOps::SingleThread(self)
+    }
+
+    extensions! {
+        breakpoints: target::ext::breakpoints::BreakpointsOps<Self>;
+        monitor_cmd: target::ext::monitor_cmd::MonitorCmdOps<Self>;
+        host_io: target::ext::host_io::HostIoOps<Self>;
+        pass_signals: target::ext::pass_signals::PassSignalsOps<Self>;
+        section_offsets: target::ext::section_offsets::SectionOffsetsOps<Self>;
+        tls_access: target::ext::tls_access::TlsAccessOps<Self>;
+        auxv: target::ext::auxv::AuxvOps<Self>;
+        exec_file: target::ext::exec_file::ExecFileOps<Self>;
+        siginfo: target::ext::siginfo::SigInfoOps<Self>;
+        libraries_svr4: target::ext::libraries_svr4::LibrariesSvr4Ops<Self>;
+        target_description_xml_override:
+            target::ext::target_description_xml_override::TargetDescriptionXmlOverrideOps<Self>;
+        extended_mode: target::ext::extended_mode::ExtendedModeOps<Self>;
+        memory_region_info: target::ext::memory_region_info::MemoryRegionInfoOps<Self>;
+        lldb_host_info: target::ext::lldb_host_info::LldbHostInfoOps<Self>;
+        lldb_register_info: target::ext::lldb_register_info::LldbRegisterInfoOps<Self>;
+        flash: target::ext::flash::FlashOps<Self>;
+        tracepoints: target::ext::tracepoints::TracepointsOps<Self>;
+        symbol_lookup: target::ext::symbol_lookup::SymbolLookupOps<Self>;
+    }
+}
+
+impl SingleThreadOps for MockTarget {
+    fn resume(
+        &mut self,
+        action: ResumeAction,
+        gdb_interrupt: GdbInterrupt<'_>,
+    ) -> Result<StopReason<u32>, Self::Error> {
+        self.resume_actions.push(action);
+        if self.run_until_interrupt {
+            let mut gdb_interrupt = gdb_interrupt.no_async();
+            while !gdb_interrupt.pending() {}
+            return Ok(StopReason::GdbInterrupt);
+        }
+        while let Some(reason) = self.stop_reasons.pop_front() {
+            match reason {
+                StopReason::Signal(sig) if self.passed_signals.contains(&sig) => continue,
+                reason => return Ok(reason),
+            }
+        }
+        Ok(StopReason::DoneStep)
+    }
+
+    fn supports_resume_with_signal(&self) -> bool {
+        !self.minimal
+    }
+
+    fn supports_single_step(&self) -> bool {
+        !self.minimal
+    }
+
+    extensions! {
+        support_reverse_cont: target::ext::base::singlethread::SingleThreadReverseContOps<Self>;
+        support_reverse_step: target::ext::base::singlethread::SingleThreadReverseStepOps<Self>;
+        support_streaming_read:
+            target::ext::base::singlethread::SingleThreadStreamingReadOps<'_, Self>;
+    }
+
+    fn read_registers(&mut self, regs: &mut ArmCoreRegs) -> TargetResult<(), Self> {
+        *regs = self.regs.clone();
+        Ok(())
+    }
+
+    fn write_registers(&mut self, regs: &ArmCoreRegs) -> TargetResult<(), Self> {
+        self.regs = regs.clone();
+        Ok(())
+    }
+
+    fn read_addrs(&mut self, start_addr: u32, data: &mut [u8]) -> TargetResult<(), Self> {
+        self.mem_reads += 1;
+        let start = start_addr.wrapping_sub(self.mem_base) as usize;
+        let src = self.mem.get(start..start + data.len()).ok_or(())?;
+        data.copy_from_slice(src);
+        Ok(())
+    }
+
+    fn write_addrs(&mut self, start_addr: u32, data: &[u8]) -> TargetResult<(), Self> {
+        let start = start_addr.wrapping_sub(self.mem_base) as usize;
+        let dst = self
+            .mem
+            .get_mut(start..start + data.len())
+            .ok_or(TargetError::Errno(errno::EFAULT))?;
+        dst.copy_from_slice(data);
+        Ok(())
+    }
+}
+
+impl target::ext::base::singlethread::SingleThreadReverseCont for MockTarget {
+    fn reverse_cont(
+        &mut self,
+        _gdb_interrupt: GdbInterrupt<'_>,
+    ) -> Result<StopReason<u32>, Self::Error> {
+        self.reverse_resumes.push("bc");
+        Ok(self
+            .stop_reasons
+            .pop_front()
+            .unwrap_or(StopReason::DoneStep))
+    }
+}
+
+impl target::ext::base::singlethread::SingleThreadReverseStep for MockTarget {
+    fn reverse_step(
+        &mut self,
+        _gdb_interrupt: GdbInterrupt<'_>,
+    ) -> Result<StopReason<u32>, Self::Error> {
+        self.reverse_resumes.push("bs");
+        Ok(self
+            .stop_reasons
+            .pop_front()
+            .unwrap_or(StopReason::DoneStep))
+    }
+}
+
+impl target::ext::base::singlethread::SingleThreadStreamingRead for MockTarget {
+    fn read_addrs_streaming(
+        &mut self,
+        start_addr: u32,
+        len: usize,
+        write: &mut dyn FnMut(&[u8]),
+    ) -> TargetResult<(), Self> {
+        let start = (start_addr as usize).min(self.mem.len());
+        let end = (start + len).min(self.mem.len());
+        self.mem[start..end]
+            .chunks(16)
+            .for_each(|chunk| write(chunk));
+        if end - start != len {
+            return Err(TargetError::NonFatal);
+        }
+        Ok(())
+    }
+}
+
+impl target::ext::breakpoints::Breakpoints for MockTarget {
+    extensions! {
+        sw_breakpoint: target::ext::breakpoints::SwBreakpointOps<Self>;
+        hw_watchpoint: target::ext::breakpoints::HwWatchpointOps<Self>;
+        breakpoint_conditions: target::ext::breakpoints::BreakpointConditionsOps<'_, Self>;
+    }
+}
+
+impl target::ext::breakpoints::BreakpointConditions for MockTarget {
+    fn set_breakpoint_conditions(
+        &mut self,
+        addr: u32,
+        conds: Conditions<'_, '_>,
+    ) -> TargetResult<(), Self> {
+        let conds = conds.map(|c| c.to_vec()).collect();
+        self.breakpoint_conditions.retain(|(a, _)| *a != addr);
+        self.breakpoint_conditions.push((addr, conds));
+        Ok(())
+    }
+}
+
+impl target::ext::breakpoints::SwBreakpoint for MockTarget {
+    fn add_sw_breakpoint(
+        &mut self,
+        addr: u32,
+        kind: ArmBreakpointKind,
+    ) -> TargetResult<bool, Self> {
+        self.sw_breakpoints.push((addr, kind));
+        Ok(true)
+    }
+
+    fn remove_sw_breakpoint(
+        &mut self,
+        addr: u32,
+        kind: ArmBreakpointKind,
+    ) -> TargetResult<bool, Self> {
+        let len = self.sw_breakpoints.len();
+        self.sw_breakpoints.retain(|b| *b != (addr, kind));
+        Ok(self.sw_breakpoints.len() != len)
+    }
+}
+
+impl target::ext::breakpoints::HwWatchpoint for MockTarget {
+    fn add_hw_watchpoint(
+        &mut self,
+        addr: u32,
+        len: u32,
+        kind: WatchKind,
+    ) -> TargetResult<bool, Self> {
+        self.hw_watchpoints.push((addr, len, kind));
+        Ok(true)
+    }
+
+    fn remove_hw_watchpoint(
+        &mut self,
+        addr: u32,
+        len: u32,
+        kind: WatchKind,
+    ) -> TargetResult<bool, Self> {
+        let count = self.hw_watchpoints.len();
+        self.hw_watchpoints.retain(|w| *w != (addr, len, kind));
+        Ok(self.hw_watchpoints.len() != count)
+    }
+}
+
+impl target::ext::monitor_cmd::MonitorCmd for MockTarget {
+    fn handle_monitor_cmd(
+        &mut self,
+        cmd: &[u8],
+        mut out: target::ext::monitor_cmd::ConsoleOutput<'_>,
+    ) -> Result<(), Self::Error> {
+        self.monitor_cmds.push(cmd.to_vec());
+        out.write_raw(b"handled ");
+        gdbstub::output!(out, "by {}", "target");
+        gdbstub::outputln!(out,);
+        Ok(())
+    }
+}
+
+impl HostIo for MockTarget {
+    fn open(
+        &mut self,
+        filename: &[u8],
+        _flags: HostIoOpenFlags,
+        _mode: HostIoOpenMode,
+    ) -> HostIoResult<usize, Self> {
+        FILES
+            .iter()
+            .position(|(name, _)| *name == filename)
+            .ok_or(HostIoError::Errno(HostIoErrno::ENOENT))
+    }
+
+    fn close(&mut self, fd: usize) -> HostIoResult<(), Self> {
+        file(fd)?;
+        Ok(())
+    }
+
+    fn pread(&mut self, fd: usize, offset: usize, buf: &mut [u8]) -> HostIoResult<usize, Self> {
+        let data = file(fd)?.get(offset..).unwrap_or(&[]);
+        let len = data.len().min(buf.len());
+        buf[..len].copy_from_slice(&data[..len]);
+        Ok(len)
+    }
+
+    fn support_fstat(&mut self) -> Option<target::ext::host_io::HostIoFstatOps<Self>> {
+        Some(self)
+    }
+}
+
+impl target::ext::host_io::HostIoFstat for MockTarget {
+    fn fstat(&mut self, fd: usize) -> HostIoResult<HostIoStat, Self> {
+        Ok(HostIoStat {
+            st_mode: HostIoOpenMode::S_IFREG.bits() | 0o644,
+            st_size: file(fd)?.len() as u64,
+            ..HostIoStat::default()
+        })
+    }
+}
+
+fn file(fd: usize) -> HostIoResult<&'static [u8], MockTarget> {
+    FILES
+        .get(fd)
+        .map(|(_, contents)| *contents)
+        .ok_or(HostIoError::Errno(HostIoErrno::EBADF))
+}
+
+impl target::ext::section_offsets::SectionOffsets for MockTarget {
+    fn get_section_offsets(&mut self) -> Result<Offsets<u32>, Self::Error> {
+        Ok(Offsets::Sections {
+            text: 0x1000,
+            data: 0x2000,
+            bss: None,
+        })
+    }
+}
+
+impl target::ext::pass_signals::PassSignals for MockTarget {
+    fn set_pass_signals(
+        &mut self,
+        signals: &mut dyn Iterator<Item = Signal>,
+    ) -> TargetResult<(), Self> {
+        self.passed_signals = signals.collect();
+        Ok(())
+    }
+
+    fn support_program_signals(
+        &mut self,
+    ) -> Option<target::ext::pass_signals::ProgramSignalsOps<Self>> {
+        Some(self)
+    }
+}
+
+impl target::ext::pass_signals::ProgramSignals for MockTarget {
+    fn set_program_signals(
+        &mut self,
+        signals: &mut dyn Iterator<Item = Signal>,
+    ) -> TargetResult<(), Self> {
+        self.program_signals = signals.collect();
+        Ok(())
+    }
+}
+
+impl target::ext::tls_access::TlsAccess for MockTarget {
+    fn get_tls_addr(&mut self, tid: Tid, offset: u32, load_module: u32) -> TargetResult<u32, Self> {
+        assert_eq!(tid.get(), 1);
+        let (_, block) = TLS_BLOCKS
+            .iter()
+            .find(|(lm, _)| *lm == load_module)
+            .ok_or(TargetError::Errno(errno::EINVAL))?;
+        Ok(block + offset)
+    }
+}
+
+impl target::ext::auxv::Auxv for MockTarget {
+    fn get_auxv(&mut self) -> TargetResult<&[u8], Self> {
+        Ok(AUXV)
+    }
+}
+
+impl target::ext::exec_file::ExecFile for MockTarget {
+    fn get_exec_file(&mut self, pid: Option<Pid>) -> TargetResult<&[u8], Self> {
+        match pid {
+            Some(pid) if pid.get() != EXEC_FILE.0 => Err(TargetError::Errno(errno::ENOENT)),
+            _ => Ok(EXEC_FILE.1),
+        }
+    }
+}
+
+impl target::ext::siginfo::SigInfo for MockTarget {
+    fn get_siginfo(&mut self, tid: Tid) -> TargetResult<&[u8], Self> {
+        assert_eq!(Some(tid), tid_from_zero_based(0));
+        Ok(&self.siginfo)
+    }
+
+    fn support_siginfo_write(&mut self) -> Option<target::ext::siginfo::SigInfoWriteOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl target::ext::siginfo::SigInfoWrite for MockTarget {
+    fn set_siginfo(&mut self, _tid: Tid, offset: usize, data: &[u8]) -> TargetResult<(), Self> {
+        let dst = self
+            .siginfo
+            .get_mut(offset..offset + data.len())
+            .ok_or(TargetError::Errno(errno::EINVAL))?;
+        dst.copy_from_slice(data);
+        Ok(())
+    }
+}
+
+impl target::ext::libraries_svr4::LibrariesSvr4 for MockTarget {
+    fn list_libraries(
+        &mut self,
+        library: &mut dyn FnMut(target::ext::libraries_svr4::LibrarySvr4<'_, u32>),
+    ) -> Result<(), Self::Error> {
+        for &(name, lm, l_addr, l_ld) in LIBRARIES {
+            library(target::ext::libraries_svr4::LibrarySvr4 {
+                name,
+                lm,
+                l_addr,
+                l_ld,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl target::ext::target_description_xml_override::TargetDescriptionXmlOverride for MockTarget {
+    fn target_description_xml(&self) -> &str {
+        TARGET_XML
+    }
+
+    fn target_description_xml_features(&self) -> &[TargetDescriptionFeature<'_>] {
+        TARGET_XML_FEATURES
+    }
+}
+
+impl target::ext::memory_region_info::MemoryRegionInfo for MockTarget {
+    fn get_memory_region(&mut self, addr: u32) -> TargetResult<MemoryRegion<u32>, Self> {
+        if let Some(&(start, size, perms)) = MEMORY_REGIONS
+            .iter()
+            .find(|(start, size, _)| addr >= *start && addr - start < *size)
+        {
+            return Ok(MemoryRegion {
+                start,
+                size,
+                readable: perms.contains('r'),
+                writable: perms.contains('w'),
+                executable: perms.contains('x'),
+            });
+        }
+
+        // report the gap between the surrounding regions
+        let start = (MEMORY_REGIONS.iter())
+            .map(|(start, size, _)| start + size)
+            .filter(|end| *end <= addr)
+            .max()
+            .unwrap_or(0);
+        let end = (MEMORY_REGIONS.iter())
+            .map(|(start, _, _)| *start)
+            .filter(|start| *start > addr)
+            .min();
+        Ok(MemoryRegion {
+            start,
+            size: end.unwrap_or(0).wrapping_sub(start),
+            readable: false,
+            writable: false,
+            executable: false,
+        })
+    }
+}
+
+impl target::ext::lldb_host_info::LldbHostInfo for MockTarget {
+    fn get_host_info(&mut self) -> HostInfo<'_> {
+        HOST_INFO
+    }
+}
+
+impl target::ext::lldb_register_info::LldbRegisterInfo for MockTarget {
+    fn get_register_info(&mut self, reg_id: usize) -> Option<Register<'_>> {
+        LLDB_REGISTERS.get(reg_id).copied()
+    }
+}
+
+impl target::ext::symbol_lookup::SymbolLookup for MockTarget {
+    fn start_symbol_lookup(&mut self) -> TargetResult<(), Self> {
+        self.pending_symbols = (self.symbols.iter())
+            .filter(|(_, addr)| addr.is_none())
+            .map(|(name, _)| *name)
+            .collect();
+        Ok(())
+    }
+
+    fn next_symbol(&mut self) -> TargetResult<Option<&[u8]>, Self> {
+        Ok(self.pending_symbols.first().map(|name| name.as_bytes()))
+    }
+
+    fn symbol_resolved(&mut self, name: &[u8], addr: Option<u32>) -> TargetResult<(), Self> {
+        if self.pending_symbols.first().map(|s| s.as_bytes()) != Some(name) {
+            return Err(TargetError::NonFatal);
+        }
+        let name = self.pending_symbols.remove(0);
+        for symbol in self.symbols.iter_mut() {
+            if symbol.0 == name {
+                symbol.1 = addr;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl target::ext::flash::Flash for MockTarget {
+    fn flash_erase(&mut self, start_addr: u32, length: u32) -> TargetResult<(), Self> {
+        self.flash_erased.push((start_addr, length));
+        Ok(())
+    }
+
+    fn flash_write(&mut self, start_addr: u32, data: &[u8]) -> TargetResult<(), Self> {
+        let end = start_addr + data.len() as u32;
+        if !(self.flash_erased.iter())
+            .any(|&(start, len)| start <= start_addr && end <= start + len)
+        {
+            return Err(TargetError::Errno(errno::EIO));
+        }
+        self.flash_pending.push((start_addr, data.to_vec()));
+        Ok(())
+    }
+
+    fn flash_done(&mut self) -> TargetResult<(), Self> {
+        for (addr, data) in self.flash_pending.drain(..) {
+            let start = addr as usize;
+            self.mem[start..start + data.len()].copy_from_slice(&data);
+        }
+        self.flash_erased.clear();
+        Ok(())
+    }
+}
+
+impl target::ext::tracepoints::Tracepoints for MockTarget {
+    fn trace_init(&mut self) -> TargetResult<(), Self> {
+        self.tracepoints.clear();
+        self.trace_frames.clear();
+        self.selected_frame = None;
+        Ok(())
+    }
+
+    fn tracepoint_create(&mut self, tp: NewTracepoint<'_, u32>) -> TargetResult<(), Self> {
+        self.tracepoints.push(MockTracepoint {
+            number: tp.number.0,
+            addr: tp.addr,
+            enabled: tp.enabled,
+            step_count: tp.step_count,
+            pass_count: tp.pass_count,
+            condition: tp.condition.map(|c| c.to_vec()),
+            actions: Vec::new(),
+        });
+        Ok(())
+    }
+
+    fn tracepoint_add_action(
+        &mut self,
+        tp: Tracepoint,
+        addr: u32,
+        action: TracepointAction<'_, u32>,
+        while_stepping: bool,
+    ) -> TargetResult<(), Self> {
+        let defined = (self.tracepoints.iter_mut())
+            .find(|t| t.number == tp.0 && t.addr == addr)
+            .ok_or(TargetError::Errno(errno::EINVAL))?;
+        defined
+            .actions
+            .push((format!("{:?}", action), while_stepping));
+        Ok(())
+    }
+
+    fn trace_status(&mut self) -> TargetResult<ExperimentStatus, Self> {
+        Ok(self.trace_status)
+    }
+
+    fn trace_start(&mut self) -> TargetResult<(), Self> {
+        self.trace_status = ExperimentStatus::Running;
+        Ok(())
+    }
+
+    fn trace_stop(&mut self) -> TargetResult<(), Self> {
+        self.trace_status = ExperimentStatus::Stopped;
+        Ok(())
+    }
+
+    fn select_frame(&mut self, req: FrameRequest<u32>) -> TargetResult<Option<TraceFrame>, Self> {
+        let start = self.selected_frame.map(|n| n + 1).unwrap_or(0);
+        let found = match req {
+            FrameRequest::Number(n) => Some(n).filter(|n| *n < self.trace_frames.len()),
+            req => (self.trace_frames.iter().enumerate())
+                .skip(start)
+                .find(|(_, (tp, pc))| match req {
+                    FrameRequest::Pc(addr) => *pc == addr,
+                    FrameRequest::Tracepoint(t) => *tp == t.0,
+                    FrameRequest::InRange(start, end) => (start..=end).contains(pc),
+                    FrameRequest::OutsideRange(start, end) => !(start..=end).contains(pc),
+                    FrameRequest::Number(_) => unreachable!(),
+                })
+                .map(|(n, _)| n),
+        };
+
+        self.selected_frame = found;
+        Ok(found.map(|n| TraceFrame {
+            number: n,
+            tracepoint: Tracepoint(self.trace_frames[n].0),
+        }))
+    }
+
+    fn deselect_frame(&mut self) -> TargetResult<(), Self> {
+        self.selected_frame = None;
+        Ok(())
+    }
+}
+
+impl target::ext::extended_mode::ExtendedMode for MockTarget {
+    fn run(&mut self, filename: Option<&[u8]>, args: Args) -> TargetResult<Pid, Self> {
+        self.ran.push((
+            filename.map(|f| f.to_vec()),
+            args.map(|a| a.to_vec()).collect(),
+        ));
+        Ok(Pid::new(RUN_PID).unwrap())
+    }
+
+    fn attach(&mut self, _pid: Pid) -> TargetResult<(), Self> {
+        Ok(())
+    }
+
+    fn query_if_attached(&mut self, pid: Pid) -> TargetResult<AttachKind, Self> {
+        // every process other than the spawned one is reported as having been
+        // attached to
+        if pid.get() == RUN_PID && !self.ran.is_empty() {
+            Ok(AttachKind::Run)
+        } else {
+            Ok(AttachKind::Attach)
+        }
+    }
+
+    fn kill(&mut self, _pid: Option<Pid>) -> TargetResult<ShouldTerminate, Self> {
+        Ok(ShouldTerminate::No)
+    }
+
+    fn detach(&mut self, pid: Option<Pid>) -> TargetResult<(), Self> {
+        self.detached.push(pid);
+        Ok(())
+    }
+
+    fn select_process(&mut self, pid: Pid) -> TargetResult<(), Self> {
+        self.selected.push(pid);
+        Ok(())
+    }
+
+    fn restart(&mut self) -> Result<(), Self::Error> {
+        self.restarts += 1;
+        Ok(())
+    }
+
+    fn configure_aslr(&mut self) -> Option<target::ext::extended_mode::ConfigureAslrOps<Self>> {
+        Some(self)
+    }
+
+    fn configure_env(&mut self) -> Option<target::ext::extended_mode::ConfigureEnvOps<Self>> {
+        Some(self)
+    }
+
+    fn configure_startup_shell(
+        &mut self,
+    ) -> Option<target::ext::extended_mode::ConfigureStartupShellOps<Self>> {
+        Some(self)
+    }
+
+    fn configure_working_dir(
+        &mut self,
+    ) -> Option<target::ext::extended_mode::ConfigureWorkingDirOps<Self>> {
+        Some(self)
+    }
+}
+
+impl target::ext::extended_mode::ConfigureAslr for MockTarget {
+    fn cfg_aslr(&mut self, enabled: bool) -> TargetResult<(), Self> {
+        self.aslr = enabled;
+        Ok(())
+    }
+}
+
+impl target::ext::extended_mode::ConfigureWorkingDir for MockTarget {
+    fn cfg_working_dir(&mut self, dir: Option<&[u8]>) -> TargetResult<(), Self> {
+        self.working_dir = dir.map(|d| d.to_vec());
+        Ok(())
+    }
+}
+
+impl target::ext::extended_mode::ConfigureEnv for MockTarget {
+    fn set_env(&mut self, key: &[u8], val: Option<&[u8]>) -> TargetResult<(), Self> {
+        self.env.retain(|(k, _)| k != key);
+        self.env.push((key.to_vec(), val.map(|v| v.to_vec())));
+        Ok(())
+    }
+
+    fn remove_env(&mut self, key: &[u8]) -> TargetResult<(), Self> {
+        self.env.retain(|(k, _)| k != key);
+        Ok(())
+    }
+
+    fn reset_env(&mut self) -> TargetResult<(), Self> {
+        self.env.clear();
+        Ok(())
+    }
+}
+
+impl target::ext::extended_mode::ConfigureStartupShell for MockTarget {
+    fn cfg_startup_with_shell(&mut self, enabled: bool) -> TargetResult<(), Self> {
+        self.startup_with_shell = enabled;
+        Ok(())
+    }
+}
+
+/// A multi-threaded ARM target, which natively uses 0-based thread ids (mapped
+/// to/from GDB thread ids via `tid_{from,to}_zero_based`). Each thread has its
+/// own registers, but all threads share the same memory.
+pub struct MockMultiThreadTarget {
+    pub mem: Vec<u8>,
+    /// `(name, regs)` pairs describing each active thread, indexed by the
+    /// thread's 0-based id.
+    pub threads: Vec<(&'static str, ArmCoreRegs)>,
+    /// The process each thread belongs to, indexed by the thread's 0-based id.
+    /// Threads without an entry belong to process 1.
+    pub thread_pids: Vec<Pid>,
+    /// Stop reasons reported by subsequent calls to `resume`. Once empty,
+    /// `resume` reports `DoneStep`.
+    pub stop_reasons: VecDeque<ThreadStopReason<u32>>,
+    /// Stop reasons reported by subsequent calls to `wait_for_stop` (in
+    /// non-stop mode). Once empty, `wait_for_stop` reports `GdbInterrupt`.
+    pub non_stop_stops: VecDeque<ThreadStopReason<u32>>,
+    /// The number of calls made to `wait_for_stop`.
+    pub non_stop_waits: usize,
+    /// Threads which GDB requested to stop (via `vCont;t`), where `None`
+    /// indicates all threads. Each request queues a `Stopped` stop reason.
+    pub stop_requests: Vec<Option<Tid>>,
+    /// Resume actions passed to the target, alongside the corresponding
+    /// thread (where `None` indicates the default resume action).
+    pub resume_actions: Vec<(Option<Tid>, ResumeAction)>,
+    pub sw_breakpoints: Vec<u32>,
+    /// Processes selected via `select_process`, in order.
+    pub selected_pids: Vec<Pid>,
+}
+
+/// The path reported for processes which called `exec`.
+pub const EXEC_PATH: &[u8] = b"/bin/sh";
+
+impl MockMultiThreadTarget {
+    pub fn new() -> MockMultiThreadTarget {
+        MockMultiThreadTarget {
+            mem: vec![0; 0x1000],
+            threads: vec![
+                ("main", ArmCoreRegs::default()),
+                ("worker", ArmCoreRegs::default()),
+            ],
+            thread_pids: Vec::new(),
+            stop_reasons: VecDeque::new(),
+            non_stop_stops: VecDeque::new(),
+            non_stop_waits: 0,
+            stop_requests: Vec::new(),
+            resume_actions: Vec::new(),
+            sw_breakpoints: Vec::new(),
+            selected_pids: Vec::new(),
+        }
+    }
+
+    fn thread(&mut self, tid: Tid) -> Option<&mut (&'static str, ArmCoreRegs)> {
+        self.threads.get_mut(tid_to_zero_based(tid))
+    }
+
+    fn pid(&self, id: usize) -> Pid {
+        (self.thread_pids.get(id).copied()).unwrap_or_else(|| Pid::new(1).unwrap())
+    }
+}
+
+impl Target for MockMultiThreadTarget {
+    type Arch = gdbstub_arch::arm::Armv4t;
+    type Error = &'static str;
+
+    fn base_ops(&mut self) -> target::ext::base::BaseOps<Self::Arch, Self::Error> {
+        target::ext::base::BaseOps::MultiThread(self)
+    }
+
+    fn breakpoints(&mut self) -> Option<target::ext::breakpoints::BreakpointsOps<Self>> {
+        Some(self)
+    }
+
+    fn extended_mode(&mut self) -> Option<target::ext::extended_mode::ExtendedModeOps<Self>> {
+        Some(self)
+    }
+}
+
+impl target::ext::extended_mode::ExtendedMode for MockMultiThreadTarget {
+    fn run(&mut self, _filename: Option<&[u8]>, _args: Args) -> TargetResult<Pid, Self> {
+        Err(().into())
+    }
+
+    fn attach(&mut self, _pid: Pid) -> TargetResult<(), Self> {
+        Err(().into())
+    }
+
+    fn query_if_attached(&mut self, _pid: Pid) -> TargetResult<AttachKind, Self> {
+        Ok(AttachKind::Attach)
+    }
+
+    fn kill(&mut self, _pid: Option<Pid>) -> TargetResult<ShouldTerminate, Self> {
+        Ok(ShouldTerminate::No)
+    }
+
+    fn restart(&mut self) -> Result<(), Self::Error> {
+        Err("restart not supported")
+    }
+
+    fn select_process(&mut self, pid: Pid) -> TargetResult<(), Self> {
+        self.selected_pids.push(pid);
+        Ok(())
+    }
+
+    fn catch_events(&mut self) -> Option<target::ext::extended_mode::CatchEventsOps<Self>> {
+        Some(self)
+    }
+}
+
+impl target::ext::extended_mode::CatchEvents for MockMultiThreadTarget {
+    fn exec_path(&mut self, _pid: Pid) -> Result<&[u8], Self::Error> {
+        Ok(EXEC_PATH)
+    }
+}
+
+impl target::ext::breakpoints::Breakpoints for MockMultiThreadTarget {
+    fn sw_breakpoint(&mut self) -> Option<target::ext::breakpoints::SwBreakpointOps<Self>> {
+        Some(self)
+    }
+}
+
+impl target::ext::breakpoints::SwBreakpoint for MockMultiThreadTarget {
+    fn add_sw_breakpoint(
+        &mut self,
+        addr: u32,
+        _kind: ArmBreakpointKind,
+    ) -> TargetResult<bool, Self> {
+        self.sw_breakpoints.push(addr);
+        Ok(true)
+    }
+
+    fn remove_sw_breakpoint(
+        &mut self,
+        addr: u32,
+        _kind: ArmBreakpointKind,
+    ) -> TargetResult<bool, Self> {
+        let len = self.sw_breakpoints.len();
+        self.sw_breakpoints.retain(|a| *a != addr);
+        Ok(self.sw_breakpoints.len() != len)
+    }
+}
+
+impl MultiThreadOps for MockMultiThreadTarget {
+    fn resume(
+        &mut self,
+        default_resume_action: ResumeAction,
+        _gdb_interrupt: GdbInterrupt<'_>,
+    ) -> Result<ThreadStopReason<u32>, Self::Error> {
+        self.resume_actions.push((None, default_resume_action));
+        Ok(self
+            .stop_reasons
+            .pop_front()
+            .unwrap_or(ThreadStopReason::DoneStep))
+    }
+
+    fn clear_resume_actions(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_resume_action(&mut self, tid: Tid, action: ResumeAction) -> Result<(), Self::Error> {
+        self.resume_actions.push((Some(tid), action));
+        Ok(())
+    }
+
+    fn support_non_stop(
+        &mut self,
+    ) -> Option<target::ext::base::multithread::MultiThreadNonStopOps<Self>> {
+        Some(self)
+    }
+
+    fn support_thread_extra_info(
+        &mut self,
+    ) -> Option<target::ext::base::multithread::MultiThreadExtraInfoOps<Self>> {
+        Some(self)
+    }
+
+    fn support_thread_list(
+        &mut self,
+    ) -> Option<target::ext::base::multithread::ThreadListOps<Self>> {
+        Some(self)
+    }
+
+    fn support_multiprocess(
+        &mut self,
+    ) -> Option<target::ext::base::multithread::MultiThreadMultiprocessOps<Self>> {
+        Some(self)
+    }
+
+    fn read_registers(&mut self, regs: &mut ArmCoreRegs, tid: Tid) -> TargetResult<(), Self> {
+        *regs = self.thread(tid).ok_or(())?.1.clone();
+        Ok(())
+    }
+
+    fn write_registers(&mut self, regs: &ArmCoreRegs, tid: Tid) -> TargetResult<(), Self> {
+        self.thread(tid).ok_or(())?.1 = regs.clone();
+        Ok(())
+    }
+
+    fn read_addrs(
+        &mut self,
+        start_addr: u32,
+        data: &mut [u8],
+        _tid: Tid,
+    ) -> TargetResult<(), Self> {
+        let start = start_addr as usize;
+        let src = self.mem.get(start..start + data.len()).ok_or(())?;
+        data.copy_from_slice(src);
+        Ok(())
+    }
+
+    fn write_addrs(&mut self, start_addr: u32, data: &[u8], _tid: Tid) -> TargetResult<(), Self> {
+        let start = start_addr as usize;
+        let dst = self.mem.get_mut(start..start + data.len()).ok_or(())?;
+        dst.copy_from_slice(data);
+        Ok(())
+    }
+
+    fn list_active_threads(
+        &mut self,
+        register_thread: &mut dyn FnMut(Tid),
+    ) -> Result<(), Self::Error> {
+        for id in 0..self.threads.len() {
+            register_thread(tid_from_zero_based(id).unwrap())
+        }
+        Ok(())
+    }
+}
+
+impl target::ext::base::multithread::MultiThreadNonStop for MockMultiThreadTarget {
+    fn resume_threads(
+        &mut self,
+        default_resume_action: Option<ResumeAction>,
+    ) -> Result<(), Self::Error> {
+        if let Some(action) = default_resume_action {
+            self.resume_actions.push((None, action));
+        }
+        Ok(())
+    }
+
+    fn wait_for_stop(
+        &mut self,
+        _gdb_interrupt: GdbInterrupt<'_>,
+    ) -> Result<ThreadStopReason<u32>, Self::Error> {
+        self.non_stop_waits += 1;
+        // as all packets are sent up-front, there's no need to check for an
+        // interrupt (which would otherwise never occur once the packets run out)
+        Ok(self
+            .non_stop_stops
+            .pop_front()
+            .unwrap_or(ThreadStopReason::GdbInterrupt))
+    }
+
+    fn request_stop(&mut self, tid: Option<Tid>) -> Result<(), Self::Error> {
+        self.stop_requests.push(tid);
+        match tid {
+            Some(tid) => self
+                .non_stop_stops
+                .push_back(ThreadStopReason::Stopped(tid)),
+            None => self.non_stop_stops.extend(
+                (0..self.threads.len())
+                    .map(|id| ThreadStopReason::Stopped(tid_from_zero_based(id).unwrap())),
+            ),
+        }
+        Ok(())
+    }
+}
+
+impl target::ext::base::multithread::MultiThreadExtraInfo for MockMultiThreadTarget {
+    fn thread_extra_info(&mut self, tid: Tid, buf: &mut [u8]) -> TargetResult<usize, Self> {
+        let name = match self.thread(tid) {
+            Some((name, _)) => name.as_bytes(),
+            None => return Err(TargetError::NonFatal),
+        };
+        let len = name.len().min(buf.len());
+        buf[..len].copy_from_slice(&name[..len]);
+        Ok(len)
+    }
+}
+
+impl target::ext::base::multithread::ThreadList for MockMultiThreadTarget {
+    fn list_threads(
+        &mut self,
+        thread_info: &mut dyn FnMut(target::ext::base::multithread::ThreadInfo<'_>),
+    ) -> Result<(), Self::Error> {
+        // each thread runs on the core matching its index
+        for (id, (name, _)) in self.threads.iter().enumerate() {
+            thread_info(target::ext::base::multithread::ThreadInfo {
+                tid: tid_from_zero_based(id).unwrap(),
+                name: Some(name),
+                core: Some(id),
+                pid: Some(self.pid(id)),
+            })
+        }
+        Ok(())
+    }
+}
+
+impl target::ext::base::multithread::MultiThreadMultiprocess for MockMultiThreadTarget {
+    fn thread_pid(&mut self, tid: Tid) -> Result<Pid, Self::Error> {
+        Ok(self.pid(tid_to_zero_based(tid)))
+    }
+
+    fn list_active_process_threads(
+        &mut self,
+        thread_is_active: &mut dyn FnMut(Pid, Tid),
+    ) -> Result<(), Self::Error> {
+        for id in 0..self.threads.len() {
+            thread_is_active(self.pid(id), tid_from_zero_based(id).unwrap());
+        }
+        Ok(())
+    }
+}
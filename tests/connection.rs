@@ -0,0 +1,175 @@
+//! End-to-end sessions over the built-in `Connection` wrappers, and over
+//! connections with read timeouts.
+
+mod common;
+
+use std::collections::VecDeque;
+use std::io::{self, Cursor, Read, Write};
+use std::time::Duration;
+
+use common::{packet, responses, MockTarget, EOF};
+use gdbstub::{BufferedConnection, Connection, ConnectionExt, GdbStub, GdbStubError, IoConnection};
+
+/// An in-memory pipe, which reports EOF once all incoming data has been
+/// consumed.
+struct Pipe {
+    rx: Cursor<Vec<u8>>,
+    tx: Vec<u8>,
+}
+
+impl Read for Pipe {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.rx.read(buf)
+    }
+}
+
+impl Write for Pipe {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.tx.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn io_connection() {
+    let rx = [packet("qAttached"), packet("?")].concat();
+    let mut conn = IoConnection::new(Pipe {
+        rx: Cursor::new(rx),
+        tx: Vec::new(),
+    });
+
+    let result = GdbStub::new(&mut conn as &mut dyn Connection<Error = io::Error>)
+        .run(&mut MockTarget::minimal());
+    match result {
+        Err(GdbStubError::ConnectionRead(e)) if e.kind() == io::ErrorKind::UnexpectedEof => {}
+        other => panic!("unexpected session result: {:?}", other),
+    }
+
+    assert_eq!(responses(&conn.into_inner().tx), ["1", "S05"]);
+}
+
+/// A connection which can check whether data is available, but can't peek at
+/// it (i.e: `peek` simply reports readiness, using a placeholder value).
+struct ReadinessOnlyConnection {
+    rx: VecDeque<u8>,
+    tx: Vec<u8>,
+}
+
+impl Connection for ReadinessOnlyConnection {
+    type Error = &'static str;
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        self.rx.pop_front().ok_or(EOF)
+    }
+
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+        self.tx.push(byte);
+        Ok(())
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+        Ok(if self.rx.is_empty() { None } else { Some(0) })
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[test]
+fn buffered_connection() {
+    let mut target = MockTarget::new();
+    target.run_until_interrupt = true;
+
+    let rx = [packet("vCont;c"), vec![0x03], packet("?")].concat();
+    let mut conn = BufferedConnection::new(ReadinessOnlyConnection {
+        rx: rx.into_iter().collect(),
+        tx: Vec::new(),
+    });
+
+    let result = GdbStub::new(&mut conn as &mut dyn Connection<Error = _>).run(&mut target);
+    assert!(matches!(result, Err(GdbStubError::ConnectionRead(EOF))));
+
+    // the interrupt is consumed along with the stop, and isn't reported twice
+    assert_eq!(responses(&conn.get_ref().tx), ["T02thread:p01.01;", "S05"]);
+}
+
+/// A connection where `None` entries in `rx` simulate the GDB client going
+/// quiet for longer than the read timeout.
+///
+/// The connection traits are implemented on `&mut StallingConnection`, so that
+/// the sent data can be inspected once the session ends.
+struct StallingConnection {
+    rx: VecDeque<Option<u8>>,
+    tx: Vec<u8>,
+    /// Timeouts passed to `read_with_timeout`, in order.
+    timeouts: Vec<Duration>,
+}
+
+impl StallingConnection {
+    fn new(chunks: &[&[u8]]) -> StallingConnection {
+        let mut rx = VecDeque::new();
+        for chunk in chunks {
+            rx.extend(chunk.iter().copied().map(Some));
+            rx.push_back(None);
+        }
+        StallingConnection {
+            rx,
+            tx: Vec::new(),
+            timeouts: Vec::new(),
+        }
+    }
+}
+
+impl Connection for &mut StallingConnection {
+    type Error = &'static str;
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        panic!("`read` called when a timeout was requested")
+    }
+
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+        self.tx.push(byte);
+        Ok(())
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+        Ok(self.rx.front().copied().flatten())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl ConnectionExt for &mut StallingConnection {
+    fn read_with_timeout(&mut self, timeout: Duration) -> Result<Option<u8>, Self::Error> {
+        self.timeouts.push(timeout);
+        self.rx.pop_front().ok_or(EOF)
+    }
+}
+
+#[test]
+fn read_timeout() {
+    let full = packet("qAttached");
+    let mut conn = StallingConnection::new(&[&packet("?"), &full[..4], &full]);
+
+    let result = GdbStub::new(&mut conn)
+        .run_with_read_timeout(&mut MockTarget::minimal(), Duration::from_millis(100));
+    assert!(matches!(result, Err(GdbStubError::ConnectionRead(EOF))));
+
+    // timeouts between packets are ignored, whereas a partially received
+    // packet is dropped (and nack'd)
+    assert!(conn.tx.starts_with(b"+$S05#b8-+"));
+    assert_eq!(responses(&conn.tx), ["S05", "1"]);
+
+    // every read (including the final one, which hits EOF) uses the timeout
+    assert!(!conn.timeouts.is_empty());
+    assert!(conn
+        .timeouts
+        .iter()
+        .all(|t| *t == Duration::from_millis(100)));
+}
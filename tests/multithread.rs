@@ -0,0 +1,465 @@
+//! End-to-end sessions against a multi-threaded target, exercising thread
+//! selection, per-thread resume actions, stop events, and non-stop mode.
+
+mod common;
+
+use common::{check_ok, run, run_ok, run_with_buffer_size, MockMultiThreadTarget, MockTarget};
+use gdbstub::common::{Pid, Signal, Tid};
+use gdbstub::target::ext::base::multithread::ThreadStopReason;
+use gdbstub::target::ext::base::ResumeAction;
+use gdbstub::GdbStubError;
+
+fn tid(id: usize) -> Tid {
+    Tid::new(id).unwrap()
+}
+
+fn pid(id: usize) -> Pid {
+    Pid::new(id).unwrap()
+}
+
+#[test]
+fn thread_queries() {
+    let mut target = MockMultiThreadTarget::new();
+    target.threads[0].1.pc = 0x1111;
+    target.threads[1].1.pc = 0x2222;
+
+    let res = run_ok(
+        &mut target,
+        &[
+            "qSupported:multiprocess+",
+            "vCont?",
+            "qfThreadInfo",
+            "qsThreadInfo",
+            "Hg2",
+            "g",
+            "qC",
+            "Hg1",
+            "g",
+            "qC",
+            "qThreadExtraInfo,1",
+            "qThreadExtraInfo,p1.2",
+            "qThreadExtraInfo,3",
+            "Tp1.2",
+            "Tp1.5",
+            "T0",
+            "T-1",
+        ],
+    );
+    for feature in [
+        ";qXfer:threads:read+",
+        ";QThreadEvents+",
+        ";QNonStop+",
+        ";fork-events+;vfork-events+;exec-events+",
+    ]
+    .iter()
+    {
+        assert!(res[0].contains(feature), "{}", feature);
+    }
+    assert!(res[1].ends_with(";t"));
+    assert_eq!(res[2..5], ["mp01.01,p01.02", "l", "OK"]);
+
+    // pc is the 16th 32-bit register
+    let pc = |regs: &str| regs[15 * 8..16 * 8].to_string();
+    assert_eq!(pc(&res[5]), "22220000");
+    assert_eq!(res[6..8], ["QCp01.02", "OK"]);
+    assert_eq!(pc(&res[8]), "11110000");
+    assert_eq!(res[9], "QCp01.01");
+
+    // "main", "worker", and an unknown thread (which is a non-fatal error)
+    assert_eq!(res[10..13], ["6d61696e", "776f726b6572", "E79"]);
+    assert_eq!(res[13..], ["OK", "E01", "OK", "E16"]);
+
+    target.threads[1].0 = "<worker & co>";
+    let res = run_ok(
+        &mut target,
+        &[
+            "qXfer:threads:read::0,1000",
+            "qXfer:threads:read::0,10",
+            "qXfer:threads:read::10,1000",
+        ],
+    );
+    let xml = concat!(
+        "<?xml version=\"1.0\"?>\n",
+        "<threads>\n",
+        "<thread id=\"p1.1\" core=\"0\" name=\"main\"/>\n",
+        "<thread id=\"p1.2\" core=\"1\" name=\"&lt;worker &amp; co&gt;\"/>\n",
+        "</threads>\n",
+    );
+    assert_eq!(
+        res,
+        [
+            format!("l{}", xml),
+            format!("m{}", &xml[..0x10]),
+            format!("l{}", &xml[0x10..]),
+        ]
+    );
+
+    // the single-threaded target has a single, implicit thread
+    let mut target = MockTarget::new();
+    let res = run_ok(
+        &mut target,
+        &[
+            "qSupported",
+            "qC",
+            "qfThreadInfo",
+            "qsThreadInfo",
+            "qfThreadInfo",
+            "T1",
+            "T2",
+            "T0",
+            "T-1",
+        ],
+    );
+    assert!(!res[0].contains("QThreadEvents"));
+    assert_eq!(
+        res[1..],
+        ["QCp01.01", "mp01.01", "l", "mp01.01", "OK", "E01", "OK", "E16"]
+    );
+}
+
+#[test]
+fn thread_queries_fit_in_packet() {
+    let mut target = MockMultiThreadTarget::new();
+    target.threads = vec![("worker", Default::default()); 40];
+    target.threads[0].0 = Box::leak("x".repeat(400).into_boxed_str());
+
+    let res = check_ok(run_with_buffer_size(
+        &mut target,
+        &[
+            "qThreadExtraInfo,1",
+            "qfThreadInfo",
+            "qsThreadInfo",
+            "qsThreadInfo",
+            "qsThreadInfo",
+        ],
+        400,
+    ));
+    assert!(res.iter().all(|r| r.len() <= 400));
+
+    // the hex-encoded description is truncated to fit in GDB's packet buffer
+    assert!(res[0].chars().all(|c| c == '7' || c == '8'));
+
+    // the thread list is paginated
+    assert_eq!(res.last().unwrap(), "l");
+    let tids = res[1..res.len() - 1]
+        .iter()
+        .flat_map(|r| r.strip_prefix('m').unwrap().split(','))
+        .collect::<Vec<_>>();
+    let expected = (1..=40)
+        .map(|i| format!("p01.{:02x}", i))
+        .collect::<Vec<_>>();
+    assert_eq!(tids, expected);
+
+    // starting over resets the cursor
+    let res = run_ok(&mut target, &["qfThreadInfo"]);
+    assert!(res[0].starts_with("mp01.01,p01.02,"));
+}
+
+#[test]
+fn resume() {
+    let mut target = MockMultiThreadTarget::new();
+
+    // a wildcard action coexists with specific actions on either side of it
+    let res = run_ok(&mut target, &["vCont;s:p1.2;c", "vCont;c;s:p1.2;s:p1.1"]);
+    assert_eq!(res, ["S05", "S05"]);
+    assert_eq!(
+        target.resume_actions,
+        [
+            (Some(tid(2)), ResumeAction::Step),
+            (None, ResumeAction::Continue),
+            (Some(tid(2)), ResumeAction::Step),
+            (Some(tid(1)), ResumeAction::Step),
+            (None, ResumeAction::Continue),
+        ]
+    );
+
+    // thread 2 belongs to process 1, so the action for `p2.2` doesn't apply to
+    // it
+    target.resume_actions.clear();
+    let res = run_ok(&mut target, &["vCont;c:p1.2;s:p2.2"]);
+    assert_eq!(res, ["S05"]);
+    assert!(!target
+        .resume_actions
+        .contains(&(Some(tid(2)), ResumeAction::Step)));
+
+    target.resume_actions.clear();
+    for packet in [
+        "vCont;c:p1.2;s:p1.2",
+        "vCont;s:p1.2;c;s:p1.2",
+        // a thread-id without a pid belongs to the current process
+        "vCont;c:2;s:p1.2",
+    ]
+    .iter()
+    {
+        let (result, res) = run(&mut target, &[packet]);
+        assert!(
+            matches!(result, Err(GdbStubError::PacketParse(_))),
+            "{}",
+            packet
+        );
+        assert!(res.is_empty(), "{}", packet);
+        assert!(target.resume_actions.is_empty(), "{}", packet);
+    }
+
+    // the PC of the thread selected via `Hc` is updated, not the one selected
+    // via `Hg`, and there's no single thread to resume from the given address
+    // once all threads are selected
+    let res = run_ok(
+        &mut target,
+        &["Hg1", "Hc2", "c00000200", "Hc-1", "c00000300", "s00000300"],
+    );
+    assert_eq!(res, ["OK", "OK", "S05", "OK", "E16", "E16"]);
+    assert_eq!(target.threads[0].1.pc, 0);
+    assert_eq!(target.threads[1].1.pc, 0x200);
+
+    // the thread which received the signal becomes the current thread
+    target.stop_reasons = vec![
+        ThreadStopReason::ThreadSignal {
+            tid: tid(2),
+            signal: Signal::SIGUSR1,
+        },
+        ThreadStopReason::Signal(Signal::SIGUSR1),
+    ]
+    .into();
+    let res = run_ok(&mut target, &["Hg1", "c", "qC", "c"]);
+    assert_eq!(res, ["OK", "T1ethread:p01.02;", "QCp01.02", "S1e"]);
+
+    // processes are selected alongside their threads
+    let res = run_ok(&mut target, &["Hcp2.1", "Hgp2.2", "Hcp3.-1"]);
+    assert_eq!(res, ["OK", "OK", "OK"]);
+    assert_eq!(target.selected_pids, [pid(2), pid(3)]);
+}
+
+#[test]
+fn stop_events() {
+    let mut target = MockMultiThreadTarget::new();
+
+    // the target is resumed until it stops for a reason GDB asked about
+    target.stop_reasons = vec![
+        ThreadStopReason::ThreadCreated(tid(2)),
+        ThreadStopReason::ThreadExited {
+            tid: tid(2),
+            status: 0,
+        },
+        ThreadStopReason::Fork {
+            tid: tid(1),
+            new_pid: pid(2),
+            new_tid: tid(3),
+        },
+        ThreadStopReason::Exec(tid(1)),
+    ]
+    .into();
+    let res = run_ok(
+        &mut target,
+        &[
+            "qSupported:fork-events+",
+            "QThreadEvents:1",
+            "QThreadEvents:0",
+            "vCont;c",
+            "vCont;c",
+        ],
+    );
+    assert_eq!(
+        res[1..],
+        ["OK", "OK", "T05thread:p01.01;fork:p02.03;", "S05"]
+    );
+    assert_eq!(target.resume_actions, [(None, ResumeAction::Continue); 5]);
+
+    target.stop_reasons = vec![
+        ThreadStopReason::ThreadCreated(tid(2)),
+        ThreadStopReason::ThreadExited {
+            tid: tid(2),
+            status: 3,
+        },
+        ThreadStopReason::Fork {
+            tid: tid(1),
+            new_pid: pid(2),
+            new_tid: tid(3),
+        },
+        ThreadStopReason::VFork {
+            tid: tid(2),
+            new_pid: pid(4),
+            new_tid: tid(4),
+        },
+        ThreadStopReason::VForkDone(tid(2)),
+        ThreadStopReason::Exec(tid(1)),
+    ]
+    .into();
+    let res = run_ok(
+        &mut target,
+        &[
+            "qSupported:fork-events+;vfork-events+;exec-events+",
+            "QThreadEvents:1",
+            "vCont;c",
+            "vCont;c",
+            "vCont;c",
+            "vCont;c",
+            "vCont;c",
+            "vCont;c",
+        ],
+    );
+    assert_eq!(
+        res[1..],
+        [
+            "OK",
+            "T05thread:p01.02;create:;",
+            "w03;p01.02",
+            "T05thread:p01.01;fork:p02.03;",
+            "T05thread:p01.02;vfork:p04.04;",
+            "T05thread:p01.02;vforkdone:;",
+            "T05thread:p01.01;exec:2f62696e2f7368;",
+        ]
+    );
+
+    // the stopped thread's process is selected
+    target.thread_pids = vec![pid(1), pid(2)];
+    target.stop_reasons = vec![
+        ThreadStopReason::ThreadSignal {
+            tid: tid(2),
+            signal: Signal::SIGUSR1,
+        },
+        ThreadStopReason::ThreadExited {
+            tid: tid(2),
+            status: 3,
+        },
+    ]
+    .into();
+    let res = run_ok(
+        &mut target,
+        &[
+            "qfThreadInfo",
+            "qXfer:threads:read::0,1000",
+            "c",
+            "qC",
+            "QThreadEvents:1",
+            "vCont;c",
+        ],
+    );
+    assert_eq!(res[0], "mp01.01,p02.02");
+    assert!(res[1].contains("<thread id=\"p2.2\""));
+    assert_eq!(res[2..4], ["T1ethread:p02.02;", "QCp02.02"]);
+    assert_eq!(res[5], "w03;p02.02");
+    assert_eq!(target.selected_pids, [pid(2)]);
+}
+
+#[test]
+fn non_stop() {
+    let mut target = MockMultiThreadTarget::new();
+
+    // all-stop mode is the default, and `vCont;t` is only valid in non-stop
+    // mode
+    let res = run_ok(
+        &mut target,
+        &["vCont;t:p1.2", "vCont;c", "QNonStop:1", "QNonStop:0", "c"],
+    );
+    assert_eq!(res, ["E16", "S05", "OK", "OK", "S05"]);
+    assert!(target.stop_requests.is_empty());
+
+    // initial stops are drained via `vStopped`, and there's nothing for
+    // `vCtrlC` to stop
+    let res = run_ok(
+        &mut target,
+        &["QNonStop:1", "?", "vStopped", "vStopped", "vCtrlC"],
+    );
+    assert_eq!(
+        res,
+        ["OK", "T05thread:p01.01;", "T05thread:p01.02;", "OK", "OK"]
+    );
+
+    target.resume_actions.clear();
+    target.non_stop_stops = vec![
+        ThreadStopReason::SwBreak(tid(2)),
+        ThreadStopReason::SwBreak(tid(1)),
+    ]
+    .into();
+    let res = run_ok(
+        &mut target,
+        &[
+            "qSupported:swbreak+",
+            "QNonStop:1",
+            "vCont;c",
+            // thread 2 hits a breakpoint while GDB is busy reading memory
+            "m0,4",
+            "vStopped",
+            // resume thread 2, while thread 1 keeps running
+            "vCont;c:p1.2",
+            "vStopped",
+        ],
+    );
+    assert_eq!(
+        res[1..],
+        [
+            "OK",
+            "OK",
+            "%Stop:T05thread:p01.02;swbreak:;",
+            "00000000",
+            "OK",
+            "%Stop:T05thread:p01.01;swbreak:;",
+            "OK",
+            "OK",
+        ]
+    );
+    assert_eq!(
+        target.resume_actions,
+        [
+            (None, ResumeAction::Continue),
+            (Some(tid(2)), ResumeAction::Continue)
+        ]
+    );
+
+    // running threads are polled once before each of the `m` packets, and once
+    // more before the connection runs out of data (as opposed to once per byte)
+    target.non_stop_waits = 0;
+    let res = run_ok(&mut target, &["QNonStop:1", "vCont;c", "m0,4", "m0,4"]);
+    assert_eq!(res, ["OK", "OK", "00000000", "00000000"]);
+    assert_eq!(target.non_stop_waits, 3);
+
+    let res = run_ok(
+        &mut target,
+        &[
+            "QNonStop:1",
+            "vCont;c",
+            "vCont;t:p1.2",
+            "vStopped",
+            // stop all threads
+            "vCont;t",
+            "vStopped",
+            "vStopped",
+            "vCont;c",
+            "vCtrlC",
+            "vStopped",
+            "vStopped",
+        ],
+    );
+    assert_eq!(
+        res,
+        [
+            "OK",
+            "OK",
+            "OK",
+            "%Stop:T00thread:p01.02;",
+            "OK",
+            "OK",
+            "%Stop:T00thread:p01.01;",
+            "OK",
+            "%Stop:T00thread:p01.02;",
+            "OK",
+            "OK",
+            "OK",
+            "%Stop:T00thread:p01.01;",
+            "OK",
+            "%Stop:T00thread:p01.02;",
+            "OK",
+        ]
+    );
+    assert_eq!(target.stop_requests, [Some(tid(2)), None, None]);
+
+    // single-threaded targets don't support non-stop mode
+    let mut target = MockTarget::new();
+    let res = run_ok(
+        &mut target,
+        &["qSupported", "QNonStop:1", "vStopped", "vCont;t", "vCont;c"],
+    );
+    assert!(!res[0].contains("QNonStop"));
+    assert_eq!(res[1..], ["", "", "E16", "S05"]);
+}
@@ -0,0 +1,292 @@
+//! End-to-end sessions exercising the stub's handling of the underlying
+//! protocol: acks, packet framing, interrupts, and the builder's options.
+
+mod common;
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use common::{
+    packet, pump_all, responses, run, run_ok, run_with_buffer_size, MockConnection, MockTarget, EOF,
+};
+use gdbstub::{
+    Connection, DisconnectReason, GdbStub, GdbStubBuilderError, GdbStubError, PacketDirection,
+};
+
+fn run_raw(
+    target: &mut MockTarget,
+    rx: &[u8],
+) -> (GdbStubError<&'static str, &'static str>, Vec<u8>) {
+    let mut conn = MockConnection::new(rx);
+    let err = GdbStub::new(&mut conn as &mut dyn Connection<Error = _>)
+        .run(target)
+        .unwrap_err();
+    (err, conn.tx)
+}
+
+#[test]
+fn acks() {
+    let mut target = MockTarget::minimal();
+
+    let rx = [
+        // GDB re-sends a corrupted packet after it gets nack'd
+        &b"$?#00"[..],
+        &packet("?"),
+        // a `$` before the end of the previous packet starts a new packet
+        b"$qAttac",
+        &packet("?"),
+        b"$qAttached#",
+        &packet("qAttached"),
+        // GDB acks the stub's `OK`, after which neither side sends acks...
+        &packet("QStartNoAckMode"),
+        b"+",
+        &packet("?"),
+        // ...and a stray ack must not desync the session
+        b"+",
+        &packet("qAttached"),
+        // corrupted packets can no longer be re-sent
+        b"$?#00",
+    ]
+    .concat();
+
+    let (err, tx) = run_raw(&mut target, &rx);
+    assert!(matches!(err, GdbStubError::ChecksumMismatch));
+    assert!(tx.starts_with(b"-+$S05#b8"));
+    assert_eq!(tx.iter().filter(|b| **b == b'-').count(), 1);
+    assert_eq!(responses(&tx), ["S05", "S05", "1", "OK", "S05", "1"]);
+
+    let no_ack = packet("OK");
+    let no_ack_start = tx
+        .windows(no_ack.len())
+        .position(|w| w == &no_ack[..])
+        .unwrap();
+    assert!(!tx[no_ack_start..].contains(&b'+'));
+}
+
+#[test]
+fn malformed_packets() {
+    let mut target = MockTarget::minimal();
+
+    let res = run_ok(
+        &mut target,
+        &[
+            "vWhatever",
+            "vMustReplyEmpty",
+            "vKillX",
+            "vContFoo",
+            "vCont?",
+        ],
+    );
+    // unknown `v` packets reply empty, without shadowing known ones
+    assert_eq!(res, ["", "", "", "", "vCont;c"]);
+
+    let (res, _) = run(&mut target, &["qSearch:memory:100;4;gd}"]);
+    assert!(matches!(res, Err(GdbStubError::PacketParse(_))));
+}
+
+static TRACE: Mutex<Vec<(PacketDirection, Vec<u8>)>> = Mutex::new(Vec::new());
+
+fn tracer(direction: PacketDirection, data: &[u8]) {
+    TRACE.lock().unwrap().push((direction, data.to_vec()));
+}
+
+#[test]
+fn builder_options() {
+    let mut target = MockTarget::minimal();
+
+    let mut conn = MockConnection::new(&packet("qSupported:swbreak+"));
+    GdbStub::builder(&mut conn as &mut dyn Connection<Error = _>)
+        .packet_buffer_size(0x400)
+        .build()
+        .unwrap()
+        .run(&mut target)
+        .unwrap_err();
+    assert!(responses(&conn.tx)[0].starts_with("PacketSize=0400;"));
+
+    // an armv4t `G` packet is "$G" + 168 hex-encoded bytes + "#XX"
+    let res = GdbStub::<MockTarget, _>::builder(MockConnection::new(&[]))
+        .packet_buffer_size(340)
+        .build();
+    assert!(matches!(
+        res,
+        Err(GdbStubBuilderError::PacketBufTooSmall(341))
+    ));
+    let mut buf = [0; 340];
+    let res = GdbStub::<MockTarget, _>::builder(MockConnection::new(&[]))
+        .with_packet_buffer(&mut buf)
+        .build();
+    assert!(matches!(
+        res,
+        Err(GdbStubBuilderError::PacketBufTooSmall(341))
+    ));
+    let mut buf = [0; 341];
+    let res = GdbStub::<MockTarget, _>::builder(MockConnection::new(&[]))
+        .with_packet_buffer(&mut buf)
+        .build();
+    assert!(res.is_ok());
+
+    // run-length encoding is opt-in
+    for &rle in [false, true].iter() {
+        let mut conn = MockConnection::new(&packet("m100,10"));
+        let mut builder = GdbStub::builder(&mut conn as &mut dyn Connection<Error = _>);
+        if rle {
+            builder = builder.rle(true);
+        }
+        builder.build().unwrap().run(&mut target).unwrap_err();
+
+        assert_eq!(conn.tx.contains(&b'*'), rle);
+        assert_eq!(responses(&conn.tx), ["00".repeat(0x10)]);
+    }
+
+    // no-ack mode can be enabled up-front, for reliable transports
+    let mut conn = MockConnection::new(&packet("?"));
+    GdbStub::builder(&mut conn as &mut dyn Connection<Error = _>)
+        .no_ack_mode(true)
+        .build()
+        .unwrap()
+        .run(&mut target)
+        .unwrap_err();
+    assert_eq!(conn.tx, packet("S05"));
+
+    let mut conn = MockConnection::new(&[packet("?"), b"+".to_vec()].concat());
+    GdbStub::builder(&mut conn as &mut dyn Connection<Error = _>)
+        .packet_tracer(tracer)
+        .build()
+        .unwrap()
+        .run(&mut target)
+        .unwrap_err();
+    assert_eq!(
+        *TRACE.lock().unwrap(),
+        [
+            (PacketDirection::Incoming, b"$?#3f".to_vec()),
+            (PacketDirection::Outgoing, b"$S05#b8".to_vec()),
+            (PacketDirection::Incoming, b"+".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn oversized_packets() {
+    let mut target = MockTarget::minimal();
+    let mut buf = [0; 400];
+    let mut gdb = GdbStub::builder(MockConnection::new(&[]))
+        .with_packet_buffer(&mut buf)
+        .build()
+        .unwrap()
+        .into_state_machine();
+    let mut tx = Vec::new();
+
+    // a packet which exactly fits in the buffer is fine...
+    let fits = packet(&format!("qUnknown{}", "a".repeat(400 - 12)));
+    assert_eq!(fits.len(), 400);
+    assert!(matches!(
+        pump_all(&mut gdb, &mut target, &fits, &mut tx),
+        Ok(None)
+    ));
+    assert_eq!(tx, b"+$#00");
+    tx.clear();
+
+    // ...but one which is a single byte larger is discarded, and rejected
+    let too_large = format!("qUnknown{}", "a".repeat(400 - 11));
+    assert!(matches!(
+        pump_all(&mut gdb, &mut target, &packet(&too_large), &mut tx),
+        Err(GdbStubError::PacketTooLarge)
+    ));
+    // (it's still acked, as a nack would have GDB re-send the same packet)
+    assert_eq!(tx, b"+$E0c#d8");
+
+    // the session remains in sync with the client
+    tx.clear();
+    assert!(matches!(
+        pump_all(&mut gdb, &mut target, &packet("?"), &mut tx),
+        Ok(None)
+    ));
+    assert_eq!(responses(&tx), ["S05"]);
+
+    // blocking sessions carry on until the connection runs out of data
+    let (result, res) = run_with_buffer_size(&mut target, &[&too_large, "?"], 400);
+    assert!(matches!(result, Err(GdbStubError::ConnectionRead(EOF))));
+    assert_eq!(res, ["E0c", "S05"]);
+}
+
+#[test]
+fn disconnects_are_classified() {
+    let mut target = MockTarget::minimal();
+
+    // an intentional detach ends the session cleanly...
+    let (result, _) = run(&mut target, &["D"]);
+    assert_eq!(result.unwrap(), DisconnectReason::Disconnect);
+
+    // ...whereas the connection dropping mid-stream is reported as an error
+    let (err, _) = run_raw(&mut target, &packet("?")[..3]);
+    assert!(err.is_connection_error());
+    assert!(!err.is_target_error());
+}
+
+/// A connection which only supports peeking at data once it has been reported
+/// as readable, with GDB's interrupt "arriving" after a few polls.
+struct NonBlockingConnection {
+    rx: VecDeque<u8>,
+    tx: Vec<u8>,
+    polls_until_interrupt: usize,
+}
+
+impl Connection for NonBlockingConnection {
+    type Error = &'static str;
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        self.rx.pop_front().ok_or(EOF)
+    }
+
+    fn write(&mut self, byte: u8) -> Result<(), Self::Error> {
+        self.tx.push(byte);
+        Ok(())
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+        match self.rx.front() {
+            Some(b) => Ok(Some(*b)),
+            None => Err("peek would block"),
+        }
+    }
+
+    fn poll_readable(&mut self) -> Result<bool, Self::Error> {
+        if self.rx.is_empty() && self.polls_until_interrupt != 0 {
+            self.polls_until_interrupt -= 1;
+            if self.polls_until_interrupt == 0 {
+                self.rx.push_back(0x03);
+            }
+        }
+        Ok(!self.rx.is_empty())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[test]
+fn interrupts() {
+    let mut target = MockTarget::minimal();
+
+    // the target isn't running, so there's nothing for `vCtrlC` to interrupt,
+    // and a lone interrupt isn't treated as part of the following packet
+    let rx = [packet("vCtrlC"), packet("?"), vec![0x03], packet("?")].concat();
+    let (err, tx) = run_raw(&mut target, &rx);
+    assert!(matches!(err, GdbStubError::ConnectionRead(EOF)));
+    assert_eq!(responses(&tx), ["OK", "S05", "T02thread:p01.01;", "S05"]);
+    assert!(target.resume_actions.is_empty());
+
+    target.run_until_interrupt = true;
+    let mut conn = NonBlockingConnection {
+        rx: packet("vCont;c").into_iter().collect(),
+        tx: Vec::new(),
+        polls_until_interrupt: 16,
+    };
+    let result = GdbStub::new(&mut conn as &mut dyn Connection<Error = _>).run(&mut target);
+    assert!(matches!(result, Err(GdbStubError::ConnectionRead(EOF))));
+
+    // the interrupt is consumed along with the stop, and isn't reported twice
+    assert_eq!(responses(&conn.tx), ["T02thread:p01.01;"]);
+    assert!(conn.rx.is_empty());
+}
@@ -0,0 +1,418 @@
+//! End-to-end sessions exercising register access (`g` / `G` / `p` / `P`), and
+//! expedited registers in stop replies.
+
+mod common;
+
+use std::cell::Cell;
+use std::collections::VecDeque;
+
+use common::{hex, run_ok, MockTarget};
+use gdbstub::arch::{Arch, DeserializeError, RegId, Registers};
+use gdbstub::common::Signal;
+use gdbstub::target;
+use gdbstub::target::ext::base::singlethread::{
+    GdbInterrupt, ResumeAction, SingleThreadOps, StopReason,
+};
+use gdbstub::target::ext::base::SingleRegisterAccess;
+use gdbstub::target::{Target, TargetError, TargetResult};
+
+thread_local! {
+    /// Number of times the `gdb_serialize_into` fast path succeeded.
+    static FAST_PATH: Cell<usize> = Cell::new(0);
+}
+
+/// A MIPS-like architecture, whose general purpose registers are either 32 or
+/// 64 bits wide depending on the target's current mode, alongside a 512-bit
+/// vector register, and a (hypothetical) 1024-bit vector register which are
+/// only accessible via `p` / `P`.
+enum ModalArch {}
+
+impl Arch for ModalArch {
+    type Usize = u64;
+    type Registers = ModalRegs;
+    type BreakpointKind = ();
+    type RegId = ModalRegId;
+}
+
+#[derive(Debug)]
+enum ModalRegId {
+    R(usize),
+    Pc,
+    Zmm,
+    Huge,
+}
+
+impl RegId for ModalRegId {
+    fn from_raw_id(id: usize) -> Option<(Self, usize)> {
+        match id {
+            0 | 1 => Some((ModalRegId::R(id), 8)),
+            2 => Some((ModalRegId::Pc, 8)),
+            3 => Some((ModalRegId::Zmm, 64)),
+            4 => Some((ModalRegId::Huge, 128)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ModalRegs {
+    wide: bool,
+    r: [u64; 2],
+    pc: u64,
+    /// Whether the pc is unavailable.
+    pc_missing: bool,
+    zmm: [u8; 64],
+    huge: [u8; 128],
+}
+
+impl Default for ModalRegs {
+    fn default() -> ModalRegs {
+        ModalRegs {
+            wide: false,
+            r: [0; 2],
+            pc: 0,
+            pc_missing: false,
+            zmm: [0; 64],
+            huge: [0; 128],
+        }
+    }
+}
+
+impl ModalRegs {
+    fn width(&self) -> usize {
+        if self.wide {
+            8
+        } else {
+            4
+        }
+    }
+}
+
+impl Registers for ModalRegs {
+    type ProgramCounter = u64;
+
+    fn pc(&self) -> u64 {
+        self.pc
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        for (i, reg) in self.r.iter().chain(core::iter::once(&self.pc)).enumerate() {
+            for b in reg.to_le_bytes().iter().take(self.width()) {
+                if i == 2 && self.pc_missing {
+                    write_byte(None)
+                } else {
+                    write_byte(Some(*b))
+                }
+            }
+        }
+    }
+
+    // only implemented for the (common) 32-bit mode
+    fn gdb_serialize_into(&self, buf: &mut [u8]) -> Option<usize> {
+        if self.wide || self.pc_missing {
+            return None;
+        }
+
+        let buf = buf.get_mut(..12)?;
+        for (chunk, reg) in buf
+            .chunks_exact_mut(4)
+            .zip([self.r[0], self.r[1], self.pc].iter())
+        {
+            chunk.copy_from_slice(&(*reg as u32).to_le_bytes());
+        }
+        FAST_PATH.with(|n| n.set(n.get() + 1));
+        Some(buf.len())
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), DeserializeError> {
+        let width = self.width();
+        if bytes.len() != width * 3 {
+            return Err(DeserializeError::SizeMismatch);
+        }
+
+        let mut regs = bytes.chunks_exact(width).map(|c| {
+            let mut buf = [0; 8];
+            buf[..width].copy_from_slice(c);
+            u64::from_le_bytes(buf)
+        });
+        self.r[0] = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.r[1] = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        self.pc = regs.next().ok_or(DeserializeError::SizeMismatch)?;
+        Ok(())
+    }
+
+    const EXPEDITE: bool = true;
+
+    fn gdb_expedite(&self, mut write_reg: impl FnMut(usize, &[u8])) {
+        write_reg(2, &self.pc.to_le_bytes()[..self.width()]);
+    }
+}
+
+/// A target which switches into its 64-bit mode when resumed.
+struct ModalTarget {
+    regs: ModalRegs,
+    registers_readable: bool,
+    /// Stop reasons to report from subsequent resumes. Once empty, `DoneStep`
+    /// is reported.
+    stop_reasons: VecDeque<StopReason<u64>>,
+}
+
+impl ModalTarget {
+    fn new() -> ModalTarget {
+        let mut regs = ModalRegs {
+            r: [0x1111_2222, 0x3333_4444],
+            pc: 0x8000_0000,
+            ..ModalRegs::default()
+        };
+        for (i, b) in regs.zmm.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        for (i, b) in regs.huge.iter_mut().enumerate() {
+            *b = 0x80 + i as u8;
+        }
+        ModalTarget {
+            regs,
+            registers_readable: true,
+            stop_reasons: VecDeque::new(),
+        }
+    }
+}
+
+impl Target for ModalTarget {
+    type Arch = ModalArch;
+    type Error = &'static str;
+
+    fn base_ops(&mut self) -> target::ext::base::BaseOps<Self::Arch, Self::Error> {
+        target::ext::base::BaseOps::SingleThread(self)
+    }
+
+    fn target_description_xml_override(
+        &mut self,
+    ) -> Option<target::ext::target_description_xml_override::TargetDescriptionXmlOverrideOps<Self>>
+    {
+        Some(self)
+    }
+}
+
+impl SingleThreadOps for ModalTarget {
+    fn resume(
+        &mut self,
+        _action: ResumeAction,
+        _gdb_interrupt: GdbInterrupt<'_>,
+    ) -> Result<StopReason<u64>, Self::Error> {
+        self.regs.wide = true;
+        Ok(self
+            .stop_reasons
+            .pop_front()
+            .unwrap_or(StopReason::DoneStep))
+    }
+
+    fn read_registers(&mut self, regs: &mut ModalRegs) -> TargetResult<(), Self> {
+        if !self.registers_readable {
+            return Err(TargetError::NonFatal);
+        }
+        *regs = self.regs.clone();
+        Ok(())
+    }
+
+    fn write_registers(&mut self, regs: &ModalRegs) -> TargetResult<(), Self> {
+        self.regs = regs.clone();
+        Ok(())
+    }
+
+    fn single_register_access(
+        &mut self,
+    ) -> Option<target::ext::base::SingleRegisterAccessOps<(), Self>> {
+        Some(self)
+    }
+
+    fn read_addrs(&mut self, _start_addr: u64, _data: &mut [u8]) -> TargetResult<(), Self> {
+        Err(TargetError::NonFatal)
+    }
+
+    fn write_addrs(&mut self, _start_addr: u64, _data: &[u8]) -> TargetResult<(), Self> {
+        Err(TargetError::NonFatal)
+    }
+}
+
+impl SingleRegisterAccess<()> for ModalTarget {
+    fn read_register(
+        &mut self,
+        _tid: (),
+        reg_id: ModalRegId,
+        dst: &mut [u8],
+    ) -> TargetResult<(), Self> {
+        match reg_id {
+            ModalRegId::R(i) => dst.copy_from_slice(&self.regs.r[i].to_le_bytes()),
+            ModalRegId::Pc => dst.copy_from_slice(&self.regs.pc.to_le_bytes()),
+            ModalRegId::Zmm => dst.copy_from_slice(&self.regs.zmm),
+            ModalRegId::Huge => dst.copy_from_slice(&self.regs.huge),
+        }
+        Ok(())
+    }
+
+    fn write_register(
+        &mut self,
+        _tid: (),
+        reg_id: ModalRegId,
+        val: &[u8],
+    ) -> TargetResult<(), Self> {
+        let mut buf = [0; 8];
+        match reg_id {
+            ModalRegId::R(i) => {
+                buf.copy_from_slice(val);
+                self.regs.r[i] = u64::from_le_bytes(buf);
+            }
+            ModalRegId::Pc => {
+                buf.copy_from_slice(val);
+                self.regs.pc = u64::from_le_bytes(buf);
+            }
+            ModalRegId::Zmm => self.regs.zmm.copy_from_slice(val),
+            ModalRegId::Huge => self.regs.huge.copy_from_slice(val),
+        }
+        Ok(())
+    }
+}
+
+impl target::ext::target_description_xml_override::TargetDescriptionXmlOverride for ModalTarget {
+    fn target_description_xml(&self) -> &str {
+        if self.regs.wide {
+            r#"<target version="1.0"><architecture>mips:isa64</architecture></target>"#
+        } else {
+            r#"<target version="1.0"><architecture>mips:isa32</architecture></target>"#
+        }
+    }
+}
+
+#[test]
+fn registers_use_current_mode() {
+    let mut target = ModalTarget::new();
+
+    let res = run_ok(
+        &mut target,
+        &[
+            "g",
+            "qXfer:features:read:target.xml:0,1000",
+            "G010000000200000003000000",
+            // truncated
+            "G0100000002000000030000",
+            // over-long
+            "G01000000020000000300000004000000",
+            "g",
+        ],
+    );
+    assert_eq!(res[0], "222211114444333300000080");
+    assert!(res[1].contains("mips:isa32"));
+    assert_eq!(res[2..], ["OK", "E16", "E16", "010000000200000003000000"]);
+    assert_eq!(target.regs.r, [1, 2]);
+    assert_eq!(target.regs.pc, 3);
+    assert_eq!(FAST_PATH.with(Cell::get), 2);
+
+    // the pc is expedited in stop replies, using the current mode's width
+    target.stop_reasons.push_back(StopReason::SignalWithInfo {
+        signal: Signal::SIGSEGV,
+        addr: 0x800,
+    });
+    let description = hex(b"fault address: 0x0800");
+    let res = run_ok(
+        &mut target,
+        &[
+            "c",
+            "g",
+            "qXfer:features:read:target.xml:0,1000",
+            "G010000000000000102000000000000020300000000000003",
+        ],
+    );
+    assert_eq!(
+        res[0],
+        format!(
+            "T0bthread:p01.01;02:0300000000000000;description:{};",
+            description
+        )
+    );
+    assert_eq!(res[1], "010000000000000002000000000000000300000000000000");
+    assert!(res[2].contains("mips:isa64"));
+    assert_eq!(res[3], "OK");
+    assert_eq!(
+        target.regs.r,
+        [0x0100_0000_0000_0001, 0x0200_0000_0000_0002]
+    );
+    assert_eq!(target.regs.pc, 0x0300_0000_0000_0003);
+    // the fast path doesn't support the 64-bit mode
+    assert_eq!(FAST_PATH.with(Cell::get), 2);
+
+    // unavailable registers fall back to the slow path
+    target.regs.wide = false;
+    target.regs.pc_missing = true;
+    let res = run_ok(&mut target, &["g"]);
+    assert_eq!(res, ["0100000002000000xxxxxxxx"]);
+    assert_eq!(FAST_PATH.with(Cell::get), 2);
+
+    // unreadable registers are left for GDB to fetch via `g`
+    target.registers_readable = false;
+    target.stop_reasons.push_back(StopReason::SignalWithInfo {
+        signal: Signal::SIGSEGV,
+        addr: 0x800,
+    });
+    let res = run_ok(&mut target, &["c"]);
+    assert_eq!(
+        res,
+        [format!("T0bthread:p01.01;description:{};", description)]
+    );
+}
+
+#[test]
+fn single_register_access() {
+    let mut target = ModalTarget::new();
+    let zmm = target.regs.zmm;
+    let huge = target.regs.huge;
+
+    // registers may be larger than the stub's internal buffer
+    let res = run_ok(&mut target, &["p2", "p3", "p4", "p5"]);
+    assert_eq!(
+        res,
+        [
+            "0000008000000000".to_string(),
+            hex(&zmm),
+            hex(&huge),
+            "".to_string()
+        ]
+    );
+
+    let res = run_ok(
+        &mut target,
+        &[
+            "P2=0000000800000000",
+            "P2=0000",
+            "P3=ff",
+            &format!("P3={}", "aa".repeat(64)),
+            "p3",
+        ],
+    );
+    assert_eq!(
+        res,
+        [
+            "OK".to_string(),
+            "E16".into(),
+            "E16".into(),
+            "OK".into(),
+            "aa".repeat(64)
+        ]
+    );
+    assert_eq!(target.regs.pc, 0x0800_0000);
+
+    // `ArmCoreRegs` supports `Registers::INDEXED_ACCESS`, so `p` / `P` work
+    // without the `SingleRegisterAccess` extension
+    let mut target = MockTarget::new();
+    target.regs.r[1] = 0x1234_5678;
+
+    let res = run_ok(
+        &mut target,
+        &["p1", "p10", "Pf=00010008", "p1a", "Pf=00", "P1a=00000000"],
+    );
+    assert_eq!(
+        res,
+        ["78563412", "xxxxxxxxxxxxxxxxxxxxxxxx", "OK", "", "E16", ""]
+    );
+    assert_eq!(target.regs.pc, 0x0800_0100);
+}
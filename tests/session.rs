@@ -0,0 +1,1119 @@
+//! End-to-end sessions against a single-threaded target, exercising each of the
+//! target extensions from GDB's point of view.
+
+mod common;
+
+use common::{
+    check_ok, hex, run, run_ok, run_with_buffer_size, MockTarget, MockTracepoint, AUXV, RUN_PID,
+};
+use gdbstub::common::{Pid, Signal};
+use gdbstub::target::ext::base::singlethread::StopReason;
+use gdbstub::target::ext::base::{ReplayLogPosition, ResumeAction};
+use gdbstub::target::ext::breakpoints::WatchKind;
+use gdbstub::target::ext::tracepoints::{ExperimentStatus, Tracepoint};
+use gdbstub::{DisconnectReason, GdbStubError};
+use gdbstub_arch::arm::ArmBreakpointKind;
+
+const TARGET_XML: &str = r#"<target version="1.0"><architecture>arm</architecture><xi:include href="arm-core.xml"/><xi:include href="arm-vfp.xml"/></target>"#;
+
+const LIBRARY_LIST: &str = "<?xml version=\"1.0\"?>\n\
+<library-list-svr4 version=\"1.0\">\n\
+<library name=\"/lib/libc.so.6\" lm=\"0x1000\" l_addr=\"0x0\" l_ld=\"0x7f8a3e18\"/>\n\
+<library name=\"/tmp/a&amp;b.so\" lm=\"0x1040\" l_addr=\"0x20000000\" l_ld=\"0x20001f00\"/>\n\
+</library-list-svr4>\n";
+
+/// `arm-none-eabi`, hex-encoded.
+const TRIPLE: &str = "61726d2d6e6f6e652d65616269";
+
+/// Collect the console output sent by the stub (i.e: hex-encoded `O` packets).
+fn console_output(res: &[String]) -> String {
+    res.iter()
+        .filter_map(|r| r.strip_prefix('O'))
+        .filter(|r| !r.is_empty() && r.len() % 2 == 0)
+        .map(|hex| {
+            (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap() as char)
+                .collect::<String>()
+        })
+        .collect()
+}
+
+#[test]
+fn run_control() {
+    let mut target = MockTarget::new();
+
+    let res = run_ok(
+        &mut target,
+        &["qSupported:multiprocess+;swbreak+", "vCont?"],
+    );
+    for feature in [
+        ";ConditionalBreakpoints+",
+        ";QPassSignals+;QProgramSignals+",
+        ";ReverseContinue+;ReverseStep+",
+    ]
+    .iter()
+    {
+        assert!(res[0].contains(feature), "{}", feature);
+    }
+    assert_eq!(res[1], "vCont;c;C;s;S");
+
+    // breakpoints must be removed using the same kind they were inserted with,
+    // and the arch picks a kind when the client doesn't specify one
+    let res = run_ok(
+        &mut target,
+        &[
+            "Z0,1000,4",
+            "Z0,2000,2",
+            "Z0,2004,3",
+            "z0,2000,4",
+            "z0,2000,2",
+            "Z0,3000,1",
+            "Z0,3000,0",
+            "z0,3000,0",
+        ],
+    );
+    assert_eq!(res, ["OK", "OK", "OK", "E16", "OK", "E16", "OK", "OK"]);
+    assert_eq!(
+        target.sw_breakpoints,
+        [
+            (0x1000, ArmBreakpointKind::Arm32),
+            (0x2004, ArmBreakpointKind::Thumb32),
+        ]
+    );
+
+    target.breakpoint_conditions.clear();
+    let res = run_ok(&mut target, &["Z0,4000,4;X2,2201X3,0a0b0c"]);
+    assert_eq!(res, ["OK"]);
+    assert_eq!(
+        target.breakpoint_conditions,
+        [(0x4000, vec![vec![0x22, 0x01], vec![0x0a, 0x0b, 0x0c]])]
+    );
+
+    // GDB re-inserts the breakpoint without any conditions once they're
+    // removed, and breakpoint commands don't affect the conditions
+    let res = run_ok(&mut target, &["Z0,4000,4", "Z0,5000,4;X1,27;cmds:0,X1,01"]);
+    assert_eq!(res, ["OK", "OK"]);
+    assert_eq!(
+        target.breakpoint_conditions,
+        [(0x4000, vec![]), (0x5000, vec![vec![0x27]])]
+    );
+
+    // watching a single byte sends a "kind" of 1, which isn't a valid
+    // `ArmBreakpointKind`, and the length must match the one the watchpoint
+    // was added with
+    let res = run_ok(
+        &mut target,
+        &[
+            "Z2,1000,1",
+            "z2,1000,1",
+            "Z3,2000,10",
+            "Z4,3000,1",
+            "z3,2000,10",
+            "z4,3000,4",
+        ],
+    );
+    assert_eq!(res, ["OK", "OK", "OK", "OK", "OK", "E16"]);
+    assert_eq!(target.hw_watchpoints, [(0x3000, 1, WatchKind::ReadWrite)]);
+
+    target.stop_reasons = vec![
+        StopReason::SwBreak,
+        StopReason::Watch {
+            kind: WatchKind::Write,
+            addr: 0x1000,
+        },
+        StopReason::Watch {
+            kind: WatchKind::Read,
+            addr: 0xdead_beef,
+        },
+        StopReason::Watch {
+            kind: WatchKind::ReadWrite,
+            addr: 0,
+        },
+    ]
+    .into();
+    let res = run_ok(
+        &mut target,
+        &["qSupported:multiprocess+;swbreak+", "c", "c", "c", "c"],
+    );
+    assert_eq!(
+        res[1..],
+        [
+            "T05thread:p01.01;swbreak:;",
+            "T05thread:p01.01;watch:00001000;",
+            "T05thread:p01.01;rwatch:deadbeef;",
+            "T05thread:p01.01;awatch:00000000;",
+        ]
+    );
+
+    // `swbreak` is only reported to clients which support it
+    target.stop_reasons = vec![StopReason::SwBreak, StopReason::SwBreak].into();
+    let res = run_ok(&mut target, &["qSupported:multiprocess+;hwbreak+", "c"]);
+    assert_eq!(res[1], "T05thread:p01.01;");
+    let res = run_ok(&mut target, &["qSupported:swbreak-", "c"]);
+    assert_eq!(res[1], "T05thread:p01.01;");
+
+    target.resume_actions.clear();
+    target.stop_reasons = vec![
+        StopReason::Signal(Signal::SIGUSR1),
+        StopReason::Signal(Signal::SIGALRM),
+        StopReason::Signal(Signal::SIGSEGV),
+        StopReason::SignalWithInfo {
+            signal: Signal::SIGSEGV,
+            addr: 0x800,
+        },
+        StopReason::Signal(Signal::Other(0x4d)),
+        StopReason::ReplayLog(ReplayLogPosition::Begin),
+        StopReason::DoneStep,
+        StopReason::ReplayLog(ReplayLogPosition::End),
+    ]
+    .into();
+    let res = run_ok(
+        &mut target,
+        &["QPassSignals:e;1e", "c", "c", "vCont;C4d", "bc", "bs", "c"],
+    );
+    assert_eq!(
+        res,
+        [
+            "OK".to_string(),
+            // passed signals don't stop the target
+            "S0b".to_string(),
+            format!(
+                "T0bthread:p01.01;description:{};",
+                hex(b"fault address: 0x0800")
+            ),
+            // uncommon signals are passed through
+            "S4d".to_string(),
+            "T05replaylog:begin;".to_string(),
+            "S05".to_string(),
+            "T05replaylog:end;".to_string(),
+        ]
+    );
+    assert_eq!(target.passed_signals, [Signal::SIGALRM, Signal::SIGUSR1]);
+    assert_eq!(target.reverse_resumes, ["bc", "bs"]);
+    assert_eq!(
+        target.resume_actions,
+        [
+            ResumeAction::Continue,
+            ResumeAction::Continue,
+            ResumeAction::ContinueWithSignal(Signal::Other(0x4d)),
+            ResumeAction::Continue,
+        ]
+    );
+
+    target.resume_actions.clear();
+    target.regs.pc = 0x100;
+    let res = run_ok(
+        &mut target,
+        &[
+            // signals GDB explicitly resumes with are delivered regardless
+            "QProgramSignals:0e;2",
+            "vCont;S1e",
+            // resume from a specific address
+            "c00000200",
+            "s300",
+            // malformed actions
+            "vCont;",
+            "vCont;x",
+            "vCont;c;",
+            // specific actions override the default action
+            "vCont;s:p1.1;c",
+            "vCont;c:p1.-1",
+            "QPassSignals:",
+        ],
+    );
+    assert_eq!(
+        res,
+        ["OK", "S05", "S05", "S05", "E00", "E00", "E00", "S05", "S05", "OK"]
+    );
+    assert_eq!(target.program_signals, [Signal::SIGALRM, Signal::SIGINT]);
+    assert!(target.passed_signals.is_empty());
+    assert_eq!(target.regs.pc, 0x300);
+    assert_eq!(
+        target.resume_actions,
+        [
+            ResumeAction::StepWithSignal(Signal::SIGUSR1),
+            ResumeAction::Continue,
+            ResumeAction::Step,
+            ResumeAction::Step,
+            ResumeAction::Continue,
+        ]
+    );
+}
+
+#[test]
+fn target_exit() {
+    let mut target = MockTarget::new();
+    target.stop_reasons.push_back(StopReason::Exited(0x2a));
+    let (result, res) = run(&mut target, &["c"]);
+    assert_eq!(result.unwrap(), DisconnectReason::TargetExited(0x2a));
+    assert_eq!(res, ["W2a"]);
+
+    target
+        .stop_reasons
+        .push_back(StopReason::Terminated(Signal::SIGKILL));
+    let (result, res) = run(&mut target, &["c"]);
+    assert_eq!(
+        result.unwrap(),
+        DisconnectReason::TargetTerminated(Signal::SIGKILL)
+    );
+    assert_eq!(res, ["X09"]);
+}
+
+#[test]
+fn memory() {
+    // memory is read via `read_addrs`, as streaming reads aren't supported
+    let mut target = MockTarget::minimal();
+    target.mem[0x100..0x109].copy_from_slice(b"123456789");
+    target.mem[0x180..0x184].copy_from_slice(b"gdb!");
+    target.mem[0x200..0x203].copy_from_slice(b"a#b");
+    target.mem[0xffc..].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+
+    let res = check_ok(run_with_buffer_size(
+        &mut target,
+        &[
+            "qSearch:memory:100;100;gdb!",
+            "qSearch:memory:100;83;gdb!",
+            "qSearch:memory:100;84;gdb!",
+            // '#' is sent escaped
+            "qSearch:memory:0;1000;a}\x03b",
+            // the search runs off the end of the target's memory
+            "qSearch:memory:f00;200;gdb!",
+            "qCRC:100,9",
+            "qCRC:100,0",
+            "qCRC:ffc,8",
+            "mffc,4",
+            "mffc,10",
+            "m1000,4",
+            "M1000,4:00000000",
+        ],
+        400,
+    ));
+    assert_eq!(res[..4], ["1,0180", "0", "1,0180", "1,0200"]);
+    assert!(res[4].starts_with('E'));
+    assert_eq!(res[5..8], ["C0376e6e7", "Cffffffff", "E01"]);
+    // only the accessible prefix is returned...
+    assert_eq!(res[8..10], ["deadbeef", "deadbeef"]);
+    // ...and when nothing could be read, `TargetError::NonFatal` is reported as
+    // a generic EREMOTEIO, whereas `TargetError::Errno` reports the specific
+    // code (EFAULT)
+    assert_eq!(res[10..], ["E79", "E0e"]);
+
+    // with a small packet buffer, the region is read in multiple chunks
+    for (i, b) in target.mem[0x100..0x900].iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    target.mem[0xc00..].iter_mut().for_each(|b| *b = 0xaa);
+    let res = check_ok(run_with_buffer_size(
+        &mut target,
+        &["qCRC:100,800", "mc00,800"],
+        400,
+    ));
+    // computed independently, using the same CRC-32 variant as GDB
+    assert_eq!(res, ["C45412e64".to_string(), "aa".repeat(0x400)]);
+
+    // the initial read fails, followed by a binary search over the 0x800 byte
+    // chunk for its readable prefix
+    target.mem_reads = 0;
+    let res = check_ok(run_with_buffer_size(&mut target, &["mc01,800"], 0x1000));
+    assert_eq!(res, ["aa".repeat(0x3ff)]);
+    assert_eq!(target.mem_reads, 1 + 11);
+
+    // with a 400 byte packet buffer, the search reads memory in chunks of ~370
+    // bytes, so some of these matches straddle the boundary between reads
+    let packet = "qSearch:memory:100;200;gdbstub";
+    for offset in 0x160..0x180 {
+        target.mem.iter_mut().for_each(|b| *b = 0);
+        target.mem[0x100 + offset..][..7].copy_from_slice(b"gdbstub");
+
+        let res = check_ok(run_with_buffer_size(&mut target, &[packet], 400));
+        assert_eq!(res, [format!("1,{:04x}", 0x100 + offset)]);
+    }
+
+    // the search stops at the end of the address space, instead of wrapping
+    // around to address 0
+    target.mem.iter_mut().for_each(|b| *b = 0);
+    target.mem_base = 0xffff_f000;
+    let packets = [
+        "qSearch:memory:fffff000;1000;gdb!",
+        "qSearch:memory:fffff000;2000;gdb!",
+    ];
+    let res = check_ok(run_with_buffer_size(&mut target, &packets, 400));
+    assert_eq!(res, ["0", "0"]);
+
+    target.mem[0xffc..].copy_from_slice(b"gdb!");
+    let res = check_ok(run_with_buffer_size(&mut target, &packets[1..], 400));
+    assert_eq!(res, ["1,fffffffc"]);
+}
+
+#[test]
+fn qxfer_objects() {
+    let mut target = MockTarget::new();
+
+    let res = run_ok(&mut target, &["qSupported:multiprocess+"]);
+    for feature in [
+        ";qXfer:auxv:read+",
+        ";qXfer:exec-file:read+",
+        ";qXfer:siginfo:read+;qXfer:siginfo:write+",
+        ";qXfer:libraries-svr4:read+",
+    ]
+    .iter()
+    {
+        assert!(res[0].contains(feature), "{}", feature);
+    }
+
+    // binary data is escaped, and split into pages
+    let res = run_ok(
+        &mut target,
+        &[
+            "qXfer:auxv:read::0,1000",
+            "qXfer:auxv:read::0,8",
+            "qXfer:auxv:read::8,8",
+            "qXfer:auxv:read::10,100",
+            "qXfer:auxv:read::18,100",
+        ],
+    );
+    assert_eq!(AUXV.len(), 0x18);
+    assert_eq!(
+        res,
+        [
+            "l\x06\0\0\0\0\x10\0\0\x09\0\0\0}\x04\0\0\x08\0\0\0\0\0\0\0\0",
+            "m\x06\0\0\0\0\x10\0\0",
+            "m\x09\0\0\0}\x04\0\0\x08",
+            "l\0\0\0\0\0\0\0\0",
+            "l",
+        ]
+    );
+
+    let res = run_ok(
+        &mut target,
+        &[
+            "qXfer:exec-file:read:2a:0,1000",
+            "qXfer:exec-file:read::0,1000",
+            "qXfer:exec-file:read:5:0,1000",
+            "qXfer:exec-file:read:2a:0,8",
+            "qXfer:exec-file:read:2a:8,8",
+            "qXfer:exec-file:read:2a:d,8",
+        ],
+    );
+    assert_eq!(
+        res,
+        [
+            "l/usr/bin/true",
+            "l/usr/bin/true",
+            "E02",
+            "m/usr/bin",
+            "l/true",
+            "l"
+        ]
+    );
+
+    let res = run_ok(
+        &mut target,
+        &[
+            "qXfer:siginfo:read::0,8",
+            "qXfer:siginfo:read::8,100",
+            "qXfer:siginfo:read::10,100",
+            // si_signo = SIGUSR1
+            "qXfer:siginfo:write::0:\x0a",
+            // si_addr = 0x3a7d (i.e: data containing ':' and an escaped '}')
+            "qXfer:siginfo:write::c:\x7d\x5d\x3a",
+            // out of bounds
+            "qXfer:siginfo:write::10:\x00",
+        ],
+    );
+    assert_eq!(
+        res,
+        [
+            "m\x0b\0\0\0\0\0\0\0",
+            "l\x01\0\0\0\0\x10\0\0",
+            "l",
+            "01",
+            "02",
+            "E16"
+        ]
+    );
+    assert_eq!(
+        target.siginfo,
+        [0x0a, 0, 0, 0, 0, 0, 0, 0, 0x01, 0, 0, 0, 0x7d, 0x3a, 0, 0]
+    );
+
+    let res = run_ok(
+        &mut target,
+        &[
+            "qXfer:libraries-svr4:read::0,1000",
+            "qXfer:libraries-svr4:read::0,40",
+            "qXfer:libraries-svr4:read::40,1000",
+        ],
+    );
+    assert_eq!(
+        res,
+        [
+            format!("l{}", LIBRARY_LIST),
+            format!("m{}", &LIBRARY_LIST[..0x40]),
+            format!("l{}", &LIBRARY_LIST[0x40..]),
+        ]
+    );
+
+    // additional features are included in the target description, and served
+    // by annex
+    let res = run_ok(
+        &mut target,
+        &[
+            "qXfer:features:read:target.xml:0,1000",
+            "qXfer:features:read:arm-core.xml:0,1000",
+            "qXfer:features:read:arm-vfp.xml:0,1000",
+            "qXfer:features:read:missing.xml:0,1000",
+        ],
+    );
+    assert_eq!(
+        res,
+        [
+            format!("l{}", TARGET_XML),
+            format!("l{}", common::TARGET_XML_FEATURES[0].xml),
+            format!("l{}", common::TARGET_XML_FEATURES[1].xml),
+            "E00".to_string(),
+        ]
+    );
+
+    let packets = (0..TARGET_XML.len())
+        .step_by(10)
+        .map(|offset| format!("qXfer:features:read:target.xml:{:x},a", offset))
+        .collect::<Vec<_>>();
+    let res = run_ok(
+        &mut target,
+        &packets.iter().map(|p| p.as_str()).collect::<Vec<_>>(),
+    );
+    let mut xml = String::new();
+    for (i, chunk) in res.iter().enumerate() {
+        let (kind, data) = chunk.split_at(1);
+        assert_eq!(kind, if i == res.len() - 1 { "l" } else { "m" });
+        xml += data;
+    }
+    assert_eq!(xml, TARGET_XML);
+}
+
+#[test]
+fn target_services() {
+    let mut target = MockTarget::new();
+
+    // `monitor gdbstub version` is handled by the stub itself, whereas other
+    // commands are forwarded to the target
+    let version = format!("qRcmd,{}", hex(b"gdbstub version"));
+    let res = run_ok(&mut target, &[&version]);
+    assert_eq!(res.last().unwrap(), "OK");
+    let out = console_output(&res);
+    assert!(out.contains(&format!("gdbstub {}", env!("CARGO_PKG_VERSION"))));
+    assert!(out.contains("features:"));
+    assert!(target.monitor_cmds.is_empty());
+
+    let res = run_ok(&mut target, &[&format!("qRcmd,{}", hex(b"ping"))]);
+    assert_eq!(res.last().unwrap(), "OK");
+    assert_eq!(console_output(&res), "handled by target\n");
+    assert_eq!(target.monitor_cmds, [b"ping".to_vec()]);
+
+    let open = format!("vFile:open:{},0,0", hex(b"/lib/libfoo.so"));
+    let missing = format!("vFile:open:{},0,0", hex(b"/missing"));
+    let unlink = format!("vFile:unlink:{}", hex(b"/etc/motd"));
+    let readlink = format!("vFile:readlink:{}", hex(b"/etc/motd"));
+    let res = run_ok(
+        &mut target,
+        &[
+            &open,
+            "vFile:pread:0,100,0",
+            "vFile:pread:0,3,5",
+            "vFile:pread:0,100,100",
+            "vFile:close:0",
+            &missing,
+            "vFile:pread:5,10,0",
+            "vFile:close:5",
+            "vFile:pwrite:1,0,abc",
+            &unlink,
+            &readlink,
+            "vFile:setfs:0",
+            "vFile:fstat:1",
+        ],
+    );
+    assert_eq!(
+        res[..12],
+        [
+            "F00",
+            // special characters in the file's contents are escaped
+            "F08;\x7fELF}\x04}\x03}]}\x0a",
+            "F03;}\x03}]}\x0a",
+            "F00;",
+            "F0",
+            // ENOENT, then EBADF
+            "F-1,02",
+            "F-1,09",
+            "F-1,09",
+            // unimplemented ops report EINVAL
+            "F-1,16",
+            "F-1,16",
+            "F-1,16",
+            "F-1,16",
+        ]
+    );
+    let stat = res[12]
+        .strip_prefix("F40;")
+        .unwrap()
+        .chars()
+        .map(|c| c as u8)
+        .collect::<Vec<_>>();
+    assert_eq!(stat.len(), 64);
+    // st_mode
+    assert_eq!(stat[8..12], (0o100644u32).to_be_bytes());
+    // st_size
+    assert_eq!(stat[28..36], 5u64.to_be_bytes());
+
+    let res = run_ok(
+        &mut target,
+        &[
+            "vFlashErase:00000100,00000100",
+            // the data is binary-escaped, and may contain ':' characters
+            "vFlashWrite:100:ab:}]}\x03",
+            "vFlashWrite:180:cd",
+            "vFlashWrite:1fe:abcd",
+        ],
+    );
+    assert_eq!(res, ["OK", "OK", "OK", "E05"]);
+    // nothing is committed until flashing is done
+    assert!(target.mem[0x100..0x182].iter().all(|b| *b == 0));
+    assert_eq!(target.flash_pending.len(), 2);
+
+    let res = run_ok(&mut target, &["vFlashDone"]);
+    assert_eq!(res, ["OK"]);
+    assert_eq!(&target.mem[0x100..0x106], b"ab:}#\0");
+    assert_eq!(&target.mem[0x180..0x182], b"cd");
+    assert!(target.flash_pending.is_empty());
+
+    // stray lookup results are ignored
+    let res = run_ok(
+        &mut target,
+        &[
+            &format!("qSymbol:1000:{}", hex(b"main")),
+            "qSymbol::",
+            &format!("qSymbol:1234:{}", hex(b"_dl_debug_state")),
+            &format!("qSymbol::{}", hex(b"main")),
+        ],
+    );
+    assert_eq!(
+        res,
+        [
+            "OK".to_string(),
+            format!("qSymbol:{}", hex(b"_dl_debug_state")),
+            format!("qSymbol:{}", hex(b"main")),
+            "OK".to_string(),
+        ]
+    );
+    assert_eq!(
+        target.symbols,
+        [("_dl_debug_state", Some(0x1234)), ("main", None)]
+    );
+
+    // GDB may kick off another lookup (e.g: after loading a new object file),
+    // at which point the target requests any symbols it still needs
+    let res = run_ok(
+        &mut target,
+        &[
+            "qSymbol::",
+            &format!("qSymbol:abcd:{}", hex(b"main")),
+            "qSymbol::",
+        ],
+    );
+    assert_eq!(
+        res,
+        [
+            format!("qSymbol:{}", hex(b"main")),
+            "OK".into(),
+            "OK".into()
+        ]
+    );
+    assert_eq!(
+        target.symbols,
+        [("_dl_debug_state", Some(0x1234)), ("main", Some(0xabcd))]
+    );
+
+    let res = run_ok(
+        &mut target,
+        &[
+            "qGetTLSAddr:p01.01,10,40000100",
+            "qGetTLSAddr:1,8,40000200",
+            "qGetTLSAddr:p01.01,10,40000300",
+            // GDB expects `Bss=` to be present, even if it's not reported by
+            // the target
+            "qOffsets",
+        ],
+    );
+    assert_eq!(
+        res,
+        [
+            "20000010",
+            "20001008",
+            "E16",
+            "Text=1000;Data=2000;Bss=2000"
+        ]
+    );
+}
+
+#[test]
+fn streaming_reads() {
+    let mut target = MockTarget::new();
+    target.mem[0x100..0x104].copy_from_slice(&[b'#', 0x01, b'}', b'A']);
+    target.mem[0xffc..].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+
+    let res = run_ok(&mut target, &["m100,4", "mffc,10", "m1000,4"]);
+    assert_eq!(res, ["23017d41", "deadbeef", "E79"]);
+
+    // the read doesn't need to fit in the packet buffer
+    for (i, b) in target.mem[0x100..0x900].iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    let res = check_ok(run_with_buffer_size(&mut target, &["m100,800"], 400));
+    assert_eq!(res, [hex(&target.mem[0x100..0x900])]);
+    assert_eq!(target.mem_reads, 0);
+}
+
+#[test]
+fn lldb_queries() {
+    let mut target = MockTarget::new();
+
+    let res = run_ok(
+        &mut target,
+        &[
+            "qHostInfo",
+            "qProcessInfo",
+            "qRegisterInfo0",
+            "qRegisterInfo1",
+            "qRegisterInfo2",
+            "qRegisterInfo3",
+            "qMemoryRegionInfo",
+            "qMemoryRegionInfo:1000",
+            "qMemoryRegionInfo:2fff",
+            "qMemoryRegionInfo:8800",
+            // unmapped
+            "qMemoryRegionInfo:0",
+            "qMemoryRegionInfo:3000",
+            "qMemoryRegionInfo:9000",
+        ],
+    );
+    assert_eq!(
+        res,
+        [
+            format!("triple:{};vendor:unknown;ptrsize:4;", TRIPLE),
+            format!("pid:01;triple:{};vendor:unknown;ptrsize:4;", TRIPLE),
+            "name:r0;bitsize:32;offset:0;encoding:uint;format:hex;\
+             set:General Purpose Registers;"
+                .to_string(),
+            "name:r11;alt-name:fp;bitsize:32;offset:4;encoding:uint;format:hex;\
+             set:General Purpose Registers;gcc:11;dwarf:11;generic:fp;"
+                .to_string(),
+            "name:r0b;bitsize:8;offset:0;encoding:sint;format:decimal;\
+             set:General Purpose Registers;container-regs:00;invalidate-regs:00,10;"
+                .to_string(),
+            // terminates enumeration
+            "E45".to_string(),
+            "OK".to_string(),
+            "start:1000;size:2000;permissions:rx;".to_string(),
+            "start:1000;size:2000;permissions:rx;".to_string(),
+            "start:8000;size:1000;permissions:rw;".to_string(),
+            "start:00;size:1000;".to_string(),
+            "start:3000;size:5000;".to_string(),
+            "start:9000;size:ffff7000;".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn tracepoints() {
+    let mut target = MockTarget::new();
+
+    let res = run_ok(
+        &mut target,
+        &[
+            "qSupported:multiprocess+",
+            "qTStatus",
+            "QTStart",
+            "qTStatus",
+            "QTStop",
+            "qTStatus",
+        ],
+    );
+    assert!(res[0].contains(";ConditionalTracepoints+"));
+    assert_eq!(res[1..], ["T0;tnotrun:0", "OK", "T1", "OK", "T0;tstop:0"]);
+
+    target.trace_status = ExperimentStatus::PassCount(Tracepoint(0x12));
+    let res = run_ok(&mut target, &["qTStatus"]);
+    assert_eq!(res, ["T0;tpasscount:12"]);
+
+    let res = run_ok(
+        &mut target,
+        &[
+            "QTinit",
+            "QTDP:1:00000100:E:2:a-",
+            "QTDP:-1:00000100:R8001-",
+            "QTDP:-1:00000100:MFFFFFFFF,200,4M0d,fffffffffffffffc,8-",
+            "QTDP:-1:00000100:SR01-",
+            "QTDP:-1:00000100:X2,2627",
+            "QTDP:2:00000200:D:0:0:X3,220027",
+            // unknown action
+            "QTDP:-1:00000100:Q1",
+            // truncated bytecode
+            "QTDP:-1:00000100:X4,26",
+            // unknown tracepoint
+            "QTDP:-2:00000100:R01",
+        ],
+    );
+    assert_eq!(res[..7], ["OK"; 7]);
+    assert_eq!(res[7..], ["E16"; 3]);
+    assert_eq!(
+        target.tracepoints,
+        [
+            MockTracepoint {
+                number: 1,
+                addr: 0x100,
+                enabled: true,
+                step_count: 2,
+                pass_count: 10,
+                condition: None,
+                actions: vec![
+                    ("Registers { mask: [128, 1] }".into(), false),
+                    (
+                        "Memory { basereg: None, offset: 512, length: 4 }".into(),
+                        false
+                    ),
+                    (
+                        "Memory { basereg: Some(13), offset: 4294967292, length: 8 }".into(),
+                        false
+                    ),
+                    ("Registers { mask: [1] }".into(), true),
+                    ("Expression { expr: [38, 39] }".into(), true),
+                ],
+            },
+            MockTracepoint {
+                number: 2,
+                addr: 0x200,
+                enabled: false,
+                step_count: 0,
+                pass_count: 0,
+                condition: Some(vec![0x22, 0x00, 0x27]),
+                actions: Vec::new(),
+            },
+        ]
+    );
+
+    target.trace_frames = vec![(1, 0x100), (2, 0x200), (1, 0x100)];
+    let res = run_ok(
+        &mut target,
+        &[
+            "QTFrame:1",
+            "QTFrame:5",
+            "QTFrame:pc:00000100",
+            "QTFrame:pc:00000100",
+            "QTFrame:pc:00000100",
+            "QTFrame:ffffffff",
+            "QTFrame:tdp:2",
+            "QTFrame:range:00000150:00000250",
+            "QTFrame:-1",
+            "QTFrame:outside:00000150:00000250",
+        ],
+    );
+    assert_eq!(
+        res,
+        ["F01T02", "F-1", "F00T01", "F02T01", "F-1", "OK", "F01T02", "F-1", "OK", "F00T01"]
+    );
+
+    // QTinit discards any existing tracepoints
+    let res = run_ok(&mut target, &["QTinit"]);
+    assert_eq!(res, ["OK"]);
+    assert!(target.tracepoints.is_empty());
+}
+
+#[test]
+fn extended_mode() {
+    let mut target = MockTarget::new();
+    let run_pid = format!("{:x}", RUN_PID);
+
+    let res = run_ok(&mut target, &["qSupported:multiprocess+"]);
+    for feature in [
+        ";QStartupWithShell+",
+        ";QEnvironmentHexEncoded+",
+        ";QEnvironmentUnset+",
+        ";QEnvironmentReset+",
+        ";QDisableRandomization+",
+        ";QSetWorkingDir+",
+    ]
+    .iter()
+    {
+        assert!(res[0].contains(feature), "{}", feature);
+    }
+
+    // GDB connected using `target remote`, and never sent `!`. LLDB attaches to
+    // processes without ever sending `!` either.
+    let res = run_ok(
+        &mut target,
+        &["vRun;", "R00", "vAttach;2a", "qAttached:2a", "qProcessInfo"],
+    );
+    // `R` has no reply, even when it's ignored
+    assert_eq!(
+        res,
+        [
+            "E01".to_string(),
+            "".to_string(),
+            "1".to_string(),
+            format!("pid:2a;triple:{};vendor:unknown;ptrsize:4;", TRIPLE),
+        ]
+    );
+    assert!(target.ran.is_empty());
+    assert_eq!(target.restarts, 0);
+
+    // the replies to `qAttached` must be the same with or without `alloc`
+    // (where they're cached after the first query)
+    let res = run_ok(
+        &mut target,
+        &[
+            "!",
+            "vAttach;2a",
+            "vRun;;",
+            "qAttached:2a",
+            &format!("qAttached:{}", run_pid),
+            "qAttached:2a",
+            &format!("qAttached:{}", run_pid),
+            "R00",
+            "?",
+        ],
+    );
+    assert_eq!(res, ["OK", "", "S05", "1", "0", "1", "0", "S05"]);
+    assert_eq!(target.ran, [(None, vec![])]);
+    assert_eq!(target.restarts, 1);
+
+    // `run "" -l`
+    target.ran.clear();
+    let res = run_ok(&mut target, &["!", "vRun;2f62696e2f6c73;;2d6c"]);
+    assert_eq!(res, ["OK", "S05"]);
+    assert_eq!(
+        target.ran,
+        [(
+            Some(b"/bin/ls".to_vec()),
+            vec![b"".to_vec(), b"-l".to_vec()]
+        )]
+    );
+
+    let res = run_ok(&mut target, &["!", "vRun;", "vKill;2a", "?", "vRun;", "?"]);
+    assert_eq!(res, ["OK", "S05", "OK", "X09;process:2a", "S05", "S05"]);
+    let res = run_ok(&mut target, &["!", "vRun;", "k", "?"]);
+    assert_eq!(res, ["OK", "S05", "X09", "X09"]);
+
+    // re-selecting the current process doesn't notify the target
+    let res = run_ok(
+        &mut target,
+        &[
+            "!",
+            "vAttach;2a",
+            "vAttach;2b",
+            "Hgp2a.1",
+            "qC",
+            "Hgp2a.1",
+            "Hgp2b.0",
+            "qC",
+        ],
+    );
+    assert_eq!(
+        res,
+        ["OK", "", "", "OK", "QCp2a.01", "OK", "OK", "QCp2b.01"]
+    );
+    assert_eq!(
+        target.selected,
+        [Pid::new(0x2a).unwrap(), Pid::new(0x2b).unwrap()]
+    );
+
+    // FOO=bar, EMPTY=, FOO=baz, then unset FOO
+    let res = run_ok(
+        &mut target,
+        &[
+            "QStartupWithShell:0",
+            "QDisableRandomization:1",
+            "QDisableRandomization:2",
+            // "/tmp"
+            "QSetWorkingDir:2f746d70",
+            "QEnvironmentHexEncoded:464f4f3d626172",
+            "QEnvironmentHexEncoded:454d5054593d",
+            "QEnvironmentHexEncoded:464f4f3d62617a",
+        ],
+    );
+    assert_eq!(res, ["OK", "OK", "E16", "OK", "OK", "OK", "OK"]);
+    assert!(!target.startup_with_shell);
+    assert!(!target.aslr);
+    assert_eq!(target.working_dir, Some(b"/tmp".to_vec()));
+    assert_eq!(
+        target.env,
+        [
+            (b"EMPTY".to_vec(), None),
+            (b"FOO".to_vec(), Some(b"baz".to_vec())),
+        ]
+    );
+
+    let res = run_ok(&mut target, &["QEnvironmentUnset:464f4f"]);
+    assert_eq!(res, ["OK"]);
+    assert_eq!(target.env, [(b"EMPTY".to_vec(), None)]);
+
+    // an empty path resets the working directory
+    let res = run_ok(
+        &mut target,
+        &[
+            "QStartupWithShell:1",
+            "QDisableRandomization:0",
+            "QSetWorkingDir:",
+            "QEnvironmentReset",
+        ],
+    );
+    assert_eq!(res, ["OK"; 4]);
+    assert!(target.startup_with_shell);
+    assert!(target.aslr);
+    assert_eq!(target.working_dir, None);
+    assert!(target.env.is_empty());
+
+    let (result, res) = run(&mut target, &["!", "vAttach;2a", "vAttach;2b", "D", "?"]);
+    assert_eq!(result.unwrap(), DisconnectReason::Disconnect);
+    assert_eq!(res, ["OK", "", "", "OK"]);
+    assert_eq!(target.detached, [None]);
+}
+
+// processes are only tracked when `alloc` is available (otherwise, every
+// detach ends the session, and the exec-file isn't remembered)
+#[test]
+#[cfg(feature = "alloc")]
+fn extended_mode_process_tracking() {
+    let mut target = MockTarget::new();
+
+    let (result, res) = run(
+        &mut target,
+        &[
+            "!",
+            "vAttach;2a",
+            "vAttach;2b",
+            "D;2a",
+            "qAttached:2b",
+            "D;2b",
+            "?",
+        ],
+    );
+    // the session only ends once the last process has been detached from
+    assert_eq!(result.unwrap(), DisconnectReason::Disconnect);
+    assert_eq!(res, ["OK", "", "", "OK", "1", "OK"]);
+    assert_eq!(
+        target.detached,
+        [Some(Pid::new(0x2a).unwrap()), Some(Pid::new(0x2b).unwrap())]
+    );
+
+    // "/bin/ls" (i.e: `set remote exec-file /bin/ls`), followed by an empty
+    // filename with args
+    let res = run_ok(
+        &mut target,
+        &["!", "vRun;2f62696e2f6c73", "vRun;;2d6c", "vRun;"],
+    );
+    assert_eq!(res, ["OK", "S05", "S05", "S05"]);
+    assert_eq!(
+        target.ran,
+        [
+            (Some(b"/bin/ls".to_vec()), vec![]),
+            (Some(b"/bin/ls".to_vec()), vec![b"-l".to_vec()]),
+            (Some(b"/bin/ls".to_vec()), vec![]),
+        ]
+    );
+}
+
+#[test]
+fn unsupported_extensions() {
+    let mut target = MockTarget::minimal();
+
+    let res = run_ok(
+        &mut target,
+        &["qSupported:multiprocess+;swbreak+", "vCont?"],
+    );
+    for feature in [
+        "ConditionalBreakpoints",
+        "QPassSignals",
+        "QProgramSignals",
+        "Reverse",
+        "qXfer:auxv",
+        "qXfer:exec-file",
+        "qXfer:siginfo",
+        "qXfer:libraries-svr4",
+        "ConditionalTracepoints",
+        "QStartupWithShell",
+    ]
+    .iter()
+    {
+        assert!(!res[0].contains(feature), "{}", feature);
+    }
+    assert_eq!(res[1], "vCont;c");
+
+    let version = format!("qRcmd,{}", hex(b"gdbstub version"));
+    let ping = format!("qRcmd,{}", hex(b"ping"));
+    let open = format!("vFile:open:{},0,0", hex(b"/etc/motd"));
+    let unsupported = [
+        "Z0,1000,4",
+        "Z1,1000,4",
+        "Z2,1000,4",
+        "QPassSignals:0e;1e",
+        "QProgramSignals:0e",
+        "bc",
+        "bs",
+        "qXfer:auxv:read::0,1000",
+        "qXfer:exec-file:read:2a:0,1000",
+        "qXfer:siginfo:read::0,1000",
+        "qXfer:siginfo:write::0:\x0b",
+        "qXfer:libraries-svr4:read::0,1000",
+        &ping,
+        &open,
+        "vFile:setfs:0",
+        "vFlashErase:00000100,00000100",
+        "vFlashWrite:100:ab",
+        "vFlashDone",
+        "qOffsets",
+        "qGetTLSAddr:p01.01,10,40000100",
+        "qHostInfo",
+        "qProcessInfo",
+        "qRegisterInfo0",
+        "qMemoryRegionInfo",
+        "qMemoryRegionInfo:1000",
+        "QTinit",
+        "QTDP:1:00001000:E:0:0",
+        "QStartupWithShell:0",
+        "QSetWorkingDir:",
+    ];
+    let res = run_ok(&mut target, &unsupported);
+    assert_eq!(res, vec![""; unsupported.len()]);
+
+    let res = run_ok(&mut target, &[&version, "qTStatus", "qSymbol::"]);
+    // the stub's own monitor commands, GDB's trace status probe, and symbol
+    // lookups are answered regardless
+    let out = console_output(&res);
+    assert!(out.contains(&format!("gdbstub {}", env!("CARGO_PKG_VERSION"))));
+    assert_eq!(res[res.len() - 3..], ["OK", "T0;tnotrun:0", "OK"]);
+
+    // GDB falls back to stepping using temporary breakpoints
+    let (result, _) = run(&mut target, &["vCont;s"]);
+    assert!(matches!(result, Err(GdbStubError::PacketUnexpected)));
+
+    target.stop_reasons.push_back(StopReason::Watch {
+        kind: WatchKind::Write,
+        addr: 0x1000,
+    });
+    let (result, _) = run(&mut target, &["c"]);
+    assert!(matches!(result, Err(GdbStubError::UnsupportedStopReason)));
+
+    let res = run_ok(&mut target, &["Hgp2a.1", "qC"]);
+    assert_eq!(res, ["OK", "QCp01.01"]);
+    assert!(target.selected.is_empty());
+
+    let (result, res) = run(&mut target, &["D;1", "?"]);
+    assert_eq!(result.unwrap(), DisconnectReason::Disconnect);
+    assert_eq!(res, ["OK"]);
+    assert!(target.detached.is_empty());
+
+    let (result, res) = run(&mut target, &["k"]);
+    assert_eq!(result.unwrap(), DisconnectReason::Kill);
+    assert!(res.is_empty());
+}
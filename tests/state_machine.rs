@@ -0,0 +1,180 @@
+mod common;
+
+use common::{packet, pump_all, report_stop_all, responses, MockMultiThreadTarget, MockTarget};
+use gdbstub::common::{Signal, Tid};
+use gdbstub::target::ext::base::multithread::ThreadStopReason;
+use gdbstub::target::ext::base::ResumeAction;
+use gdbstub::{Connection, DisconnectReason, GdbStub, ResumeRequest, StateMachineAction};
+
+/// A connection which is never used for I/O, as incoming bytes are passed to
+/// the state machine directly, and outgoing bytes are handed back via
+/// `StateMachineAction::Send`.
+struct NoIoConnection;
+
+impl Connection for NoIoConnection {
+    type Error = &'static str;
+
+    fn read(&mut self) -> Result<u8, Self::Error> {
+        panic!("the state machine should never read from the connection")
+    }
+
+    fn write(&mut self, _byte: u8) -> Result<(), Self::Error> {
+        panic!("the state machine should never write to the connection")
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Self::Error> {
+        panic!("the state machine should never peek at the connection")
+    }
+
+    fn poll_readable(&mut self) -> Result<bool, Self::Error> {
+        panic!("the state machine should never poll the connection")
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[test]
+fn pump_bytes() {
+    let mut target = MockTarget::new();
+    target.mem[0x10..0x14].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+
+    let mut gdb = GdbStub::new(NoIoConnection).into_state_machine();
+
+    // nothing is sent until the packet has been received in its entirety
+    let pkt = packet("m10,4");
+    let (last, head) = pkt.split_last().unwrap();
+    for b in head {
+        assert_eq!(gdb.pump(&mut target, *b).unwrap(), None);
+    }
+    let sent = match gdb.pump(&mut target, *last).unwrap() {
+        Some(StateMachineAction::Send(bytes)) => bytes.to_vec(),
+        action => panic!("unexpected action: {:?}", action),
+    };
+    assert_eq!(sent, b"+$deadbeef#20");
+    assert_eq!(gdb.next_action().unwrap(), None);
+
+    // acks and packets can be interleaved freely
+    let mut tx = Vec::new();
+    let bytes = [&b"+"[..], &packet("qAttached:2a"), b"+"].concat();
+    let action = pump_all(&mut gdb, &mut target, &bytes, &mut tx).unwrap();
+    assert_eq!(action, None);
+    assert_eq!(responses(&tx), ["1"]);
+}
+
+#[test]
+fn resume_is_left_to_the_host() {
+    let mut target = MockTarget::new();
+    let mut gdb = GdbStub::new(NoIoConnection).into_state_machine();
+
+    // the ack is sent before the host is asked to resume the target
+    let mut tx = Vec::new();
+    let action = pump_all(&mut gdb, &mut target, &packet("vCont;C1e"), &mut tx).unwrap();
+    assert_eq!(
+        action,
+        Some(StateMachineAction::Resume(ResumeRequest::Resume(
+            ResumeAction::ContinueWithSignal(Signal::SIGUSR1)
+        )))
+    );
+    assert_eq!(tx, b"+");
+
+    // the target isn't resumed by the stub, and no stop reply is sent until the
+    // host reports that the target stopped
+    assert!(target.resume_actions.is_empty());
+
+    tx.clear();
+    let action =
+        report_stop_all(&mut gdb, &mut target, ThreadStopReason::DoneStep, &mut tx).unwrap();
+    assert_eq!(action, None);
+    assert_eq!(responses(&tx), ["S05"]);
+}
+
+#[test]
+fn interrupt_while_running() {
+    let mut target = MockTarget::new();
+    let mut gdb = GdbStub::new(NoIoConnection).into_state_machine();
+
+    let mut tx = Vec::new();
+    let action = pump_all(&mut gdb, &mut target, &packet("c"), &mut tx).unwrap();
+    assert!(matches!(action, Some(StateMachineAction::Resume(_))));
+
+    // the `0x03` is handed to the host, which is responsible for stopping the
+    // target
+    assert_eq!(
+        gdb.pump(&mut target, 0x03).unwrap(),
+        Some(StateMachineAction::Interrupt)
+    );
+
+    tx.clear();
+    let action = report_stop_all(
+        &mut gdb,
+        &mut target,
+        ThreadStopReason::GdbInterrupt,
+        &mut tx,
+    )
+    .unwrap();
+    assert_eq!(action, None);
+    assert_eq!(responses(&tx), ["T02thread:p01.01;"]);
+}
+
+#[test]
+fn unrequested_stops_resume_again() {
+    let mut target = MockMultiThreadTarget::new();
+    let mut gdb = GdbStub::new(NoIoConnection).into_state_machine();
+
+    let mut tx = Vec::new();
+    let resume = pump_all(&mut gdb, &mut target, &packet("vCont;c"), &mut tx)
+        .unwrap()
+        .unwrap();
+
+    // GDB didn't ask for thread events
+    tx.clear();
+    let action = report_stop_all(
+        &mut gdb,
+        &mut target,
+        ThreadStopReason::ThreadCreated(Tid::new(2).unwrap()),
+        &mut tx,
+    )
+    .unwrap();
+    assert_eq!(action, Some(resume));
+    assert!(tx.is_empty());
+}
+
+#[test]
+fn pump_interrupt_between_packets() {
+    let mut target = MockTarget::new();
+    let mut gdb = GdbStub::new(NoIoConnection).into_state_machine();
+
+    let mut tx = Vec::new();
+    let bytes = [vec![0x03], packet("m10,1")].concat();
+    assert_eq!(
+        pump_all(&mut gdb, &mut target, &bytes, &mut tx).unwrap(),
+        None
+    );
+    assert_eq!(responses(&tx), ["T02thread:p01.01;", "00"]);
+}
+
+#[test]
+fn pump_disconnect() {
+    let mut target = MockTarget::new();
+    let mut gdb = GdbStub::new(NoIoConnection).into_state_machine();
+
+    // the reply is sent before the host is told that the session ended
+    let pkt = packet("D");
+    let (last, head) = pkt.split_last().unwrap();
+    let mut tx = Vec::new();
+    assert_eq!(
+        pump_all(&mut gdb, &mut target, head, &mut tx).unwrap(),
+        None
+    );
+    match gdb.pump(&mut target, *last).unwrap() {
+        Some(StateMachineAction::Send(bytes)) => tx.extend_from_slice(bytes),
+        action => panic!("unexpected action: {:?}", action),
+    }
+    assert_eq!(
+        gdb.next_action().unwrap(),
+        Some(StateMachineAction::Disconnect(DisconnectReason::Disconnect))
+    );
+    assert_eq!(responses(&tx), ["OK"]);
+}